@@ -0,0 +1,21 @@
+#![feature(test)]
+
+extern crate test;
+extern crate scribe;
+
+use test::Bencher;
+use scribe::Buffer;
+use scribe::buffer::Position;
+
+#[bench]
+fn bench_search(b: &mut Bencher) {
+    let mut buffer = Buffer::new();
+    buffer.insert("scribe library\n".repeat(10000));
+
+    // Move the gap to the middle of the buffer, as it
+    // would be after editing, so that both halves are searched.
+    buffer.cursor.move_to(Position{ line: 5000, offset: 3 });
+    buffer.insert("-");
+
+    b.iter(|| buffer.search("library"));
+}
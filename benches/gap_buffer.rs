@@ -0,0 +1,57 @@
+#![feature(test)]
+
+extern crate test;
+extern crate scribe;
+
+use test::Bencher;
+use scribe::buffer::{GapBuffer, Position, Range};
+
+#[bench]
+fn bench_insert(b: &mut Bencher) {
+    // Alternate inserting at the start and end of the buffer,
+    // forcing the gap to move across the data on every insert.
+    let mut buffer = GapBuffer::new("scribe\n".repeat(1000));
+    let start = Position{ line: 0, offset: 0 };
+    let end = Position{ line: 1000, offset: 0 };
+
+    b.iter(|| {
+        buffer.insert("scribe", &start);
+        buffer.insert("scribe", &end);
+    });
+}
+
+#[bench]
+fn bench_delete(b: &mut Bencher) {
+    b.iter(|| {
+        let mut buffer = GapBuffer::new("scribe\n".repeat(1000));
+
+        // Delete the first line until there's nothing left.
+        for _ in 0..1000 {
+            buffer.delete(&Range::new(
+                Position{ line: 0, offset: 0 },
+                Position{ line: 1, offset: 0 }
+            ));
+        }
+    });
+}
+
+#[bench]
+fn bench_read(b: &mut Bencher) {
+    let mut buffer = GapBuffer::new("scribe\n".repeat(1000));
+    buffer.insert("scribe", &Position{ line: 500, offset: 0 });
+    let range = Range::new(
+        Position{ line: 250, offset: 0 },
+        Position{ line: 750, offset: 0 }
+    );
+
+    // Read a range spanning the gap.
+    b.iter(|| buffer.read(&range));
+}
+
+#[bench]
+fn bench_chars(b: &mut Bencher) {
+    let mut buffer = GapBuffer::new("scribe\n".repeat(1000));
+    buffer.insert("scribe", &Position{ line: 500, offset: 0 });
+
+    b.iter(|| buffer.chars().count());
+}
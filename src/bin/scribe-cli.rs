@@ -0,0 +1,290 @@
+//! Applies a script of editing commands to files, without a frontend.
+//!
+//! Scripts are read from the file named by the first argument (or standard
+//! input, if there isn't one), with one command per line:
+//!
+//! ```text
+//! # Blank lines and lines starting with '#' are skipped.
+//! open notes.txt
+//! goto 3 1
+//! insert "TODO: "
+//! replace "colour" "color"
+//! save
+//! ```
+//!
+//! `goto` takes a one-based line and column (in grapheme clusters), and
+//! `replace` replaces every occurrence of its first argument. Arguments are
+//! separated by whitespace; those containing whitespace can be quoted, with
+//! `\"`, `\\`, `\n`, and `\t` escapes (other backslashes are kept as-is, so
+//! that Windows paths can be quoted). Editing commands are run against the
+//! last file opened, and opening another file with unsaved changes, or
+//! ending the script with them, is an error. The script stops at the first
+//! failing command.
+
+extern crate scribe;
+
+use scribe::{Buffer, Error, ErrorKind};
+use scribe::buffer::{Command, Position};
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process;
+
+// A parsed script line.
+#[derive(Debug, PartialEq)]
+enum Step {
+    Open(PathBuf),
+    Save,
+    Edit(Command),
+}
+
+fn main() {
+    let script = match read_script(env::args().nth(1)) {
+        Ok(script) => script,
+        Err(error) => {
+            eprintln!("scribe-cli: couldn't read script: {}", error);
+            process::exit(2);
+        },
+    };
+
+    if let Err(error) = run(&script) {
+        eprintln!("scribe-cli: {}", error);
+        process::exit(1);
+    }
+}
+
+fn read_script(path: Option<String>) -> io::Result<String> {
+    match path {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut script = String::new();
+            io::stdin().read_to_string(&mut script)?;
+            Ok(script)
+        },
+    }
+}
+
+// Runs each of the script's commands, returning the first failure, prefixed
+// with its line number, or a failure if the last file has unsaved changes.
+fn run(script: &str) -> Result<(), String> {
+    let mut buffer: Option<Buffer> = None;
+
+    for (index, line) in script.lines().enumerate() {
+        let step = parse(line).map_err(|error| format!("line {}: {}", index + 1, error))?;
+        if let Some(step) = step {
+            apply(step, &mut buffer).map_err(|error| format!("line {}: {}", index + 1, error))?;
+        }
+    }
+
+    match buffer {
+        Some(ref buffer) if buffer.modified() => {
+            Err(format!("{} has unsaved changes at the end of the script", describe(buffer)))
+        },
+        _ => Ok(()),
+    }
+}
+
+fn apply(step: Step, buffer: &mut Option<Buffer>) -> Result<(), String> {
+    if let Step::Open(path) = step {
+        if let Some(ref current) = *buffer {
+            if current.modified() {
+                return Err(format!("{} has unsaved changes", describe(current)));
+            }
+        }
+        let opened = Buffer::from_file(&path)
+            .map_err(|error| format!("couldn't open {}: {}", path.display(), error))?;
+        *buffer = Some(opened);
+
+        return Ok(());
+    }
+
+    let current = buffer.as_mut().ok_or("no file is open")?;
+    match step {
+        Step::Save => current.save()
+            .map_err(|error| format!("couldn't save {}: {}", describe(current), error)),
+        Step::Edit(command) => {
+            if let Command::MoveTo(position) = command {
+                current.check_position(&position).map_err(describe_error)?;
+            }
+            current.execute(command);
+
+            Ok(())
+        },
+        Step::Open(_) => unreachable!(),
+    }
+}
+
+fn describe(buffer: &Buffer) -> String {
+    buffer.path.as_ref()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| "buffer".to_string())
+}
+
+// Describes the error using one-based positions, as `goto` takes them.
+fn describe_error(error: Error) -> String {
+    match *error.kind() {
+        ErrorKind::OutOfBounds(position, ref bounds) => format!(
+            "line {} column {} is out of bounds (the file ends at line {} column {})",
+            position.line + 1,
+            position.offset + 1,
+            bounds.end().line + 1,
+            bounds.end().offset + 1
+        ),
+        _ => error.to_string(),
+    }
+}
+
+// Parses a script line, returning None for blank lines and comments.
+fn parse(line: &str) -> Result<Option<Step>, String> {
+    let arguments = tokenize(line)?;
+    let (name, arguments) = match arguments.split_first() {
+        Some((name, arguments)) if !name.starts_with('#') => (name, arguments),
+        _ => return Ok(None),
+    };
+
+    let expected = match name.as_str() {
+        "open" | "insert" => 1,
+        "goto" | "replace" => 2,
+        "save" => 0,
+        _ => return Err(format!("unknown command \"{}\"", name)),
+    };
+    if arguments.len() != expected {
+        return Err(format!("{} takes {} argument(s), but {} were given", name, expected, arguments.len()));
+    }
+
+    let step = match name.as_str() {
+        "open" => Step::Open(PathBuf::from(&arguments[0])),
+        "save" => Step::Save,
+        "insert" => Step::Edit(Command::Insert(arguments[0].clone())),
+        "replace" => Step::Edit(Command::ReplaceAll{
+            needle: arguments[0].clone(),
+            replacement: arguments[1].clone(),
+        }),
+        _ => Step::Edit(Command::MoveTo(Position{
+            line: one_based(&arguments[0])?,
+            offset: one_based(&arguments[1])?,
+        })),
+    };
+
+    Ok(Some(step))
+}
+
+// Converts a one-based line or column to a zero-based offset.
+fn one_based(argument: &str) -> Result<usize, String> {
+    match argument.parse::<usize>() {
+        Ok(value) if value > 0 => Ok(value - 1),
+        _ => Err(format!("\"{}\" isn't a line or column number", argument)),
+    }
+}
+
+// Splits a line into whitespace-separated arguments, handling quotes.
+fn tokenize(line: &str) -> Result<Vec<String>, String> {
+    let mut arguments = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        let quoted = match chars.peek() {
+            Some(&'"') => { chars.next(); true },
+            Some(_) => false,
+            None => return Ok(arguments),
+        };
+
+        let mut argument = String::new();
+        loop {
+            match chars.next() {
+                Some('"') if quoted => break,
+                Some('\\') if quoted => match chars.next() {
+                    Some('n') => argument.push('\n'),
+                    Some('t') => argument.push('\t'),
+                    Some(c @ '"') | Some(c @ '\\') => argument.push(c),
+                    Some(c) => {
+                        argument.push('\\');
+                        argument.push(c);
+                    },
+                    None => return Err("unterminated quote".to_string()),
+                },
+                Some(c) if !quoted && c.is_whitespace() => break,
+                Some(c) => argument.push(c),
+                None if quoted => return Err("unterminated quote".to_string()),
+                None => break,
+            }
+        }
+        arguments.push(argument);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, run, tokenize, Step};
+    use scribe::buffer::{Command, Position};
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn tokenize_handles_quotes_and_escapes() {
+        assert_eq!(
+            tokenize(r#"  replace "a \"b\"\n" c\d  "#).unwrap(),
+            vec!["replace", "a \"b\"\n", "c\\d"]
+        );
+        assert!(tokenize(r#"insert "scribe"#).is_err());
+        assert_eq!(
+            tokenize(r#"open "C:\Users\scribe\My Files\a.txt""#).unwrap(),
+            vec!["open", "C:\\Users\\scribe\\My Files\\a.txt"]
+        );
+    }
+
+    #[test]
+    fn parse_converts_goto_to_zero_based_positions() {
+        assert_eq!(
+            parse("goto 3 1").unwrap(),
+            Some(Step::Edit(Command::MoveTo(Position{ line: 2, offset: 0 })))
+        );
+        assert!(parse("goto 0 1").is_err());
+        assert!(parse("goto 1").is_err());
+        assert!(parse("jump 1 1").is_err());
+        assert_eq!(parse("  # comment").unwrap(), None);
+        assert_eq!(parse("").unwrap(), None);
+    }
+
+    #[test]
+    fn run_applies_commands_to_opened_files() {
+        let path = env::temp_dir().join("scribe_cli_run_test");
+        fs::write(&path, "colour\nlibrary").unwrap();
+
+        let script = format!(
+            "open \"{}\"\ngoto 2 1\ninsert \"scribe \"\nreplace colour color\nsave\n",
+            path.display()
+        );
+        run(&script).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "color\nscribe library");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn run_reports_failures_with_line_numbers() {
+        assert_eq!(run("\nsave").unwrap_err(), "line 2: no file is open");
+
+        let path = env::temp_dir().join("scribe_cli_failure_test");
+        fs::write(&path, "scribe").unwrap();
+
+        let script = format!("open \"{0}\"\ngoto 2 1", path.display());
+        assert_eq!(
+            run(&script).unwrap_err(),
+            "line 2: line 2 column 1 is out of bounds (the file ends at line 1 column 7)"
+        );
+
+        let script = format!("open \"{0}\"\ninsert x\nopen \"{0}\"", path.display());
+        assert!(run(&script).unwrap_err().ends_with("has unsaved changes"));
+
+        let script = format!("open \"{0}\"\ninsert x", path.display());
+        assert!(run(&script).unwrap_err().ends_with("has unsaved changes at the end of the script"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "scribe");
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+//! Abbreviation expansion.
+
+use buffer::{Buffer, Distance, Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+use util::mirror_case;
+
+impl Buffer {
+    /// Replaces the word immediately preceding the cursor with its expansion
+    /// from the buffer's `abbreviations` table, moving the cursor to the end
+    /// of the expansion. Returns whether or not an expansion took place.
+    ///
+    /// Words are runs of alphanumeric characters and underscores. A word that
+    /// isn't in the table is looked up in lower-case, in which case the word's
+    /// case pattern is applied to the expansion (see `util::mirror_case`).
+    /// The expansion is undone as a single operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.abbreviations.insert("teh".to_string(), "the".to_string());
+    /// buffer.insert("Teh");
+    /// buffer.cursor.move_to_end_of_line();
+    ///
+    /// assert!(buffer.expand_abbreviation_before_cursor());
+    /// assert_eq!(buffer.data(), "The");
+    /// assert_eq!(buffer.cursor.offset, 3);
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "Teh");
+    /// ```
+    pub fn expand_abbreviation_before_cursor(&mut self) -> bool {
+        if self.read_only { return false; }
+
+        let word = match self.word_before_cursor() {
+            Some(word) => word,
+            None => return false,
+        };
+        let expansion = match self.abbreviations.get(&word) {
+            Some(expansion) => expansion.clone(),
+            None => match self.abbreviations.get(&word.to_lowercase()) {
+                Some(expansion) => mirror_case(&word, expansion),
+                None => return false,
+            },
+        };
+
+        let end = *self.cursor;
+        let start = Position{
+            line: end.line,
+            offset: end.offset - word.graphemes(true).count(),
+        };
+        let result = self.try_batch(|buffer| {
+            buffer.delete_range(Range::new(start, end));
+            buffer.insert_at(expansion.clone(), start);
+        });
+        if result.is_err() { return false; }
+        self.cursor.move_to(start + Distance::of_str(&expansion));
+
+        true
+    }
+
+    // Finds the run of word characters ending at the cursor.
+    fn word_before_cursor(&self) -> Option<String> {
+        let data = self.data();
+        let line = data.split('\n').nth(self.cursor.line)?;
+        let preceding: Vec<&str> = line.graphemes(true).take(self.cursor.offset).collect();
+        let length = preceding
+            .iter()
+            .rev()
+            .take_while(|grapheme| grapheme.chars().all(|c| c.is_alphanumeric() || c == '_'))
+            .count();
+
+        if length == 0 {
+            None
+        } else {
+            Some(preceding[preceding.len() - length..].concat())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position};
+
+    #[test]
+    fn expand_abbreviation_before_cursor_prefers_exact_matches() {
+        let mut buffer = Buffer::new();
+        buffer.abbreviations.insert("ID".to_string(), "identifier".to_string());
+        buffer.abbreviations.insert("id".to_string(), "index".to_string());
+        buffer.insert("ID");
+        buffer.cursor.move_to(Position{ line: 0, offset: 2 });
+
+        assert!(buffer.expand_abbreviation_before_cursor());
+        assert_eq!(buffer.data(), "identifier");
+    }
+
+    #[test]
+    fn expand_abbreviation_before_cursor_only_considers_the_preceding_word() {
+        let mut buffer = Buffer::new();
+        buffer.abbreviations.insert("teh".to_string(), "the".to_string());
+        buffer.insert("steh teh");
+        buffer.cursor.move_to(Position{ line: 0, offset: 4 });
+        assert!(!buffer.expand_abbreviation_before_cursor());
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 5 });
+        assert!(!buffer.expand_abbreviation_before_cursor());
+        assert_eq!(buffer.data(), "steh teh");
+    }
+
+    #[test]
+    fn expand_abbreviation_before_cursor_handles_multi_line_snippets() {
+        let mut buffer = Buffer::new();
+        buffer.abbreviations.insert("fn".to_string(), "fn () {\n}".to_string());
+        buffer.insert("  fn");
+        buffer.cursor.move_to(Position{ line: 0, offset: 4 });
+
+        assert!(buffer.expand_abbreviation_before_cursor());
+        assert_eq!(buffer.data(), "  fn () {\n}");
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 1 });
+    }
+
+    #[test]
+    fn expand_abbreviation_before_cursor_ignores_read_only_buffers() {
+        let mut buffer = Buffer::new();
+        buffer.abbreviations.insert("teh".to_string(), "the".to_string());
+        buffer.insert("teh");
+        buffer.cursor.move_to(Position{ line: 0, offset: 3 });
+        buffer.read_only = true;
+
+        assert!(!buffer.expand_abbreviation_before_cursor());
+    }
+}
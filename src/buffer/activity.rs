@@ -0,0 +1,115 @@
+//! Edit and cursor movement timestamps.
+
+use buffer::Buffer;
+use std::time::Instant;
+
+impl Buffer {
+    /// The time of the buffer's most recent modification (including those
+    /// made by undoing and redoing), if it's been modified since it was
+    /// created or loaded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// assert!(buffer.last_edited().is_none());
+    ///
+    /// buffer.insert("scribe");
+    /// assert!(buffer.last_edited().is_some());
+    /// ```
+    pub fn last_edited(&self) -> Option<Instant> {
+        self.last_edited
+    }
+
+    /// The time of the buffer's most recent modification or cursor
+    /// movement (see `Cursor::last_moved`), whichever is later.
+    pub fn last_active(&self) -> Option<Instant> {
+        self.last_edited.max(self.cursor.last_moved())
+    }
+
+    /// The time at which the current stretch of editing began: the first
+    /// modification not preceded by another within the buffer's
+    /// `idle_interval`. Returns `None` if the buffer hasn't been modified
+    /// within the interval (i.e. it's idle).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use std::time::Duration;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.idle_interval = Duration::from_secs(60);
+    /// buffer.insert("scribe");
+    /// let started = buffer.editing_since().unwrap();
+    ///
+    /// buffer.insert(" library");
+    /// assert_eq!(buffer.editing_since(), Some(started));
+    /// ```
+    pub fn editing_since(&self) -> Option<Instant> {
+        let last_edited = self.last_edited?;
+        if last_edited.elapsed() > self.idle_interval {
+            return None;
+        }
+
+        self.editing_since
+    }
+
+    // Records a modification, starting a new stretch
+    // of editing if the buffer had gone idle.
+    pub(crate) fn record_edit(&mut self) {
+        let now = Instant::now();
+        if self.editing_since().is_none() {
+            self.editing_since = Some(now);
+        }
+        self.last_edited = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn undo_and_redo_update_last_edited() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        let inserted = buffer.last_edited().unwrap();
+
+        buffer.undo();
+        let undone = buffer.last_edited().unwrap();
+        assert!(undone >= inserted);
+
+        buffer.redo();
+        assert!(buffer.last_edited().unwrap() >= undone);
+    }
+
+    #[test]
+    fn editing_since_restarts_after_the_buffer_goes_idle() {
+        let mut buffer = Buffer::new();
+        buffer.idle_interval = Duration::from_millis(50);
+        buffer.insert("scribe");
+        let started = buffer.editing_since().unwrap();
+
+        thread::sleep(Duration::from_millis(100));
+        assert!(buffer.editing_since().is_none());
+
+        buffer.insert(" library");
+        assert!(buffer.editing_since().unwrap() > started);
+    }
+
+    #[test]
+    fn last_active_includes_cursor_movement() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        let edited = buffer.last_edited();
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 2 });
+        assert!(buffer.last_active() >= edited);
+        assert_eq!(buffer.last_active(), buffer.cursor.last_moved());
+    }
+}
@@ -0,0 +1,131 @@
+//! Moving positions by character counts, across line boundaries.
+
+use buffer::{Buffer, Position};
+use unicode_segmentation::UnicodeSegmentation;
+
+impl Buffer {
+    /// The position `count` characters (i.e. Unicode scalar values, with
+    /// newlines counting as one) after the specified position, as reported
+    /// by tools describing matches using offsets and lengths. Positions
+    /// landing within a grapheme cluster are moved to its end. Returns None
+    /// if either position is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary");
+    ///
+    /// let start = Position{ line: 0, offset: 4 };
+    /// assert_eq!(buffer.position_after(&start, 5), Some(Position{ line: 1, offset: 2 }));
+    /// assert_eq!(buffer.position_after(&start, 11), None);
+    /// ```
+    pub fn position_after(&self, position: &Position, count: usize) -> Option<Position> {
+        if !self.data.borrow().in_bounds(position) {
+            return None;
+        }
+
+        let mut current = *position;
+        let mut remaining = count;
+        loop {
+            let line = self.line(current.line)?;
+            for grapheme in line.graphemes(true).skip(current.offset) {
+                if remaining == 0 {
+                    return Some(current);
+                }
+                remaining = remaining.saturating_sub(grapheme.chars().count());
+                current.offset += 1;
+            }
+
+            // Cross the newline, if there's a line beyond it.
+            if remaining == 0 {
+                return Some(current);
+            } else if current.line + 1 >= self.line_count() {
+                return None;
+            }
+            remaining -= 1;
+            current = Position{ line: current.line + 1, offset: 0 };
+        }
+    }
+
+    /// The position `count` characters before the specified position (see
+    /// `position_after`). Positions landing within a grapheme cluster are
+    /// moved to its start. Returns None if either position is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary");
+    ///
+    /// let end = Position{ line: 1, offset: 2 };
+    /// assert_eq!(buffer.position_before(&end, 5), Some(Position{ line: 0, offset: 4 }));
+    /// assert_eq!(buffer.position_before(&end, 10), None);
+    /// ```
+    pub fn position_before(&self, position: &Position, count: usize) -> Option<Position> {
+        if !self.data.borrow().in_bounds(position) {
+            return None;
+        }
+
+        let mut current = *position;
+        let mut remaining = count;
+        loop {
+            let line = self.line(current.line)?;
+            let graphemes: Vec<&str> = line.graphemes(true).take(current.offset).collect();
+            for grapheme in graphemes.iter().rev() {
+                if remaining == 0 {
+                    return Some(current);
+                }
+                remaining = remaining.saturating_sub(grapheme.chars().count());
+                current.offset -= 1;
+            }
+
+            // Cross the preceding newline, if there is one.
+            if remaining == 0 {
+                return Some(current);
+            } else if current.line == 0 {
+                return None;
+            }
+            remaining -= 1;
+            current.line -= 1;
+            current.offset = self.line(current.line)?.graphemes(true).count();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position};
+
+    #[test]
+    fn positions_within_grapheme_clusters_are_moved_outwards() {
+        let mut buffer = Buffer::new();
+        buffer.insert("a\u{e9}e\u{301}z");
+
+        let start = Position{ line: 0, offset: 0 };
+        assert_eq!(buffer.position_after(&start, 3), Some(Position{ line: 0, offset: 3 }));
+        assert_eq!(buffer.position_after(&start, 5), Some(Position{ line: 0, offset: 4 }));
+
+        let end = Position{ line: 0, offset: 4 };
+        assert_eq!(buffer.position_before(&end, 2), Some(Position{ line: 0, offset: 2 }));
+    }
+
+    #[test]
+    fn positions_can_be_moved_to_the_buffer_bounds() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\n");
+
+        let start = Position{ line: 0, offset: 0 };
+        let end = Position{ line: 1, offset: 0 };
+        assert_eq!(buffer.position_after(&start, 7), Some(end));
+        assert_eq!(buffer.position_before(&end, 7), Some(start));
+        assert_eq!(buffer.position_after(&end, 0), Some(end));
+        assert_eq!(buffer.position_after(&Position{ line: 2, offset: 0 }, 0), None);
+    }
+}
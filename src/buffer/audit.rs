@@ -0,0 +1,165 @@
+//! Append-only buffers, which keep a timestamped record of their insertions.
+
+use buffer::{Buffer, Position, Range};
+use buffer::operation::Operation;
+use std::time::SystemTime;
+
+/// The metadata key under which append-only buffers
+/// record their insertions (see `Buffer::audit_log`).
+pub const AUDIT_LOG_KEY: &str = "audit_log";
+
+/// An insertion made to an append-only buffer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditEntry {
+    pub time: SystemTime,
+    pub position: Position,
+    pub content: String,
+}
+
+impl Buffer {
+    /// The insertions made while the buffer was `append_only`, from the
+    /// oldest to the most recent, stored in its metadata under
+    /// `AUDIT_LOG_KEY`. Insertions that are undone and redone are
+    /// recorded each time they're applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    /// use scribe::{Error, ErrorKind};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.append_only = true;
+    /// buffer.insert("scribe");
+    /// assert_eq!(buffer.audit_log()[0].content, "scribe");
+    ///
+    /// // Content can't be removed, including by undoing insertions.
+    /// let range = Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 3 });
+    /// match buffer.try_delete_range(range) {
+    ///     Err(Error(ErrorKind::AppendOnly, _)) => (),
+    ///     _ => panic!("deletion wasn't refused"),
+    /// }
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "scribe");
+    /// ```
+    pub fn audit_log(&self) -> &[AuditEntry] {
+        self.metadata::<Vec<AuditEntry>>(AUDIT_LOG_KEY).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    // Records an insertion in the audit log, if the buffer is append-only.
+    pub(crate) fn audit_insertion(&mut self, content: &str, position: Position) {
+        if !self.append_only { return; }
+
+        let entry = AuditEntry{ time: SystemTime::now(), position, content: content.to_string() };
+        match self.metadata_mut::<Vec<AuditEntry>>(AUDIT_LOG_KEY) {
+            Some(log) => log.push(entry),
+            None => self.set_metadata(AUDIT_LOG_KEY, vec![entry]),
+        }
+    }
+
+    // Whether removing the range's content should be refused, as is the case
+    // for non-empty ranges in append-only buffers. Refusals are counted, so
+    // that batched changes can detect them and be discarded (see `batch`).
+    pub(crate) fn refuse_removal(&mut self, range: &Range) -> bool {
+        let refused = self.append_only && range.start() != range.end();
+        if refused {
+            self.refused_removals += 1;
+        }
+
+        refused
+    }
+
+    // The number of removals refused so far (see `refuse_removal`).
+    pub(crate) fn refused_removals(&self) -> usize {
+        self.refused_removals
+    }
+
+    // Discards audit log entries beyond the specified length,
+    // recorded for insertions that have since been rolled back.
+    pub(crate) fn truncate_audit_log(&mut self, length: usize) {
+        if let Some(log) = self.metadata_mut::<Vec<AuditEntry>>(AUDIT_LOG_KEY) {
+            log.truncate(length);
+        }
+    }
+
+    // Whether applying (or reversing) the operation would remove content
+    // from the buffer, which append-only buffers don't allow.
+    pub(crate) fn refuses_operation(&self, operation: &dyn Operation, reversed: bool) -> bool {
+        self.append_only && operation.edits().iter().any(|edit| {
+            let removed = if reversed { &edit.inserted } else { &edit.removed };
+            !removed.is_empty()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, Range};
+
+    #[test]
+    fn append_only_buffers_refuse_removals() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary");
+        buffer.append_only = true;
+
+        let range = Range::new(Position{ line: 0, offset: 0 }, Position{ line: 1, offset: 0 });
+        buffer.delete_range(range.clone());
+        buffer.replace(&range, "editor\n");
+        buffer.replace_contents("editor");
+        assert_eq!(buffer.replace_all("scribe", "editor"), 0);
+        assert!(buffer.try_replace(&range, "editor\n").is_err());
+        assert_eq!(buffer.data(), "scribe\nlibrary");
+
+        // Insertions made beforehand aren't recorded, and can't be undone.
+        assert!(buffer.audit_log().is_empty());
+        buffer.undo();
+        assert_eq!(buffer.data(), "scribe\nlibrary");
+    }
+
+    #[test]
+    fn append_only_buffers_roll_back_batched_changes_that_remove_content() {
+        let content = "<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\n";
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        buffer.append_only = true;
+
+        let range = buffer.conflicts()[0].range.clone();
+        assert!(!buffer.accept_ours(&range));
+        assert_eq!(buffer.data(), content);
+
+        buffer.batch(|buffer| {
+            buffer.insert("scribe\n");
+            buffer.delete_range(range.clone());
+        });
+        assert_eq!(buffer.data(), content);
+        assert!(buffer.audit_log().is_empty());
+
+        // Batches without removals are applied.
+        buffer.batch(|buffer| buffer.insert("scribe\n"));
+        assert_eq!(buffer.audit_log().len(), 1);
+    }
+
+    #[test]
+    fn append_only_buffers_allow_restoring_deletions() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe library");
+        buffer.delete_range(Range::new(Position{ line: 0, offset: 6 }, Position{ line: 0, offset: 14 }));
+        buffer.undo();
+        buffer.append_only = true;
+
+        // Redoing the deletion is refused, but undoing it is allowed.
+        buffer.redo();
+        assert_eq!(buffer.data(), "scribe library");
+        buffer.append_only = false;
+        buffer.redo();
+        buffer.append_only = true;
+        buffer.undo();
+        assert_eq!(buffer.data(), "scribe library");
+
+        let log = buffer.audit_log();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].content, " library");
+        assert_eq!(log[0].position, Position{ line: 0, offset: 6 });
+    }
+}
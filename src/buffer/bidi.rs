@@ -0,0 +1,150 @@
+//! Bidirectional text layout, using the Unicode Bidirectional Algorithm.
+
+use buffer::{Buffer, Position};
+use unicode_bidi::BidiInfo;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A sequence of graphemes on a line sharing the same embedding level (and
+/// so, direction), produced by `Buffer::bidi_runs`. Offsets are logical,
+/// based on grapheme clusters (like those of `Position`); the run spans
+/// `start` (inclusive) through `end` (exclusive).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BidiRun {
+    pub start: usize,
+    pub end: usize,
+    pub level: u8,
+}
+
+impl BidiRun {
+    /// Whether the run's graphemes are displayed from right to left.
+    pub fn is_rtl(&self) -> bool {
+        self.level % 2 == 1
+    }
+}
+
+impl Buffer {
+    /// The bidirectional runs on the specified line, in the order they're
+    /// displayed (from left to right). The line's base direction is taken
+    /// from its first strongly directional character. Empty lines have no
+    /// runs. Returns None if the line doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::BidiRun;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("abc \u{5D0}\u{5D1}\u{5D2}");
+    ///
+    /// let runs = buffer.bidi_runs(0).unwrap();
+    /// assert_eq!(runs, vec![
+    ///     BidiRun{ start: 0, end: 4, level: 0 },
+    ///     BidiRun{ start: 4, end: 7, level: 1 },
+    /// ]);
+    /// assert!(runs[1].is_rtl());
+    /// ```
+    pub fn bidi_runs(&self, line: usize) -> Option<Vec<BidiRun>> {
+        let content = self.line(line)?;
+
+        Some(visual_runs(&content))
+    }
+
+    /// The visual column (in graphemes, from the left) at which the grapheme
+    /// at the specified position is displayed, once its line's bidirectional
+    /// runs are reordered (see `bidi_runs`). Frontends can use this to draw
+    /// the cursor over the correct grapheme on mixed-direction lines.
+    /// Positions at the end of a line map to the column following it.
+    /// Returns None if the position is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("abc \u{5D0}\u{5D1}\u{5D2}");
+    ///
+    /// // The first Hebrew letter is displayed rightmost.
+    /// assert_eq!(buffer.visual_offset(&Position{ line: 0, offset: 4 }), Some(6));
+    /// assert_eq!(buffer.visual_offset(&Position{ line: 0, offset: 6 }), Some(4));
+    /// assert_eq!(buffer.visual_offset(&Position{ line: 0, offset: 1 }), Some(1));
+    /// assert_eq!(buffer.visual_offset(&Position{ line: 0, offset: 7 }), Some(7));
+    /// ```
+    pub fn visual_offset(&self, position: &Position) -> Option<usize> {
+        let content = self.line(position.line)?;
+        let length = content.graphemes(true).count();
+        if position.offset > length {
+            return None;
+        }
+
+        let mut column = 0;
+        for run in visual_runs(&content) {
+            if run.start <= position.offset && position.offset < run.end {
+                return Some(if run.is_rtl() {
+                    column + run.end - 1 - position.offset
+                } else {
+                    column + position.offset - run.start
+                });
+            }
+            column += run.end - run.start;
+        }
+
+        Some(length)
+    }
+}
+
+// Resolves the line's embedding levels, returning its runs in visual order.
+fn visual_runs(line: &str) -> Vec<BidiRun> {
+    let info = BidiInfo::new(line, None);
+    let paragraph = match info.paragraphs.first() {
+        Some(paragraph) => paragraph,
+        None => return Vec::new(),
+    };
+
+    // Runs are reported using byte offsets; convert them to graphemes.
+    let boundaries: Vec<usize> = line.grapheme_indices(true).map(|(index, _)| index).collect();
+    let grapheme_offset = |byte_offset: usize| {
+        boundaries.iter().take_while(|&&boundary| boundary < byte_offset).count()
+    };
+
+    let (levels, runs) = info.visual_runs(paragraph, paragraph.range.clone());
+    runs.into_iter()
+        .map(|run| BidiRun{
+            start: grapheme_offset(run.start),
+            end: grapheme_offset(run.end),
+            level: levels[run.start].number(),
+        })
+        .filter(|run| run.start < run.end)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{BidiRun, Buffer, Position};
+
+    #[test]
+    fn bidi_runs_are_ordered_visually_in_right_to_left_lines() {
+        let mut buffer = Buffer::new();
+        buffer.insert("\u{5E9}\u{5DC}\u{5D5}\u{5DD} abc!\n");
+
+        assert_eq!(buffer.bidi_runs(0).unwrap(), vec![
+            BidiRun{ start: 8, end: 9, level: 1 },
+            BidiRun{ start: 5, end: 8, level: 2 },
+            BidiRun{ start: 0, end: 5, level: 1 },
+        ]);
+        assert_eq!(buffer.bidi_runs(1), Some(Vec::new()));
+        assert_eq!(buffer.bidi_runs(2), None);
+    }
+
+    #[test]
+    fn visual_offset_counts_graphemes() {
+        let mut buffer = Buffer::new();
+        buffer.insert("\u{5D0}\u{5B8}\u{5D1} नी");
+
+        assert_eq!(buffer.visual_offset(&Position{ line: 0, offset: 0 }), Some(3));
+        assert_eq!(buffer.visual_offset(&Position{ line: 0, offset: 3 }), Some(0));
+        assert_eq!(buffer.visual_offset(&Position{ line: 0, offset: 5 }), None);
+    }
+}
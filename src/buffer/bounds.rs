@@ -0,0 +1,142 @@
+//! Bounds-checking, producing errors that describe the valid positions.
+
+use buffer::{Buffer, GapBuffer, Position, Range};
+use errors::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+impl Buffer {
+    /// Checks that the position is within the buffer, returning an
+    /// `OutOfBounds` error otherwise. The error carries the position, along
+    /// with the range of valid positions on its line (or in the buffer, if
+    /// the line doesn't exist).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    /// use scribe::{Error, ErrorKind};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary");
+    ///
+    /// assert!(buffer.check_position(&Position{ line: 1, offset: 7 }).is_ok());
+    /// match buffer.check_position(&Position{ line: 0, offset: 7 }) {
+    ///     Err(Error(ErrorKind::OutOfBounds(position, bounds), _)) => {
+    ///         assert_eq!(position, Position{ line: 0, offset: 7 });
+    ///         assert_eq!(bounds, Range::new(
+    ///             Position{ line: 0, offset: 0 },
+    ///             Position{ line: 0, offset: 6 }
+    ///         ));
+    ///     },
+    ///     _ => panic!("position wasn't reported as out of bounds"),
+    /// }
+    /// ```
+    pub fn check_position(&self, position: &Position) -> Result<()> {
+        check_position(&self.data.borrow(), position)
+    }
+
+    /// Like `delete`, but returns an `OutOfBounds` error (see
+    /// `check_position`) if there's nothing after the cursor to delete,
+    /// or an `AppendOnly` error if the buffer is `append_only`.
+    pub fn try_delete(&mut self) -> Result<()> {
+        let range = self.deletion_range();
+        self.try_delete_range(range)
+    }
+
+    /// Like `delete_range`, but returns an `OutOfBounds` error (see
+    /// `check_position`) for the first of the range's positions that's
+    /// out of bounds, rather than ignoring the range, or an `AppendOnly`
+    /// error if the range isn't empty and the buffer is `append_only`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    ///
+    /// let range = Range::new(Position{ line: 0, offset: 3 }, Position{ line: 1, offset: 0 });
+    /// assert!(buffer.try_delete_range(range).is_err());
+    /// assert_eq!(buffer.data(), "scribe");
+    /// ```
+    pub fn try_delete_range(&mut self, range: Range) -> Result<()> {
+        self.check_range(&range)?;
+        self.try_batch(|buffer| buffer.delete_range(range))
+    }
+
+    /// Like `replace`, but returns an `OutOfBounds` error (see
+    /// `check_position`) for the first of the range's positions that's
+    /// out of bounds, rather than ignoring the range, or an `AppendOnly`
+    /// error if the range isn't empty and the buffer is `append_only`.
+    pub fn try_replace(&mut self, range: &Range, data: &str) -> Result<()> {
+        self.check_range(range)?;
+        self.try_batch(|buffer| buffer.replace(range, data))
+    }
+
+    fn check_range(&self, range: &Range) -> Result<()> {
+        self.check_position(&range.start())?;
+        self.check_position(&range.end())
+    }
+}
+
+// Returns an error describing the valid positions, if the position isn't one.
+pub(crate) fn check_position(data: &GapBuffer, position: &Position) -> Result<()> {
+    if data.in_bounds(position) {
+        Ok(())
+    } else {
+        Err(ErrorKind::OutOfBounds(*position, valid_range(data, position.line)).into())
+    }
+}
+
+// The range of valid positions on the line, or in the
+// entire buffer, if the line doesn't exist.
+pub(crate) fn valid_range(data: &GapBuffer, line: usize) -> Range {
+    let last_line = data.line_count() - 1;
+    let (start, end_line) = if line <= last_line {
+        (Position{ line, offset: 0 }, line)
+    } else {
+        (Position::new(), last_line)
+    };
+
+    // Read the end line, which ends at the following newline, if there is one.
+    let line_start = data.line_start(end_line).unwrap_or(0);
+    let line_end = data.line_start(end_line + 1).map(|offset| offset - 1).unwrap_or(usize::MAX);
+    let (first_half, second_half) = data.byte_slices(line_start, line_end);
+    let length = format!("{}{}", first_half, second_half).graphemes(true).count();
+
+    Range::new(start, Position{ line: end_line, offset: length })
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, Range};
+    use errors::{Error, ErrorKind};
+
+    #[test]
+    fn lines_beyond_the_buffer_report_the_buffer_bounds() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nnी");
+
+        match buffer.check_position(&Position{ line: 2, offset: 0 }) {
+            Err(Error(ErrorKind::OutOfBounds(_, bounds), _)) => assert_eq!(bounds, Range::new(
+                Position{ line: 0, offset: 0 },
+                Position{ line: 1, offset: 1 }
+            )),
+            _ => panic!("position wasn't reported as out of bounds"),
+        }
+    }
+
+    #[test]
+    fn try_delete_reports_the_end_of_the_buffer() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        buffer.cursor.move_to(Position{ line: 0, offset: 5 });
+
+        assert!(buffer.try_delete().is_ok());
+        assert_eq!(buffer.data(), "scrib");
+        assert!(buffer.try_delete().is_err());
+    }
+}
@@ -0,0 +1,134 @@
+//! Raw, read-only access to a buffer's content.
+
+use buffer::{Buffer, GapBuffer, Range};
+use std::cell::Ref;
+
+/// A read-only view of a buffer's content as UTF-8 encoded bytes, produced by
+/// `Buffer::bytes` and `Buffer::byte_slice`. The content isn't copied; since
+/// it's stored in a gap buffer, it's made available as two contiguous slices.
+/// The buffer can't be modified while the view exists.
+pub struct ByteSlice<'a> {
+    data: Ref<'a, GapBuffer>,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> ByteSlice<'a> {
+    /// The content, split into the portions found on either side of the
+    /// buffer's gap. Either (or both) may be empty.
+    pub fn as_slices(&self) -> (&[u8], &[u8]) {
+        let (first, second) = self.data.byte_slices(self.start, self.end);
+
+        (first.as_bytes(), second.as_bytes())
+    }
+
+    /// Iterates over the content's bytes.
+    pub fn iter(&self) -> impl Iterator<Item=u8> + '_ {
+        let (first, second) = self.as_slices();
+
+        first.iter().chain(second.iter()).cloned()
+    }
+
+    /// The length of the content, in bytes.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether the view is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Buffer {
+    /// A read-only view of the buffer's entire content, as bytes, useful for
+    /// hashing or writing it elsewhere without copying it into a `String`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    ///
+    /// let bytes = buffer.bytes();
+    /// assert_eq!(bytes.len(), 6);
+    /// assert_eq!(bytes.iter().collect::<Vec<u8>>(), b"scribe");
+    /// ```
+    pub fn bytes(&self) -> ByteSlice<'_> {
+        let data = self.data.borrow();
+        let end = data.len();
+
+        ByteSlice{ data, start: 0, end }
+    }
+
+    /// Like `bytes`, but limited to the specified range.
+    /// Returns None if the range is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary");
+    ///
+    /// let range = Range::new(
+    ///     Position{ line: 0, offset: 3 },
+    ///     Position{ line: 1, offset: 3 }
+    /// );
+    /// let bytes = buffer.byte_slice(&range).unwrap();
+    /// let (first, second) = bytes.as_slices();
+    /// assert_eq!([first, second].concat(), b"ibe\nlib");
+    /// ```
+    pub fn byte_slice(&self, range: &Range) -> Option<ByteSlice<'_>> {
+        let (start, end) = self.byte_range(range)?;
+
+        Some(ByteSlice{ data: self.data.borrow(), start, end })
+    }
+
+    // Maps the range to byte offsets in the buffer's data.
+    pub(crate) fn byte_range(&self, range: &Range) -> Option<(usize, usize)> {
+        let data = self.data.borrow();
+
+        Some((data.byte_offset(&range.start())?, data.byte_offset(&range.end())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, Range};
+
+    #[test]
+    fn byte_slice_spans_the_gap() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe library");
+        buffer.cursor.move_to(Position{ line: 0, offset: 6 });
+        buffer.insert(",");
+
+        let range = Range::new(Position{ line: 0, offset: 4 }, Position{ line: 0, offset: 9 });
+        let bytes = buffer.byte_slice(&range).unwrap();
+        assert_eq!(bytes.as_slices(), (&b"be,"[..], &b" l"[..]));
+        assert_eq!(bytes.len(), 5);
+    }
+
+    #[test]
+    fn byte_slice_returns_none_for_out_of_bounds_ranges() {
+        let buffer = Buffer::new();
+        let range = Range::new(Position::new(), Position{ line: 0, offset: 1 });
+
+        assert!(buffer.byte_slice(&range).is_none());
+        assert!(buffer.bytes().is_empty());
+    }
+
+    #[test]
+    fn byte_slice_uses_byte_lengths_for_multi_byte_characters() {
+        let mut buffer = Buffer::new();
+        buffer.insert("ñandú");
+
+        let range = Range::new(Position::new(), Position{ line: 0, offset: 5 });
+        assert_eq!(buffer.byte_slice(&range).unwrap().len(), 7);
+    }
+}
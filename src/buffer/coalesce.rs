@@ -0,0 +1,219 @@
+//! Rate-limited notifications, merging rapid-fire buffer modifications.
+
+use buffer::{Buffer, Edit, Range};
+use buffer::selection::shift_position;
+use std::cell::{Cell, RefCell};
+use std::time::{Duration, Instant};
+
+/// A batch of modifications, reported to observers registered using
+/// `Buffer::on_coalesced_change`. The `range` spans all of the content
+/// the modifications inserted, and the points at which they removed
+/// content, in terms of the buffer's current content.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoalescedChange {
+    pub range: Range,
+    pub edits: usize,
+}
+
+// An observer, along with the modifications it has yet to be notified of.
+pub(crate) struct CoalescedObserver {
+    interval: Duration,
+    last_notified: Cell<Option<Instant>>,
+    pending: RefCell<Option<CoalescedChange>>,
+    callback: Box<dyn Fn(&CoalescedChange)>,
+}
+
+impl CoalescedObserver {
+    // Merges the edit into the pending change, notifying the
+    // observer if it hasn't been notified within its interval.
+    fn add(&self, edit: &Edit) {
+        let change = match self.pending.borrow_mut().take() {
+            Some(change) => CoalescedChange{
+                range: merge(&change.range, edit),
+                edits: change.edits + 1,
+            },
+            None => CoalescedChange{ range: affected_range(edit), edits: 1 },
+        };
+        *self.pending.borrow_mut() = Some(change);
+
+        let due = self.last_notified.get().is_none_or(|last| last.elapsed() >= self.interval);
+        if due {
+            self.flush();
+        }
+    }
+
+    fn flush(&self) {
+        let change = self.pending.borrow_mut().take();
+        if let Some(change) = change {
+            self.last_notified.set(Some(Instant::now()));
+            (self.callback)(&change);
+        }
+    }
+}
+
+impl Buffer {
+    /// Registers an observer, called with batches of modifications made to
+    /// the buffer (see `CoalescedChange`), no more than once per `interval`.
+    /// Useful when the buffer is modified in rapid succession (e.g. while
+    /// streaming text into it, or replaying edits), for observers that don't
+    /// need to see each individual edit. Returns an ID that can be used to
+    /// remove the observer (see `remove_change_observer`).
+    ///
+    /// The first modification following a quiet interval is reported
+    /// immediately; those that follow are held until the interval has
+    /// elapsed and another modification is made, or until `flush_changes`
+    /// is called, which should be done periodically (e.g. once per frame).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{CoalescedChange, Position, Range};
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use std::time::Duration;
+    ///
+    /// let changes = Rc::new(RefCell::new(Vec::new()));
+    /// let observed_changes = changes.clone();
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.on_coalesced_change(Duration::from_secs(60), move |change: &CoalescedChange| {
+    ///     observed_changes.borrow_mut().push(change.clone())
+    /// });
+    ///
+    /// buffer.insert("scribe");
+    /// for character in "library ".chars().rev() {
+    ///     buffer.insert(character.to_string());
+    /// }
+    /// buffer.flush_changes();
+    ///
+    /// assert_eq!(*changes.borrow(), vec![
+    ///     CoalescedChange{
+    ///         range: Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 6 }),
+    ///         edits: 1,
+    ///     },
+    ///     CoalescedChange{
+    ///         range: Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 8 }),
+    ///         edits: 8,
+    ///     },
+    /// ]);
+    /// ```
+    pub fn on_coalesced_change<F>(&mut self, interval: Duration, observer: F) -> usize
+        where F: Fn(&CoalescedChange) + 'static
+    {
+        let id = self.next_observer_id;
+        self.next_observer_id += 1;
+        self.coalesced_observers.push((id, CoalescedObserver{
+            interval,
+            last_notified: Cell::new(None),
+            pending: RefCell::new(None),
+            callback: Box::new(observer),
+        }));
+
+        id
+    }
+
+    /// Notifies observers registered using `on_coalesced_change` of any
+    /// modifications being held, regardless of when they were last notified.
+    pub fn flush_changes(&self) {
+        for (_, observer) in &self.coalesced_observers {
+            observer.flush();
+        }
+    }
+
+    // Adds the edit to each coalesced observer's pending change.
+    pub(crate) fn notify_coalesced_observers(&self, edit: &Edit) {
+        for (_, observer) in &self.coalesced_observers {
+            observer.add(edit);
+        }
+    }
+}
+
+// The range of the buffer's current content affected by the edit.
+fn affected_range(edit: &Edit) -> Range {
+    if edit.removed.is_empty() || !edit.inserted.is_empty() {
+        edit.range.clone()
+    } else {
+        Range::new(edit.range.start(), edit.range.start())
+    }
+}
+
+// Moves the range to account for the edit, and extends it to cover the edit.
+fn merge(range: &Range, edit: &Edit) -> Range {
+    // Replacements (e.g. reloads) span the buffer's entire content.
+    if !edit.removed.is_empty() && !edit.inserted.is_empty() {
+        return edit.range.clone();
+    }
+
+    let affected = affected_range(edit);
+    let mut start = shift_position(range.start(), edit, false);
+    let mut end = shift_position(range.end(), edit, false);
+    if affected.start() < start {
+        start = affected.start();
+    }
+    if affected.end() > end {
+        end = affected.end();
+    }
+
+    Range::new(start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, CoalescedChange, Position, Range};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    fn observe(buffer: &mut Buffer, interval: Duration) -> (usize, Rc<RefCell<Vec<CoalescedChange>>>) {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let observed_changes = changes.clone();
+        let id = buffer.on_coalesced_change(interval, move |change| {
+            observed_changes.borrow_mut().push(change.clone())
+        });
+
+        (id, changes)
+    }
+
+    #[test]
+    fn changes_are_reported_immediately_without_an_interval() {
+        let mut buffer = Buffer::new();
+        let (_, changes) = observe(&mut buffer, Duration::from_secs(0));
+        buffer.insert("scribe");
+        buffer.insert("library ");
+
+        assert_eq!(changes.borrow().len(), 2);
+        assert_eq!(changes.borrow()[1].edits, 1);
+    }
+
+    #[test]
+    fn merged_ranges_account_for_deletions() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary\ntoolkit");
+        let (_, changes) = observe(&mut buffer, Duration::from_secs(60));
+        buffer.insert("x");
+        buffer.cursor.move_to(Position{ line: 2, offset: 0 });
+        buffer.insert("new ");
+        buffer.delete_range(Range::new(Position{ line: 0, offset: 1 }, Position{ line: 1, offset: 0 }));
+        buffer.flush_changes();
+
+        let changes = changes.borrow();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1], CoalescedChange{
+            range: Range::new(Position{ line: 0, offset: 1 }, Position{ line: 1, offset: 4 }),
+            edits: 2,
+        });
+    }
+
+    #[test]
+    fn removed_observers_are_not_notified() {
+        let mut buffer = Buffer::new();
+        let (id, changes) = observe(&mut buffer, Duration::from_secs(60));
+        buffer.insert("scribe");
+        buffer.insert("library ");
+
+        assert!(buffer.remove_change_observer(id));
+        buffer.flush_changes();
+        assert_eq!(changes.borrow().len(), 1);
+    }
+}
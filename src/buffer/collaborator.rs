@@ -0,0 +1,183 @@
+//! Presence of remote participants editing the same content.
+
+use buffer::{Buffer, Edit, Position, Range};
+use buffer::selection::shift_position;
+use errors::*;
+
+/// A remote participant's cursor and selection, tracked by the buffer (see
+/// `Buffer::set_collaborator`) so that they follow the content they're on
+/// as local edits are made.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collaborator {
+    pub id: usize,
+    pub label: String,
+    pub cursor: Position,
+    pub selection: Option<Range>,
+}
+
+impl Collaborator {
+    pub fn new<T: Into<String>>(id: usize, label: T, cursor: Position) -> Collaborator {
+        Collaborator{ id, label: label.into(), cursor, selection: None }
+    }
+
+    // The overlay styles used for the collaborator's cursor and selection.
+    pub(crate) fn cursor_style(&self) -> String {
+        format!("collaborator.{}.cursor", self.id)
+    }
+
+    pub(crate) fn selection_style(&self) -> String {
+        format!("collaborator.{}.selection", self.id)
+    }
+}
+
+impl Buffer {
+    /// Adds a collaborator, or updates the one with the same ID. Their cursor
+    /// and selection are moved along with local edits, and are included in
+    /// the buffer's `overlays` at the `OverlayLayer::Collaborators` priority:
+    /// the cursor as an empty span styled "collaborator.<id>.cursor", and the
+    /// selection styled "collaborator.<id>.selection". Returns an
+    /// `OutOfBounds` error (see `check_position`) if any of their positions
+    /// are out of bounds, leaving the collaborators unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Collaborator, LineRange, Position};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("library");
+    /// buffer.set_collaborator(Collaborator::new(7, "Ada", Position{ line: 0, offset: 3 })).unwrap();
+    ///
+    /// buffer.insert("scribe ");
+    /// assert_eq!(buffer.collaborators()[0].cursor, Position{ line: 0, offset: 10 });
+    ///
+    /// let spans = buffer.overlays(&LineRange::new(0, 1));
+    /// assert_eq!(spans[0].styles, vec!["collaborator.7.cursor"]);
+    /// assert_eq!(spans[0].range.start(), spans[0].range.end());
+    ///
+    /// assert!(buffer.remove_collaborator(7));
+    /// assert!(buffer.collaborators().is_empty());
+    /// ```
+    pub fn set_collaborator(&mut self, collaborator: Collaborator) -> Result<()> {
+        self.check_position(&collaborator.cursor)?;
+        if let Some(ref selection) = collaborator.selection {
+            self.check_position(&selection.start())?;
+            self.check_position(&selection.end())?;
+        }
+
+        match self.collaborators.iter_mut().find(|existing| existing.id == collaborator.id) {
+            Some(existing) => *existing = collaborator,
+            None => self.collaborators.push(collaborator),
+        }
+
+        Ok(())
+    }
+
+    /// Removes the collaborator with the specified ID,
+    /// returning whether or not they existed.
+    pub fn remove_collaborator(&mut self, id: usize) -> bool {
+        let count = self.collaborators.len();
+        self.collaborators.retain(|collaborator| collaborator.id != id);
+
+        self.collaborators.len() != count
+    }
+
+    /// The buffer's collaborators, in the order they were added.
+    pub fn collaborators(&self) -> &[Collaborator] {
+        &self.collaborators
+    }
+
+    // Adjusts the collaborators' positions to account for the edit.
+    pub(crate) fn shift_collaborators(&mut self, edit: &Edit) {
+        for collaborator in &mut self.collaborators {
+            collaborator.cursor = shift_position(collaborator.cursor, edit, false);
+            collaborator.selection = collaborator.selection.as_ref().map(|selection| {
+                let start = shift_position(selection.start(), edit, false);
+                let end = if selection.end() == selection.start() {
+                    start
+                } else {
+                    shift_position(selection.end(), edit, true)
+                };
+
+                Range::new(start, end)
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Collaborator, LineRange, OverlayLayer, Overlay, Position, Range};
+
+    #[test]
+    fn collaborator_selections_follow_their_content() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary");
+        let mut collaborator = Collaborator::new(1, "Ada", Position{ line: 1, offset: 7 });
+        collaborator.selection = Some(Range::new(
+            Position{ line: 1, offset: 0 },
+            Position{ line: 1, offset: 7 }
+        ));
+        buffer.set_collaborator(collaborator).unwrap();
+
+        buffer.delete_range(Range::new(Position{ line: 0, offset: 3 }, Position{ line: 1, offset: 0 }));
+        assert_eq!(buffer.collaborators()[0].cursor, Position{ line: 0, offset: 10 });
+        assert_eq!(buffer.collaborators()[0].selection, Some(Range::new(
+            Position{ line: 0, offset: 3 },
+            Position{ line: 0, offset: 10 }
+        )));
+
+        buffer.undo();
+        assert_eq!(buffer.collaborators()[0].cursor, Position{ line: 1, offset: 7 });
+    }
+
+    #[test]
+    fn collaborators_follow_replaced_contents() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary");
+        buffer.set_collaborator(Collaborator::new(1, "Ada", Position{ line: 1, offset: 3 })).unwrap();
+
+        buffer.replace_contents("a\nscribe\nlibrary");
+        assert_eq!(buffer.collaborators()[0].cursor, Position{ line: 2, offset: 3 });
+    }
+
+    #[test]
+    fn set_collaborator_rejects_out_of_bounds_positions() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        buffer.set_collaborator(Collaborator::new(1, "Ada", Position{ line: 0, offset: 0 })).unwrap();
+
+        let mut collaborator = Collaborator::new(1, "Ada", Position{ line: 0, offset: 2 });
+        collaborator.selection = Some(Range::new(
+            Position{ line: 0, offset: 2 },
+            Position{ line: 1, offset: 0 }
+        ));
+        assert!(buffer.set_collaborator(collaborator).is_err());
+        assert_eq!(buffer.collaborators()[0].cursor, Position{ line: 0, offset: 0 });
+    }
+
+    #[test]
+    fn collaborators_are_drawn_above_other_overlays() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        let mut collaborator = Collaborator::new(3, "Ada", Position{ line: 0, offset: 6 });
+        collaborator.selection = Some(Range::new(
+            Position{ line: 0, offset: 0 },
+            Position{ line: 0, offset: 6 }
+        ));
+        buffer.set_collaborator(collaborator).unwrap();
+        buffer.add_overlay_provider(OverlayLayer::Collaborators.priority() + 1, |_, _| vec![
+            Overlay::new(Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 6 }), "top"),
+        ]);
+        buffer.add_overlay_provider(OverlayLayer::Collaborators.priority(), |_, _| vec![
+            Overlay::new(Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 6 }), "bottom"),
+        ]);
+
+        let spans = buffer.overlays(&LineRange::new(0, 1));
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].styles, vec!["bottom", "collaborator.3.selection", "top"]);
+        assert_eq!(spans[1].range, Range::new(Position{ line: 0, offset: 6 }, Position{ line: 0, offset: 6 }));
+        assert_eq!(spans[1].styles, vec!["collaborator.3.cursor"]);
+    }
+}
@@ -0,0 +1,163 @@
+//! Buffer operations represented as values.
+
+use buffer::{Buffer, Position, Range};
+
+/// An editing or cursor movement operation that can be run using
+/// `Buffer::execute`. Representing operations as values lets frontends bind
+/// them to configurable keymaps, record and replay sequences of them (e.g.
+/// as macros), and drive buffers from command streams in tests.
+///
+/// Each variant corresponds to the buffer or cursor method of the same name.
+/// Operations involving I/O (e.g. saving) aren't included, since their
+/// failures need to be handled by the caller.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Insert(String),
+    Delete,
+    DeleteRange(Range),
+    Replace { range: Range, data: String },
+    ReplaceAll { needle: String, replacement: String },
+    ReplaceAllPreservingCase { needle: String, replacement: String },
+    ReplaceContents(String),
+    IncrementNumber(i64),
+    ExpandAbbreviationBeforeCursor,
+    EnsureTrailingNewline,
+    SetPending(String),
+    CommitPending,
+    CancelPending,
+    AcceptOurs(Range),
+    AcceptTheirs(Range),
+    AcceptBoth(Range),
+    StartOperationGroup,
+    EndOperationGroup,
+    Undo,
+    Redo,
+    MoveTo(Position),
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    MoveToStartOfLine,
+    MoveToEndOfLine,
+    MoveToStartOfLogicalLine,
+    MoveToEndOfLogicalLine,
+    MoveToFirstLine,
+    MoveToLastLine,
+    MoveToStartOfBuffer,
+    MoveToEndOfBuffer,
+    MoveToNextWord,
+    MoveToPreviousWord,
+    MoveToNextCell,
+    MoveToPreviousCell,
+}
+
+impl Buffer {
+    /// Runs the command. Any result the corresponding method
+    /// returns (e.g. whether the cursor could be moved) is discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Command, Position};
+    ///
+    /// let commands = vec![
+    ///     Command::Insert("scribe".to_string()),
+    ///     Command::MoveToEndOfLine,
+    ///     Command::Insert(" library".to_string()),
+    ///     Command::MoveTo(Position{ line: 0, offset: 0 }),
+    /// ];
+    ///
+    /// let mut buffer = Buffer::new();
+    /// for command in commands {
+    ///     buffer.execute(command);
+    /// }
+    ///
+    /// assert_eq!(buffer.data(), "scribe library");
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 0 });
+    /// ```
+    pub fn execute(&mut self, command: Command) {
+        match command {
+            Command::Insert(data) => self.insert(data),
+            Command::Delete => self.delete(),
+            Command::DeleteRange(range) => self.delete_range(range),
+            Command::Replace{ range, data } => self.replace(&range, &data),
+            Command::ReplaceAll{ needle, replacement } => {
+                self.replace_all(&needle, &replacement);
+            },
+            Command::ReplaceAllPreservingCase{ needle, replacement } => {
+                self.replace_all_preserving_case(&needle, &replacement);
+            },
+            Command::ReplaceContents(content) => self.replace_contents(&content),
+            Command::IncrementNumber(delta) => { self.increment_number(delta); },
+            Command::ExpandAbbreviationBeforeCursor => { self.expand_abbreviation_before_cursor(); },
+            Command::EnsureTrailingNewline => { self.ensure_trailing_newline(); },
+            Command::SetPending(content) => self.set_pending(&content),
+            Command::CommitPending => { self.commit_pending(); },
+            Command::CancelPending => { self.cancel_pending(); },
+            Command::AcceptOurs(range) => { self.accept_ours(&range); },
+            Command::AcceptTheirs(range) => { self.accept_theirs(&range); },
+            Command::AcceptBoth(range) => { self.accept_both(&range); },
+            Command::StartOperationGroup => self.start_operation_group(),
+            Command::EndOperationGroup => self.end_operation_group(),
+            Command::Undo => self.undo(),
+            Command::Redo => self.redo(),
+            Command::MoveTo(position) => { self.cursor.move_to(position); },
+            Command::MoveUp => self.cursor.move_up(),
+            Command::MoveDown => self.cursor.move_down(),
+            Command::MoveLeft => self.cursor.move_left(),
+            Command::MoveRight => self.cursor.move_right(),
+            Command::MoveToStartOfLine => self.cursor.move_to_start_of_line(),
+            Command::MoveToEndOfLine => self.cursor.move_to_end_of_line(),
+            Command::MoveToStartOfLogicalLine => self.cursor.move_to_start_of_logical_line(),
+            Command::MoveToEndOfLogicalLine => self.cursor.move_to_end_of_logical_line(),
+            Command::MoveToFirstLine => self.cursor.move_to_first_line(),
+            Command::MoveToLastLine => self.cursor.move_to_last_line(),
+            Command::MoveToStartOfBuffer => self.cursor.move_to_start_of_buffer(),
+            Command::MoveToEndOfBuffer => self.cursor.move_to_end_of_buffer(),
+            Command::MoveToNextWord => self.move_to_next_word(),
+            Command::MoveToPreviousWord => self.move_to_previous_word(),
+            Command::MoveToNextCell => { self.move_to_next_cell(); },
+            Command::MoveToPreviousCell => { self.move_to_previous_cell(); },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Command;
+    use buffer::{Buffer, Position};
+
+    #[test]
+    fn execute_produces_the_same_result_as_direct_calls() {
+        let mut direct = Buffer::new();
+        direct.insert("scribe\nlibrary");
+        direct.cursor.move_down();
+        direct.start_operation_group();
+        direct.delete();
+        direct.insert("L");
+        direct.end_operation_group();
+        direct.replace_all("scribe", "amp");
+
+        let mut executed = Buffer::new();
+        for command in [
+            Command::Insert("scribe\nlibrary".to_string()),
+            Command::MoveDown,
+            Command::StartOperationGroup,
+            Command::Delete,
+            Command::Insert("L".to_string()),
+            Command::EndOperationGroup,
+            Command::ReplaceAll{ needle: "scribe".to_string(), replacement: "amp".to_string() },
+        ] {
+            executed.execute(command);
+        }
+
+        assert_eq!(executed.data(), direct.data());
+        assert_eq!(*executed.cursor, *direct.cursor);
+
+        executed.execute(Command::Undo);
+        executed.execute(Command::Undo);
+        assert_eq!(executed.data(), "scribe\nlibrary");
+        assert_eq!(*executed.cursor, Position{ line: 1, offset: 0 });
+    }
+}
@@ -0,0 +1,241 @@
+//! Merge conflict detection and resolution.
+
+use buffer::{Buffer, Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A region of the buffer delimited by merge conflict markers, from the start
+/// of its `<<<<<<<` line through the end of its `>>>>>>>` line (including
+/// its newline). Each side's content includes its trailing newline. The
+/// `base` content is only present in conflicts using the diff3 style,
+/// which include a `|||||||` section.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    pub range: Range,
+    pub ours: String,
+    pub base: Option<String>,
+    pub theirs: String,
+}
+
+// The section of a conflict being parsed.
+enum Section {
+    Ours,
+    Base,
+    Theirs,
+}
+
+impl Buffer {
+    /// Finds the merge conflicts in the buffer. Conflicts missing any of
+    /// their markers are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\n<<<<<<< HEAD\nlibrary\n=======\neditor\n>>>>>>> branch\n");
+    ///
+    /// let conflicts = buffer.conflicts();
+    /// assert_eq!(conflicts.len(), 1);
+    /// assert_eq!(conflicts[0].ours, "library\n");
+    /// assert_eq!(conflicts[0].theirs, "editor\n");
+    /// assert_eq!(conflicts[0].base, None);
+    /// assert_eq!(
+    ///     conflicts[0].range,
+    ///     Range::new(Position{ line: 1, offset: 0 }, Position{ line: 6, offset: 0 })
+    /// );
+    /// ```
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        find_conflicts(&self.data())
+    }
+
+    /// Whether the buffer's file contained merge conflicts when it was loaded.
+    pub fn loaded_with_conflicts(&self) -> bool {
+        self.loaded_with_conflicts
+    }
+
+    /// Resolves the conflict at the start of `range` (see `conflicts`) by
+    /// replacing it with our side's content. Returns false if there's no
+    /// conflict there, or the buffer is read-only or append-only. The cursor is moved to the start of the resolved
+    /// content, and the change can be undone as a single operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("<<<<<<< HEAD\nlibrary\n=======\neditor\n>>>>>>> branch\n");
+    ///
+    /// let range = buffer.conflicts()[0].range.clone();
+    /// assert!(buffer.accept_ours(&range));
+    /// assert_eq!(buffer.data(), "library\n");
+    /// ```
+    pub fn accept_ours(&mut self, range: &Range) -> bool {
+        self.resolve_conflict(range, |conflict| conflict.ours.clone())
+    }
+
+    /// Like `accept_ours`, but keeps their side's content.
+    pub fn accept_theirs(&mut self, range: &Range) -> bool {
+        self.resolve_conflict(range, |conflict| conflict.theirs.clone())
+    }
+
+    /// Like `accept_ours`, but keeps both sides' content: ours, then theirs.
+    pub fn accept_both(&mut self, range: &Range) -> bool {
+        self.resolve_conflict(range, |conflict| format!("{}{}", conflict.ours, conflict.theirs))
+    }
+
+    fn resolve_conflict<F>(&mut self, range: &Range, resolution: F) -> bool
+        where F: Fn(&Conflict) -> String
+    {
+        if self.read_only { return false; }
+
+        let conflict = match self.conflicts().into_iter().find(|c| c.range.includes(&range.start())) {
+            Some(conflict) => conflict,
+            None => return false,
+        };
+        let mut content = resolution(&conflict);
+
+        // Don't add a newline if the conflict ended without one.
+        if conflict.range.end().offset > 0 && content.ends_with('\n') {
+            content.pop();
+        }
+
+        let start = conflict.range.start();
+        let result = self.try_batch(|buffer| {
+            buffer.delete_range(conflict.range.clone());
+            buffer.insert_at(content, start);
+        });
+        if result.is_err() { return false; }
+        self.cursor.move_to(start);
+
+        true
+    }
+}
+
+pub(crate) fn find_conflicts(data: &str) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    let mut current: Option<(Position, Section, Conflict)> = None;
+
+    for (line_number, line) in data.split_inclusive('\n').enumerate() {
+        let content = line.trim_end_matches(['\r', '\n']);
+
+        if is_marker(content, "<<<<<<<") {
+            // Start over if we encounter a new conflict before the last has ended.
+            let conflict = Conflict{
+                range: Range::new(Position::new(), Position::new()),
+                ours: String::new(),
+                base: None,
+                theirs: String::new(),
+            };
+            current = Some((Position{ line: line_number, offset: 0 }, Section::Ours, conflict));
+            continue;
+        }
+
+        let (start, mut section, mut conflict) = match current.take() {
+            Some(state) => state,
+            None => continue,
+        };
+
+        match section {
+            Section::Ours if is_marker(content, "|||||||") => {
+                section = Section::Base;
+                conflict.base = Some(String::new());
+            },
+            Section::Ours | Section::Base if content == "=======" => section = Section::Theirs,
+            Section::Theirs if is_marker(content, ">>>>>>>") => {
+                let end = if line.ends_with('\n') {
+                    Position{ line: line_number + 1, offset: 0 }
+                } else {
+                    Position{ line: line_number, offset: line.graphemes(true).count() }
+                };
+                conflict.range = Range::new(start, end);
+                conflicts.push(conflict);
+                continue;
+            },
+            Section::Ours => conflict.ours.push_str(line),
+            Section::Base => conflict.base.get_or_insert_with(String::new).push_str(line),
+            Section::Theirs => conflict.theirs.push_str(line),
+        }
+
+        current = Some((start, section, conflict));
+    }
+
+    conflicts
+}
+
+// Markers are followed by a label (e.g. "<<<<<<< HEAD"), if anything.
+fn is_marker(line: &str, marker: &str) -> bool {
+    line.strip_prefix(marker).is_some_and(|label| label.is_empty() || label.starts_with(' '))
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, Range};
+    #[cfg(feature = "fs")]
+    use std::env;
+    #[cfg(feature = "fs")]
+    use std::fs;
+
+    #[test]
+    fn conflicts_include_diff3_base_content() {
+        let mut buffer = Buffer::new();
+        buffer.insert("<<<<<<< ours\na\n||||||| base\nb\n=======\nc\n>>>>>>> theirs");
+
+        let conflicts = buffer.conflicts();
+        assert_eq!(conflicts[0].ours, "a\n");
+        assert_eq!(conflicts[0].base, Some("b\n".to_string()));
+        assert_eq!(conflicts[0].theirs, "c\n");
+        assert_eq!(conflicts[0].range.end(), Position{ line: 6, offset: 14 });
+    }
+
+    #[test]
+    fn conflicts_ignore_incomplete_and_misplaced_markers() {
+        let mut buffer = Buffer::new();
+        buffer.insert("=======\n<<<<<<<\na\n=======\nb\n<<<<<<<x\n<<<<<<<\nc\n=======\n>>>>>>>\n");
+
+        let conflicts = buffer.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].ours, "c\n");
+        assert_eq!(conflicts[0].theirs, "");
+        assert_eq!(conflicts[0].range.start(), Position{ line: 6, offset: 0 });
+    }
+
+    #[test]
+    fn accept_both_is_undone_as_a_single_operation() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\n<<<<<<<\na\n=======\nb\n>>>>>>>");
+        let range = Range::new(Position{ line: 3, offset: 0 }, Position{ line: 3, offset: 0 });
+
+        assert!(buffer.accept_both(&range));
+        assert_eq!(buffer.data(), "scribe\na\nb");
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 0 });
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "scribe\n<<<<<<<\na\n=======\nb\n>>>>>>>");
+    }
+
+    #[test]
+    fn accept_theirs_returns_false_outside_of_conflicts() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\n");
+
+        assert!(!buffer.accept_theirs(&Range::new(Position::new(), Position::new())));
+        assert_eq!(buffer.data(), "scribe\n");
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn loaded_with_conflicts_reflects_the_file_contents() {
+        let path = env::temp_dir().join("scribe_loaded_with_conflicts");
+        fs::write(&path, "<<<<<<<\na\n=======\nb\n>>>>>>>\n").unwrap();
+
+        let buffer = Buffer::from_file(&path).unwrap();
+        assert!(buffer.loaded_with_conflicts());
+        assert!(!Buffer::from_file(&env::current_dir().unwrap().join("tests/sample/file"))
+            .unwrap()
+            .loaded_with_conflicts());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,170 @@
+//! Text surrounding the cursor, for completion engines and assistants.
+
+use buffer::{Buffer, Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The lines surrounding a buffer's cursor, produced by
+/// `Buffer::context_around_cursor`. The cursor sits `cursor_offset` bytes
+/// into `text`, which starts at `start` in the buffer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CursorContext {
+    pub text: String,
+    pub cursor_offset: usize,
+    pub start: Position,
+}
+
+impl CursorContext {
+    /// The context's text preceding the cursor.
+    pub fn before_cursor(&self) -> &str {
+        &self.text[..self.cursor_offset]
+    }
+
+    /// The context's text following the cursor.
+    pub fn after_cursor(&self) -> &str {
+        &self.text[self.cursor_offset..]
+    }
+
+    /// Shortens the context to at most `length` bytes, dividing them evenly
+    /// between either side of the cursor (giving any that one side doesn't
+    /// need to the other). Text is only removed in whole graphemes, so the
+    /// context may end up slightly shorter than requested. The `start` and
+    /// `cursor_offset` fields are updated to match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary");
+    /// buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+    ///
+    /// let mut context = buffer.context_around_cursor(1, 0);
+    /// context.truncate(6);
+    /// assert_eq!(context.text, "be\nlib");
+    /// assert_eq!(context.cursor_offset, 3);
+    /// assert_eq!(context.start, Position{ line: 0, offset: 4 });
+    /// ```
+    pub fn truncate(&mut self, length: usize) {
+        if self.text.len() <= length {
+            return;
+        }
+
+        // Split the budget, giving any surplus to the other side.
+        let available_after = self.text.len() - self.cursor_offset;
+        let mut after = available_after.min(length / 2);
+        let before = self.cursor_offset.min(length - after);
+        after = available_after.min(length - before);
+
+        // Snap the bounds inwards, to the nearest grapheme boundaries.
+        let boundaries: Vec<usize> = self.text
+            .grapheme_indices(true)
+            .map(|(index, _)| index)
+            .chain(Some(self.text.len()))
+            .collect();
+        let start = boundaries.iter()
+            .find(|&&boundary| boundary >= self.cursor_offset - before)
+            .cloned()
+            .unwrap_or(self.cursor_offset);
+        let end = boundaries.iter()
+            .rev()
+            .find(|&&boundary| boundary <= self.cursor_offset + after)
+            .cloned()
+            .unwrap_or(self.cursor_offset);
+
+        // Move the start past the removed text.
+        let removed = &self.text[..start];
+        match removed.rfind('\n') {
+            Some(index) => {
+                self.start.line += removed.matches('\n').count();
+                self.start.offset = removed[index + 1..].graphemes(true).count();
+            },
+            None => self.start.offset += removed.graphemes(true).count(),
+        }
+
+        self.text = self.text[start..end].to_string();
+        self.cursor_offset -= start;
+    }
+}
+
+impl Buffer {
+    /// The text of the cursor's line, along with up to `lines_before` lines
+    /// preceding it and `lines_after` lines following it, and the cursor's
+    /// location within that text. Only those lines are read from the buffer.
+    /// See `CursorContext::truncate` to limit the context's length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary\neditor\ntoolkit");
+    /// buffer.cursor.move_to(Position{ line: 2, offset: 3 });
+    ///
+    /// let context = buffer.context_around_cursor(1, 5);
+    /// assert_eq!(context.text, "library\neditor\ntoolkit");
+    /// assert_eq!(context.before_cursor(), "library\nedi");
+    /// assert_eq!(context.after_cursor(), "tor\ntoolkit");
+    /// assert_eq!(context.start, Position{ line: 1, offset: 0 });
+    /// ```
+    pub fn context_around_cursor(&self, lines_before: usize, lines_after: usize) -> CursorContext {
+        let cursor = *self.cursor;
+        let start = Position{ line: cursor.line.saturating_sub(lines_before), offset: 0 };
+        let last_line = (cursor.line + lines_after).min(self.line_count() - 1);
+        let end = Position{
+            line: last_line,
+            offset: self.line(last_line).map(|line| line.graphemes(true).count()).unwrap_or(0),
+        };
+
+        let before = self.read(&Range::new(start, cursor)).unwrap_or_default();
+        let after = self.read(&Range::new(cursor, end)).unwrap_or_default();
+
+        CursorContext{
+            cursor_offset: before.len(),
+            text: before + &after,
+            start,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position};
+
+    #[test]
+    fn context_around_cursor_stops_at_the_buffer_bounds() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\n");
+
+        let context = buffer.context_around_cursor(3, 3);
+        assert_eq!(context.text, "scribe\n");
+        assert_eq!(context.cursor_offset, 0);
+    }
+
+    #[test]
+    fn truncate_keeps_whole_graphemes() {
+        let mut buffer = Buffer::new();
+        buffer.insert("n\u{ee}\u{300}ce");
+        buffer.cursor.move_to(Position{ line: 0, offset: 2 });
+
+        let mut context = buffer.context_around_cursor(0, 0);
+        context.truncate(4);
+        assert_eq!(context.before_cursor(), "");
+        assert_eq!(context.after_cursor(), "ce");
+        assert_eq!(context.start, Position{ line: 0, offset: 2 });
+    }
+
+    #[test]
+    fn truncate_gives_unused_space_to_the_other_side() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+
+        let mut context = buffer.context_around_cursor(0, 0);
+        context.truncate(4);
+        assert_eq!(context.text, "scri");
+        assert_eq!(context.cursor_offset, 0);
+    }
+}
@@ -2,17 +2,44 @@
 use std::ops::{Deref, DerefMut};
 use std::rc::Rc;
 use std::cell::RefCell;
-use buffer::{Position, GapBuffer};
+use std::time::Instant;
+use buffer::{Position, GapBuffer, Range};
+use buffer::bounds::valid_range;
+use errors::*;
+use buffer::logical_line::logical_line_range;
 use unicode_segmentation::UnicodeSegmentation;
 
 /// Read-only wrapper for a `Position`, to allow field level access to a
 /// buffer's cursor while simultaneously enforcing bounds-checking when
 /// updating its value.
+///
+/// The cursor's `style` determines whether it sits between characters or on
+/// them, which affects where it can be moved (see `CursorStyle`). Whether it
+/// can be placed after the last character on a line can also be controlled
+/// independently of its style (see `set_past_end_of_line`).
 #[derive(Clone)]
 pub struct Cursor {
     pub data: Rc<RefCell<GapBuffer>>,
     pub position: Position,
     sticky_offset: usize,
+    moved: Option<Instant>,
+    style: CursorStyle,
+    past_end_of_line: bool,
+}
+
+/// How a cursor relates to the characters around it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CursorStyle {
+    /// The cursor sits between characters (e.g. in insert mode), and can be
+    /// placed after the last character on a line. Typically drawn as a bar.
+    #[default]
+    Bar,
+
+    /// The cursor sits on a character (e.g. in a modal editor's normal mode),
+    /// so by default, it can't be placed after the last character on a line,
+    /// unless the line is empty. Typically drawn as a block covering the
+    /// character.
+    Block,
 }
 
 impl Deref for Cursor {
@@ -35,7 +62,86 @@ impl Cursor {
         Cursor{
             data,
             position,
-            sticky_offset: position.offset
+            sticky_offset: position.offset,
+            moved: None,
+            style: CursorStyle::default(),
+            past_end_of_line: true,
+        }
+    }
+
+    /// The cursor's style, which is `CursorStyle::Bar` by default.
+    pub fn style(&self) -> CursorStyle {
+        self.style
+    }
+
+    /// Changes the cursor's style, allowing or preventing it from being placed
+    /// after the last character on a line accordingly (see
+    /// `set_past_end_of_line`). Cursors changed to `CursorStyle::Block` that
+    /// are after the last character on a line are moved onto it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{CursorStyle, Position};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary");
+    /// buffer.cursor.move_to_end_of_line();
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 6 });
+    ///
+    /// buffer.cursor.set_style(CursorStyle::Block);
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 5 });
+    ///
+    /// buffer.cursor.move_down();
+    /// buffer.cursor.move_to_end_of_line();
+    /// assert_eq!(*buffer.cursor, Position{ line: 1, offset: 6 });
+    /// assert!(!buffer.cursor.move_to(Position{ line: 1, offset: 7 }));
+    /// ```
+    pub fn set_style(&mut self, style: CursorStyle) {
+        self.style = style;
+        self.set_past_end_of_line(style == CursorStyle::Bar);
+    }
+
+    /// Whether the cursor can be placed after the last character on a line.
+    pub fn past_end_of_line(&self) -> bool {
+        self.past_end_of_line
+    }
+
+    /// Allows or prevents the cursor from being placed after the last
+    /// character on a line, regardless of its style. Movements that would
+    /// otherwise end there (e.g. `move_to_end_of_line`) stop on the last
+    /// character instead, and cursors already there are moved onto it.
+    /// Changing the cursor's style resets this (see `set_style`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{CursorStyle, Position};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    ///
+    /// // Let a block cursor reach the end of the line, as
+    /// // a modal editor might in its visual mode.
+    /// buffer.cursor.set_style(CursorStyle::Block);
+    /// buffer.cursor.set_past_end_of_line(true);
+    /// buffer.cursor.move_to_end_of_line();
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 6 });
+    ///
+    /// buffer.cursor.set_past_end_of_line(false);
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 5 });
+    /// ```
+    pub fn set_past_end_of_line(&mut self, allowed: bool) {
+        self.past_end_of_line = allowed;
+
+        let mut position = self.position;
+        while position.offset > 0 && !self.in_bounds(&position) {
+            position.offset -= 1;
+        }
+        if position != self.position {
+            self.move_to(position);
         }
     }
 
@@ -63,7 +169,10 @@ impl Cursor {
     /// assert_eq!(buffer.cursor.offset, 2);
     /// ```
     pub fn move_to(&mut self, position: Position) -> bool {
-        if self.data.borrow().in_bounds(&position) {
+        if self.in_bounds(&position) {
+            if position != self.position {
+                self.moved = Some(Instant::now());
+            }
             self.position = position;
 
             // Remember this offset so that we can try
@@ -75,6 +184,58 @@ impl Cursor {
         false
     }
 
+    /// Like `move_to`, but returns an `OutOfBounds` error carrying the
+    /// position and the range of positions the cursor can occupy on its line
+    /// (or in the buffer, if the line doesn't exist), rather than `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{CursorStyle, Position};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    /// buffer.cursor.set_style(CursorStyle::Block);
+    ///
+    /// let error = buffer.cursor.try_move_to(Position{ line: 0, offset: 6 }).unwrap_err();
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "position 0:6 is out of bounds (valid positions are 0:0 to 0:5)"
+    /// );
+    /// ```
+    pub fn try_move_to(&mut self, position: Position) -> Result<()> {
+        if self.move_to(position) {
+            return Ok(());
+        }
+
+        let bounds = valid_range(&self.data.borrow(), position.line);
+        let end = Position{ line: bounds.end().line, offset: self.last_offset(bounds.end().offset) };
+
+        Err(ErrorKind::OutOfBounds(position, Range::new(bounds.start(), end)).into())
+    }
+
+    /// The time of the cursor's most recent movement using one of its
+    /// `move_*` methods, if it's been moved since it was created. Movement
+    /// caused by buffer modifications is included, making it suitable for
+    /// resetting a blinking cursor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    /// assert!(buffer.cursor.last_moved().is_none());
+    ///
+    /// buffer.cursor.move_right();
+    /// assert!(buffer.cursor.last_moved().is_some());
+    /// ```
+    pub fn last_moved(&self) -> Option<Instant> {
+        self.moved
+    }
+
     /// Decrements the cursor line. The location is bounds-checked against
     /// the data and the cursor will not be updated if it is out-of-bounds.
     pub fn move_up(&mut self) {
@@ -86,13 +247,11 @@ impl Cursor {
 
         // Try moving to the same offset on the line above, falling back to its EOL.
         if !self.move_to(new_position) {
-            let mut target_offset = 0;
-            for (line_number, line) in self.data.borrow().to_string().lines().enumerate() {
-                if line_number == target_line {
-                    target_offset = line.graphemes(true).count();
-                }
-            }
-            self.move_to(Position{ line: target_line, offset: target_offset });
+            let length = match self.data.borrow().line_length(target_line) {
+                Some(length) => length,
+                None => return,
+            };
+            self.move_to(Position{ line: target_line, offset: self.last_offset(length) });
 
             // Moving the position successfully updates the sticky offset, but we
             // haven't actually moved to where we really wanted to go (offset-wise).
@@ -109,13 +268,11 @@ impl Cursor {
 
         // Try moving to the same offset on the line below, falling back to its EOL.
         if !self.move_to(new_position) {
-            let mut target_offset = 0;
-            for (line_number, line) in self.data.borrow().to_string().lines().enumerate() {
-                if line_number == target_line {
-                    target_offset = line.graphemes(true).count();
-                }
-            }
-            self.move_to(Position{ line: target_line, offset: target_offset });
+            let length = match self.data.borrow().line_length(target_line) {
+                Some(length) => length,
+                None => return,
+            };
+            self.move_to(Position{ line: target_line, offset: self.last_offset(length) });
 
             // Moving the position successfully updates the sticky offset, but we
             // haven't actually moved to where we really wanted to go (offset-wise).
@@ -147,30 +304,47 @@ impl Cursor {
         self.move_to(new_position);
     }
 
-    /// Moves the cursor offset to after the last character on the current line.
+    /// Moves the cursor offset to after the last character on the current line
+    /// (or onto it, for `CursorStyle::Block` cursors). When on the empty line
+    /// following a trailing newline, this is offset 0.
     pub fn move_to_end_of_line(&mut self) {
-        let data = self.data.borrow().to_string();
-        let current_line = data.lines().nth(self.line);
-        if let Some(line) = current_line {
-            let new_position = Position{ line: self.line, offset: line.graphemes(true).count() };
+        let length = self.data.borrow().line_length(self.line);
+        if let Some(length) = length {
+            let new_position = Position{ line: self.line, offset: self.last_offset(length) };
             self.move_to(new_position);
         }
     }
 
+    /// Moves the cursor to the start of the first line in the logical line
+    /// (see `Buffer::logical_line`) that includes the current line.
+    pub fn move_to_start_of_logical_line(&mut self) {
+        let data = self.data.borrow().to_string();
+        if let Some(range) = logical_line_range(&data, self.line) {
+            self.move_to(Position{ line: range.start(), offset: 0 });
+        }
+    }
+
+    /// Moves the cursor to the end of the last line in the logical line
+    /// (see `Buffer::logical_line`) that includes the current line.
+    pub fn move_to_end_of_logical_line(&mut self) {
+        let data = self.data.borrow().to_string();
+        if let Some(range) = logical_line_range(&data, self.line) {
+            let line = range.end() - 1;
+            let length = self.data.borrow().line_length(line).unwrap_or(0);
+            self.move_to(Position{ line, offset: self.last_offset(length) });
+        }
+    }
+
     /// Moves the cursor to the last line in the buffer.
     pub fn move_to_last_line(&mut self) {
         // Figure out the number and length of the last line.
-        let mut line = 0;
-        let mut length = 0;
-        for c in self.data.borrow().to_string().graphemes(true) {
-            if c == "\n" {
-                line += 1;
-                length = 0;
-            } else {
-                length += 1;
-            }
-        }
+        let (line, length) = {
+            let data = self.data.borrow();
+            let line = data.last_line();
+            (line, data.line_length(line).unwrap_or(0))
+        };
 
+        let length = self.last_offset(length);
         let target_position =
             if length < self.sticky_offset {
                 // Current offset is beyond the last line's length; move to the end of it.
@@ -185,15 +359,9 @@ impl Cursor {
     /// Moves the cursor to the first line in the buffer.
     pub fn move_to_first_line(&mut self) {
         // Figure out the length of the first line.
-        let length = self
-            .data
-            .borrow()
-            .to_string()
-            .lines()
-            .nth(0)
-            .map(|line| line.graphemes(true).count())
-            .unwrap_or(0);
+        let length = self.data.borrow().line_length(0).unwrap_or(0);
 
+        let length = self.last_offset(length);
         let target_position =
             if length < self.sticky_offset {
                 // Current offset is beyond the first line's length; move to the end of it.
@@ -204,11 +372,194 @@ impl Cursor {
             };
         self.move_to(target_position);
     }
+
+    /// Moves the cursor to the start of the buffer.
+    pub fn move_to_start_of_buffer(&mut self) {
+        self.move_to(Position::new());
+    }
+
+    /// Moves the cursor to the end of the buffer: after its last character
+    /// (or onto it, for `CursorStyle::Block` cursors).
+    pub fn move_to_end_of_buffer(&mut self) {
+        let line = self.data.borrow().last_line();
+        let length = self.data.borrow().line_length(line).unwrap_or(0);
+        self.move_to(Position{ line, offset: self.last_offset(length) });
+    }
+
+    /// Moves the cursor to the start of the next word, where words are runs
+    /// of alphanumeric characters and underscores, or of other non-whitespace
+    /// characters (e.g. operators). Moves to the end of the buffer if there
+    /// are no more words.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("let total=1;\n  x");
+    ///
+    /// buffer.cursor.move_to_next_word();
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 4 });
+    /// buffer.cursor.move_to_next_word();
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 9 });
+    /// buffer.cursor.move_to_next_word();
+    /// buffer.cursor.move_to_next_word();
+    /// buffer.cursor.move_to_next_word();
+    /// assert_eq!(*buffer.cursor, Position{ line: 1, offset: 2 });
+    ///
+    /// buffer.cursor.move_to_previous_word();
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 11 });
+    /// ```
+    pub fn move_to_next_word(&mut self) {
+        match self.next_word(|lines| self.classified_graphemes(lines)) {
+            Some(position) => { self.move_to(position); },
+            None => self.move_to_end_of_buffer(),
+        }
+    }
+
+    // The start of the next word, using the classes produced for a range of
+    // lines to delimit words. Lines are classified in windows that double in
+    // size, starting with the cursor's, so that the rest of the buffer is
+    // only examined when there are no words near the cursor.
+    pub(crate) fn next_word<F>(&self, classify: F) -> Option<Position>
+        where F: Fn(::std::ops::Range<usize>) -> Vec<(Position, GraphemeClass)>
+    {
+        let last_line = self.data.borrow().last_line();
+        let mut count = 1;
+
+        loop {
+            let end = (self.position.line + count).min(last_line + 1);
+            let graphemes = classify(self.position.line..end);
+            let mut index = graphemes.iter().take_while(|&&(position, _)| position < self.position).count();
+
+            if let Some(&(_, class)) = graphemes.get(index) {
+                if class != GraphemeClass::Whitespace {
+                    while graphemes.get(index).is_some_and(|&(_, next)| next == class) {
+                        index += 1;
+                    }
+                }
+            }
+            while graphemes.get(index).is_some_and(|&(_, class)| class == GraphemeClass::Whitespace) {
+                index += 1;
+            }
+
+            if let Some(&(position, _)) = graphemes.get(index) {
+                return Some(position);
+            }
+            if end > last_line { return None; }
+            count *= 2;
+        }
+    }
+
+    /// Moves the cursor to the start of the current word, or the previous
+    /// word if it's already at the start of one (see `move_to_next_word`).
+    /// Moves to the start of the buffer if there are no preceding words.
+    pub fn move_to_previous_word(&mut self) {
+        match self.previous_word(|lines| self.classified_graphemes(lines)) {
+            Some(position) => { self.move_to(position); },
+            None => self.move_to_start_of_buffer(),
+        }
+    }
+
+    // Like next_word, for the start of the current or previous word,
+    // classifying windows of lines ending with the cursor's.
+    pub(crate) fn previous_word<F>(&self, classify: F) -> Option<Position>
+        where F: Fn(::std::ops::Range<usize>) -> Vec<(Position, GraphemeClass)>
+    {
+        let mut count = 1;
+
+        loop {
+            let start = (self.position.line + 1).saturating_sub(count);
+            let graphemes = classify(start..self.position.line + 1);
+            let mut index = graphemes.iter().take_while(|&&(position, _)| position < self.position).count();
+
+            while index > 0 && graphemes[index - 1].1 == GraphemeClass::Whitespace {
+                index -= 1;
+            }
+            if index > 0 {
+                let class = graphemes[index - 1].1;
+                while index > 0 && graphemes[index - 1].1 == class {
+                    index -= 1;
+                }
+            }
+
+            if index > 0 || start == 0 {
+                return graphemes.get(index).map(|&(position, _)| position);
+            }
+            count *= 2;
+        }
+    }
+
+    // Whether the position is in bounds, and on a character (or an
+    // empty line), if the cursor can't be placed past the end of a line.
+    fn in_bounds(&self, position: &Position) -> bool {
+        let data = self.data.borrow();
+        if !data.in_bounds(position) {
+            return false;
+        }
+
+        self.past_end_of_line || position.offset == 0 ||
+            data.in_bounds(&Position{ line: position.line, offset: position.offset + 1 })
+    }
+
+    // The furthest offset the cursor can occupy on a line of the specified length.
+    fn last_offset(&self, length: usize) -> usize {
+        if self.past_end_of_line {
+            length
+        } else {
+            length.saturating_sub(1)
+        }
+    }
+
+    // The position and class of each grapheme on the lines in the range,
+    // including their line breaks.
+    pub(crate) fn classified_graphemes(&self, lines: ::std::ops::Range<usize>) -> Vec<(Position, GraphemeClass)> {
+        let data = self.data.borrow().lines_within(lines.clone());
+        let mut position = Position{ line: lines.start, offset: 0 };
+
+        data.graphemes(true).map(|grapheme| {
+            let current = position;
+            position = if grapheme == "\n" {
+                Position{ line: position.line + 1, offset: 0 }
+            } else {
+                Position{ line: position.line, offset: position.offset + 1 }
+            };
+
+            (current, GraphemeClass::of(grapheme))
+        }).collect()
+    }
+}
+
+// The kinds of grapheme delimiting words. Words are runs of
+// graphemes sharing a class, other than whitespace.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum GraphemeClass {
+    Whitespace,
+    Word,
+    Punctuation,
+
+    // Part of the group of lexemes with the specified index.
+    #[cfg(feature = "syntax")]
+    Token(usize),
+}
+
+impl GraphemeClass {
+    pub(crate) fn of(grapheme: &str) -> GraphemeClass {
+        if grapheme.chars().all(char::is_whitespace) {
+            GraphemeClass::Whitespace
+        } else if grapheme.chars().any(|c| c.is_alphanumeric() || c == '_') {
+            GraphemeClass::Word
+        } else {
+            GraphemeClass::Punctuation
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use buffer::{Cursor, GapBuffer, Position};
+    use buffer::{Cursor, CursorStyle, GapBuffer, Position};
     use std::rc::Rc;
     use std::cell::RefCell;
 
@@ -317,6 +668,31 @@ mod tests {
         assert_eq!(cursor.offset, 15);
     }
 
+    #[test]
+    fn logical_line_motions_span_continued_lines() {
+        let buffer = Rc::new(RefCell::new(GapBuffer::new(
+            "x = 1\nresult = run(\"नी\",\n    2)\ny = 2".to_string()
+        )));
+        let mut cursor = Cursor::new(buffer, Position{ line: 1, offset: 3 });
+        cursor.move_to_end_of_logical_line();
+        assert_eq!(*cursor, Position{ line: 2, offset: 6 });
+
+        cursor.move_to_start_of_logical_line();
+        assert_eq!(*cursor, Position{ line: 1, offset: 0 });
+    }
+
+    #[test]
+    fn move_to_end_of_line_resets_persisted_offset_on_trailing_empty_line() {
+        let buffer = Rc::new(RefCell::new(GapBuffer::new("first\nsecond line\n".to_string())));
+        let mut cursor = Cursor::new(buffer, Position{ line: 1, offset: 8 });
+        cursor.move_down();
+        assert_eq!(*cursor, Position{ line: 2, offset: 0 });
+
+        cursor.move_to_end_of_line();
+        cursor.move_up();
+        assert_eq!(*cursor, Position{ line: 1, offset: 0 });
+    }
+
     #[test]
     fn move_up_does_nothing_if_at_the_start_of_line() {
         let buffer = Rc::new(RefCell::new(GapBuffer::new("This is a test.".to_string())));
@@ -335,6 +711,48 @@ mod tests {
         assert_eq!(cursor.offset, 0);
     }
 
+    #[test]
+    fn buffer_motions_move_to_the_start_and_end_of_the_data() {
+        let buffer = Rc::new(RefCell::new(GapBuffer::new("scribe\nनी library".to_string())));
+        let mut cursor = Cursor::new(buffer, Position{ line: 0, offset: 3 });
+
+        cursor.move_to_end_of_buffer();
+        assert_eq!(*cursor, Position{ line: 1, offset: 9 });
+        cursor.move_to_start_of_buffer();
+        assert_eq!(*cursor, Position{ line: 0, offset: 0 });
+    }
+
+    #[test]
+    fn word_motions_stop_at_the_buffer_edges() {
+        let buffer = Rc::new(RefCell::new(GapBuffer::new("  scribe  ".to_string())));
+        let mut cursor = Cursor::new(buffer, Position{ line: 0, offset: 1 });
+
+        cursor.move_to_previous_word();
+        assert_eq!(*cursor, Position{ line: 0, offset: 0 });
+        cursor.move_to_next_word();
+        assert_eq!(*cursor, Position{ line: 0, offset: 2 });
+        cursor.move_to_next_word();
+        assert_eq!(*cursor, Position{ line: 0, offset: 10 });
+        cursor.move_to_previous_word();
+        assert_eq!(*cursor, Position{ line: 0, offset: 2 });
+    }
+
+    #[test]
+    fn word_motions_cross_runs_of_blank_lines() {
+        let data = format!("scribe{}library", "\n".repeat(20));
+        let buffer = Rc::new(RefCell::new(GapBuffer::new(data)));
+        let mut cursor = Cursor::new(buffer, Position{ line: 0, offset: 2 });
+
+        cursor.move_to_next_word();
+        assert_eq!(*cursor, Position{ line: 20, offset: 0 });
+        cursor.move_to_next_word();
+        assert_eq!(*cursor, Position{ line: 20, offset: 7 });
+        cursor.move_to_previous_word();
+        assert_eq!(*cursor, Position{ line: 20, offset: 0 });
+        cursor.move_to_previous_word();
+        assert_eq!(*cursor, Position{ line: 0, offset: 0 });
+    }
+
     #[test]
     fn move_to_last_line_counts_graphemes_as_a_single_offset() {
         let buffer = Rc::new(RefCell::new(GapBuffer::new(
@@ -401,4 +819,46 @@ mod tests {
         assert_eq!(cursor.line, 0);
         assert_eq!(cursor.offset, 5);
     }
+
+    #[test]
+    fn block_cursors_move_onto_the_last_character_of_shorter_lines() {
+        let buffer = Rc::new(RefCell::new(GapBuffer::new("Another line\nनीx\n\nlast".to_string())));
+        let mut cursor = Cursor::new(buffer, Position{ line: 0, offset: 10 });
+        cursor.set_style(CursorStyle::Block);
+
+        cursor.move_down();
+        assert_eq!(*cursor, Position{ line: 1, offset: 1 });
+        cursor.move_right();
+        assert_eq!(*cursor, Position{ line: 1, offset: 1 });
+        cursor.move_down();
+        assert_eq!(*cursor, Position{ line: 2, offset: 0 });
+        cursor.move_to_end_of_buffer();
+        assert_eq!(*cursor, Position{ line: 3, offset: 3 });
+    }
+
+    #[test]
+    fn bar_cursors_can_be_placed_after_the_last_character() {
+        let buffer = Rc::new(RefCell::new(GapBuffer::new("first\nlast".to_string())));
+        let mut cursor = Cursor::new(buffer, Position{ line: 0, offset: 0 });
+        cursor.set_style(CursorStyle::Block);
+        cursor.set_style(CursorStyle::Bar);
+
+        assert!(cursor.move_to(Position{ line: 0, offset: 5 }));
+        cursor.move_to_last_line();
+        assert_eq!(*cursor, Position{ line: 1, offset: 4 });
+    }
+
+    #[test]
+    fn bar_cursors_can_be_kept_off_the_end_of_lines() {
+        let buffer = Rc::new(RefCell::new(GapBuffer::new("first\nlast".to_string())));
+        let mut cursor = Cursor::new(buffer, Position{ line: 1, offset: 4 });
+        cursor.set_past_end_of_line(false);
+        assert_eq!(*cursor, Position{ line: 1, offset: 3 });
+        assert_eq!(cursor.style(), CursorStyle::Bar);
+
+        cursor.move_to_end_of_buffer();
+        assert_eq!(*cursor, Position{ line: 1, offset: 3 });
+        cursor.set_style(CursorStyle::Bar);
+        assert!(cursor.past_end_of_line());
+    }
 }
@@ -0,0 +1,227 @@
+//! Position and buffer summaries, suitable for status bars.
+
+use buffer::{Buffer, LineEnding, Position};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Describes a position in a buffer in the various units status bars
+/// display. All values are zero-based, and offsets are relative to the
+/// start of the buffer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PositionDescription {
+    pub line: usize,
+    /// The on-screen column, with tabs extending to the next multiple of
+    /// the buffer's `tab_width`, and all other graphemes one column wide.
+    pub visual_column: usize,
+    pub byte_offset: usize,
+    pub char_offset: usize,
+    /// How far through the buffer the position's line is, as a
+    /// percentage; 0 on the first line and 100 on the last.
+    pub percentage: usize,
+}
+
+/// The information a status bar typically displays about a buffer,
+/// produced by `Buffer::status`. The cursor's line and column are
+/// zero-based.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BufferStatus {
+    /// The buffer's file name, or "untitled" if it doesn't have a path.
+    pub label: String,
+    /// The name of the buffer's syntax definition (e.g. "Rust"), if any.
+    pub syntax: Option<String>,
+    pub modified: bool,
+    pub read_only: bool,
+    pub line_ending: LineEnding,
+    pub cursor: Position,
+    /// The cursor's on-screen column (see `PositionDescription`).
+    pub visual_column: usize,
+    pub line_count: usize,
+    /// How far through the buffer the cursor's line is (see `PositionDescription`).
+    pub percentage: usize,
+}
+
+impl Buffer {
+    /// Summarizes the buffer for display in a status bar (see
+    /// `BufferStatus`). Unlike `describe_position`, this only segments the
+    /// cursor's line into grapheme clusters; the rest of the buffer is just
+    /// scanned for line breaks to count its lines, so that it's cheap enough
+    /// to call whenever the status bar is drawn.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{LineEnding, Position};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\n\tlibrary\n");
+    /// buffer.cursor.move_to(Position{ line: 1, offset: 2 });
+    ///
+    /// let status = buffer.status();
+    /// assert_eq!(status.label, "untitled");
+    /// assert!(status.modified);
+    /// assert!(!status.read_only);
+    /// assert_eq!(status.line_ending, LineEnding::Lf);
+    /// assert_eq!(status.cursor, Position{ line: 1, offset: 2 });
+    /// assert_eq!(status.visual_column, 5);
+    /// assert_eq!(status.line_count, 3);
+    /// assert_eq!(status.percentage, 50);
+    /// ```
+    pub fn status(&self) -> BufferStatus {
+        let cursor = *self.cursor;
+        let line = self.line(cursor.line).unwrap_or_default();
+        let visual_column = line.graphemes(true)
+            .take(cursor.offset)
+            .fold(0, |column, grapheme| advance_column(column, grapheme, self.tab_width));
+
+        let line_count = self.line_count();
+        let percentage = (cursor.line * 100).checked_div(line_count - 1).unwrap_or(100);
+
+        #[cfg(feature = "syntax")]
+        let syntax = self.syntax_definition.as_ref().map(|def| def.name.clone());
+        #[cfg(not(feature = "syntax"))]
+        let syntax = None;
+
+        BufferStatus{
+            label: self.file_name().unwrap_or_else(|| "untitled".to_string()),
+            syntax,
+            modified: self.modified(),
+            read_only: self.read_only,
+            line_ending: self.line_ending,
+            cursor,
+            visual_column,
+            line_count,
+            percentage,
+        }
+    }
+
+    /// Describes the specified position (see `PositionDescription`),
+    /// returning None if it's out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\n\tlibrarié\n");
+    ///
+    /// let description = buffer.describe_position(&Position{ line: 1, offset: 9 }).unwrap();
+    /// assert_eq!(description.line, 1);
+    /// assert_eq!(description.visual_column, 12);
+    /// assert_eq!(description.byte_offset, 17);
+    /// assert_eq!(description.char_offset, 16);
+    /// assert_eq!(description.percentage, 50);
+    ///
+    /// assert!(buffer.describe_position(&Position{ line: 3, offset: 0 }).is_none());
+    /// ```
+    pub fn describe_position(&self, position: &Position) -> Option<PositionDescription> {
+        if !self.data.borrow().in_bounds(position) {
+            return None;
+        }
+
+        let data = self.data();
+        let mut description = PositionDescription{
+            line: position.line,
+            visual_column: 0,
+            byte_offset: 0,
+            char_offset: 0,
+            percentage: 100,
+        };
+        let mut current_position = Position::new();
+
+        for grapheme in data.graphemes(true) {
+            if current_position >= *position { break; }
+
+            description.byte_offset += grapheme.len();
+            description.char_offset += grapheme.chars().count();
+            if grapheme == "\n" {
+                current_position = Position{ line: current_position.line + 1, offset: 0 };
+                description.visual_column = 0;
+            } else {
+                current_position.offset += 1;
+                description.visual_column = advance_column(description.visual_column, grapheme, self.tab_width);
+            }
+        }
+
+        let last_line = self.line_count() - 1;
+        if let Some(percentage) = (position.line * 100).checked_div(last_line) {
+            description.percentage = percentage;
+        }
+
+        Some(description)
+    }
+}
+
+// The on-screen column following a grapheme displayed at the specified
+// column, with tabs extending to the next multiple of the tab width.
+fn advance_column(column: usize, grapheme: &str, tab_width: usize) -> usize {
+    if grapheme == "\t" && tab_width > 0 {
+        column + tab_width - column % tab_width
+    } else {
+        column + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position};
+    #[cfg(feature = "fs")]
+    use std::path::Path;
+
+    #[test]
+    fn describe_position_expands_tabs_to_tab_stops() {
+        let mut buffer = Buffer::new();
+        buffer.insert("ab\tc\t");
+        buffer.tab_width = 8;
+
+        let description = buffer.describe_position(&Position{ line: 0, offset: 5 }).unwrap();
+        assert_eq!(description.visual_column, 16);
+    }
+
+    #[test]
+    fn describe_position_counts_multi_character_graphemes_as_one_column() {
+        let mut buffer = Buffer::new();
+        buffer.insert("नी\nx");
+
+        let description = buffer.describe_position(&Position{ line: 0, offset: 1 }).unwrap();
+        assert_eq!(description.visual_column, 1);
+        assert_eq!(description.char_offset, 2);
+        assert_eq!(description.byte_offset, 6);
+
+        let description = buffer.describe_position(&Position{ line: 1, offset: 1 }).unwrap();
+        assert_eq!(description.char_offset, 4);
+        assert_eq!(description.percentage, 100);
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn status_describes_the_buffer_file() {
+        let mut buffer = Buffer::from_file(Path::new("tests/sample/file")).unwrap();
+        buffer.read_only = true;
+
+        let status = buffer.status();
+        assert_eq!(status.label, "file");
+        assert!(!status.modified);
+        assert!(status.read_only);
+        assert_eq!(status.syntax, None);
+        assert_eq!(status.percentage, 0);
+    }
+
+    #[test]
+    fn status_reports_single_line_buffers_as_complete() {
+        let buffer = Buffer::new();
+
+        let status = buffer.status();
+        assert_eq!(status.line_count, 1);
+        assert_eq!(status.percentage, 100);
+    }
+
+    #[test]
+    fn describe_position_reports_single_line_buffers_as_complete() {
+        let buffer = Buffer::new();
+
+        let description = buffer.describe_position(&Position::new()).unwrap();
+        assert_eq!(description.percentage, 100);
+    }
+}
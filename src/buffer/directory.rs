@@ -0,0 +1,173 @@
+//! Read-only buffers listing the contents of a directory.
+
+use buffer::{Buffer, GapBuffer};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The entries of a directory, ordered as they're listed in the buffer
+/// produced by `Buffer::from_directory`, such that each line number
+/// maps to the entry displayed on that line.
+#[derive(Debug, PartialEq)]
+pub struct DirectoryListing {
+    entries: Vec<PathBuf>,
+}
+
+impl DirectoryListing {
+    /// Reads the specified directory's entries. Sub-directories are listed
+    /// first, followed by all other entries, each sorted by name. Unless the
+    /// directory is the root, a parent entry is listed ahead of both.
+    pub fn new(path: &Path) -> io::Result<DirectoryListing> {
+        let mut directories = Vec::new();
+        let mut files = Vec::new();
+
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+
+            if entry.file_type()?.is_dir() {
+                directories.push(entry.path());
+            } else {
+                files.push(entry.path());
+            }
+        }
+        directories.sort();
+        files.sort();
+
+        let mut entries = Vec::new();
+        if let Some(parent) = path.parent() {
+            entries.push(parent.to_path_buf());
+        }
+        entries.append(&mut directories);
+        entries.append(&mut files);
+
+        Ok(DirectoryListing{ entries })
+    }
+
+    /// Returns the entry listed on the specified line, if any.
+    pub fn entry(&self, line: usize) -> Option<&Path> {
+        self.entries.get(line).map(|entry| entry.as_path())
+    }
+
+    /// Returns all of the listing's entries, in display order.
+    pub fn entries(&self) -> &[PathBuf] {
+        &self.entries
+    }
+
+    // Builds the buffer contents for the listing, one entry per line. The
+    // parent entry is labelled "..", and directories have a trailing slash.
+    fn render(&self, path: &Path) -> String {
+        let lines: Vec<String> = self.entries.iter().map(|entry| {
+            let name = if Some(entry.as_path()) == path.parent() {
+                String::from("..")
+            } else {
+                entry
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            };
+
+            if entry.is_dir() {
+                format!("{}/", name)
+            } else {
+                name
+            }
+        }).collect();
+
+        lines.join("\n")
+    }
+}
+
+impl Buffer {
+    /// Creates a new read-only buffer listing the contents of the specified
+    /// directory, one entry per line (see `DirectoryListing` for ordering).
+    /// The provided path is converted to its canonical, absolute equivalent,
+    /// and stored alongside the buffer data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use std::path::Path;
+    ///
+    /// let buffer = Buffer::from_directory(Path::new("tests/sample")).unwrap();
+    /// assert_eq!(buffer.data(), "../\nfile");
+    /// assert!(buffer.read_only);
+    /// ```
+    pub fn from_directory(path: &Path) -> io::Result<Buffer> {
+        let path = path.canonicalize()?;
+        let listing = DirectoryListing::new(&path)?;
+
+        let mut buffer = Buffer::new();
+        *buffer.data.borrow_mut() = GapBuffer::new(listing.render(&path));
+        buffer.path = Some(path);
+        buffer.read_only = true;
+        buffer.directory_listing = Some(listing);
+
+        Ok(buffer)
+    }
+
+    /// Returns the directory listing backing this buffer,
+    /// if it was created using `from_directory`.
+    pub fn directory_listing(&self) -> Option<&DirectoryListing> {
+        self.directory_listing.as_ref()
+    }
+
+    /// Returns the directory entry listed on the cursor's line,
+    /// if the buffer was created using `from_directory`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use std::path::Path;
+    ///
+    /// let mut buffer = Buffer::from_directory(Path::new("tests/sample")).unwrap();
+    /// buffer.cursor.move_down();
+    ///
+    /// assert_eq!(
+    ///     buffer.directory_entry_at_cursor().unwrap(),
+    ///     Path::new("tests/sample/file").canonicalize().unwrap()
+    /// );
+    /// ```
+    pub fn directory_entry_at_cursor(&self) -> Option<&Path> {
+        self.directory_listing
+            .as_ref()
+            .and_then(|listing| listing.entry(self.cursor.line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::Buffer;
+    use std::path::Path;
+
+    #[test]
+    fn from_directory_lists_directories_before_files() {
+        let buffer = Buffer::from_directory(Path::new("tests")).unwrap();
+        assert_eq!(buffer.data(), "../\nsample/");
+    }
+
+    #[test]
+    fn from_directory_maps_parent_line_to_parent_directory() {
+        let buffer = Buffer::from_directory(Path::new("tests/sample")).unwrap();
+        assert_eq!(
+            buffer.directory_entry_at_cursor().unwrap(),
+            Path::new("tests").canonicalize().unwrap()
+        );
+    }
+
+    #[test]
+    fn from_directory_produces_a_buffer_that_ignores_edits() {
+        let mut buffer = Buffer::from_directory(Path::new("tests/sample")).unwrap();
+        buffer.insert("scribe");
+        buffer.delete();
+        assert_eq!(buffer.data(), "../\nfile");
+        assert!(buffer.save().is_err());
+    }
+
+    #[test]
+    fn directory_entry_at_cursor_is_none_for_regular_buffers() {
+        let buffer = Buffer::from_file(Path::new("tests/sample/file")).unwrap();
+        assert!(buffer.directory_entry_at_cursor().is_none());
+    }
+}
@@ -1,3 +1,5 @@
+use unicode_segmentation::UnicodeSegmentation;
+
 /// A vector value representing a span in a buffer. Unlike the
 /// Range type, whose two positions are absolutes, a Distance
 /// is meant to be used relative to a Position.
@@ -8,8 +10,8 @@ pub struct Distance {
 }
 
 impl Distance {
-    /// Calculates the distance covered by a string. The
-    /// offset is calculated from the last line of the string.
+    /// Calculates the distance covered by a string. The offset is
+    /// calculated from the last line of the string, in grapheme clusters.
     ///
     /// # Examples
     ///
@@ -25,7 +27,7 @@ impl Distance {
     pub fn of_str(from: &str) -> Distance {
         Distance{
             lines: from.chars().filter(|&c| c == '\n').count(),
-            offset: from.split('\n').last().map(|l| l.len()).unwrap_or(0)
+            offset: from.split('\n').last().map(|l| l.graphemes(true).count()).unwrap_or(0)
         }
     }
 }
@@ -42,6 +44,14 @@ mod tests {
         });
     }
 
+    #[test]
+    fn of_str_counts_grapheme_clusters() {
+        assert_eq!(Distance::of_str("scribe\nनी"), Distance{
+            lines: 1,
+            offset: 1
+        });
+    }
+
     #[test]
     fn of_str_works_with_a_trailing_newline() {
         assert_eq!(Distance::of_str("trailing newline\n"), Distance{
@@ -0,0 +1,299 @@
+//! Detection and lexing of regions written in embedded languages.
+
+use buffer::{Buffer, Position, Range, TokenSet};
+use syntect::parsing::SyntaxSet;
+
+/// A range of lines written in a language other than the buffer's own (e.g.
+/// a fenced code block in a Markdown document). The range spans the lines
+/// between the region's delimiters, exclusive of the delimiters themselves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmbeddedRegion {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub language: String,
+}
+
+// Previously detected regions. Regions are discarded from the point of
+// an edit onwards, and detection resumes after the last remaining region.
+#[derive(Default)]
+pub(crate) struct RegionCache {
+    syntax: Option<String>,
+    regions: Vec<EmbeddedRegion>,
+    complete: bool,
+}
+
+// The delimiter conventions used by the buffer's syntax.
+#[derive(Clone, Copy, PartialEq)]
+enum Delimiters {
+    Fences,
+    Tags,
+}
+
+// A detected region that hasn't been closed yet.
+struct OpenRegion {
+    start_line: usize,
+    language: String,
+    closing_delimiter: String,
+}
+
+impl Buffer {
+    /// Detects regions of the buffer written in embedded languages: fenced
+    /// code blocks in Markdown documents (using the fence's info string as
+    /// the language), and script and style elements in HTML documents. Only
+    /// the portion of the buffer after the earliest edit since the previous
+    /// call is re-examined.
+    ///
+    /// Regions are only detected for buffers with Markdown
+    /// or HTML syntax definitions, and must be closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate syntect;
+    /// extern crate scribe;
+    ///
+    /// use scribe::Buffer;
+    /// use scribe::buffer::EmbeddedRegion;
+    /// use syntect::parsing::SyntaxSet;
+    ///
+    /// let syntax_set = SyntaxSet::load_defaults_newlines();
+    /// let mut buffer = Buffer::new();
+    /// buffer.syntax_definition = syntax_set.find_syntax_by_name("Markdown").cloned();
+    /// buffer.insert("# Example\n```rust\nlet x = 1;\n```\n");
+    ///
+    /// assert_eq!(
+    ///     buffer.embedded_regions(),
+    ///     vec![EmbeddedRegion{ start_line: 2, end_line: 3, language: "rust".to_string() }]
+    /// );
+    /// ```
+    pub fn embedded_regions(&self) -> Vec<EmbeddedRegion> {
+        let syntax = self.syntax_definition.as_ref().map(|def| def.name.clone());
+        let mut cache = self.embedded_regions.borrow_mut();
+
+        if cache.syntax != syntax {
+            *cache = RegionCache{ syntax, ..Default::default() };
+        }
+
+        if !cache.complete {
+            let start_line = cache.regions.last().map(|region| region.end_line + 1).unwrap_or(0);
+            let delimiters = cache.syntax.as_ref().and_then(|name| delimiters_for(name));
+
+            if let Some(delimiters) = delimiters {
+                let data = self.data();
+                let regions = detect_regions(&data, start_line, delimiters);
+                cache.regions.extend(regions);
+            }
+            cache.complete = true;
+        }
+
+        cache.regions.clone()
+    }
+
+    /// Produces a set of tokens for the embedded region, using the syntax
+    /// set's definition for its language. Token positions are relative to
+    /// the start of the buffer. Returns None if the language isn't
+    /// recognized, or the region is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate syntect;
+    /// extern crate scribe;
+    ///
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Token};
+    /// use syntect::parsing::SyntaxSet;
+    ///
+    /// let syntax_set = SyntaxSet::load_defaults_newlines();
+    /// let mut buffer = Buffer::new();
+    /// buffer.syntax_definition = syntax_set.find_syntax_by_name("Markdown").cloned();
+    /// buffer.insert("```rust\nlet x = 1;\n```\n");
+    ///
+    /// let region = &buffer.embedded_regions()[0];
+    /// let tokens = buffer.embedded_tokens(region, &syntax_set).unwrap();
+    /// match tokens.iter().next() {
+    ///     Some(Token::Lexeme(lexeme)) => {
+    ///         assert_eq!(lexeme.value, "let");
+    ///         assert_eq!(lexeme.position, Position{ line: 1, offset: 0 });
+    ///     },
+    ///     _ => panic!("expected a lexeme"),
+    /// }
+    /// ```
+    pub fn embedded_tokens<'a>(&self, region: &EmbeddedRegion, syntax_set: &'a SyntaxSet) -> Option<TokenSet<'a>> {
+        let def = syntax_set.find_syntax_by_token(&region.language)?;
+        let data = self.read(&Range::new(
+            Position{ line: region.start_line, offset: 0 },
+            Position{ line: region.end_line, offset: 0 }
+        ))?;
+
+        Some(TokenSet::new(data, def).with_line_offset(region.start_line).with_max_line_length(self.max_line_length))
+    }
+
+    // Discards regions that may have been affected by a change to the specified line.
+    pub(crate) fn invalidate_embedded_regions(&self, line: usize) {
+        let mut cache = self.embedded_regions.borrow_mut();
+        cache.regions.retain(|region| region.end_line < line);
+        cache.complete = false;
+    }
+}
+
+fn delimiters_for(syntax: &str) -> Option<Delimiters> {
+    if syntax.contains("Markdown") {
+        Some(Delimiters::Fences)
+    } else if syntax.starts_with("HTML") {
+        Some(Delimiters::Tags)
+    } else {
+        None
+    }
+}
+
+fn detect_regions(data: &str, start_line: usize, delimiters: Delimiters) -> Vec<EmbeddedRegion> {
+    let mut regions = Vec::new();
+    let mut open_region: Option<OpenRegion> = None;
+
+    for (line_number, line) in data.lines().enumerate().skip(start_line) {
+        let closed = match open_region {
+            Some(ref region) => closes(line, &region.closing_delimiter, delimiters),
+            None => false,
+        };
+
+        if closed {
+            if let Some(region) = open_region.take() {
+                regions.push(EmbeddedRegion{
+                    start_line: region.start_line,
+                    end_line: line_number,
+                    language: region.language,
+                });
+            }
+        } else if open_region.is_none() {
+            open_region = opens(line, line_number, delimiters);
+        }
+    }
+
+    regions
+}
+
+// Determines whether the line opens a region (which starts on the next line).
+fn opens(line: &str, line_number: usize, delimiters: Delimiters) -> Option<OpenRegion> {
+    let trimmed_line = line.trim();
+
+    match delimiters {
+        Delimiters::Fences => {
+            let fence_char = trimmed_line.chars().next().filter(|&c| c == '`' || c == '~')?;
+            let fence_length = trimmed_line.chars().take_while(|&c| c == fence_char).count();
+            if fence_length < 3 { return None; }
+
+            let info = trimmed_line[fence_length..].trim();
+            Some(OpenRegion{
+                start_line: line_number + 1,
+                language: info.split_whitespace().next().unwrap_or("").to_string(),
+                closing_delimiter: trimmed_line[..fence_length].to_string(),
+            })
+        },
+        Delimiters::Tags => {
+            let lowercase_line = trimmed_line.to_lowercase();
+
+            for &(tag, language) in [("script", "js"), ("style", "css")].iter() {
+                let closing_tag = format!("</{}", tag);
+
+                // Elements opened and closed on the same line aren't regions.
+                if lowercase_line.contains(&format!("<{}", tag)) && !lowercase_line.contains(&closing_tag) {
+                    return Some(OpenRegion{
+                        start_line: line_number + 1,
+                        language: language.to_string(),
+                        closing_delimiter: closing_tag,
+                    });
+                }
+            }
+
+            None
+        },
+    }
+}
+
+fn closes(line: &str, closing_delimiter: &str, delimiters: Delimiters) -> bool {
+    let trimmed_line = line.trim();
+
+    match delimiters {
+        // Closing fences must be at least as long as the opening fence.
+        Delimiters::Fences => {
+            trimmed_line.starts_with(closing_delimiter) &&
+                trimmed_line.chars().all(|c| closing_delimiter.starts_with(c))
+        },
+        Delimiters::Tags => trimmed_line.to_lowercase().contains(closing_delimiter),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, EmbeddedRegion, Position, Range};
+    use syntect::parsing::SyntaxSet;
+
+    fn buffer_with_syntax(name: &str, content: &str) -> Buffer {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let mut buffer = Buffer::new();
+        buffer.syntax_definition = syntax_set.find_syntax_by_name(name).cloned();
+        buffer.insert(content);
+
+        buffer
+    }
+
+    #[test]
+    fn embedded_regions_detects_html_script_and_style_elements() {
+        let buffer = buffer_with_syntax(
+            "HTML",
+            "<style>\np { color: red; }\n</style>\n<script>run();</script>\n<SCRIPT type=\"text/javascript\">\nrun();\n</SCRIPT>\n"
+        );
+
+        assert_eq!(buffer.embedded_regions(), vec![
+            EmbeddedRegion{ start_line: 1, end_line: 2, language: "css".to_string() },
+            EmbeddedRegion{ start_line: 5, end_line: 6, language: "js".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn embedded_regions_requires_closing_fences_to_match() {
+        let buffer = buffer_with_syntax("Markdown", "````md\n```rust\n```\n````\n");
+
+        assert_eq!(buffer.embedded_regions(), vec![
+            EmbeddedRegion{ start_line: 1, end_line: 3, language: "md".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn embedded_regions_are_updated_after_edits() {
+        let mut buffer = buffer_with_syntax("Markdown", "```rust\nlet x = 1;\n```\n\n```ruby\nx = 1\n```\n");
+        assert_eq!(buffer.embedded_regions().len(), 2);
+
+        // Remove the second region's closing fence.
+        buffer.delete_range(Range::new(
+            Position{ line: 6, offset: 0 },
+            Position{ line: 7, offset: 0 }
+        ));
+        assert_eq!(buffer.embedded_regions(), vec![
+            EmbeddedRegion{ start_line: 1, end_line: 2, language: "rust".to_string() },
+        ]);
+
+        // Insert a line into the first region.
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        buffer.insert("let y = 2;\n");
+        assert_eq!(buffer.embedded_regions(), vec![
+            EmbeddedRegion{ start_line: 1, end_line: 3, language: "rust".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn embedded_regions_are_not_detected_for_other_syntaxes() {
+        let buffer = buffer_with_syntax("Rust", "```rust\nlet x = 1;\n```\n");
+        assert!(buffer.embedded_regions().is_empty());
+    }
+
+    #[test]
+    fn embedded_tokens_returns_none_for_unknown_languages() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let buffer = buffer_with_syntax("Markdown", "```unknown\ndata\n```\n");
+        let region = &buffer.embedded_regions()[0];
+
+        assert!(buffer.embedded_tokens(region, &syntax_set).is_none());
+    }
+}
@@ -0,0 +1,259 @@
+//! Streaming access to regions of a buffer.
+
+use buffer::{Buffer, GapBuffer, LineRange, Range};
+use buffer::gap_buffer::breaks_within;
+use std::cell::RefCell;
+#[cfg(feature = "fs")]
+use std::fs::File;
+#[cfg(feature = "fs")]
+use std::io::{self, Write};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+/// An iterator over the lines in a region of a buffer, produced by
+/// `Buffer::lines_between` and `Buffer::lines`. Lines are read from the
+/// buffer one at a time, and exclude their trailing newline characters.
+pub struct LinesBetween<'a> {
+    data: &'a RefCell<GapBuffer>,
+    offset: usize,
+    end: usize,
+
+    // Whether an empty line starting at the end offset is included,
+    // rather than being treated as the end of the previous line.
+    inclusive: bool,
+}
+
+impl<'a> Iterator for LinesBetween<'a> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.offset > self.end || (self.offset == self.end && !self.inclusive) {
+            return None;
+        }
+
+        let data = self.data.borrow();
+        let (first, second) = data.byte_slices(self.offset, self.end);
+        let mut line = String::new();
+
+        // The line may straddle the buffer's gap, which never
+        // separates a carriage return from its line feed.
+        for part in &[first, second] {
+            if let Some(index) = breaks_within(part).next() {
+                line.push_str(&part[..index]);
+                self.offset += index + 1;
+                return Some(line);
+            }
+
+            line.push_str(part);
+            self.offset += part.len();
+        }
+
+        // The last line has been read.
+        self.offset = self.end + 1;
+        Some(line)
+    }
+}
+
+impl Buffer {
+    /// Iterates over the lines in the specified range, reading them from the
+    /// buffer as they're requested, rather than copying the entire range.
+    /// Returns None if the range is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary\neditor\n");
+    ///
+    /// let range = Range::new(
+    ///     Position{ line: 0, offset: 3 },
+    ///     Position{ line: 2, offset: 0 }
+    /// );
+    /// let lines: Vec<String> = buffer.lines_between(&range).unwrap().collect();
+    /// assert_eq!(lines, vec!["ibe", "library"]);
+    /// ```
+    pub fn lines_between(&self, range: &Range) -> Option<LinesBetween<'_>> {
+        let (offset, end) = self.byte_range(range)?;
+
+        Some(LinesBetween{ data: &self.data, offset, end, inclusive: false })
+    }
+
+    /// The content of the specified line, excluding its trailing newline,
+    /// read without copying the rest of the buffer. Returns None if the
+    /// line doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary\n");
+    ///
+    /// assert_eq!(buffer.line(1), Some("library".to_string()));
+    /// assert_eq!(buffer.line(2), Some(String::new()));
+    /// assert_eq!(buffer.line(3), None);
+    /// ```
+    pub fn line(&self, line: usize) -> Option<String> {
+        self.lines(&LineRange::new(line, line + 1))?.next()
+    }
+
+    /// Iterates over the lines in the specified range (e.g. those visible in
+    /// a viewport), reading them from the buffer as they're requested.
+    /// Lines beyond the end of the buffer are omitted; returns None if the
+    /// range starts beyond it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::LineRange;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary\neditor");
+    ///
+    /// let lines: Vec<String> = buffer.lines(&LineRange::new(1, 5)).unwrap().collect();
+    /// assert_eq!(lines, vec!["library", "editor"]);
+    /// ```
+    pub fn lines(&self, range: &LineRange) -> Option<LinesBetween<'_>> {
+        let data = self.data.borrow();
+        let offset = data.line_start(range.start())?;
+        if range.end() == range.start() {
+            return Some(LinesBetween{ data: &self.data, offset, end: offset, inclusive: false });
+        }
+        let end = data.line_start(range.end()).map(|start| start - 1).unwrap_or_else(|| data.len());
+
+        Some(LinesBetween{ data: &self.data, offset, end, inclusive: true })
+    }
+
+    /// Writes the content in the specified range to a file at `path`,
+    /// creating or truncating it. The content is written directly from
+    /// the buffer, without being copied. Returns an error if the range
+    /// is out of bounds, or the file can't be written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    /// use std::env;
+    /// use std::fs;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary\n");
+    ///
+    /// let path = env::temp_dir().join("scribe_export_range_example");
+    /// let range = Range::new(
+    ///     Position{ line: 1, offset: 0 },
+    ///     Position{ line: 2, offset: 0 }
+    /// );
+    /// buffer.export_range(&path, &range).unwrap();
+    ///
+    /// assert_eq!(fs::read_to_string(&path).unwrap(), "library\n");
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn export_range(&self, path: &Path, range: &Range) -> io::Result<()> {
+        let (start, end) = self.byte_range(range).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "range is out of bounds")
+        })?;
+        let mut file = File::create(path)?;
+
+        let data = self.data.borrow();
+        let (first, second) = data.byte_slices(start, end);
+        file.write_all(first.as_bytes())?;
+        file.write_all(second.as_bytes())?;
+
+        file.sync_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, LineRange, Position, Range};
+    #[cfg(feature = "fs")]
+    use std::env;
+    #[cfg(feature = "fs")]
+    use std::fs;
+
+    #[test]
+    fn lines_between_handles_lines_spanning_the_gap() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary");
+        // Move the gap into the middle of the first line.
+        buffer.cursor.move_to(Position{ line: 0, offset: 3 });
+        buffer.insert("-");
+
+        let range = Range::new(Position::new(), Position{ line: 1, offset: 7 });
+        let lines: Vec<String> = buffer.lines_between(&range).unwrap().collect();
+        assert_eq!(lines, vec!["scr-ibe", "library"]);
+    }
+
+    #[test]
+    fn lines_between_returns_nothing_for_empty_ranges() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+
+        let range = Range::new(Position{ line: 0, offset: 2 }, Position{ line: 0, offset: 2 });
+        assert_eq!(buffer.lines_between(&range).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn lines_between_returns_none_for_out_of_bounds_ranges() {
+        let buffer = Buffer::new();
+
+        let range = Range::new(Position::new(), Position{ line: 1, offset: 0 });
+        assert!(buffer.lines_between(&range).is_none());
+    }
+
+    #[test]
+    fn lines_reads_lines_on_both_sides_of_the_gap() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary\neditor\n");
+        buffer.cursor.move_to(Position{ line: 1, offset: 3 });
+        buffer.insert("-");
+
+        let lines: Vec<String> = buffer.lines(&LineRange::new(0, 4)).unwrap().collect();
+        assert_eq!(lines, vec!["scribe", "lib-rary", "editor", ""]);
+        assert_eq!(buffer.line(1), Some("lib-rary".to_string()));
+    }
+
+    #[test]
+    fn lines_handles_empty_buffers_and_ranges() {
+        let buffer = Buffer::new();
+
+        assert_eq!(buffer.line(0), Some(String::new()));
+        assert_eq!(buffer.lines(&LineRange::new(0, 0)).unwrap().count(), 0);
+        assert!(buffer.lines(&LineRange::new(1, 2)).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn export_range_writes_content_on_both_sides_of_the_gap() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe library");
+        buffer.cursor.move_to(Position{ line: 0, offset: 6 });
+        buffer.insert(",");
+
+        let path = env::temp_dir().join("scribe_export_range_gap");
+        let range = Range::new(Position{ line: 0, offset: 2 }, Position{ line: 0, offset: 10 });
+        buffer.export_range(&path, &range).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "ribe, li");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn export_range_rejects_out_of_bounds_ranges() {
+        let buffer = Buffer::new();
+        let path = env::temp_dir().join("scribe_export_range_out_of_bounds");
+        let range = Range::new(Position::new(), Position{ line: 0, offset: 1 });
+
+        assert!(buffer.export_range(&path, &range).is_err());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,227 @@
+//! Filtering buffer content through external commands.
+
+use buffer::{Buffer, Position, Range};
+use std::io::{self, Write};
+use std::process::Stdio;
+use std::thread;
+use unicode_segmentation::UnicodeSegmentation;
+use util::shell_command;
+
+impl Buffer {
+    /// Pipes the lines spanned by `range` through `command`, which is run
+    /// using the platform's shell (e.g. `sort`, `jq .`, or `column -t`), and
+    /// replaces them with its output as a single undoable operation. A range
+    /// ending at the start of a line doesn't include that line.
+    ///
+    /// Returns an error if the buffer is read-only or append-only, or if the
+    /// command can't be run or exits unsuccessfully, in which case the buffer
+    /// is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("header\ncharlie\nalpha\nbravo\n");
+    ///
+    /// let range = Range::new(
+    ///     Position{ line: 1, offset: 2 },
+    ///     Position{ line: 4, offset: 0 }
+    /// );
+    /// # if cfg!(unix) {
+    /// buffer.filter_range(&range, "sort").unwrap();
+    /// assert_eq!(buffer.data(), "header\nalpha\nbravo\ncharlie\n");
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "header\ncharlie\nalpha\nbravo\n");
+    /// # }
+    /// ```
+    pub fn filter_range(&mut self, range: &Range, command: &str) -> io::Result<()> {
+        if self.read_only {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "buffer is read-only"
+            ));
+        }
+
+        let line_range = self.filtered_lines(range);
+        let input = self.read(&line_range).unwrap_or_default();
+        let mut output = run_filter(command, input.clone())?;
+
+        // Don't join the output with the line that followed the input.
+        if input.ends_with('\n') && !output.ends_with('\n') {
+            output.push('\n');
+        }
+
+        self.try_batch(|buffer| {
+            buffer.delete_range(line_range.clone());
+            buffer.insert_at(output, line_range.start());
+        }).map_err(|error| io::Error::new(io::ErrorKind::PermissionDenied, error.to_string()))?;
+        self.cursor.move_to(line_range.start());
+
+        Ok(())
+    }
+
+    /// Pipes the content within `range` through `command` (run as with
+    /// `filter_range`), returning its output without modifying the buffer,
+    /// so that it can be previewed (e.g. the result of `jq .` on a selected
+    /// JSON value). Unlike `filter_range`, the range isn't expanded to cover
+    /// whole lines, and read-only buffers are supported.
+    ///
+    /// Returns an `InvalidInput` error if the range is out of bounds, or an
+    /// error if the command can't be run or exits unsuccessfully.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("let words = \"scribe library\";");
+    ///
+    /// let range = Range::new(
+    ///     Position{ line: 0, offset: 13 },
+    ///     Position{ line: 0, offset: 27 }
+    /// );
+    /// # if cfg!(unix) {
+    /// assert_eq!(buffer.pipe_range(&range, "wc -w | tr -d ' '").unwrap(), "2\n");
+    /// assert_eq!(buffer.data(), "let words = \"scribe library\";");
+    /// # }
+    /// ```
+    pub fn pipe_range(&self, range: &Range, command: &str) -> io::Result<String> {
+        let input = self.read(range).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "range is out of bounds")
+        })?;
+
+        run_filter(command, input)
+    }
+
+    // Expands the range to cover whole lines, including the
+    // trailing newline of the last one, if present.
+    fn filtered_lines(&self, range: &Range) -> Range {
+        let mut end_line = range.end().line;
+        if range.end().offset == 0 && end_line > range.start().line {
+            end_line -= 1;
+        }
+
+        let next_line = Position{ line: end_line + 1, offset: 0 };
+        let end = if self.data.borrow().in_bounds(&next_line) {
+            next_line
+        } else {
+            // The last line doesn't have a trailing newline.
+            let length = self
+                .data()
+                .split('\n')
+                .nth(end_line)
+                .map(|line| line.graphemes(true).count())
+                .unwrap_or(0);
+            Position{ line: end_line, offset: length }
+        };
+
+        Range::new(Position{ line: range.start().line, offset: 0 }, end)
+    }
+}
+
+// Runs the command with the input on its standard input, returning its standard output.
+fn run_filter(command: &str, input: String) -> io::Result<String> {
+    let mut child = shell_command(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Write the input on a separate thread, so that a command
+    // with a large amount of output can't cause a deadlock.
+    let mut stdin = child.stdin.take().ok_or_else(|| {
+        io::Error::other("couldn't open command input")
+    })?;
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child.wait_with_output()?;
+    // Commands aren't required to read their input.
+    let _ = writer.join();
+
+    if !output.status.success() {
+        return Err(io::Error::other(
+            format!(
+                "command exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+        ));
+    }
+
+    String::from_utf8(output.stdout).map_err(|error| {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use buffer::{Buffer, Position, Range};
+    use std::io;
+
+    #[test]
+    fn filter_range_leaves_the_buffer_unchanged_when_the_command_fails() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\n");
+        let range = Range::new(Position::new(), Position{ line: 0, offset: 1 });
+
+        let error = buffer.filter_range(&range, "echo oops >&2; exit 3").unwrap_err();
+        assert!(error.to_string().ends_with(": oops"));
+        assert_eq!(buffer.data(), "scribe\n");
+    }
+
+    #[test]
+    fn pipe_range_rejects_out_of_bounds_ranges() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        let range = Range::new(Position::new(), Position{ line: 2, offset: 0 });
+
+        let error = buffer.pipe_range(&range, "cat").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn pipe_range_works_with_read_only_buffers() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary");
+        buffer.read_only = true;
+        let range = Range::new(Position{ line: 0, offset: 3 }, Position{ line: 1, offset: 3 });
+
+        assert_eq!(buffer.pipe_range(&range, "tr a-z A-Z").unwrap(), "IBE\nLIB");
+    }
+
+    #[test]
+    fn filter_range_handles_last_lines_without_trailing_newlines() {
+        let mut buffer = Buffer::new();
+        buffer.insert("b\na");
+        let range = Range::new(Position::new(), Position{ line: 1, offset: 1 });
+
+        buffer.filter_range(&range, "sort").unwrap();
+        assert_eq!(buffer.data(), "a\nb\n");
+    }
+
+    #[test]
+    fn filter_range_adds_a_newline_to_output_when_followed_by_other_lines() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary");
+        let range = Range::new(Position::new(), Position::new());
+
+        buffer.filter_range(&range, "printf amp").unwrap();
+        assert_eq!(buffer.data(), "amp\nlibrary");
+    }
+
+    #[test]
+    fn filter_range_refuses_read_only_buffers() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        buffer.read_only = true;
+        let range = Range::new(Position::new(), Position::new());
+
+        assert!(buffer.filter_range(&range, "cat").is_err());
+    }
+}
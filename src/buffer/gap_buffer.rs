@@ -1,12 +1,32 @@
 //! Buffer type's underlying data structure.
-
+//!
+//! The gap buffer can be used on its own, independent of the `Buffer` type,
+//! for applications that need efficient position-based text storage without
+//! cursors, history, or lexing. Like the position and range types, it only
+//! relies on `core` and `alloc`, so that it can be used without `std`.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::iter::Chain;
+use core::str::{self, Chars};
 use super::Position;
 use super::Range;
-use std::borrow::Borrow;
 use unicode_segmentation::UnicodeSegmentation;
 
 /// A UTF-8 string buffer designed to minimize reallocations,
 /// maintaining performance amid frequent modifications.
+///
+/// Data is stored in a single allocation, with an unused region (the gap)
+/// positioned at the most recent edit, so that successive, close-proximity
+/// edits only need to shift a small amount of data. Positions are line and
+/// grapheme cluster-based (see `Position`).
+///
+/// The buffer's contents can be retrieved as a string using its `Display`
+/// implementation (e.g. `to_string`), or iterated over without allocating
+/// using `chars`.
 pub struct GapBuffer {
     data: Vec<u8>,
     gap_start: usize,
@@ -77,6 +97,7 @@ impl GapBuffer {
 
         self.move_gap(offset);
         self.write_to_gap(data);
+        self.check_invariants();
     }
 
     /// Returns the specified range of data from the buffer.
@@ -127,19 +148,120 @@ impl GapBuffer {
         Some(data)
     }
 
-    /// Returns a string representation of the buffer data (without gap).
+    /// Returns an iterator over the buffer's characters (excluding the gap),
+    /// without allocating an intermediate string.
     ///
     /// # Examples
     ///
     /// ```
-    /// use scribe::buffer::GapBuffer;
+    /// use scribe::buffer::{GapBuffer, Position};
     ///
     /// let mut buffer = GapBuffer::new("my data".to_string());
-    /// assert_eq!(buffer.to_string(), "my data");
+    /// buffer.insert("new ", &Position{ line: 0, offset: 3 });
+    ///
+    /// assert_eq!(buffer.chars().filter(|&c| c == ' ').count(), 2);
+    /// ```
+    pub fn chars(&self) -> Chain<Chars<'_>, Chars<'_>> {
+        let (first_half, second_half) = self.halves();
+
+        first_half.chars().chain(second_half.chars())
+    }
+
+    /// The length of the buffer's contents, in bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::buffer::GapBuffer;
+    ///
+    /// let buffer = GapBuffer::new("scribé".to_string());
+    /// assert_eq!(buffer.len(), 7);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.data.len() - self.gap_length
+    }
+
+    /// The number of lines in the buffer's contents, including
+    /// the empty line following a trailing newline. As with positions,
+    /// a carriage return and line feed form a single grapheme, which
+    /// isn't treated as a line break.
+    ///
+    /// # Examples
+    ///
     /// ```
-    pub fn to_string(&self) -> String {
-        String::from_utf8_lossy(&self.data[..self.gap_start]).to_string() +
-        &*String::from_utf8_lossy(&self.data[self.gap_start+self.gap_length..])
+    /// use scribe::buffer::GapBuffer;
+    ///
+    /// let buffer = GapBuffer::new("scribe\nlibrary\n".to_string());
+    /// assert_eq!(buffer.line_count(), 3);
+    /// ```
+    pub fn line_count(&self) -> usize {
+        self.last_line() + 1
+    }
+
+    /// The size of the buffer's allocation (its contents and gap), in bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::buffer::GapBuffer;
+    ///
+    /// let buffer = GapBuffer::new(String::with_capacity(64));
+    /// assert_eq!(buffer.capacity(), 64);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Releases unused space, shrinking the gap as much as the allocator
+    /// allows. Useful after large deletions, at the cost of having to
+    /// reallocate on the next insert.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::buffer::{GapBuffer, Position, Range};
+    ///
+    /// let mut buffer = GapBuffer::new("scribe".repeat(100));
+    /// buffer.delete(&Range::new(
+    ///     Position{ line: 0, offset: 6 },
+    ///     Position{ line: 0, offset: 600 }
+    /// ));
+    /// buffer.shrink_to_fit();
+    ///
+    /// assert!(buffer.capacity() < 600);
+    /// assert_eq!(buffer.to_string(), "scribe");
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        // Move the gap to the end of the buffer so that
+        // it can be released by truncating the data.
+        let end = self.data.len();
+        self.move_gap(end);
+        self.data.truncate(self.gap_start);
+        self.data.shrink_to_fit();
+
+        // The allocator may not release all of the space;
+        // anything that remains becomes the new gap.
+        let capacity = self.data.capacity();
+        self.gap_length = capacity - self.gap_start;
+        unsafe {
+            self.data.set_len(capacity);
+        }
+
+        self.check_invariants();
+    }
+
+    /// Whether or not the buffer has any contents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::buffer::GapBuffer;
+    ///
+    /// assert!(GapBuffer::new(String::new()).is_empty());
+    /// assert!(!GapBuffer::new("scribe".to_string()).is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
     /// Removes the specified range of data from the buffer.
@@ -189,6 +311,8 @@ impl GapBuffer {
                 }
             }
         };
+
+        self.check_invariants();
     }
 
     /// Checks whether or not the specified position is in bounds of the buffer data.
@@ -209,57 +333,152 @@ impl GapBuffer {
         self.find_offset(position) != None
     }
 
-    // Maps a position to its offset equivalent in the data.
-    fn find_offset(&self, position: &Position) -> Option<usize> {
-        let first_half = String::from_utf8_lossy(&self.data[..self.gap_start]);
-        let mut line = 0;
-        let mut line_offset = 0;
-
-        for (offset, grapheme) in (&*first_half).grapheme_indices(true) {
-            // Check to see if we've found the position yet.
-            if line == position.line && line_offset == position.offset {
-                return Some(offset);
-            }
-
-            // Advance the line and offset characters.
-            if grapheme == "\n" {
-                line+=1;
-                line_offset = 0;
-            } else {
-                line_offset+=1;
-            }
+    // Describes the first violation of the buffer's invariants, if any (see
+    // `validate`). Kept free of the crate's error types, like the rest of
+    // this module, which only relies on `core` and `alloc`.
+    pub(crate) fn violation(&self) -> Option<String> {
+        if self.gap_start + self.gap_length > self.data.len() {
+            return Some(format!(
+                "gap ({}..{}) extends beyond the buffer's {} byte allocation",
+                self.gap_start,
+                self.gap_start + self.gap_length,
+                self.data.len()
+            ));
         }
 
-        // We didn't find the position *within* the first half, but it could
-        // be right after it, which means it's right at the start of the gap.
-        if line == position.line && line_offset == position.offset {
-            return Some(self.gap_start+self.gap_length);
+        if str::from_utf8(&self.data[..self.gap_start]).is_err() ||
+           str::from_utf8(&self.data[self.gap_start+self.gap_length..]).is_err() {
+            return Some(format!("gap at offset {} splits a UTF-8 character", self.gap_start));
         }
 
-        // We haven't reached the position yet, so we'll move on to the other half.
-        let second_half = String::from_utf8_lossy(&self.data[self.gap_start+self.gap_length..]);
-        for (offset, grapheme) in (&*second_half).grapheme_indices(true) {
-            // Check to see if we've found the position yet.
-            if line == position.line && line_offset == position.offset {
-                return Some(self.gap_start + self.gap_length + offset);
-            }
+        None
+    }
 
-            // Advance the line and offset characters.
-            if grapheme == "\n" {
-                line+=1;
-                line_offset = 0;
-            } else {
-                line_offset+=1;
+    // Panics if the buffer's invariants don't hold, in debug builds only.
+    fn check_invariants(&self) {
+        if cfg!(debug_assertions) {
+            if let Some(violation) = self.violation() {
+                panic!("invariant violation: {}", violation);
             }
         }
+    }
+
+    // The buffer contents on either side of the gap. Data is only ever
+    // written as whole strings, and the gap is only moved to offsets
+    // found on grapheme boundaries, so both halves are valid UTF-8.
+    fn halves(&self) -> (&str, &str) {
+        (
+            str::from_utf8(&self.data[..self.gap_start])
+                .expect("gap buffer data before the gap isn't valid UTF-8"),
+            str::from_utf8(&self.data[self.gap_start+self.gap_length..])
+                .expect("gap buffer data after the gap isn't valid UTF-8")
+        )
+    }
 
-        // We didn't find the position *within* the second half, but it could
-        // be right after it, which means it's at the end of the buffer.
-        if line == position.line && line_offset == position.offset {
-            return Some(self.data.len());
+    // Maps a position to its byte offset in the buffer's
+    // contents (i.e. excluding the gap).
+    pub(crate) fn byte_offset(&self, position: &Position) -> Option<usize> {
+        let offset = self.find_offset(position)?;
+
+        if offset >= self.gap_start + self.gap_length {
+            Some(offset - self.gap_length)
+        } else {
+            Some(offset)
         }
+    }
 
-        None
+    // Maps a line number to the byte offset of its start in the
+    // buffer's contents (see `byte_offset`), if the line exists.
+    pub(crate) fn line_start(&self, line: usize) -> Option<usize> {
+        if line == 0 { return Some(0); }
+
+        self.line_breaks().nth(line - 1).map(|offset| offset + 1)
+    }
+
+    // Returns the contents between two byte offsets (see `byte_offset`), as
+    // the portions on either side of the gap, so that they needn't be copied.
+    pub(crate) fn byte_slices(&self, start: usize, end: usize) -> (&str, &str) {
+        let (first_half, second_half) = self.halves();
+        let split = first_half.len();
+        let end = end.min(split + second_half.len());
+        let start = start.min(end);
+
+        (
+            &first_half[start.min(split)..end.min(split)],
+            &second_half[start.max(split) - split..end.max(split) - split]
+        )
+    }
+
+    // The byte offsets of the line breaks in the buffer's contents (see
+    // `byte_offset`). A carriage return and line feed form a single
+    // grapheme, which isn't treated as a line break by positions.
+    fn line_breaks(&self) -> impl Iterator<Item = usize> + '_ {
+        let (first_half, second_half) = self.halves();
+        let split = first_half.len();
+
+        breaks_within(first_half).chain(breaks_within(second_half).map(move |offset| offset + split))
+    }
+
+    // The byte offsets of the start and end of a line's contents (see
+    // `byte_offset`), excluding its line break, if the line exists.
+    fn line_bounds(&self, line: usize) -> Option<(usize, usize)> {
+        let mut breaks = self.line_breaks();
+        let start = if line == 0 { 0 } else { breaks.nth(line - 1)? + 1 };
+        let end = breaks.next().unwrap_or_else(|| self.len());
+
+        Some((start, end))
+    }
+
+    // The number of grapheme clusters on a line (i.e. the offset after its
+    // last one), found without segmenting the rest of the buffer.
+    pub(crate) fn line_length(&self, line: usize) -> Option<usize> {
+        let (start, end) = self.line_bounds(line)?;
+        let (first, second) = self.byte_slices(start, end);
+
+        Some(first.graphemes(true).count() + second.graphemes(true).count())
+    }
+
+    // The contents of the lines in the range, including their line breaks,
+    // copied without reading the lines that follow them.
+    pub(crate) fn lines_within(&self, lines: core::ops::Range<usize>) -> String {
+        let mut breaks = self.line_breaks();
+        let start = match lines.start {
+            0 => 0,
+            line => match breaks.nth(line - 1) {
+                Some(offset) => offset + 1,
+                None => return String::new(),
+            },
+        };
+        let end = match lines.end.checked_sub(lines.start + 1) {
+            Some(count) => breaks.nth(count).map(|offset| offset + 1).unwrap_or_else(|| self.len()),
+            None => start,
+        };
+        let (first, second) = self.byte_slices(start, end);
+
+        format!("{}{}", first, second)
+    }
+
+    // The number of the buffer's last line, as it's counted by positions.
+    pub(crate) fn last_line(&self) -> usize {
+        self.line_breaks().count()
+    }
+
+    // Maps a position to its offset equivalent in the data. The position's
+    // line is found by scanning for line breaks, so that only its own content
+    // needs to be segmented into grapheme clusters.
+    fn find_offset(&self, position: &Position) -> Option<usize> {
+        let (start, end) = self.line_bounds(position.line)?;
+        let (first, second) = self.byte_slices(start, end);
+        let second_start = start.max(self.gap_start);
+
+        // Graphemes are found on either side of the gap separately.
+        let offsets = first.grapheme_indices(true)
+            .map(|(offset, _)| start + offset)
+            .chain(second.grapheme_indices(true).map(|(offset, _)| second_start + offset))
+            .chain(Some(end));
+
+        offsets.map(|offset| if offset >= self.gap_start { offset + self.gap_length } else { offset })
+            .nth(position.offset)
     }
 
     fn move_gap(&mut self, offset: usize) {
@@ -299,6 +518,41 @@ impl GapBuffer {
     }
 }
 
+// The byte offsets of the line feeds in the data that aren't preceded by a
+// carriage return (i.e. that aren't part of a "\r\n" grapheme cluster).
+pub(crate) fn breaks_within(data: &str) -> impl Iterator<Item = usize> + '_ {
+    let bytes = data.as_bytes();
+
+    bytes.iter()
+        .enumerate()
+        .filter(move |&(offset, &byte)| byte == b'\n' && (offset == 0 || bytes[offset - 1] != b'\r'))
+        .map(|(offset, _)| offset)
+}
+
+impl Default for GapBuffer {
+    fn default() -> Self {
+        GapBuffer::new(String::new())
+    }
+}
+
+impl fmt::Display for GapBuffer {
+    /// Writes the buffer data (without gap).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::buffer::GapBuffer;
+    ///
+    /// let buffer = GapBuffer::new("my data".to_string());
+    /// assert_eq!(buffer.to_string(), "my data");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (first_half, second_half) = self.halves();
+        f.write_str(first_half)?;
+        f.write_str(second_half)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use buffer::{GapBuffer, Position, Range};
@@ -475,6 +729,70 @@ mod tests {
         assert_eq!(gb.read(&range).unwrap(), " library");
     }
 
+    #[test]
+    fn chars_excludes_gap_contents() {
+        let mut gb = GapBuffer::new("scribe".to_string());
+        gb.delete(&Range::new(
+            Position{ line: 0, offset: 2 },
+            Position{ line: 0, offset: 4 }
+        ));
+
+        assert_eq!(gb.chars().collect::<String>(), "scbe");
+    }
+
+    #[test]
+    fn len_excludes_gap_length() {
+        let mut gb = GapBuffer::new("scribe".to_string());
+        gb.insert(" library", &Position{ line: 0, offset: 6 });
+        assert_eq!(gb.len(), 14);
+
+        gb.delete(&Range::new(
+            Position{ line: 0, offset: 0 },
+            Position{ line: 0, offset: 7 }
+        ));
+        assert_eq!(gb.len(), 7);
+    }
+
+    #[test]
+    fn shrink_to_fit_preserves_contents_around_the_gap() {
+        let mut gb = GapBuffer::new("scribe library".to_string());
+        gb.delete(&Range::new(
+            Position{ line: 0, offset: 2 },
+            Position{ line: 0, offset: 7 }
+        ));
+        gb.shrink_to_fit();
+        assert_eq!(gb.to_string(), "sclibrary");
+
+        gb.insert("ribe ", &Position{ line: 0, offset: 2 });
+        assert_eq!(gb.to_string(), "scribe library");
+    }
+
+    #[test]
+    fn default_produces_an_empty_buffer() {
+        let mut gb = GapBuffer::default();
+        assert!(gb.is_empty());
+
+        gb.insert("scribe", &Position{ line: 0, offset: 0 });
+        assert_eq!(gb.to_string(), "scribe");
+    }
+
+    #[test]
+    fn validate_detects_gaps_splitting_characters() {
+        let mut gb = GapBuffer::new("नी".to_string());
+        gb.gap_start = 1;
+        gb.gap_length = 0;
+
+        assert!(gb.validate().is_err());
+    }
+
+    #[test]
+    fn validate_detects_gaps_exceeding_the_allocation() {
+        let mut gb = GapBuffer::new("scribe".to_string());
+        gb.gap_length = gb.data.len() + 1;
+
+        assert!(gb.validate().is_err());
+    }
+
     #[test]
     fn in_bounds_considers_grapheme_clusters() {
         let gb = GapBuffer::new("scribe नी library".to_string());
@@ -483,4 +801,39 @@ mod tests {
         assert!(gb.in_bounds(&in_bounds));
         assert!(!gb.in_bounds(&out_of_bounds));
     }
+
+    #[test]
+    fn positions_are_found_on_lines_spanning_the_gap() {
+        let mut gb = GapBuffer::new("scribe\nlibrary\neditor".to_string());
+        gb.insert("é", &Position{ line: 1, offset: 3 });
+
+        let range = Range::new(Position{ line: 1, offset: 2 }, Position{ line: 1, offset: 5 });
+        assert_eq!(gb.read(&range).unwrap(), "bér");
+        assert_eq!(gb.line_length(1), Some(8));
+        assert_eq!(gb.last_line(), 2);
+        assert!(gb.in_bounds(&Position{ line: 1, offset: 8 }));
+        assert!(!gb.in_bounds(&Position{ line: 1, offset: 9 }));
+        assert!(!gb.in_bounds(&Position{ line: 3, offset: 0 }));
+    }
+
+    #[test]
+    fn lines_within_includes_line_breaks() {
+        let mut gb = GapBuffer::new("scribe\nlibrary\neditor".to_string());
+        gb.insert("é", &Position{ line: 1, offset: 3 });
+
+        assert_eq!(gb.lines_within(0..1), "scribe\n");
+        assert_eq!(gb.lines_within(1..3), "libérary\neditor");
+        assert_eq!(gb.lines_within(2..5), "editor");
+        assert_eq!(gb.lines_within(3..4), "");
+    }
+
+    #[test]
+    fn line_count_treats_carriage_return_line_feeds_as_one_grapheme() {
+        let gb = GapBuffer::new("a\r\nb\nc".to_string());
+
+        assert_eq!(gb.line_count(), 2);
+        assert_eq!(gb.line_count(), gb.last_line() + 1);
+        assert_eq!(gb.line_start(1), Some(5));
+        assert!(gb.in_bounds(&Position{ line: 0, offset: 3 }));
+    }
 }
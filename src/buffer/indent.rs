@@ -0,0 +1,467 @@
+//! Rule-based indentation of new and existing lines.
+
+use buffer::{Buffer, Position, Range};
+use std::collections::HashMap;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Rules for computing a line's indentation from the line preceding it,
+/// used by `Buffer::insert_newline` and `Buffer::reindent` (see the
+/// buffer's `indent_rules` field). Lines are indented one level (of
+/// `tab_width` columns) beyond the preceding non-blank line if it ends with
+/// one of the `increase_after` tokens, and one level less if they start
+/// with one of the `decrease_on` tokens. If `tags` is set, lines following
+/// unclosed opening tags are indented, and closing tags are outdented.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IndentRules {
+    pub increase_after: Vec<String>,
+    pub decrease_on: Vec<String>,
+    pub tags: bool,
+}
+
+impl IndentRules {
+    /// Rules for languages delimiting blocks using brackets and braces.
+    pub fn brackets() -> IndentRules {
+        IndentRules{
+            increase_after: vec!["{".to_string(), "(".to_string(), "[".to_string()],
+            decrease_on: vec!["}".to_string(), ")".to_string(), "]".to_string()],
+            tags: false,
+        }
+    }
+
+    /// Rules for markup languages using opening and closing tags.
+    pub fn tags() -> IndentRules {
+        IndentRules{ tags: true, ..IndentRules::default() }
+    }
+
+    /// The built-in rules, keyed by syntax name (e.g. "Rust"). These
+    /// are applied to buffers opened by a `Workspace` (see its
+    /// `indent_rules` field), based on their syntax definitions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::buffer::IndentRules;
+    ///
+    /// let rules = IndentRules::defaults();
+    /// assert_eq!(rules["Rust"], IndentRules::brackets());
+    /// assert!(rules["HTML"].tags);
+    /// assert!(rules["Python"].increase_after.contains(&":".to_string()));
+    /// ```
+    pub fn defaults() -> HashMap<String, IndentRules> {
+        let mut rules = HashMap::new();
+        let bracketed = [
+            "C", "C#", "C++", "CSS", "Go", "Java", "JavaScript", "JSON",
+            "Objective-C", "PHP", "Rust", "Scala", "Shell-Unix-Generic",
+        ];
+        for name in bracketed.iter() {
+            rules.insert(name.to_string(), IndentRules::brackets());
+        }
+
+        let mut python = IndentRules::brackets();
+        python.increase_after.push(":".to_string());
+        rules.insert("Python".to_string(), python);
+        rules.insert("YAML".to_string(), IndentRules{
+            increase_after: vec![":".to_string()],
+            ..IndentRules::default()
+        });
+        rules.insert("HTML".to_string(), IndentRules::tags());
+        rules.insert("XML".to_string(), IndentRules::tags());
+
+        rules
+    }
+
+    // Whether the (non-blank) line should cause the next to be indented.
+    fn increases_after(&self, line: &str) -> bool {
+        let line = line.trim_end();
+
+        self.increase_after.iter().any(|token| line.ends_with(token.as_str())) ||
+            (self.tags && unclosed_tags(line) > 0)
+    }
+
+    // Whether the line should be outdented from its predecessor.
+    fn decreases_on(&self, line: &str) -> bool {
+        let line = line.trim_start();
+
+        self.decrease_on.iter().any(|token| line.starts_with(token.as_str())) ||
+            (self.tags && line.starts_with("</"))
+    }
+}
+
+impl Buffer {
+    /// Inserts a line break at the cursor, indenting the new line using the
+    /// buffer's `indent_rules`, or if it doesn't have any, copying the
+    /// indentation of the line before it. The cursor is moved to the end of
+    /// the new line's indentation. If the cursor is between an opening and
+    /// closing token (e.g. "{}"), the closing token is moved to a line of
+    /// its own. This is undone as a single operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{IndentRules, Position};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("fn main() {}");
+    /// buffer.indent_rules = Some(IndentRules::brackets());
+    /// buffer.cursor.move_to(Position{ line: 0, offset: 11 });
+    ///
+    /// buffer.insert_newline();
+    /// assert_eq!(buffer.data(), "fn main() {\n    \n}");
+    /// assert_eq!(*buffer.cursor, Position{ line: 1, offset: 4 });
+    /// ```
+    pub fn insert_newline(&mut self) {
+        if self.read_only { return; }
+
+        let position = *self.cursor;
+        let content = self.line(position.line).unwrap_or_default();
+        let split = content.grapheme_indices(true).nth(position.offset).map_or(content.len(), |(index, _)| index);
+        let between_tokens = self.indent_rules.as_ref().is_some_and(|rules| {
+            rules.increases_after(&content[..split]) && rules.decreases_on(&content[split..])
+        });
+
+        self.batch(|buffer| {
+            let line = position.line + 1;
+            if between_tokens {
+                buffer.insert_at("\n\n", position);
+                buffer.reindent_line(line + 1, false);
+            } else {
+                buffer.insert_at("\n", position);
+            }
+            let indentation = buffer.reindent_line(line, true);
+
+            buffer.cursor.move_to(Position{ line, offset: indentation });
+        });
+    }
+
+    /// Recomputes the indentation of the lines spanned by the range, in
+    /// order, using the buffer's `indent_rules` (leaving the lines alone if
+    /// it doesn't have any), as a single operation. A range ending at the
+    /// start of a line doesn't include that line. Blank lines are emptied.
+    /// The cursor stays on the same content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{IndentRules, Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("<p>\n<b>scribe</b>\n  </p>");
+    /// buffer.indent_rules = Some(IndentRules::tags());
+    /// buffer.tab_width = 2;
+    ///
+    /// buffer.reindent(&Range::new(Position{ line: 1, offset: 0 }, Position{ line: 2, offset: 1 }));
+    /// assert_eq!(buffer.data(), "<p>\n  <b>scribe</b>\n</p>");
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "<p>\n<b>scribe</b>\n  </p>");
+    /// ```
+    pub fn reindent(&mut self, range: &Range) {
+        if self.read_only || self.indent_rules.is_none() { return; }
+
+        let start = range.start().line;
+        let mut end = range.end().line;
+        if range.end().offset == 0 && end > start {
+            end -= 1;
+        }
+
+        // Compute every line's indentation in a single pass, basing each on
+        // the one computed for the closest non-blank line preceding it.
+        let data = self.data();
+        let lines: Vec<&str> = data.split('\n').collect();
+        let end = end.min(lines.len() - 1);
+        if start > end { return; }
+
+        let mut previous = lines[..start].iter()
+            .rev()
+            .find(|previous| !previous.trim().is_empty())
+            .map(|previous| previous.to_string());
+        let mut changes = Vec::new();
+        for (line, &content) in lines.iter().enumerate().take(end + 1).skip(start) {
+            let current = leading_whitespace(content);
+            if content.trim().is_empty() {
+                if !current.is_empty() {
+                    changes.push((line, current.chars().count(), String::new()));
+                }
+                continue;
+            }
+
+            let indentation = self.indentation_after(previous.as_deref(), content);
+            previous = Some(format!("{}{}", indentation, content.trim_start()));
+            if indentation != current {
+                changes.push((line, current.chars().count(), indentation));
+            }
+        }
+
+        self.apply_indentation(&lines, changes);
+    }
+
+    // Replaces the indentation of the specified lines (given as the line,
+    // the length of its current indentation, and its new indentation) using
+    // a single edit spanning them, keeping the cursor, selections, and
+    // collaborators on the same content.
+    fn apply_indentation(&mut self, lines: &[&str], changes: Vec<(usize, usize, String)>) {
+        let (first, last) = match (changes.first(), changes.last()) {
+            (Some(first), Some(last)) => (first.0, last.0),
+            _ => return,
+        };
+
+        let mut replacement = String::new();
+        let mut changed = changes.iter().peekable();
+        for (line, &content) in lines.iter().enumerate().take(last + 1).skip(first) {
+            if line > first {
+                replacement.push('\n');
+            }
+            match changed.next_if(|change| change.0 == line) {
+                Some((_, _, indentation)) if line == last => replacement.push_str(indentation),
+                Some((_, _, indentation)) => {
+                    replacement.push_str(indentation);
+                    replacement.push_str(content.trim_start_matches(char::is_whitespace));
+                },
+                None => replacement.push_str(content),
+            }
+        }
+
+        // Positions on changed lines move along with their content.
+        let shift = |position: Position| {
+            match changes.binary_search_by_key(&position.line, |change| change.0) {
+                Ok(index) => {
+                    let (_, old_length, ref indentation) = changes[index];
+                    let offset = position.offset.saturating_sub(old_length) + indentation.chars().count();
+                    Position{ line: position.line, offset }
+                },
+                Err(_) => position,
+            }
+        };
+        let shift_range = |range: &Range| Range::new(shift(range.start()), shift(range.end()));
+        let cursor = shift(*self.cursor);
+        let selections: Vec<Range> = self.selections.iter().map(&shift_range).collect();
+        let mut collaborators = self.collaborators.clone();
+        for collaborator in &mut collaborators {
+            collaborator.cursor = shift(collaborator.cursor);
+            collaborator.selection = collaborator.selection.as_ref().map(&shift_range);
+        }
+
+        let last_length = changes[changes.len() - 1].1;
+        let range = Range::new(Position{ line: first, offset: 0 }, Position{ line: last, offset: last_length });
+        if self.try_batch(|buffer| buffer.replace_verbatim(&range, &replacement)).is_err() { return; }
+
+        self.cursor.move_to(cursor);
+        self.selections = selections;
+        self.collaborators = collaborators;
+    }
+
+    /// Recomputes the indentation of every line in the buffer (see
+    /// `reindent`), as a single operation. Useful after changing the
+    /// buffer's `tab_width` or `soft_tabs` settings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{IndentRules, Position};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("fn main() {\n  if scribe {\n  library();\n  }\n}");
+    /// buffer.indent_rules = Some(IndentRules::brackets());
+    /// buffer.cursor.move_to(Position{ line: 2, offset: 3 });
+    ///
+    /// buffer.reindent_all();
+    /// assert_eq!(buffer.data(), "fn main() {\n    if scribe {\n        library();\n    }\n}");
+    /// assert_eq!(*buffer.cursor, Position{ line: 2, offset: 9 });
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "fn main() {\n  if scribe {\n  library();\n  }\n}");
+    /// ```
+    pub fn reindent_all(&mut self) {
+        let range = Range::new(Position::new(), self.end_position());
+        self.reindent(&range);
+    }
+
+    // Replaces the line's indentation with the one computed for it (or when
+    // the buffer doesn't have any rules, the previous line's), moving the
+    // cursor along with the line's content. Blank lines are emptied, unless
+    // specified otherwise. Returns the new indentation's length, in graphemes.
+    fn reindent_line(&mut self, line: usize, indent_blank: bool) -> usize {
+        let data = self.data();
+        let mut lines = data.split('\n').take(line + 1);
+        let content = lines.nth(line).unwrap_or_default().to_string();
+        let current = leading_whitespace(&content);
+        let indentation = if content.trim().is_empty() && !indent_blank {
+            String::new()
+        } else {
+            let previous = data.split('\n')
+                .take(line)
+                .filter(|previous| !previous.trim().is_empty())
+                .last();
+            self.indentation_after(previous, &content)
+        };
+
+        if indentation != current {
+            let old_length = current.chars().count();
+            let new_length = indentation.chars().count();
+            self.replace_verbatim(&Range::new(
+                Position{ line, offset: 0 },
+                Position{ line, offset: old_length }
+            ), &indentation);
+
+            if self.cursor.line == line {
+                let offset = self.cursor.offset.saturating_sub(old_length) + new_length;
+                self.cursor.move_to(Position{ line, offset });
+            }
+        }
+
+        indentation.chars().count()
+    }
+
+    // Computes the indentation for the line, based on the content it starts
+    // with and the closest non-blank line preceding it (if any).
+    fn indentation_after(&self, previous: Option<&str>, content: &str) -> String {
+        let previous = match previous {
+            Some(previous) => previous,
+            None => return String::new(),
+        };
+        let rules = match self.indent_rules {
+            Some(ref rules) => rules,
+            None => return leading_whitespace(previous).to_string(),
+        };
+
+        let tab_width = self.tab_width.max(1);
+        let mut width = leading_whitespace(previous).chars().fold(0, |width, character| {
+            if character == '\t' { width + tab_width - width % tab_width } else { width + 1 }
+        });
+        if rules.increases_after(previous) {
+            width += tab_width;
+        }
+        if rules.decreases_on(content) {
+            width = width.saturating_sub(tab_width);
+        }
+
+        if self.soft_tabs {
+            " ".repeat(width)
+        } else {
+            format!("{}{}", "\t".repeat(width / tab_width), " ".repeat(width % tab_width))
+        }
+    }
+}
+
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+// Counts the line's opening tags, less its closing and self-closing tags.
+fn unclosed_tags(line: &str) -> isize {
+    let mut count = 0;
+    for (index, _) in line.match_indices('<') {
+        match line[index + 1..].chars().next() {
+            Some('/') => count -= 1,
+            Some(c) if c.is_alphabetic() => count += 1,
+            _ => (),
+        }
+    }
+
+    count - line.matches("/>").count() as isize
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, IndentRules, Position, Range};
+
+    #[test]
+    fn insert_newline_copies_indentation_without_rules() {
+        let mut buffer = Buffer::new();
+        buffer.insert("\t  scribe {");
+        buffer.cursor.move_to(Position{ line: 0, offset: 11 });
+
+        buffer.insert_newline();
+        assert_eq!(buffer.data(), "\t  scribe {\n\t  ");
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 3 });
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "\t  scribe {");
+    }
+
+    #[test]
+    fn insert_newline_uses_tabs_without_soft_tabs() {
+        let mut buffer = Buffer::new();
+        buffer.insert("\tif scribe:");
+        buffer.indent_rules = IndentRules::defaults().remove("Python");
+        buffer.soft_tabs = false;
+        buffer.cursor.move_to(Position{ line: 0, offset: 11 });
+
+        buffer.insert_newline();
+        assert_eq!(buffer.data(), "\tif scribe:\n\t\t");
+    }
+
+    #[test]
+    fn insert_newline_splits_lines_between_graphemes() {
+        let mut buffer = Buffer::new();
+        buffer.insert("e\u{301}{}");
+        buffer.indent_rules = Some(IndentRules::brackets());
+        buffer.cursor.move_to(Position{ line: 0, offset: 2 });
+
+        buffer.insert_newline();
+        assert_eq!(buffer.data(), "e\u{301}{\n    \n}");
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 4 });
+    }
+
+    #[test]
+    fn insert_newline_reindents_moved_content() {
+        let mut buffer = Buffer::new();
+        buffer.insert("[\n  1,  ]");
+        buffer.indent_rules = Some(IndentRules::brackets());
+        buffer.tab_width = 2;
+        buffer.cursor.move_to(Position{ line: 1, offset: 4 });
+
+        buffer.insert_newline();
+        assert_eq!(buffer.data(), "[\n  1,\n]");
+        assert_eq!(*buffer.cursor, Position{ line: 2, offset: 0 });
+    }
+
+    #[test]
+    fn reindent_skips_blank_lines_and_nested_tags() {
+        let mut buffer = Buffer::new();
+        buffer.insert("<ul>\n<li>a</li>\n   \n<li>\n<br/>\n</li>\n</ul>");
+        buffer.indent_rules = Some(IndentRules::tags());
+        buffer.tab_width = 1;
+
+        let range = Range::new(Position::new(), buffer.end_position());
+        buffer.reindent(&range);
+        assert_eq!(buffer.data(), "<ul>\n <li>a</li>\n\n <li>\n  <br/>\n </li>\n</ul>");
+    }
+
+    #[test]
+    fn reindent_all_applies_changed_tab_settings() {
+        let mut buffer = Buffer::new();
+        buffer.insert("if scribe:\n    if library:\n        pass");
+        buffer.indent_rules = IndentRules::defaults().remove("Python");
+        buffer.cursor.move_to(Position{ line: 2, offset: 8 });
+
+        buffer.soft_tabs = false;
+        buffer.reindent_all();
+        assert_eq!(buffer.data(), "if scribe:\n\tif library:\n\t\tpass");
+        assert_eq!(*buffer.cursor, Position{ line: 2, offset: 2 });
+    }
+
+    #[test]
+    fn reindent_all_is_undone_as_a_single_change_and_keeps_selections() {
+        let mut buffer = Buffer::new();
+        buffer.insert("if scribe:\n  if library:\n    pass\nvalue = 1");
+        buffer.indent_rules = IndentRules::defaults().remove("Python");
+        buffer.selections = vec![Range::new(
+            Position{ line: 1, offset: 5 },
+            Position{ line: 3, offset: 4 }
+        )];
+
+        buffer.reindent_all();
+        assert_eq!(buffer.data(), "if scribe:\n    if library:\n        pass\n        value = 1");
+        assert_eq!(buffer.selections, vec![Range::new(
+            Position{ line: 1, offset: 7 },
+            Position{ line: 3, offset: 12 }
+        )]);
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "if scribe:\n  if library:\n    pass\nvalue = 1");
+    }
+}
@@ -0,0 +1,99 @@
+//! Inserting other files' content into a buffer.
+
+use buffer::{line_ending, special_file, Buffer};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// The largest file, in bytes, that `Buffer::insert_file` will insert.
+pub const INSERT_FILE_LIMIT: u64 = 16 * 1024 * 1024;
+
+impl Buffer {
+    /// Reads the file at the specified path and inserts its content at the
+    /// cursor, as a single undoable operation. Like `insert`, this doesn't
+    /// move the cursor. Its line endings are converted to the buffer's
+    /// internal representation.
+    ///
+    /// Returns an error if the buffer is read-only, if the file can't be
+    /// read as UTF-8 text (see `SpecialFile`), or if it's larger than
+    /// `INSERT_FILE_LIMIT`, in which case the buffer is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use std::env;
+    /// use std::fs;
+    ///
+    /// let path = env::temp_dir().join("scribe_insert_file_example");
+    /// fs::write(&path, "scribe\r\nlibrary\r\n").unwrap();
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("editor");
+    /// buffer.insert_file(&path).unwrap();
+    /// assert_eq!(buffer.data(), "scribe\nlibrary\neditor");
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "editor");
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn insert_file(&mut self, path: &Path) -> io::Result<()> {
+        if self.read_only {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "buffer is read-only"
+            ));
+        }
+        special_file::check(path)?;
+
+        // Check the size up front, but also limit the read,
+        // in case the file grows in the meantime.
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        let mut data = String::new();
+        file.take(INSERT_FILE_LIMIT + 1).read_to_string(&mut data)?;
+        if size > INSERT_FILE_LIMIT || data.len() as u64 > INSERT_FILE_LIMIT {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("file is too large to insert (the limit is {} bytes)", INSERT_FILE_LIMIT)
+            ));
+        }
+
+        let position = *self.cursor;
+        let data = line_ending::decode(data).data;
+        self.batch(|buffer| buffer.insert_at(data, position));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::Buffer;
+    use std::env;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    #[test]
+    fn insert_file_refuses_directories_and_binary_files() {
+        let mut buffer = Buffer::new();
+        assert!(buffer.insert_file(Path::new("tests/sample")).is_err());
+
+        let path = env::temp_dir().join("scribe_insert_file_binary");
+        fs::write(&path, [0xff, 0xfe, 0x00]).unwrap();
+        assert_eq!(buffer.insert_file(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(buffer.data(), "");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn insert_file_refuses_read_only_buffers() {
+        let mut buffer = Buffer::new();
+        buffer.read_only = true;
+
+        let error = buffer.insert_file(Path::new("tests/sample/file")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+    }
+}
@@ -0,0 +1,229 @@
+//! Locations parsed from compiler, linter, and search tool output.
+
+use buffer::{Buffer, Position};
+use std::path::PathBuf;
+
+/// A location referenced by a line of tool output, such as a compiler
+/// error or a search result. Positions are zero-based, with columns
+/// treated as grapheme offsets, and default to the start of the line
+/// when the output doesn't include a column.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JumpTarget {
+    pub path: PathBuf,
+    pub position: Position,
+    pub message: String,
+}
+
+impl JumpTarget {
+    /// Parses a single line of output in one of the following formats,
+    /// where line and column numbers are one-based:
+    ///
+    /// * `path:line:column: message` (GCC, Clang, and most linters)
+    /// * `path:line: message` or `path:line:text` (e.g. `grep -n`)
+    /// * `path(line,column): message` (MSVC and TypeScript)
+    /// * `--> path:line:column` (rustc, without a message)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::buffer::{JumpTarget, Position};
+    /// use std::path::PathBuf;
+    ///
+    /// let target = JumpTarget::parse("src/lib.rs:12:5: error: expected `;`").unwrap();
+    /// assert_eq!(target.path, PathBuf::from("src/lib.rs"));
+    /// assert_eq!(target.position, Position{ line: 11, offset: 4 });
+    /// assert_eq!(target.message, "error: expected `;`");
+    ///
+    /// assert!(JumpTarget::parse("Compiling scribe v0.8.0").is_none());
+    /// ```
+    pub fn parse(line: &str) -> Option<JumpTarget> {
+        let line = line.trim_end();
+
+        if let Some(location) = line.trim_start().strip_prefix("--> ") {
+            let (path, line_number, column, _) = split_location(location)?;
+            return Some(JumpTarget::new(path, line_number, column, ""));
+        }
+
+        if let Some(target) = parse_parenthesized(line) {
+            return Some(target);
+        }
+
+        let (path, line_number, column, message) = split_location(line)?;
+        Some(JumpTarget::new(path, line_number, column, message))
+    }
+
+    fn new(path: &str, line: usize, column: Option<usize>, message: &str) -> JumpTarget {
+        JumpTarget{
+            path: PathBuf::from(path),
+            position: Position{
+                line: line.saturating_sub(1),
+                offset: column.unwrap_or(1).saturating_sub(1),
+            },
+            message: message.trim().to_string(),
+        }
+    }
+}
+
+impl Buffer {
+    /// Parses each of the buffer's lines as a jump target (see
+    /// `JumpTarget::parse`), skipping lines that don't reference a location.
+    /// The locations rustc reports beneath its diagnostics are given the
+    /// message from the diagnostic's first line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("error[E0425]: cannot find value `x` in this scope\n --> src/main.rs:2:5\n");
+    ///
+    /// let targets = buffer.jump_targets();
+    /// assert_eq!(targets.len(), 1);
+    /// assert_eq!(targets[0].path, PathBuf::from("src/main.rs"));
+    /// assert_eq!(targets[0].position, Position{ line: 1, offset: 4 });
+    /// assert_eq!(targets[0].message, "error[E0425]: cannot find value `x` in this scope");
+    /// ```
+    pub fn jump_targets(&self) -> Vec<JumpTarget> {
+        let mut targets = Vec::new();
+        let mut diagnostic = None;
+
+        for line in self.data().lines() {
+            if is_diagnostic(line) {
+                diagnostic = Some(line.trim().to_string());
+            } else if let Some(mut target) = JumpTarget::parse(line) {
+                if target.message.is_empty() {
+                    target.message = diagnostic.take().unwrap_or_default();
+                }
+                targets.push(target);
+            }
+        }
+
+        targets
+    }
+}
+
+// Splits "path:line[:column][:message]" into its components.
+fn split_location(line: &str) -> Option<(&str, usize, Option<usize>, &str)> {
+    // Skip over Windows drive prefixes (e.g. "C:\").
+    let drive_length = match line.as_bytes() {
+        [drive, b':', b'\\', ..] | [drive, b':', b'/', ..] if drive.is_ascii_alphabetic() => 2,
+        _ => 0,
+    };
+    let separator = drive_length + line[drive_length..].find(':')?;
+    let path = &line[..separator];
+    if path.is_empty() || path.starts_with(char::is_whitespace) || path.contains(": ") {
+        return None;
+    }
+
+    let (line_number, remainder) = leading_number(&line[separator + 1..])?;
+    let remainder = match remainder.strip_prefix(':') {
+        Some(remainder) => remainder,
+        None if remainder.is_empty() => remainder,
+        None => return None,
+    };
+
+    // Columns are only recognized when they're followed by a separator.
+    match leading_number(remainder) {
+        Some((column, message)) if message.is_empty() || message.starts_with(':') => {
+            Some((path, line_number, Some(column), message.trim_start_matches(':')))
+        },
+        _ => Some((path, line_number, None, remainder)),
+    }
+}
+
+// Parses "path(line[,column]): message".
+fn parse_parenthesized(line: &str) -> Option<JumpTarget> {
+    let open = line.find('(')?;
+    let close = open + line[open..].find("):")?;
+    let path = &line[..open];
+    if path.is_empty() || path.starts_with(char::is_whitespace) || path.ends_with(' ') {
+        return None;
+    }
+
+    let mut numbers = line[open + 1..close].split(',').map(|number| number.trim().parse().ok());
+    let line_number = numbers.next()??;
+    let column = match numbers.next() {
+        Some(column) => Some(column?),
+        None => None,
+    };
+    if numbers.next().is_some() { return None; }
+
+    Some(JumpTarget::new(path, line_number, column, &line[close + 2..]))
+}
+
+fn leading_number(data: &str) -> Option<(usize, &str)> {
+    let length = data.find(|c: char| !c.is_ascii_digit()).unwrap_or(data.len());
+    let number = data[..length].parse().ok()?;
+
+    Some((number, &data[length..]))
+}
+
+// Whether the line begins a rustc-style diagnostic (e.g. "error[E0425]: ...").
+fn is_diagnostic(line: &str) -> bool {
+    ["error", "warning", "note", "help"].iter().any(|level| {
+        line.strip_prefix(level).is_some_and(|remainder| {
+            remainder.starts_with(": ") || (remainder.starts_with('[') && remainder.contains("]: "))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, JumpTarget, Position};
+    use std::path::PathBuf;
+
+    #[test]
+    fn parse_handles_grep_output_without_columns() {
+        let target = JumpTarget::parse("src/lib.rs:3:pub mod buffer;").unwrap();
+
+        assert_eq!(target.path, PathBuf::from("src/lib.rs"));
+        assert_eq!(target.position, Position{ line: 2, offset: 0 });
+        assert_eq!(target.message, "pub mod buffer;");
+    }
+
+    #[test]
+    fn parse_does_not_treat_leading_digits_in_messages_as_columns() {
+        let target = JumpTarget::parse("notes.txt:7:42 apples").unwrap();
+
+        assert_eq!(target.position, Position{ line: 6, offset: 0 });
+        assert_eq!(target.message, "42 apples");
+    }
+
+    #[test]
+    fn parse_handles_parenthesized_locations() {
+        let target = JumpTarget::parse("src/app.ts(10,3): error TS2304: Cannot find name 'x'.").unwrap();
+
+        assert_eq!(target.path, PathBuf::from("src/app.ts"));
+        assert_eq!(target.position, Position{ line: 9, offset: 2 });
+        assert_eq!(target.message, "error TS2304: Cannot find name 'x'.");
+    }
+
+    #[test]
+    fn parse_handles_windows_paths() {
+        let target = JumpTarget::parse("C:\\src\\main.c:4:1: warning: unused").unwrap();
+
+        assert_eq!(target.path, PathBuf::from("C:\\src\\main.c"));
+        assert_eq!(target.position, Position{ line: 3, offset: 0 });
+    }
+
+    #[test]
+    fn parse_rejects_lines_without_locations() {
+        assert!(JumpTarget::parse("error: could not compile `scribe`").is_none());
+        assert!(JumpTarget::parse("note: run with `RUST_BACKTRACE=1`").is_none());
+        assert!(JumpTarget::parse("http://example.com/").is_none());
+        assert!(JumpTarget::parse("").is_none());
+    }
+
+    #[test]
+    fn jump_targets_only_uses_diagnostic_messages_once() {
+        let mut buffer = Buffer::new();
+        buffer.insert("warning: unused variable\n --> src/a.rs:1:1\n --> src/b.rs:2:2\n");
+
+        let targets = buffer.jump_targets();
+        assert_eq!(targets[0].message, "warning: unused variable");
+        assert_eq!(targets[1].message, "");
+    }
+}
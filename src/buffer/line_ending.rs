@@ -0,0 +1,143 @@
+//! Line ending detection and conversion.
+
+use std::borrow::Cow;
+
+/// The sequence used to terminate lines in a buffer's file. Buffers always
+/// use "\n" internally; their line ending is applied when they're saved.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    /// The line ending's character sequence.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+
+    // Converts the data's "\n" line endings to this line ending. Lines
+    // already ending with "\r\n" (e.g. inserted that way) are left as-is.
+    pub(crate) fn apply<'a>(&self, data: &'a str) -> Cow<'a, str> {
+        match *self {
+            LineEnding::Lf => Cow::Borrowed(data),
+            LineEnding::Crlf => Cow::Owned(apply_each(data, |_| LineEnding::Crlf)),
+        }
+    }
+}
+
+// Converts the data's "\n" line endings to those of the corresponding lines
+// of a file loaded with mixed line endings, using the default line ending
+// for lines beyond those tracked.
+pub(crate) fn apply_mixed(data: &str, line_endings: &[LineEnding], default: LineEnding) -> String {
+    apply_each(data, |line| line_endings.get(line).cloned().unwrap_or(default))
+}
+
+// Terminates each line of the data with the line ending chosen for its
+// index, leaving lines that already end with "\r\n" as they are.
+fn apply_each<F: Fn(usize) -> LineEnding>(data: &str, line_ending: F) -> String {
+    let mut result = String::with_capacity(data.len());
+    for (line, content) in data.split('\n').enumerate() {
+        if line > 0 {
+            let previous = result.ends_with('\r');
+            if line_ending(line - 1) == LineEnding::Crlf && !previous {
+                result.push('\r');
+            }
+            result.push('\n');
+        }
+        result.push_str(content);
+    }
+
+    result
+}
+
+// The result of normalizing file content to "\n" line endings.
+pub(crate) struct DecodedLines {
+    pub data: String,
+    pub line_ending: LineEnding,
+
+    // The ending of each line, if the content used both line endings (in
+    // which case saving it will replace the less common of the two with the
+    // other, unless the buffer preserves formatting). Empty otherwise.
+    pub mixed: Vec<LineEnding>,
+}
+
+// Detects the dominant line ending in the data (favouring "\n" in a tie,
+// or if there are no lines), and converts any "\r\n" sequences to "\n".
+pub(crate) fn decode(data: String) -> DecodedLines {
+    let crlf_count = data.matches("\r\n").count();
+    if crlf_count == 0 {
+        return DecodedLines{ data, line_ending: LineEnding::Lf, mixed: Vec::new() };
+    }
+
+    let lf_count = data.matches('\n').count() - crlf_count;
+    let line_ending = if crlf_count > lf_count {
+        LineEnding::Crlf
+    } else {
+        LineEnding::Lf
+    };
+    let mixed = if lf_count > 0 {
+        data.split('\n').map(|line| {
+            if line.ends_with('\r') { LineEnding::Crlf } else { LineEnding::Lf }
+        }).collect()
+    } else {
+        Vec::new()
+    };
+
+    DecodedLines{
+        data: data.replace("\r\n", "\n"),
+        line_ending,
+        mixed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_mixed, decode, LineEnding};
+
+    #[test]
+    fn decode_detects_the_dominant_line_ending() {
+        let decoded = decode("scribe\r\nlibrary\r\neditor\n".to_string());
+        assert_eq!(decoded.data, "scribe\nlibrary\neditor\n");
+        assert_eq!(decoded.line_ending, LineEnding::Crlf);
+        assert_eq!(decoded.mixed, vec![LineEnding::Crlf, LineEnding::Crlf, LineEnding::Lf, LineEnding::Lf]);
+
+        let decoded = decode("scribe\r\nlibrary\neditor\n".to_string());
+        assert_eq!(decoded.line_ending, LineEnding::Lf);
+
+        let decoded = decode("scribe\r\n".to_string());
+        assert_eq!(decoded.line_ending, LineEnding::Crlf);
+        assert!(decoded.mixed.is_empty());
+    }
+
+    #[test]
+    fn decode_leaves_lone_carriage_returns_alone() {
+        let decoded = decode("scribe\rlibrary\n".to_string());
+        assert_eq!(decoded.data, "scribe\rlibrary\n");
+        assert_eq!(decoded.line_ending, LineEnding::Lf);
+    }
+
+    #[test]
+    fn apply_converts_line_feeds() {
+        assert_eq!(LineEnding::Crlf.apply("scribe\nlibrary\n"), "scribe\r\nlibrary\r\n");
+        assert_eq!(LineEnding::Lf.apply("scribe\nlibrary\n"), "scribe\nlibrary\n");
+    }
+
+    #[test]
+    fn apply_leaves_existing_crlf_line_endings_alone() {
+        assert_eq!(LineEnding::Crlf.apply("scribe\r\nlibrary\n"), "scribe\r\nlibrary\r\n");
+    }
+
+    #[test]
+    fn apply_mixed_uses_each_lines_ending() {
+        let line_endings = [LineEnding::Crlf, LineEnding::Lf];
+        assert_eq!(
+            apply_mixed("scribe\nlibrary\neditor\n", &line_endings, LineEnding::Crlf),
+            "scribe\r\nlibrary\neditor\r\n"
+        );
+    }
+}
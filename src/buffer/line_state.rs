@@ -0,0 +1,226 @@
+//! Tracking of lines changed since the buffer was last saved.
+
+use buffer::{Buffer, Position, Range};
+
+/// Describes how a line has changed since the buffer was last loaded or saved.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineState {
+    Unchanged,
+    Added,
+    Modified,
+}
+
+impl Buffer {
+    /// Describes, for each line, whether it's been added or modified since
+    /// the buffer was last loaded or saved, suitable for rendering change
+    /// bars in a gutter. States are updated as the buffer is modified (rather
+    /// than by comparing its contents to the file), and are reset when
+    /// undo/redo returns the buffer to its saved state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{LineState, Position};
+    /// use std::path::Path;
+    ///
+    /// # #[cfg(feature = "fs")] {
+    /// // The buffer's contents are "it works!\n".
+    /// let mut buffer = Buffer::from_file(Path::new("tests/sample/file")).unwrap();
+    /// buffer.insert("scribe\n");
+    /// buffer.cursor.move_to(Position{ line: 2, offset: 0 });
+    /// buffer.insert("library");
+    ///
+    /// assert_eq!(
+    ///     buffer.line_states(),
+    ///     vec![LineState::Added, LineState::Unchanged, LineState::Modified]
+    /// );
+    /// # }
+    /// ```
+    pub fn line_states(&self) -> Vec<LineState> {
+        let mut states = self.line_states.clone();
+        states.resize(self.line_count(), LineState::Unchanged);
+
+        states
+    }
+
+    // Updates line states to reflect the insertion of content
+    // at the specified position. Must be called beforehand.
+    pub(crate) fn track_insertion(&mut self, content: &str, position: &Position) {
+        if !self.data.borrow().in_bounds(position) { return; }
+
+        let added_lines = content.matches('\n').count();
+        if !self.mixed_line_endings.is_empty() {
+            // Added lines take the buffer's line ending, and the last
+            // one takes that of the line the content was inserted into.
+            let index = position.line.min(self.mixed_line_endings.len());
+            let line_ending = self.line_ending;
+            self.mixed_line_endings.splice(index..index, vec![line_ending; added_lines]);
+        }
+        let at_line_end = !self.data.borrow().in_bounds(
+            &Position{ line: position.line, offset: position.offset + 1 }
+        );
+        self.prepare_line_states();
+
+        let added_line = if added_lines == 0 {
+            self.mark_line_modified(position.line);
+            return;
+        } else if position.offset == 0 && content.ends_with('\n') {
+            // The content is inserted above the existing line.
+            position.line
+        } else if at_line_end && content.starts_with('\n') {
+            // The content is inserted below the existing line.
+            position.line + 1
+        } else {
+            self.mark_line_modified(position.line);
+            position.line + 1
+        };
+
+        let index = added_line.min(self.line_states.len());
+        self.line_states.splice(index..index, vec![LineState::Added; added_lines]);
+    }
+
+    // Updates line states to reflect the deletion of the specified
+    // range, and its content. Must be called beforehand.
+    pub(crate) fn track_deletion(&mut self, range: &Range, content: Option<&str>) {
+        if !self.data.borrow().in_bounds(&range.start()) { return; }
+
+        let start = range.start();
+        let removed_lines = content.map(|c| c.matches('\n').count()).unwrap_or(0);
+        if !self.mixed_line_endings.is_empty() {
+            // The joined line takes the ending of the last removed line.
+            let end = (start.line + removed_lines).min(self.mixed_line_endings.len());
+            self.mixed_line_endings.drain(start.line.min(end)..end);
+        }
+        self.prepare_line_states();
+
+        let removed = if start.offset == 0 && content.map(|c| c.ends_with('\n')).unwrap_or(false) {
+            // Whole lines are being removed; the line that
+            // follows them retains its state.
+            start.line..start.line + removed_lines
+        } else {
+            self.mark_line_modified(start.line);
+            start.line + 1..start.line + 1 + removed_lines
+        };
+
+        let end = removed.end.min(self.line_states.len());
+        let start = removed.start.min(end);
+        self.line_states.drain(start..end);
+    }
+
+    // Discards line states, marking all lines as unchanged.
+    pub(crate) fn reset_line_states(&mut self) {
+        self.line_states.clear();
+    }
+
+    // Populates line states for an unmodified buffer. An empty list
+    // of states is used until then, to avoid counting lines.
+    fn prepare_line_states(&mut self) {
+        if self.line_states.is_empty() {
+            self.line_states = vec![LineState::Unchanged; self.line_count()];
+        }
+    }
+
+    fn mark_line_modified(&mut self, line: usize) {
+        if let Some(state) = self.line_states.get_mut(line) {
+            if *state == LineState::Unchanged {
+                *state = LineState::Modified;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, LineState, Position, Range};
+    #[cfg(feature = "fs")]
+    use std::env;
+    #[cfg(feature = "fs")]
+    use std::fs;
+
+    fn saved_buffer(content: &str) -> Buffer {
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        buffer.history.mark();
+        buffer.reset_line_states();
+
+        buffer
+    }
+
+    #[test]
+    fn line_states_mark_lines_opened_below_as_added() {
+        let mut buffer = saved_buffer("scribe\nlibrary");
+        buffer.cursor.move_to(Position{ line: 0, offset: 6 });
+        buffer.insert("\ntext");
+
+        assert_eq!(
+            buffer.line_states(),
+            vec![LineState::Unchanged, LineState::Added, LineState::Unchanged]
+        );
+    }
+
+    #[test]
+    fn line_states_mark_split_lines_as_modified_and_added() {
+        let mut buffer = saved_buffer("scribe library");
+        buffer.cursor.move_to(Position{ line: 0, offset: 6 });
+        buffer.insert("\n");
+
+        assert_eq!(buffer.line_states(), vec![LineState::Modified, LineState::Added]);
+    }
+
+    #[test]
+    fn line_states_drop_deleted_lines() {
+        let mut buffer = saved_buffer("scribe\ntext\nlibrary");
+        buffer.delete_range(Range::new(
+            Position{ line: 1, offset: 0 },
+            Position{ line: 2, offset: 0 }
+        ));
+
+        assert_eq!(buffer.line_states(), vec![LineState::Unchanged, LineState::Unchanged]);
+    }
+
+    #[test]
+    fn line_states_mark_joined_lines_as_modified() {
+        let mut buffer = saved_buffer("scribe\nlibrary\ntext");
+        buffer.delete_range(Range::new(
+            Position{ line: 0, offset: 6 },
+            Position{ line: 1, offset: 0 }
+        ));
+
+        assert_eq!(buffer.line_states(), vec![LineState::Modified, LineState::Unchanged]);
+    }
+
+    #[test]
+    fn line_states_keep_added_lines_added_when_modified() {
+        let mut buffer = saved_buffer("scribe");
+        buffer.cursor.move_to(Position{ line: 0, offset: 6 });
+        buffer.insert("\n");
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        buffer.insert("library");
+
+        assert_eq!(buffer.line_states(), vec![LineState::Unchanged, LineState::Added]);
+    }
+
+    #[test]
+    fn line_states_are_reset_when_undoing_to_the_saved_state() {
+        let mut buffer = saved_buffer("scribe");
+        buffer.insert("\n");
+        buffer.undo();
+
+        assert_eq!(buffer.line_states(), vec![LineState::Unchanged]);
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn line_states_are_reset_when_saving() {
+        let path = env::temp_dir().join("scribe_line_states_are_reset_when_saving");
+        fs::write(&path, "scribe").unwrap();
+
+        let mut buffer = Buffer::from_file(&path).unwrap();
+        buffer.insert("\n");
+        buffer.save().unwrap();
+        assert_eq!(buffer.line_states(), vec![LineState::Unchanged; 2]);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+//! Logical lines: statements continued across several physical lines.
+
+use buffer::{Buffer, LineRange};
+
+impl Buffer {
+    /// The range of physical lines forming the logical line (i.e. statement)
+    /// that includes the specified line. Lines are continued by a trailing
+    /// backslash, or by an opening parenthesis or square bracket that's left
+    /// unclosed; brackets within quoted strings and character literals are
+    /// ignored. Braces
+    /// aren't considered, since they usually delimit blocks of statements.
+    /// Returns `None` if the line doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::LineRange;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("let total = add(\n    1,\n    2\n);\nlet x = 1;");
+    ///
+    /// assert_eq!(buffer.logical_line(2), Some(LineRange::new(0, 4)));
+    /// assert_eq!(buffer.logical_line(4), Some(LineRange::new(4, 5)));
+    /// assert_eq!(buffer.logical_line(5), None);
+    /// ```
+    pub fn logical_line(&self, line: usize) -> Option<LineRange> {
+        logical_line_range(&self.data(), line)
+    }
+}
+
+// Finds the logical line including the specified line. Since a line's
+// nesting depends on those preceding it, the data is scanned from its start.
+pub(crate) fn logical_line_range(data: &str, line: usize) -> Option<LineRange> {
+    let mut start = 0;
+    let mut depth = 0;
+    let mut line_count = 0;
+
+    for (index, content) in data.split('\n').enumerate() {
+        depth = nesting_after(content, depth);
+        line_count = index + 1;
+
+        let continued = depth > 0 || content.ends_with('\\');
+        if !continued {
+            if index >= line {
+                return Some(LineRange::new(start, index + 1));
+            }
+            start = index + 1;
+        }
+    }
+
+    // The final logical line is left unterminated; it runs to the end of the data.
+    if line < line_count {
+        Some(LineRange::new(start, line_count))
+    } else {
+        None
+    }
+}
+
+// The bracket nesting depth at the end of the line, given the depth at its
+// start. Quotes of either kind are only closed by the same kind, and a single
+// quote that isn't closed on the line (e.g. a Rust lifetime, or an apostrophe
+// in a comment) is ignored, rather than quoting the rest of the line.
+fn nesting_after(line: &str, mut depth: usize) -> usize {
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (index, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+
+        match (c, quote) {
+            ('\\', _) => escaped = true,
+            (_, Some(open)) if c == open => quote = None,
+            (_, Some(_)) => (),
+            ('"', None) => quote = Some('"'),
+            ('\'', None) if closes_quote(&line[index + 1..]) => quote = Some('\''),
+            ('(', None) | ('[', None) => depth += 1,
+            (')', None) | (']', None) => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+    }
+
+    depth
+}
+
+// Whether the rest of a line closes a single quote that precedes it.
+fn closes_quote(rest: &str) -> bool {
+    let mut escaped = false;
+
+    for c in rest.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\'' => return true,
+            _ => (),
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::logical_line_range;
+    use buffer::LineRange;
+
+    #[test]
+    fn trailing_backslashes_continue_lines() {
+        let data = "CFLAGS = -O2 \\\n    -Wall \\\n    -g\nall: build";
+
+        assert_eq!(logical_line_range(data, 1), Some(LineRange::new(0, 3)));
+        assert_eq!(logical_line_range(data, 3), Some(LineRange::new(3, 4)));
+    }
+
+    #[test]
+    fn brackets_in_strings_and_braces_are_ignored() {
+        let data = "print(\"(\")\nfn main() {\n    run();\n}";
+
+        assert_eq!(logical_line_range(data, 0), Some(LineRange::new(0, 1)));
+        assert_eq!(logical_line_range(data, 2), Some(LineRange::new(2, 3)));
+    }
+
+    #[test]
+    fn brackets_in_character_literals_are_ignored() {
+        let data = "if c == '(' {\nlet s = 'a';\nfn f<'a>(x: &'a str) {}\nlet t = \"it's\";";
+
+        assert_eq!(logical_line_range(data, 0), Some(LineRange::new(0, 1)));
+        assert_eq!(logical_line_range(data, 1), Some(LineRange::new(1, 2)));
+        assert_eq!(logical_line_range(data, 2), Some(LineRange::new(2, 3)));
+        assert_eq!(logical_line_range(data, 3), Some(LineRange::new(3, 4)));
+        assert_eq!(logical_line_range("x = ')'\ny = 1", 1), Some(LineRange::new(1, 2)));
+    }
+
+    #[test]
+    fn unclosed_brackets_continue_to_the_end_of_the_data() {
+        let data = "a\nitems = [\n    1,\n";
+
+        assert_eq!(logical_line_range(data, 3), Some(LineRange::new(1, 4)));
+        assert_eq!(logical_line_range(data, 4), None);
+    }
+}
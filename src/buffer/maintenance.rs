@@ -0,0 +1,169 @@
+//! Deferred, idle-time buffer upkeep.
+
+use buffer::Buffer;
+use util::{Budget, BudgetMeter};
+
+// Buffers whose gap exceeds this size (in bytes), as well
+// as the size of their contents, are shrunk during maintenance.
+const GAP_COMPACTION_THRESHOLD: usize = 64 * 1024;
+
+// The number of distinct maintenance tasks; see perform_task.
+const MAINTENANCE_TASKS: usize = 3;
+
+/// A summary of the work done by `Buffer::perform_maintenance`.
+#[derive(Debug, Default, PartialEq)]
+pub struct MaintenanceReport {
+    /// Whether or not the gap buffer's unused space was released.
+    pub compacted: bool,
+
+    /// The number of operations discarded from the undo history.
+    pub trimmed_operations: usize,
+
+    /// Whether or not the buffer's file has been modified
+    /// on disk since it was last loaded or saved.
+    pub modified_on_disk: bool,
+
+    /// Whether or not the budget ran out before every task was performed.
+    /// The remaining tasks are performed first on the next call.
+    pub incomplete: bool,
+}
+
+impl Buffer {
+    /// Performs deferred upkeep, intended to be called when the application
+    /// is idle: releasing excess gap buffer space left behind by large
+    /// deletions, trimming undo history beyond the buffer's `history_limit`,
+    /// and checking whether the buffer's file has been modified on disk
+    /// (which requires the `fs` feature).
+    ///
+    /// Each task counts as a unit of work against the budget. Tasks that
+    /// don't fit are deferred to the next call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::util::Budget;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.history_limit = Some(1);
+    /// buffer.insert("scribe");
+    /// buffer.insert(" ");
+    ///
+    /// let report = buffer.perform_maintenance(Budget::Units(3));
+    /// assert_eq!(report.trimmed_operations, 1);
+    /// assert!(!report.incomplete);
+    /// ```
+    pub fn perform_maintenance(&mut self, budget: Budget) -> MaintenanceReport {
+        self.perform_maintenance_tasks(&mut budget.meter(), MAINTENANCE_TASKS)
+    }
+
+    // Like perform_maintenance, spending a budget shared with other work, and
+    // only performing the tasks remaining in the current round of them, so
+    // that repeated calls finish a round, however small the budget.
+    #[cfg(feature = "workspace")]
+    pub(crate) fn perform_maintenance_within(&mut self, meter: &mut BudgetMeter) -> MaintenanceReport {
+        let remaining = MAINTENANCE_TASKS - self.maintenance_task;
+        self.perform_maintenance_tasks(meter, remaining)
+    }
+
+    fn perform_maintenance_tasks(&mut self, meter: &mut BudgetMeter, count: usize) -> MaintenanceReport {
+        let mut report = MaintenanceReport::default();
+
+        for _ in 0..count {
+            if !meter.permit() {
+                report.incomplete = true;
+                break;
+            }
+
+            let task = self.maintenance_task;
+            self.perform_task(task, &mut report);
+            self.maintenance_task = (task + 1) % MAINTENANCE_TASKS;
+        }
+
+        report
+    }
+
+    fn perform_task(&mut self, task: usize, report: &mut MaintenanceReport) {
+        match task {
+            0 => {
+                let mut data = self.data.borrow_mut();
+                let gap_length = data.capacity() - data.len();
+
+                if gap_length > GAP_COMPACTION_THRESHOLD && gap_length > data.len() {
+                    data.shrink_to_fit();
+                    report.compacted = true;
+                }
+            },
+            1 => {
+                if let Some(limit) = self.history_limit {
+                    report.trimmed_operations = self.history.trim(limit);
+                }
+            },
+            #[cfg(feature = "fs")]
+            _ => report.modified_on_disk = self.outdated(),
+            #[cfg(not(feature = "fs"))]
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, Range};
+    #[cfg(feature = "fs")]
+    use std::env;
+    #[cfg(feature = "fs")]
+    use std::fs;
+    #[cfg(feature = "fs")]
+    use std::time::{Duration, SystemTime};
+    use util::Budget;
+
+    #[test]
+    fn perform_maintenance_compacts_large_gaps() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\n".repeat(20000));
+        buffer.delete_range(Range::new(
+            Position{ line: 1, offset: 0 },
+            Position{ line: 20000, offset: 0 }
+        ));
+
+        let report = buffer.perform_maintenance(Budget::Units(3));
+        assert!(report.compacted);
+        assert!(buffer.data.borrow().capacity() < 1024);
+        assert_eq!(buffer.data(), "scribe\n");
+    }
+
+    #[test]
+    fn perform_maintenance_resumes_deferred_tasks() {
+        let mut buffer = Buffer::new();
+        buffer.history_limit = Some(0);
+        buffer.insert("scribe");
+
+        // The first task (compaction) exhausts the budget.
+        let report = buffer.perform_maintenance(Budget::Units(1));
+        assert!(report.incomplete);
+        assert_eq!(report.trimmed_operations, 0);
+
+        let report = buffer.perform_maintenance(Budget::Units(1));
+        assert_eq!(report.trimmed_operations, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn perform_maintenance_detects_files_modified_on_disk() {
+        let path = env::temp_dir().join("scribe_maintenance_modified_on_disk");
+        fs::write(&path, "scribe").unwrap();
+
+        let mut buffer = Buffer::from_file(&path).unwrap();
+        assert!(!buffer.perform_maintenance(Budget::Units(3)).modified_on_disk);
+
+        let file = fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60)).unwrap();
+        assert!(buffer.perform_maintenance(Budget::Units(3)).modified_on_disk);
+
+        buffer.save().unwrap();
+        assert!(!buffer.perform_maintenance(Budget::Units(3)).modified_on_disk);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,188 @@
+//! Pluggable pattern matching.
+
+use buffer::{Buffer, Range};
+use buffer::search::positions_of;
+#[cfg(feature = "regex")]
+use errors::*;
+#[cfg(feature = "regex")]
+use onig::{Regex, Region, SearchOptions};
+
+/// A pattern that can be found in a buffer's content (see
+/// `Buffer::find_matches`). It's implemented for string slices, which match
+/// literally, and with the `regex` feature enabled, for `RegexMatcher`.
+/// Implementing it for other types allows alternative engines to be used.
+pub trait Matcher {
+    /// Finds the first match in the haystack that starts at or after the
+    /// `start` byte offset, returning its start and end byte offsets.
+    /// The haystack is a buffer's entire content, so that patterns may
+    /// span multiple lines.
+    fn find_at(&self, haystack: &str, start: usize) -> Option<(usize, usize)>;
+}
+
+impl Matcher for str {
+    fn find_at(&self, haystack: &str, start: usize) -> Option<(usize, usize)> {
+        haystack[start..].find(self).map(|index| (start + index, start + index + self.len()))
+    }
+}
+
+/// A regular expression, using the Oniguruma engine, which supports
+/// look-around assertions and backreferences. The `^` and `$` anchors
+/// match at the start and end of each line.
+#[cfg(feature = "regex")]
+pub struct RegexMatcher {
+    regex: Regex,
+}
+
+#[cfg(feature = "regex")]
+impl RegexMatcher {
+    /// Compiles the pattern, returning an error if it's invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range, RegexMatcher};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe 1\nlibrary 22");
+    ///
+    /// let matcher = RegexMatcher::new(r"\d+$").unwrap();
+    /// assert_eq!(buffer.find_matches(&matcher), vec![
+    ///     Range::new(Position{ line: 0, offset: 7 }, Position{ line: 0, offset: 8 }),
+    ///     Range::new(Position{ line: 1, offset: 8 }, Position{ line: 1, offset: 10 }),
+    /// ]);
+    ///
+    /// assert!(RegexMatcher::new("(").is_err());
+    /// ```
+    pub fn new(pattern: &str) -> Result<RegexMatcher> {
+        match Regex::new(pattern) {
+            Ok(regex) => Ok(RegexMatcher{ regex }),
+            Err(error) => Err(ErrorKind::InvalidPattern(error.to_string()).into()),
+        }
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Matcher for RegexMatcher {
+    fn find_at(&self, haystack: &str, start: usize) -> Option<(usize, usize)> {
+        let mut region = Region::new();
+        self.regex.search_with_options(
+            haystack,
+            start,
+            haystack.len(),
+            SearchOptions::SEARCH_OPTION_NONE,
+            Some(&mut region)
+        )?;
+
+        region.pos(0)
+    }
+}
+
+impl Buffer {
+    /// Finds the ranges of all non-overlapping matches of the matcher in the
+    /// buffer, which may span multiple lines. Empty matches are included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary");
+    ///
+    /// assert_eq!(buffer.find_matches("e\nl"), vec![
+    ///     Range::new(Position{ line: 0, offset: 5 }, Position{ line: 1, offset: 1 })
+    /// ]);
+    /// ```
+    pub fn find_matches<M: Matcher + ?Sized>(&self, matcher: &M) -> Vec<Range> {
+        let data = self.data();
+        let mut offsets = Vec::new();
+        let mut start = 0;
+
+        while let Some((match_start, match_end)) = matcher.find_at(&data, start) {
+            offsets.push(match_start);
+            offsets.push(match_end);
+
+            // Step over empty matches, so that they're not found again.
+            start = if match_end > match_start {
+                match_end
+            } else {
+                match data[match_end..].chars().next() {
+                    Some(c) => match_end + c.len_utf8(),
+                    None => break,
+                }
+            };
+        }
+
+        positions_of(&data, "", &offsets)
+            .chunks(2)
+            .map(|positions| Range::new(positions[0], positions[1]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Matcher;
+    use buffer::{Buffer, Position, Range};
+
+    // Matches the needle, ignoring ASCII case.
+    struct CaseInsensitive(&'static str);
+
+    impl Matcher for CaseInsensitive {
+        fn find_at(&self, haystack: &str, start: usize) -> Option<(usize, usize)> {
+            let haystack = haystack[start..].to_ascii_lowercase();
+            let index = haystack.find(&self.0.to_ascii_lowercase())?;
+
+            Some((start + index, start + index + self.0.len()))
+        }
+    }
+
+    #[test]
+    fn find_matches_uses_custom_matchers() {
+        let mut buffer = Buffer::new();
+        buffer.insert("Scribe scribe");
+
+        assert_eq!(buffer.find_matches(&CaseInsensitive("SCRIBE")), vec![
+            Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 6 }),
+            Range::new(Position{ line: 0, offset: 7 }, Position{ line: 0, offset: 13 }),
+        ]);
+    }
+
+    #[test]
+    fn find_matches_returns_non_overlapping_matches() {
+        let mut buffer = Buffer::new();
+        buffer.insert("aaa");
+
+        assert_eq!(buffer.find_matches("aa"), vec![
+            Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 2 })
+        ]);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn find_matches_handles_empty_regex_matches() {
+        use buffer::RegexMatcher;
+
+        let mut buffer = Buffer::new();
+        buffer.insert("ñ\n");
+
+        let matcher = RegexMatcher::new("x*").unwrap();
+        assert_eq!(buffer.find_matches(&matcher).len(), 3);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn find_matches_supports_look_around() {
+        use buffer::RegexMatcher;
+
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe library scribe");
+
+        let matcher = RegexMatcher::new(r"(?<=library )scribe").unwrap();
+        assert_eq!(buffer.find_matches(&matcher), vec![
+            Range::new(Position{ line: 0, offset: 15 }, Position{ line: 0, offset: 21 })
+        ]);
+    }
+}
@@ -0,0 +1,187 @@
+//! Line-based three-way merging.
+
+use buffer::{Buffer, Conflict, Cursor, GapBuffer, Position, Range};
+use std::cell::RefCell;
+use std::rc::Rc;
+use util::matching_elements;
+
+/// The result of `Buffer::merge`: a buffer holding the merged content, and
+/// the conflicts it contains. Conflicts are written to the buffer using
+/// diff3-style markers, so they can also be found (and resolved) using
+/// the buffer's conflict methods (see `Buffer::conflicts`).
+pub struct Merge {
+    pub buffer: Buffer,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Merge {
+    /// Whether the changes were merged without any conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+// Accumulates merged content, tracking the number of lines written.
+struct MergeWriter {
+    data: String,
+    line: usize,
+    conflicts: Vec<Conflict>,
+}
+
+impl MergeWriter {
+    fn write(&mut self, lines: &[&str]) {
+        for line in lines {
+            self.data.push_str(line);
+            if line.ends_with('\n') { self.line += 1; }
+        }
+    }
+
+    fn write_conflict(&mut self, ours: &[&str], base: &[&str], theirs: &[&str]) {
+        // Markers need to start on their own line.
+        if !self.data.is_empty() && !self.data.ends_with('\n') {
+            self.data.push('\n');
+            self.line += 1;
+        }
+
+        let start = Position{ line: self.line, offset: 0 };
+        let ours = terminated(ours);
+        let base = terminated(base);
+        let theirs = terminated(theirs);
+        for section in &["<<<<<<< ours\n", &ours, "||||||| base\n", &base, "=======\n", &theirs, ">>>>>>> theirs\n"] {
+            self.write(&section.split_inclusive('\n').collect::<Vec<_>>());
+        }
+
+        self.conflicts.push(Conflict{
+            range: Range::new(start, Position{ line: self.line, offset: 0 }),
+            ours,
+            base: Some(base),
+            theirs,
+        });
+    }
+}
+
+impl Buffer {
+    /// Merges the changes made to `base` in `ours` and `theirs`, line by line.
+    /// Regions changed on only one side (or identically on both) are merged
+    /// automatically; the rest are marked as conflicts, with each side's
+    /// content. The merged buffer doesn't have a path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let base = "scribe\nlibrary\neditor\n";
+    /// let merge = Buffer::merge(base, "Scribe\nlibrary\neditor\n", "scribe\nlibrary\nEditor\n");
+    /// assert!(merge.is_clean());
+    /// assert_eq!(merge.buffer.data(), "Scribe\nlibrary\nEditor\n");
+    ///
+    /// let merge = Buffer::merge(base, "scribe\ncrate\neditor\n", "scribe\nmodule\neditor\n");
+    /// assert_eq!(merge.conflicts.len(), 1);
+    /// assert_eq!(merge.conflicts[0].ours, "crate\n");
+    /// assert_eq!(merge.conflicts[0].theirs, "module\n");
+    /// assert_eq!(merge.buffer.conflicts(), merge.conflicts);
+    /// ```
+    pub fn merge(base: &str, ours: &str, theirs: &str) -> Merge {
+        let base: Vec<&str> = base.split_inclusive('\n').collect();
+        let ours: Vec<&str> = ours.split_inclusive('\n').collect();
+        let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+        let our_matches = matching_elements(&base, &ours);
+        let their_matches = matching_elements(&base, &theirs);
+
+        let mut writer = MergeWriter{ data: String::new(), line: 0, conflicts: Vec::new() };
+        let (mut o, mut a, mut b) = (0, 0, 0);
+
+        loop {
+            // Find the next base line that's unchanged on both sides.
+            let stable = (o..base.len()).find_map(|index| {
+                match (our_matches[index], their_matches[index]) {
+                    (Some(our_index), Some(their_index)) => Some((index, our_index, their_index)),
+                    _ => None,
+                }
+            });
+            let (next_o, next_a, next_b) = stable.unwrap_or((base.len(), ours.len(), theirs.len()));
+
+            // Merge the unstable chunk preceding it.
+            let base_chunk = &base[o..next_o];
+            let our_chunk = &ours[a..next_a];
+            let their_chunk = &theirs[b..next_b];
+            if our_chunk == base_chunk || our_chunk == their_chunk {
+                writer.write(their_chunk);
+            } else if their_chunk == base_chunk {
+                writer.write(our_chunk);
+            } else {
+                writer.write_conflict(our_chunk, base_chunk, their_chunk);
+            }
+
+            if stable.is_none() { break; }
+            writer.write(&[base[next_o]]);
+            o = next_o + 1;
+            a = next_a + 1;
+            b = next_b + 1;
+        }
+
+        let data = Rc::new(RefCell::new(GapBuffer::new(writer.data)));
+        let cursor = Cursor::new(data.clone(), Position::new());
+
+        Merge{
+            buffer: Buffer{ data, cursor, ..Default::default() },
+            conflicts: writer.conflicts,
+        }
+    }
+}
+
+// Joins the lines, adding a trailing newline if they don't already have one.
+fn terminated(lines: &[&str]) -> String {
+    let mut content = lines.concat();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position};
+
+    #[test]
+    fn merge_accepts_identical_changes_and_deletions() {
+        let merge = Buffer::merge("a\nb\nc\nd\ne\n", "a\nB\nc\nd\ne\n", "a\nB\nc\ne\n");
+
+        assert!(merge.is_clean());
+        assert_eq!(merge.buffer.data(), "a\nB\nc\ne\n");
+    }
+
+    #[test]
+    fn merge_includes_base_content_in_conflicts() {
+        let merge = Buffer::merge("a\nb\nc", "a\nx\nc", "a\ny\nc");
+
+        assert_eq!(merge.conflicts.len(), 1);
+        assert_eq!(merge.conflicts[0].base, Some("b\n".to_string()));
+        assert_eq!(merge.conflicts[0].range.start(), Position{ line: 1, offset: 0 });
+        assert_eq!(merge.conflicts[0].range.end(), Position{ line: 8, offset: 0 });
+        assert_eq!(
+            merge.buffer.data(),
+            "a\n<<<<<<< ours\nx\n||||||| base\nb\n=======\ny\n>>>>>>> theirs\nc"
+        );
+    }
+
+    #[test]
+    fn merge_terminates_unterminated_conflicting_lines() {
+        let mut merge = Buffer::merge("a\nb", "a\nx", "a\ny");
+        assert_eq!(merge.conflicts[0].ours, "x\n");
+
+        let range = merge.conflicts[0].range.clone();
+        assert!(merge.buffer.accept_theirs(&range));
+        assert_eq!(merge.buffer.data(), "a\ny\n");
+    }
+
+    #[test]
+    fn merge_handles_empty_inputs() {
+        let merge = Buffer::merge("", "", "scribe\n");
+
+        assert!(merge.is_clean());
+        assert_eq!(merge.buffer.data(), "scribe\n");
+    }
+}
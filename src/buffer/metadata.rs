@@ -0,0 +1,77 @@
+//! Application-defined values attached to buffers.
+
+use buffer::Buffer;
+use std::any::Any;
+
+impl Buffer {
+    /// Attaches a value to the buffer under the specified key, replacing any
+    /// existing value. Applications and plugins can use this to keep state
+    /// alongside a buffer (e.g. a language server's document version); it's
+    /// retained when the buffer is reloaded, or unloaded by a workspace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.set_metadata("document_version", 1u64);
+    ///
+    /// *buffer.metadata_mut::<u64>("document_version").unwrap() += 1;
+    /// assert_eq!(buffer.metadata::<u64>("document_version"), Some(&2));
+    ///
+    /// // Values must be requested using their original type.
+    /// assert_eq!(buffer.metadata::<u32>("document_version"), None);
+    ///
+    /// assert!(buffer.remove_metadata("document_version"));
+    /// assert_eq!(buffer.metadata::<u64>("document_version"), None);
+    /// ```
+    pub fn set_metadata<K: Into<String>, T: Any>(&mut self, key: K, value: T) {
+        self.metadata.insert(key.into(), Box::new(value));
+    }
+
+    /// The value attached to the buffer under the specified key, if
+    /// there is one and it's of the requested type (see `set_metadata`).
+    pub fn metadata<T: Any>(&self, key: &str) -> Option<&T> {
+        self.metadata.get(key).and_then(|value| value.downcast_ref())
+    }
+
+    /// A mutable reference to the value attached to the buffer under the
+    /// specified key, if there is one and it's of the requested type.
+    pub fn metadata_mut<T: Any>(&mut self, key: &str) -> Option<&mut T> {
+        self.metadata.get_mut(key).and_then(|value| value.downcast_mut())
+    }
+
+    /// Removes the value attached to the buffer under the specified
+    /// key, returning whether or not there was one.
+    pub fn remove_metadata(&mut self, key: &str) -> bool {
+        self.metadata.remove(key).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::Buffer;
+    #[cfg(feature = "fs")]
+    use std::path::Path;
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn metadata_is_retained_when_reloading() {
+        let mut buffer = Buffer::from_file(Path::new("tests/sample/file")).unwrap();
+        buffer.set_metadata("review_status", String::from("approved"));
+        buffer.reload().unwrap();
+
+        assert_eq!(buffer.metadata::<String>("review_status").unwrap(), "approved");
+    }
+
+    #[test]
+    fn set_metadata_replaces_values_of_other_types() {
+        let mut buffer = Buffer::new();
+        buffer.set_metadata("review_status", 1);
+        buffer.set_metadata("review_status", "approved");
+
+        assert_eq!(buffer.metadata::<i32>("review_status"), None);
+        assert_eq!(buffer.metadata::<&str>("review_status"), Some(&"approved"));
+    }
+}
@@ -1,44 +1,191 @@
 //! Types related to in-memory buffers.
 
-// Lexing library
-extern crate luthor;
-
 // Published API
 pub use self::gap_buffer::GapBuffer;
+pub use self::audit::{AuditEntry, AUDIT_LOG_KEY};
+pub use self::bidi::BidiRun;
 pub use self::distance::Distance;
 
 pub use self::position::Position;
 pub use self::range::Range;
+pub use self::remap::{ClampRemap, DiffRemap, RemapPolicy};
 pub use self::line_range::LineRange;
-pub use self::cursor::Cursor;
-pub use self::token::{Lexeme, Token, TokenSet};
+pub use self::line_ending::LineEnding;
+pub use self::line_state::LineState;
+pub use self::coalesce::CoalescedChange;
+pub use self::collaborator::Collaborator;
+pub use self::command::Command;
+pub use self::conflict::Conflict;
+pub use self::context::CursorContext;
+pub use self::bytes::ByteSlice;
+pub use self::cursor::{Cursor, CursorStyle};
+pub use self::describe::{BufferStatus, PositionDescription};
+#[cfg(feature = "fs")]
+pub use self::directory::DirectoryListing;
+#[cfg(feature = "syntax")]
+pub use self::embedded::EmbeddedRegion;
+pub use self::export::LinesBetween;
+pub use self::indent::IndentRules;
+#[cfg(feature = "fs")]
+pub use self::insert_file::INSERT_FILE_LIMIT;
+pub use self::jump_target::JumpTarget;
+pub use self::maintenance::MaintenanceReport;
+pub use self::matcher::Matcher;
+#[cfg(feature = "regex")]
+pub use self::matcher::RegexMatcher;
+pub use self::merge::Merge;
+pub use self::normalization::Normalization;
+pub use self::operation::summary::{ChangeSummary, Edit};
+pub use self::overlay::{Overlay, OverlayLayer, OverlaySpan};
+pub use self::pending::PendingText;
+pub use self::preferences::BufferPreferences;
+#[cfg(feature = "syntax")]
+pub use self::reformat::ReformatOptions;
+#[cfg(feature = "fs")]
+pub use self::replay::{read_replay, write_replay, REPLAY_VERSION};
+pub use self::search::{PartialSearch, SearchResumption};
+pub use self::special_character::{SpecialCharacter, SpecialCharacterKind};
+#[cfg(feature = "fs")]
+pub use self::special_file::SpecialFile;
+pub use self::storage::{MemoryStorage, Storage};
+#[cfg(feature = "syntax")]
+pub use self::style_map::StyleMap;
+#[cfg(feature = "syntax")]
+pub use self::structure::{NodeKey, StructuralNode};
+#[cfg(feature = "syntax")]
+pub use self::syntax_error::{SyntaxError, SyntaxErrorKind};
+pub use self::table::TableCell;
+#[cfg(feature = "syntax")]
+pub use self::token::{Lexeme, LexerState, SyntaxState, Token, TokenSet};
+pub use self::unicode_form::UnicodeForm;
+pub use self::view::BufferView;
+pub use self::word::WordBoundaries;
+#[cfg(feature = "syntax")]
 pub use syntect::parsing::{Scope, ScopeStack};
 
 // Child modules
 mod gap_buffer;
+mod activity;
 mod distance;
 mod position;
 mod range;
 mod line_range;
+mod line_ending;
+mod logical_line;
+mod line_state;
+mod maintenance;
+mod matcher;
+mod merge;
+mod metadata;
+mod normalization;
+mod number;
+mod observer;
+mod abbreviation;
+mod arithmetic;
+mod audit;
+mod bidi;
+mod bounds;
+mod bytes;
+mod coalesce;
+mod collaborator;
+mod command;
+mod conflict;
+mod context;
 mod cursor;
+mod describe;
+#[cfg(feature = "fs")]
+mod directory;
+#[cfg(feature = "syntax")]
+mod embedded;
+mod export;
+#[cfg(feature = "fs")]
+mod filter;
+mod indent;
+#[cfg(feature = "fs")]
+mod insert_file;
+mod jump_target;
 mod operation;
 mod operations;
+mod overlay;
+mod pending;
+mod preferences;
+mod preview;
+#[cfg(feature = "syntax")]
+mod reformat;
+mod remap;
+mod replace;
+#[cfg(feature = "fs")]
+mod replay;
+mod search;
+mod selection;
+mod special_character;
+#[cfg(feature = "fs")]
+mod special_file;
+mod storage;
+mod stream;
+#[cfg(feature = "syntax")]
+mod style_map;
+mod split;
+#[cfg(feature = "syntax")]
+mod structure;
+#[cfg(feature = "syntax")]
+mod syntax_error;
+mod table;
+#[cfg(feature = "syntax")]
 mod token;
+mod transform;
+mod typography;
+mod unicode_form;
+mod validation;
+mod view;
+mod word;
 
 // Buffer type implementation
+#[cfg(feature = "syntax")]
 use errors::*;
+use std::any::Any;
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::default::Default;
-use std::fs::{File, OpenOptions};
+#[cfg(feature = "fs")]
+use std::env;
+#[cfg(feature = "fs")]
+use std::ffi::OsString;
+#[cfg(feature = "fs")]
+use std::fs::{self, File, OpenOptions};
 use std::io;
-use std::io::{Read, Write};
-use std::mem;
+#[cfg(feature = "fs")]
+use std::io::{BufRead, BufReader, Read, Write};
 use std::ops::Fn;
+#[cfg(feature = "fs")]
+use std::path::Component;
 use std::path::{Path, PathBuf};
+#[cfg(feature = "fs")]
+use std::process;
+use std::time::{Duration, Instant, SystemTime};
+use self::coalesce::CoalescedObserver;
+#[cfg(feature = "syntax")]
+use self::embedded::RegionCache;
+use self::observer::ChangeObserver;
 use self::operation::{Operation, OperationGroup};
 use self::operation::history::History;
+use self::operations::TypingRun;
+use self::overlay::OverlayProvider;
+#[cfg(feature = "syntax")]
+use self::token::{lex_line_within, TokenCache};
+use self::transform::InsertTransform;
+#[cfg(feature = "syntax")]
 use syntect::parsing::SyntaxDefinition;
+use unicode_segmentation::UnicodeSegmentation;
+use util::{fuzzy_rank, FuzzyMatch};
+#[cfg(feature = "syntax")]
+use util::LineIterator;
+#[cfg(feature = "workspace")]
+use util::BudgetMeter;
+
+// The length (in bytes) beyond which lines aren't lexed, unless configured otherwise.
+pub(crate) const DEFAULT_MAX_LINE_LENGTH: usize = 20_000;
 
 /// A feature-rich wrapper around an underlying gap buffer.
 ///
@@ -48,7 +195,60 @@ use syntect::parsing::SyntaxDefinition;
 ///
 /// If the buffer is configured with a `change_callback`, it will be called with
 /// a position whenever the buffer is modified; it's particularly useful for
-/// cache invalidation.
+/// cache invalidation. Observers registered using `on_change` are called
+/// with the modified range and its content; those registered using
+/// `on_coalesced_change` receive batches of modifications, at a limited rate.
+///
+/// Buffers flagged as `read_only` ignore insert and delete calls, and refuse
+/// to be saved. Buffers flagged as `append_only` refuse changes that would
+/// remove content (including undoing insertions), discarding the rest of any
+/// batched change they're part of (see `batch`), and record the time of each
+/// insertion (see `audit_log`). Buffers flagged with `insert_final_newline` will have a
+/// trailing newline added (see `ensure_trailing_newline`) when saved, unless
+/// they're also flagged to `preserve_formatting` (see `normalizations`).
+///
+/// Buffers always use "\n" line endings internally. Files using "\r\n" line
+/// endings (or mostly using them) are converted when they're loaded, setting
+/// the buffer's `line_ending`, which is applied again when it's saved.
+///
+/// If the buffer is configured with a `history_limit`, the oldest undo
+/// history beyond that number of operations is discarded during maintenance
+/// (see `perform_maintenance`).
+///
+/// If the buffer is configured with an `undo_coalescing_window`, characters
+/// typed in quick succession (i.e. single-grapheme insertions, each following
+/// the last, within the window) are undone together, a word at a time.
+///
+/// Words preceding the cursor can be replaced with their entry in the
+/// buffer's `abbreviations` table (see `expand_abbreviation_before_cursor`).
+/// Buffers flagged with `smart_punctuation` replace typed quotes and dashes
+/// with their typographic equivalents, for prose (see `insert`). Transforms
+/// registered using `add_insert_transform` can modify inserted text further.
+///
+/// The buffer's `tab_width` (4, by default) is used to determine the visual
+/// columns of positions (see `describe_position`), and the width of each
+/// indentation level used by `insert_newline` and `reindent`, which follow
+/// the buffer's `indent_rules`, indenting with spaces if `soft_tabs` is set
+/// (the default), and tabs otherwise.
+///
+/// Lines longer than the buffer's `max_line_length` (20,000 bytes, by
+/// default; `None` disables the limit), such as those in minified files,
+/// aren't lexed, so that producing tokens doesn't stall on them; each is
+/// tokenized as a single lexeme, within the scopes open at its start (see
+/// `TokenSet::with_max_line_length`). Cursor movement only examines the lines
+/// it moves between, and `lines_between` can read the visible portion of a
+/// long line without copying the rest of it, for display.
+///
+/// Modifications made more than `idle_interval` (five minutes, by default)
+/// apart are considered separate stretches of editing (see `editing_since`).
+///
+/// When the buffer's content is replaced wholesale (see `reload` and
+/// `replace_contents`), its cursor and selections are moved using its
+/// `remap_policy` (a `DiffRemap`, by default), so that they remain on the
+/// same text.
+///
+/// Applications can attach their own values to the buffer using
+/// `set_metadata`.
 pub struct Buffer {
     pub id: Option<usize>,
     data: Rc<RefCell<GapBuffer>>,
@@ -56,8 +256,81 @@ pub struct Buffer {
     pub cursor: Cursor,
     history: History,
     operation_group: Option<OperationGroup>,
+    typing_run: Option<TypingRun>,
+    #[cfg(feature = "syntax")]
     pub syntax_definition: Option<SyntaxDefinition>,
     pub change_callback: Option<Box<Fn(Position)>>,
+    pub(crate) save_callback: Option<Box<dyn Fn()>>,
+    change_observers: Vec<(usize, ChangeObserver)>,
+    coalesced_observers: Vec<(usize, CoalescedObserver)>,
+    next_observer_id: usize,
+    insert_transforms: Vec<(usize, InsertTransform)>,
+    next_transform_id: usize,
+    overlay_providers: Vec<(usize, i32, OverlayProvider)>,
+    next_overlay_provider_id: usize,
+    pub read_only: bool,
+    pub append_only: bool,
+    pub insert_final_newline: bool,
+    pub preserve_formatting: bool,
+    pub smart_punctuation: bool,
+    pub line_ending: LineEnding,
+    pub history_limit: Option<usize>,
+    pub undo_coalescing_window: Option<Duration>,
+    pub abbreviations: HashMap<String, String>,
+    pub tab_width: usize,
+    pub soft_tabs: bool,
+    pub indent_rules: Option<IndentRules>,
+    pub word_boundaries: WordBoundaries,
+    pub max_line_length: Option<usize>,
+    pub idle_interval: Duration,
+    pub remap_policy: Box<dyn RemapPolicy>,
+    metadata: HashMap<String, Box<dyn Any>>,
+    pending: Option<PendingText>,
+    #[cfg(feature = "fs")]
+    directory_listing: Option<DirectoryListing>,
+    line_states: Vec<LineState>,
+    selections: Vec<Range>,
+    collaborators: Vec<Collaborator>,
+    line_offset: usize,
+    loaded_with_conflicts: bool,
+    mixed_line_endings: Vec<LineEnding>,
+    refused_removals: usize,
+    #[cfg(feature = "syntax")]
+    embedded_regions: RefCell<RegionCache>,
+    #[cfg(feature = "syntax")]
+    token_cache: RefCell<TokenCache>,
+    #[cfg(feature = "syntax")]
+    token_data: RefCell<Option<Rc<String>>>,
+    disk_modified: Option<SystemTime>,
+    maintenance_task: usize,
+    last_edited: Option<Instant>,
+    editing_since: Option<Instant>,
+}
+
+/// The result of `Buffer::open`, distinguishing buffers
+/// loaded from existing files from those for new files.
+#[cfg(feature = "fs")]
+pub enum OpenResult {
+    Created(Buffer),
+    Loaded(Buffer),
+}
+
+#[cfg(feature = "fs")]
+impl OpenResult {
+    /// Whether or not the buffer was loaded from an existing file.
+    pub fn loaded(&self) -> bool {
+        match *self {
+            OpenResult::Loaded(_) => true,
+            OpenResult::Created(_) => false,
+        }
+    }
+
+    /// Unwraps the opened buffer.
+    pub fn into_buffer(self) -> Buffer {
+        match self {
+            OpenResult::Created(buffer) | OpenResult::Loaded(buffer) => buffer,
+        }
+    }
 }
 
 impl Default for Buffer {
@@ -74,8 +347,55 @@ impl Default for Buffer {
             cursor,
             history: History::new(),
             operation_group: None,
+            typing_run: None,
+            #[cfg(feature = "syntax")]
             syntax_definition: None,
             change_callback: None,
+            save_callback: None,
+            change_observers: Vec::new(),
+            coalesced_observers: Vec::new(),
+            next_observer_id: 0,
+            insert_transforms: Vec::new(),
+            next_transform_id: 0,
+            overlay_providers: Vec::new(),
+            next_overlay_provider_id: 0,
+            read_only: false,
+            append_only: false,
+            insert_final_newline: false,
+            preserve_formatting: false,
+            smart_punctuation: false,
+            line_ending: LineEnding::Lf,
+            history_limit: None,
+            undo_coalescing_window: None,
+            abbreviations: HashMap::new(),
+            tab_width: 4,
+            soft_tabs: true,
+            indent_rules: None,
+            word_boundaries: WordBoundaries::default(),
+            max_line_length: Some(DEFAULT_MAX_LINE_LENGTH),
+            idle_interval: Duration::from_secs(5 * 60),
+            remap_policy: Box::new(DiffRemap),
+            metadata: HashMap::new(),
+            pending: None,
+            #[cfg(feature = "fs")]
+            directory_listing: None,
+            line_states: Vec::new(),
+            selections: Vec::new(),
+            collaborators: Vec::new(),
+            line_offset: 0,
+            loaded_with_conflicts: false,
+            mixed_line_endings: Vec::new(),
+            refused_removals: 0,
+            #[cfg(feature = "syntax")]
+            embedded_regions: RefCell::new(RegionCache::default()),
+            #[cfg(feature = "syntax")]
+            token_cache: RefCell::new(TokenCache::default()),
+            #[cfg(feature = "syntax")]
+            token_data: RefCell::new(None),
+            disk_modified: None,
+            maintenance_task: 0,
+            last_edited: None,
+            editing_since: None,
         }
     }
 }
@@ -96,12 +416,24 @@ impl Buffer {
         Default::default()
     }
 
+    // Creates a read-only buffer for command output. It isn't
+    // considered modified, so that it's never saved.
+    #[cfg(feature = "workspace")]
+    pub(crate) fn for_output() -> Buffer {
+        let mut buffer = Buffer{ read_only: true, ..Default::default() };
+        buffer.history.mark();
+
+        buffer
+    }
+
     /// Creates a new buffer by reading the UTF-8 interpreted file contents of the specified path.
     /// The buffer's cursor is set to the beginning of the buffer. The buffer data's type will be
     /// inferred based on its extension, and an appropriate lexer will be used, if available (see
     /// tokens method for further information on why this happens).
-    /// The provided path is converted to its canonical, absolute equivalent,
-    /// and stored alongside the buffer data.
+    /// Directories and other special files are refused (see `SpecialFile`).
+    /// The provided path is converted to its absolute equivalent (without
+    /// resolving symbolic links; see `canonical_path`), and stored alongside
+    /// the buffer data.
     ///
     /// # Examples
     ///
@@ -115,32 +447,39 @@ impl Buffer {
     /// # assert_eq!(buffer.cursor.line, 0);
     /// # assert_eq!(buffer.cursor.offset, 0);
     /// ```
+    #[cfg(feature = "fs")]
     pub fn from_file(path: &Path) -> io::Result<Buffer> {
         Self::from_file_with_opts(path, &mut OpenOptions::new().read(true))
     }
 
     /// Like `from_file`, but allow custom OpenOptions.
+    #[cfg(feature = "fs")]
     pub fn from_file_with_opts(
         path: &Path, opts: &mut OpenOptions
     ) -> io::Result<Buffer> {
+        // Refuse to read directories, devices, and the like.
+        special_file::check(path)?;
+
         // Try to open and read the file, returning any errors encountered.
         let mut file = opts.open(path)?;
         let mut data = String::new();
         file.read_to_string(&mut data)?;
+        let decoded = line_ending::decode(data);
+        let loaded_with_conflicts = !conflict::find_conflicts(&decoded.data).is_empty();
 
-        let data = Rc::new(RefCell::new(GapBuffer::new(data)));
+        let data = Rc::new(RefCell::new(GapBuffer::new(decoded.data)));
         let cursor = Cursor::new(data.clone(), Position{ line: 0, offset: 0 });
 
         // Create a new buffer using the loaded data, path, and other defaults.
         let mut buffer =  Buffer{
-            id: None,
             data: data.clone(),
-            path: Some(try!(path.canonicalize())),
+            path: Some(try!(absolute_path(path))),
             cursor,
-            history: History::new(),
-            operation_group: None,
-            syntax_definition: None,
-            change_callback: None,
+            line_ending: decoded.line_ending,
+            loaded_with_conflicts,
+            mixed_line_endings: decoded.mixed,
+            disk_modified: file.metadata().and_then(|metadata| metadata.modified()).ok(),
+            ..Default::default()
         };
 
         // We mark the history at points where the
@@ -150,6 +489,118 @@ impl Buffer {
         Ok(buffer)
     }
 
+    /// Creates a read-only buffer holding lines `start_line` (inclusive) through
+    /// `end_line` (exclusive) of the specified file, reading no further than
+    /// necessary. The first line's position in the file is tracked (see
+    /// `line_offset`). The buffer can't be saved, as that would replace the
+    /// file with the loaded lines. Ranges extending beyond the end of the file
+    /// are truncated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use std::env;
+    /// use std::fs;
+    ///
+    /// let path = env::temp_dir().join("scribe_from_file_range_example");
+    /// fs::write(&path, "scribe\ntext\neditor\nlibrary\n").unwrap();
+    ///
+    /// let buffer = Buffer::from_file_range(&path, 1, 3).unwrap();
+    /// assert_eq!(buffer.data(), "text\neditor\n");
+    /// assert_eq!(buffer.line_offset(), 1);
+    /// assert!(buffer.read_only);
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn from_file_range(path: &Path, start_line: usize, end_line: usize) -> io::Result<Buffer> {
+        special_file::check(path)?;
+        let file = File::open(path)?;
+        let disk_modified = file.metadata().and_then(|metadata| metadata.modified()).ok();
+        let mut reader = BufReader::new(file);
+        let mut data = String::new();
+        let mut line = String::new();
+
+        for line_number in 0..end_line {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+
+            if line_number >= start_line {
+                data.push_str(&line);
+            }
+        }
+
+        let decoded = line_ending::decode(data);
+        let data = Rc::new(RefCell::new(GapBuffer::new(decoded.data)));
+        let cursor = Cursor::new(data.clone(), Position{ line: 0, offset: 0 });
+        let mut buffer = Buffer{
+            data: data.clone(),
+            path: Some(absolute_path(path)?),
+            cursor,
+            read_only: true,
+            line_ending: decoded.line_ending,
+            line_offset: start_line,
+            disk_modified,
+            ..Default::default()
+        };
+        buffer.history.mark();
+
+        Ok(buffer)
+    }
+
+    /// The line number, in the buffer's file, of its first line. This is
+    /// non-zero for buffers loaded using `from_file_range`.
+    pub fn line_offset(&self) -> usize {
+        self.line_offset
+    }
+
+    /// Opens the file at the specified path (see `from_file`), or, if it
+    /// doesn't exist, creates an empty, unmodified buffer bound to the path,
+    /// which will create the file when saved. The path's parent directory
+    /// must exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::buffer::{Buffer, OpenResult};
+    /// use std::path::Path;
+    ///
+    /// match Buffer::open(Path::new("tests/sample/new_file")).unwrap() {
+    ///     OpenResult::Created(buffer) => assert_eq!(buffer.data(), ""),
+    ///     OpenResult::Loaded(_) => panic!("file shouldn't exist"),
+    /// }
+    ///
+    /// let result = Buffer::open(Path::new("tests/sample/file")).unwrap();
+    /// assert!(result.loaded());
+    /// assert_eq!(result.into_buffer().data(), "it works!\n");
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn open(path: &Path) -> io::Result<OpenResult> {
+        match Buffer::from_file(path) {
+            Ok(buffer) => Ok(OpenResult::Loaded(buffer)),
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => {
+                let path = absolute_path(path)?;
+                if !path.parent().map(Path::is_dir).unwrap_or(false) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        "parent directory doesn't exist"
+                    ));
+                }
+
+                let mut buffer = Buffer{
+                    path: Some(path),
+                    ..Default::default()
+                };
+                buffer.history.mark();
+
+                Ok(OpenResult::Created(buffer))
+            },
+            Err(error) => Err(error),
+        }
+    }
+
     /// Returns the contents of the buffer as a string.
     ///
     /// # Examples
@@ -165,7 +616,20 @@ impl Buffer {
         self.data.borrow().to_string()
     }
 
-    /// Writes the contents of the buffer to its path.
+    /// Writes the contents of the buffer to its path. The contents are written
+    /// to a temporary file alongside the path before it's renamed into place,
+    /// so that the file is never left partially written (e.g. if the
+    /// application crashes). The existing file's permissions are carried
+    /// over, and symbolic links are written through, rather than replaced.
+    /// Files that can't be replaced this way (e.g. because the directory
+    /// isn't writable), or that would lose their ownership or hard links by
+    /// being replaced, are written in place instead.
+    ///
+    /// Returns an `InvalidInput` error if the buffer doesn't have a path, or a
+    /// `PermissionDenied` error if its file is read-only, before the buffer
+    /// is prepared for saving (see `normalizations`). Changes made to the
+    /// file by other programs are overwritten; use `outdated` to check for
+    /// them beforehand.
     ///
     /// # Examples
     ///
@@ -191,36 +655,318 @@ impl Buffer {
     ///
     /// # std::fs::remove_file(&write_path);
     /// ```
+    #[cfg(feature = "fs")]
     pub fn save(&mut self) -> io::Result<()> {
-        // Try to open and write to the file, returning any errors encountered.
-        let mut file =
-            if let Some(ref path) = self.path {
-                File::create(&path)?
-            } else {
-                File::create(&PathBuf::new())?
-            };
+        if let Some(ref path) = self.path {
+            check_writable(path)?;
+        }
+
+        self.save_with(|path, data| {
+            write_atomically(path, data).map(|file| modified_time(&file))
+        })
+    }
+
+    /// Points the buffer at a new path (converted to its absolute equivalent,
+    /// as with `from_file`) and saves it there. The buffer's original path is
+    /// restored if the save fails. Its syntax definition isn't updated to
+    /// reflect the new path; use `Workspace::save_current_buffer_as` for that.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use std::env;
+    /// use std::fs;
+    ///
+    /// let path = env::temp_dir().join("scribe_save_as_example");
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    /// assert!(buffer.save().is_err());
+    ///
+    /// buffer.save_as(&path).unwrap();
+    /// assert_eq!(buffer.path, Some(path.clone()));
+    /// assert_eq!(fs::read_to_string(&path).unwrap(), "scribe");
+    /// assert!(!buffer.modified());
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn save_as(&mut self, path: &Path) -> io::Result<()> {
+        let previous_path = self.path.replace(absolute_path(path)?);
+
+        self.save().inspect_err(|_| self.path = previous_path)
+    }
+
+    // Handles the bookkeeping common to all saves, deferring to the writer to
+    // store the data at the buffer's path, and report its modification time.
+    fn save_with<F>(&mut self, writer: F) -> io::Result<()>
+        where F: FnOnce(&Path, &[u8]) -> io::Result<Option<SystemTime>>
+    {
+        if self.read_only {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "buffer is read-only"
+            ));
+        }
+        let path = self.path.clone().ok_or_else(missing_path)?;
+
+        self.normalize();
 
         // We use to_string here because we don't want to write the gap contents.
-        file.write_all(self.data().to_string().as_bytes())?;
+        let data = self.data();
+        let preserve_line_endings = self.preserve_formatting && !self.mixed_line_endings.is_empty();
+        let data = if preserve_line_endings {
+            line_ending::apply_mixed(&data, &self.mixed_line_endings, self.line_ending).into()
+        } else {
+            self.line_ending.apply(&data)
+        };
+        self.disk_modified = writer(&path, data.as_bytes())?;
 
         // We mark the history at points where the
         // buffer is in sync with its file equivalent.
         self.history.mark();
+        if !preserve_line_endings {
+            self.mixed_line_endings.clear();
+        }
+        self.reset_line_states();
+
+        if let Some(ref callback) = self.save_callback {
+            callback();
+        }
 
         Ok(())
     }
 
+    /// The buffer's path, with symbolic links and relative components
+    /// resolved, suitable for determining whether two paths refer to the
+    /// same file. If the file doesn't exist (yet), only its parent directory
+    /// is resolved. The buffer's `path` is left as-is, for display purposes,
+    /// and so that saving writes through symbolic links.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use std::path::Path;
+    ///
+    /// let buffer = Buffer::from_file(Path::new("tests/../tests/sample/file")).unwrap();
+    /// assert_eq!(
+    ///     buffer.canonical_path().unwrap(),
+    ///     Path::new("tests/sample/file").canonicalize().unwrap()
+    /// );
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn canonical_path(&self) -> Option<PathBuf> {
+        let path = self.path.as_ref()?;
+        if let Ok(canonical_path) = path.canonicalize() {
+            return Some(canonical_path);
+        }
+
+        match (path.parent().map(Path::canonicalize), path.file_name()) {
+            (Some(Ok(parent)), Some(file_name)) => Some(parent.join(file_name)),
+            _ => Some(path.clone()),
+        }
+    }
+
+    /// Whether or not the buffer's file has been modified (or removed) by
+    /// another program since the buffer was last loaded or saved, based on
+    /// its modification time. Use `reload` to read the changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use std::env;
+    /// use std::fs::{self, OpenOptions};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// let path = env::temp_dir().join("scribe_outdated_example");
+    /// fs::write(&path, "scribe").unwrap();
+    /// let mut buffer = Buffer::from_file(&path).unwrap();
+    /// assert!(!buffer.outdated());
+    ///
+    /// fs::write(&path, "library").unwrap();
+    /// let file = OpenOptions::new().write(true).open(&path).unwrap();
+    /// file.set_modified(SystemTime::now() + Duration::from_secs(60)).unwrap();
+    /// assert!(buffer.outdated());
+    ///
+    /// buffer.reload().unwrap();
+    /// assert!(!buffer.outdated());
+    /// assert_eq!(buffer.data(), "library");
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn outdated(&self) -> bool {
+        match self.path {
+            Some(ref path) => {
+                let modified = fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok();
+
+                modified != self.disk_modified
+            },
+            None => false,
+        }
+    }
+
     /// Produces a set of tokens based on the buffer data
     /// suitable for colorized display, using a lexer for the
     /// buffer data's language and/or format.
+    ///
+    /// The lexer's output is cached as tokens are produced, and discarded
+    /// from the point of an edit onwards, so that lines preceding the
+    /// earliest edit since the previous call aren't lexed again. The
+    /// buffer's content is only copied for the first call following an edit.
+    #[cfg(feature = "syntax")]
     pub fn tokens(&self) -> Result<TokenSet> {
         if let Some(ref def) = self.syntax_definition {
-            Ok(TokenSet::new(self.data(), def))
+            self.token_cache.borrow_mut().prepare(&def.name, self.max_line_length, || SyntaxState::new(def));
+
+            Ok(TokenSet::shared(self.token_data(), def)
+               .with_max_line_length(self.max_line_length)
+               .with_cache(&self.token_cache))
         } else {
             Err(ErrorKind::MissingSyntaxDefinition)?
         }
     }
 
+    // Lexes the lines following those held in the token cache, a line at a
+    // time, until the budget has been spent. Returns whether or not every
+    // line has been lexed (which is trivially the case without a syntax).
+    #[cfg(feature = "workspace")]
+    pub(crate) fn lex_within(&self, meter: &mut BudgetMeter) -> bool {
+        let def = match self.syntax_definition {
+            Some(ref def) => def,
+            None => return true,
+        };
+
+        let mut cache = self.token_cache.borrow_mut();
+        cache.prepare(&def.name, self.max_line_length, || SyntaxState::new(def));
+        let mut state = match cache.next_state() {
+            Some(state) => state.clone(),
+            None => return true,
+        };
+
+        let data = self.token_data();
+        for (_, content) in LineIterator::new(&data).skip(cache.len()) {
+            if !meter.permit() {
+                return false;
+            }
+            let events = lex_line_within(&mut state, content, self.max_line_length);
+            cache.push(events, &state);
+        }
+
+        true
+    }
+
+    // The content lexed by `tokens`, copied from the buffer on
+    // first use, and shared until the buffer is next modified.
+    #[cfg(feature = "syntax")]
+    fn token_data(&self) -> Rc<String> {
+        self.token_data.borrow_mut().get_or_insert_with(|| Rc::new(self.data())).clone()
+    }
+
+    // Discards cached lexer output that may have been affected by a change to the specified line.
+    #[cfg(feature = "syntax")]
+    pub(crate) fn invalidate_tokens(&self, line: usize) {
+        self.token_cache.borrow_mut().invalidate(line);
+        self.token_data.borrow_mut().take();
+    }
+
+    // Discards cached tokens and embedded regions that may have been
+    // affected by a change to the specified line, if they're tracked.
+    pub(crate) fn invalidate_lexed_state(&self, line: usize) {
+        #[cfg(feature = "syntax")]
+        {
+            self.invalidate_embedded_regions(line);
+            self.invalidate_tokens(line);
+        }
+        #[cfg(not(feature = "syntax"))]
+        let _ = line;
+    }
+
+    /// Like `tokens`, but only produces tokens for the specified lines (e.g.
+    /// those visible in a viewport), with their usual positions. Lines that
+    /// precede the range are parsed to determine its scopes, but aren't
+    /// tokenized, and lines that follow it aren't lexed at all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate syntect;
+    /// extern crate scribe;
+    ///
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{LineRange, Position, Token};
+    /// use syntect::parsing::SyntaxSet;
+    ///
+    /// let syntax_set = SyntaxSet::load_defaults_newlines();
+    /// let mut buffer = Buffer::new();
+    /// buffer.syntax_definition = syntax_set.find_syntax_by_extension("rs").cloned();
+    /// buffer.insert("/*\nscribe */\nstruct Buffer;\n");
+    ///
+    /// let tokens = buffer.tokens_for_lines(&LineRange::new(1, 2)).unwrap();
+    /// let lexemes: Vec<_> = tokens.iter().filter_map(|token| match token {
+    ///     Token::Lexeme(lexeme) => Some(lexeme),
+    ///     Token::Newline => None,
+    /// }).collect();
+    ///
+    /// // The comment's scope carries over from the preceding line.
+    /// assert_eq!(lexemes[0].value, "scribe ");
+    /// assert_eq!(lexemes[0].position, Position{ line: 1, offset: 0 });
+    /// assert!(lexemes[0].scope.as_slice().iter().any(|scope| scope.build_string().starts_with("comment")));
+    /// assert!(lexemes.iter().all(|lexeme| lexeme.position.line == 1));
+    /// ```
+    #[cfg(feature = "syntax")]
+    pub fn tokens_for_lines(&self, lines: &LineRange) -> Result<TokenSet<'_>> {
+        Ok(self.tokens()?.with_lines(lines.start(), lines.end()))
+    }
+
+    /// The lexer's state at the start of the specified line (e.g. the first
+    /// line of a viewport), which can be used to lex that line and those
+    /// following it in isolation (see `TokenSet::resuming`). States stored
+    /// by the buffer's token cache are used to avoid lexing the buffer from
+    /// its start. Returns an `OutOfBounds` error if the line doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate syntect;
+    /// extern crate scribe;
+    ///
+    /// use scribe::Buffer;
+    /// use scribe::buffer::LexerState;
+    /// use syntect::parsing::SyntaxSet;
+    ///
+    /// let syntax_set = SyntaxSet::load_defaults_newlines();
+    /// let mut buffer = Buffer::new();
+    /// buffer.syntax_definition = syntax_set.find_syntax_by_extension("rs").cloned();
+    /// buffer.insert("/*\nscribe */\n");
+    ///
+    /// let state = buffer.lexer_state(1).unwrap();
+    /// assert_eq!(state.scopes().as_slice().last().unwrap().build_string(), "comment.block.rust");
+    /// assert!(buffer.lexer_state(3).is_err());
+    /// ```
+    #[cfg(feature = "syntax")]
+    pub fn lexer_state(&self, line: usize) -> Result<SyntaxState> {
+        let def = self.syntax_definition.as_ref().ok_or(ErrorKind::MissingSyntaxDefinition)?;
+        self.check_position(&Position{ line, offset: 0 })?;
+
+        let mut cache = self.token_cache.borrow_mut();
+        cache.prepare(&def.name, self.max_line_length, || SyntaxState::new(def));
+        let (start, state) = match cache.checkpoint_before(line) {
+            Some((start, state)) => (start, state.clone()),
+            None => (0, SyntaxState::new(def)),
+        };
+
+        let data = self.token_data();
+        let mut state = state;
+        for (_, content) in LineIterator::new(&data).skip(start).take(line - start) {
+            lex_line_within(&mut state, content, self.max_line_length);
+        }
+
+        Ok(state)
+    }
+
     /// Returns the scope stack for the token at the cursor location.
     ///
     /// # Examples
@@ -255,6 +1001,7 @@ impl Buffer {
     ///     )
     /// );
     /// ```
+    #[cfg(feature = "syntax")]
     pub fn current_scope(&self) -> Result<ScopeStack> {
         let mut scope = None;
         let tokens = self.tokens()?;
@@ -279,10 +1026,10 @@ impl Buffer {
     ///
     /// ```
     /// use scribe::Buffer;
-    /// use std::path::Path;
+    /// use std::path::PathBuf;
     ///
-    /// let file_path = Path::new("tests/sample/file");
-    /// let buffer = Buffer::from_file(file_path).unwrap();
+    /// let mut buffer = Buffer::new();
+    /// buffer.path = Some(PathBuf::from("tests/sample/file"));
     /// assert_eq!(buffer.file_name().unwrap(), "file");
     /// ```
     pub fn file_name(&self) -> Option<String> {
@@ -303,7 +1050,8 @@ impl Buffer {
     }
 
 
-    /// Reverses the last modification to the buffer.
+    /// Reverses the last modification to the buffer, moving the
+    /// cursor to the start of the change (i.e. where it was made).
     ///
     /// # Examples
     ///
@@ -321,20 +1069,29 @@ impl Buffer {
     /// assert_eq!("scribe library", buffer.data());
     ///
     /// // Undo the second operation.
+    /// buffer.cursor.move_to(Position{ line: 0, offset: 0});
     /// buffer.undo();
     /// assert_eq!("scribe", buffer.data());
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 6});
     ///
     /// // Undo the first operation.
     /// buffer.undo();
     /// assert_eq!("", buffer.data());
     /// ```
     pub fn undo(&mut self) {
+        self.typing_run = None;
+
         // Look for an operation to undo. First, check if there's an open, non-empty
         // operation group. If not, try taking the last operation from the buffer history.
+        // Operations whose reversal would remove content from an append-only
+        // buffer are left in place.
         let operation: Option<Box<Operation>> = match self.operation_group.take() {
             Some(group) => {
                 if group.is_empty() {
                     self.history.previous()
+                } else if self.refuses_operation(&group, true) {
+                    self.operation_group = Some(group);
+                    return;
                 } else {
                     Some(Box::new(group))
                 }
@@ -344,11 +1101,21 @@ impl Buffer {
 
         // If we found an eligible operation, reverse it.
         if let Some(mut op) = operation {
+            if self.refuses_operation(&*op, true) {
+                self.history.next();
+                return;
+            }
             op.reverse(self);
+            self.move_cursor_to_change(&*op);
+        }
+
+        if self.history.at_mark() {
+            self.reset_line_states();
         }
     }
 
-    /// Re-applies the last undone modification to the buffer.
+    /// Re-applies the last undone modification to the buffer,
+    /// moving the cursor to the start of the change.
     ///
     /// # Examples
     ///
@@ -365,9 +1132,28 @@ impl Buffer {
     /// assert_eq!("scribe", buffer.data());
     /// ```
     pub fn redo(&mut self) {
+        self.typing_run = None;
+
         // Look for an operation to apply.
         if let Some(mut op) = self.history.next() {
+            if self.refuses_operation(&*op, false) {
+                self.history.previous();
+                return;
+            }
             op.run(self);
+            self.move_cursor_to_change(&*op);
+        }
+
+        if self.history.at_mark() {
+            self.reset_line_states();
+        }
+    }
+
+    // Moves the cursor to the start of the operation's first change,
+    // if it's still in bounds (a later change may have removed it).
+    fn move_cursor_to_change(&mut self, operation: &dyn Operation) {
+        if let Some(edit) = operation.edits().first() {
+            self.cursor.move_to(edit.range.start());
         }
     }
 
@@ -395,6 +1181,14 @@ impl Buffer {
     /// Searches the buffer for (and returns positions
     /// associated with) occurrences of `needle`.
     ///
+    /// Matches consist of whole grapheme clusters: occurrences that start or
+    /// end partway through one (e.g. "e" within "é", when its accent is a
+    /// separate combining character) aren't matches.
+    ///
+    /// The buffer's content is searched in place, without being copied.
+    /// With the `parallel-search` feature enabled, buffers larger than 1MB
+    /// are instead split into chunks of lines, which are searched concurrently.
+    ///
     /// # Examples
     ///
     /// ```
@@ -413,25 +1207,75 @@ impl Buffer {
     /// );
     /// ```
     pub fn search(&self, needle: &str) -> Vec<Position> {
-        let mut results = Vec::new();
-
-        for (line, data) in self.data().lines().enumerate() {
-            for (offset, _) in data.char_indices() {
-                let haystack = &data[offset..];
-
-                // Check haystack length before slicing it and comparing bytes with needle.
-                if haystack.len() >= needle.len() && needle.as_bytes() == &haystack.as_bytes()[..needle.len()] {
-                    results.push(
-                        Position{
-                            line,
-                            offset
-                        }
-                    );
+        search::search_data(&self.data.borrow(), needle)
+    }
+
+    /// Fuzzy matches each of the buffer's lines against the query (see
+    /// `util::fuzzy_match`), returning the line numbers of those that match
+    /// alongside their results, best match first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary\nbuffer");
+    ///
+    /// let results = buffer.fuzzy_search_lines("bry");
+    /// assert_eq!(results.len(), 1);
+    /// assert_eq!(results[0].0, 1);
+    /// assert_eq!(results[0].1.offsets, vec![2, 3, 6]);
+    /// ```
+    pub fn fuzzy_search_lines(&self, query: &str) -> Vec<(usize, FuzzyMatch)> {
+        let data = self.data();
+
+        fuzzy_rank(query, data.lines().enumerate())
+    }
+
+    /// Fuzzy matches the buffer's symbols (lexemes scoped as named entities,
+    /// such as type and function names) against the query (see
+    /// `util::fuzzy_match`), returning their positions and values alongside
+    /// their results, best match first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::{Buffer, Workspace};
+    /// use scribe::buffer::Position;
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("struct Buffer;\nstruct Cursor;");
+    /// buffer.path = Some(PathBuf::from("lib.rs"));
+    /// workspace.add_buffer(buffer);
+    ///
+    /// let results = workspace.current_buffer().unwrap().fuzzy_search_symbols("cur").unwrap();
+    /// assert_eq!(results.len(), 1);
+    /// assert_eq!(results[0].0, Position{ line: 1, offset: 7 });
+    /// assert_eq!(results[0].1, "Cursor");
+    /// ```
+    #[cfg(feature = "syntax")]
+    pub fn fuzzy_search_symbols(&self, query: &str) -> Result<Vec<(Position, String, FuzzyMatch)>> {
+        let tokens = self.tokens()?;
+        let entity = Scope::new("entity.name").unwrap();
+        let symbols = tokens.iter().filter_map(|token| {
+            if let Token::Lexeme(lexeme) = token {
+                if lexeme.scope.as_slice().iter().any(|s| entity.is_prefix_of(*s)) {
+                    return Some(((lexeme.position, lexeme.value.to_string()), lexeme.value));
                 }
             }
-        }
 
-        results
+            None
+        });
+
+        Ok(
+            fuzzy_rank(query, symbols)
+                .into_iter()
+                .map(|((position, value), result)| (position, value, result))
+                .collect()
+        )
     }
 
     /// Whether or not the buffer has been modified since being read from or
@@ -443,6 +1287,7 @@ impl Buffer {
     /// use scribe::Buffer;
     /// use std::path::Path;
     ///
+    /// # #[cfg(feature = "fs")] {
     /// let file_path = Path::new("tests/sample/file");
     /// let mut buffer = Buffer::from_file(file_path).unwrap();
     ///
@@ -459,6 +1304,7 @@ impl Buffer {
     /// // Buffers without paths are always modified.
     /// buffer = Buffer::new();
     /// assert!(buffer.modified());
+    /// # }
     /// ```
     pub fn modified(&self) -> bool {
         !self.history.at_mark()
@@ -477,12 +1323,72 @@ impl Buffer {
     /// assert_eq!(buffer.line_count(), 3);
     /// ```
     pub fn line_count(&self) -> usize {
-        self.data().chars().filter(|&c| c == '\n').count() + 1
+        self.data.borrow().line_count()
+    }
+
+    /// Whether or not the buffer's last character is a newline. When it is,
+    /// the buffer's last line is empty, and the cursor can be placed on it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    /// assert!(!buffer.has_trailing_newline());
+    ///
+    /// buffer.cursor.move_to_end_of_line();
+    /// buffer.insert("\n");
+    /// assert!(buffer.has_trailing_newline());
+    /// ```
+    pub fn has_trailing_newline(&self) -> bool {
+        self.data().ends_with('\n')
+    }
+
+    /// Appends a newline to the buffer, unless it's empty or already ends
+    /// with one. The cursor position is left as-is. Returns whether or not a
+    /// newline was added; the addition is undoable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    ///
+    /// assert!(buffer.ensure_trailing_newline());
+    /// assert!(!buffer.ensure_trailing_newline());
+    /// assert_eq!(buffer.data(), "scribe\n");
+    /// ```
+    pub fn ensure_trailing_newline(&mut self) -> bool {
+        let data = self.data();
+        if self.read_only || data.is_empty() || data.ends_with('\n') {
+            return false;
+        }
+
+        let end = self.end_position();
+        self.insert_at("\n", end);
+
+        true
+    }
+
+    /// The position immediately after the last character in the buffer.
+    pub(crate) fn end_position(&self) -> Position {
+        let line = self.line_count() - 1;
+        let offset = self.line(line)
+            .map(|last_line| last_line.graphemes(true).count())
+            .unwrap_or(0);
+
+        Position{ line, offset }
     }
 
     /// Reloads the buffer from disk, discarding any in-memory modifications and
-    /// history, as well as resetting the cursor to its initial (0,0) position.
-    /// The buffer's ID and syntax definition are persisted.
+    /// history. The cursor and selections are moved to the corresponding
+    /// positions in the file's content, using the buffer's `remap_policy`.
+    /// The buffer's ID, syntax definition, settings, observers, and metadata
+    /// are persisted.
     ///
     /// # Examples
     ///
@@ -493,47 +1399,262 @@ impl Buffer {
     /// let file_path = Path::new("tests/sample/file");
     /// let mut buffer = Buffer::from_file(file_path).unwrap();
     /// buffer.insert("scribe\nlibrary\n");
+    /// buffer.cursor.move_to(Position{ line: 2, offset: 3 });
     /// buffer.reload();
     ///
     /// assert_eq!(buffer.data(), "it works!\n");
-    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 0 });
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 3 });
     /// # buffer.undo();
     /// # assert_eq!(buffer.data(), "it works!\n");
     /// ```
+    #[cfg(feature = "fs")]
     pub fn reload(&mut self) -> io::Result<()> {
-        if let Some(ref path) = self.path.clone() {
-            match Buffer::from_file(path) {
-                Ok(mut buf) => {
-                    mem::swap(self, &mut buf);
-
-                    // Restore the buffer's ID.
-                    self.id = buf.id;
-                    self.syntax_definition = buf.syntax_definition;
-                    self.change_callback = buf.change_callback;
-                },
-                Err(e) => return Err(e),
-            }
+        let previous_data = self.data();
+        let cursor = *self.cursor;
+        let selections = self.selections.clone();
+        let collaborators = self.collaborators.clone();
+        if self.path.is_some() {
+            self.read_file()?;
         }
 
         // Run the change callback, if present.
         if let Some(ref callback) = self.change_callback {
             callback(Position::new())
         }
+        self.remap_positions(&previous_data, cursor, &selections, collaborators);
+        self.notify_replacement(previous_data);
 
         Ok(())
     }
+
+    // Discards the buffer's content and history to free memory, retaining
+    // its path and configuration, so that its file can be loaded again later.
+    #[cfg(feature = "workspace")]
+    pub(crate) fn unload(&mut self) {
+        self.replace_data(String::new());
+    }
+
+    // Reads the buffer's file into it, in place of its current content and
+    // history (unlike `reload`, positions aren't remapped). The cursor is
+    // moved to the start of the buffer. Observers are only notified if the
+    // file has been modified since it was last read, as the buffer's content
+    // is otherwise unchanged from their perspective.
+    #[cfg(feature = "workspace")]
+    pub(crate) fn load(&mut self) -> io::Result<()> {
+        let previous_data = self.data();
+        if !self.read_file()? { return Ok(()); }
+
+        if let Some(ref callback) = self.change_callback {
+            callback(Position::new())
+        }
+        self.notify_replacement(previous_data);
+
+        Ok(())
+    }
+
+    // Reads the buffer's file into it, in place of its current content and
+    // history, retaining its configuration. Returns whether or not the file
+    // has been modified since it was last read.
+    #[cfg(feature = "fs")]
+    fn read_file(&mut self) -> io::Result<bool> {
+        let path = self.path.clone().ok_or_else(missing_path)?;
+        special_file::check(&path)?;
+
+        let mut file = File::open(&path)?;
+        let mut data = String::new();
+        file.read_to_string(&mut data)?;
+        let decoded = line_ending::decode(data);
+        let disk_modified = modified_time(&file);
+        let modified = disk_modified.is_none() || disk_modified != self.disk_modified;
+        self.loaded_with_conflicts = !conflict::find_conflicts(&decoded.data).is_empty();
+        self.line_ending = decoded.line_ending;
+        self.mixed_line_endings = decoded.mixed;
+        self.disk_modified = disk_modified;
+        self.line_offset = 0;
+        self.replace_data(decoded.data);
+
+        Ok(modified)
+    }
+
+    // Notifies observers that the buffer's content, previously
+    // `previous_data`, has been replaced in its entirety.
+    #[cfg(feature = "fs")]
+    fn notify_replacement(&self, previous_data: String) {
+        let data = self.data();
+        self.notify_observers(&Edit{
+            range: Range::new(Position::new(), Position::new() + Distance::of_str(&data)),
+            inserted: data,
+            removed: previous_data,
+        });
+    }
+
+    // Swaps in new content, resetting the state derived from the old content.
+    #[cfg(feature = "fs")]
+    fn replace_data(&mut self, data: String) {
+        *self.data.borrow_mut() = GapBuffer::new(data);
+        let mut cursor = Cursor::new(self.data.clone(), Position::new());
+        cursor.set_style(self.cursor.style());
+        cursor.set_past_end_of_line(self.cursor.past_end_of_line());
+        self.cursor = cursor;
+        self.history = History::new();
+        self.history.mark();
+        self.operation_group = None;
+        self.typing_run = None;
+        self.pending = None;
+        self.selections.clear();
+        self.reset_line_states();
+        #[cfg(feature = "syntax")]
+        {
+            self.embedded_regions = RefCell::new(RegionCache::default());
+            self.token_cache = RefCell::new(TokenCache::default());
+            self.token_data = RefCell::new(None);
+        }
+    }
+}
+
+// Converts the path to its absolute equivalent, resolving "." and ".."
+// components lexically, rather than by following symbolic links.
+#[cfg(feature = "fs")]
+pub(crate) fn absolute_path(path: &Path) -> io::Result<PathBuf> {
+    let mut absolute_path = if path.is_absolute() {
+        PathBuf::new()
+    } else {
+        env::current_dir()?
+    };
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => { absolute_path.pop(); },
+            component => absolute_path.push(component.as_os_str()),
+        }
+    }
+
+    Ok(absolute_path)
+}
+
+fn missing_path() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "buffer doesn't have a path")
+}
+
+#[cfg(feature = "fs")]
+fn modified_time(file: &File) -> Option<SystemTime> {
+    file.metadata().and_then(|metadata| metadata.modified()).ok()
+}
+
+// Refuses to save over files whose permissions don't allow writing.
+#[cfg(feature = "fs")]
+fn check_writable(path: &Path) -> io::Result<()> {
+    match fs::metadata(path) {
+        Ok(ref metadata) if metadata.permissions().readonly() => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "file is read-only"
+        )),
+        _ => Ok(()),
+    }
+}
+
+// Writes the data to a temporary file in the destination's directory,
+// and then renames it into place, replacing the destination atomically.
+// Symbolic links are resolved first, so that they're not replaced. If the
+// destination can't (or shouldn't) be replaced, it's written in place.
+#[cfg(feature = "fs")]
+fn write_atomically(path: &Path, data: &[u8]) -> io::Result<File> {
+    let path = &fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "path doesn't have a file name")
+    })?;
+    let mut temp_file_name = OsString::from(".");
+    temp_file_name.push(file_name);
+    temp_file_name.push(format!(".scribe-{}", process::id()));
+    let temp_path = path.with_file_name(temp_file_name);
+
+    match write_and_rename(&temp_path, path, data) {
+        Ok(file) => Ok(file),
+        Err(error) => {
+            // Don't leave a partially written file behind.
+            let _ = fs::remove_file(&temp_path);
+
+            if error.kind() == io::ErrorKind::PermissionDenied {
+                write_in_place(path, data)
+            } else {
+                Err(error)
+            }
+        },
+    }
+}
+
+#[cfg(feature = "fs")]
+fn write_and_rename(temp_path: &Path, path: &Path, data: &[u8]) -> io::Result<File> {
+    let mut file = File::create(temp_path)?;
+
+    if let Ok(metadata) = fs::metadata(path) {
+        if !replaceable(&metadata, &file.metadata()?) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "replacing the file would change its ownership or break its hard links"
+            ));
+        }
+        file.set_permissions(metadata.permissions())?;
+    }
+    file.write_all(data)?;
+    file.sync_all()?;
+    fs::rename(temp_path, path)?;
+
+    Ok(file)
+}
+
+// Whether a file can be replaced by a newly created one without changing its
+// ownership, or detaching it from other hard links to it.
+#[cfg(all(feature = "fs", unix))]
+fn replaceable(existing: &fs::Metadata, replacement: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    existing.uid() == replacement.uid() &&
+        existing.gid() == replacement.gid() &&
+        existing.nlink() <= 1
+}
+
+#[cfg(all(feature = "fs", not(unix)))]
+fn replaceable(_: &fs::Metadata, _: &fs::Metadata) -> bool {
+    true
+}
+
+#[cfg(feature = "fs")]
+fn write_in_place(path: &Path, data: &[u8]) -> io::Result<File> {
+    let mut file = OpenOptions::new().write(true).create(true).truncate(true).open(path)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+
+    Ok(file)
 }
 
 #[cfg(test)]
 mod tests {
-    extern crate syntect;
-    use syntect::parsing::SyntaxSet;
+    #[cfg(feature = "syntax")]
+    use syntect::parsing::{SyntaxDefinition, SyntaxSet};
+    #[cfg(feature = "fs")]
     use std::cell::RefCell;
+    #[cfg(feature = "fs")]
+    use std::env;
+    #[cfg(feature = "fs")]
+    use std::fs;
+    #[cfg(feature = "fs")]
+    use std::io;
+    #[cfg(feature = "fs")]
     use std::path::Path;
+    #[cfg(feature = "fs")]
     use std::rc::Rc;
+    #[cfg(feature = "fs")]
+    use std::time::Duration;
     use buffer::{Buffer, Position};
+    #[cfg(feature = "fs")]
+    use buffer::{CursorStyle, IndentRules, LineEnding, OpenResult};
+    #[cfg(feature = "syntax")]
+    use buffer::{Lexeme, LexerState, LineRange, Scope, ScopeStack, Token, TokenSet};
 
     #[test]
+    #[cfg(feature = "syntax")]
     fn reload_persists_id_and_syntax_definition() {
         let file_path = Path::new("tests/sample/file");
         let mut buffer = Buffer::from_file(file_path).unwrap();
@@ -554,6 +1675,65 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "fs")]
+    fn reload_persists_append_only_buffers() {
+        let mut buffer = Buffer::from_file(Path::new("tests/sample/file")).unwrap();
+        buffer.append_only = true;
+        buffer.reload().unwrap();
+
+        assert!(buffer.append_only);
+        buffer.delete();
+        assert_eq!(buffer.data(), "it works!\n");
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn reload_persists_user_settings() {
+        let mut buffer = Buffer::from_file(Path::new("tests/sample/file")).unwrap();
+        buffer.read_only = true;
+        buffer.preserve_formatting = true;
+        buffer.smart_punctuation = true;
+        buffer.history_limit = Some(5);
+        buffer.abbreviations.insert("sc".to_string(), "scribe".to_string());
+        buffer.tab_width = 8;
+        buffer.soft_tabs = false;
+        buffer.indent_rules = Some(IndentRules::brackets());
+        buffer.max_line_length = Some(10);
+        buffer.idle_interval = Duration::from_secs(3);
+        buffer.reload().unwrap();
+
+        assert!(buffer.read_only);
+        assert!(buffer.preserve_formatting);
+        assert!(buffer.smart_punctuation);
+        assert_eq!(buffer.history_limit, Some(5));
+        assert_eq!(buffer.abbreviations.get("sc").map(String::as_str), Some("scribe"));
+        assert_eq!(buffer.tab_width, 8);
+        assert!(!buffer.soft_tabs);
+        assert!(buffer.indent_rules.is_some());
+        assert_eq!(buffer.max_line_length, Some(10));
+        assert_eq!(buffer.idle_interval, Duration::from_secs(3));
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn reload_persists_cursor_style() {
+        let mut buffer = Buffer::from_file(Path::new("tests/sample/file")).unwrap();
+        buffer.cursor.set_style(CursorStyle::Block);
+        buffer.reload().unwrap();
+
+        assert_eq!(buffer.cursor.style(), CursorStyle::Block);
+        assert!(!buffer.cursor.past_end_of_line());
+
+        buffer.cursor.set_style(CursorStyle::Bar);
+        buffer.cursor.set_past_end_of_line(false);
+        buffer.reload().unwrap();
+
+        assert_eq!(buffer.cursor.style(), CursorStyle::Bar);
+        assert!(!buffer.cursor.past_end_of_line());
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
     fn reload_calls_change_callback_with_zero_position() {
         // Load a buffer with some data and modify it.
         let file_path = Path::new("tests/sample/file");
@@ -576,6 +1756,40 @@ mod tests {
         assert_eq!(*tracked_position.borrow(), Position::new());
     }
 
+    #[test]
+    #[cfg(feature = "fs")]
+    fn save_adds_trailing_newline_when_configured() {
+        let path = env::temp_dir().join("scribe_save_adds_trailing_newline");
+        let mut buffer = Buffer::new();
+        buffer.path = Some(path.clone());
+        buffer.insert_final_newline = true;
+        buffer.insert("scribe");
+        buffer.save().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "scribe\n");
+        assert!(!buffer.modified());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn ensure_trailing_newline_leaves_empty_buffers_alone() {
+        let mut buffer = Buffer::new();
+        assert!(!buffer.ensure_trailing_newline());
+        assert_eq!(buffer.data(), "");
+    }
+
+    #[test]
+    fn ensure_trailing_newline_appends_to_lines_containing_carriage_return_line_feeds() {
+        let mut buffer = Buffer::new();
+        buffer.insert("a\r\nb");
+        assert_eq!(buffer.line_count(), 1);
+        assert_eq!(buffer.line(0), Some("a\r\nb".to_string()));
+
+        assert!(buffer.ensure_trailing_newline());
+        assert_eq!(buffer.data(), "a\r\nb\n");
+        assert_eq!(buffer.line_count(), 2);
+    }
+
     #[test]
     fn delete_joins_lines_when_invoked_at_end_of_line() {
         let mut buffer = Buffer::new();
@@ -618,6 +1832,23 @@ mod tests {
         assert_eq!("scribe", buffer.data());
     }
 
+    #[test]
+    fn undo_and_redo_move_the_cursor_to_the_change() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary");
+        buffer.cursor.move_to(Position{ line: 1, offset: 3 });
+        buffer.delete();
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 0 });
+        buffer.undo();
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 3 });
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 0 });
+        buffer.redo();
+        assert_eq!(buffer.data(), "scribe\nlibary");
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 3 });
+    }
+
     #[test]
     fn correctly_called_operation_groups_are_undone_correctly() {
         let mut buffer = Buffer::new();
@@ -713,4 +1944,289 @@ mod tests {
         // Use a matching term.
         assert!(buffer.search("scribé").len() > 0);
     }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn open_creates_unmodified_buffers_for_missing_files() {
+        let path = env::temp_dir().join("scribe_open_creates_missing_files");
+        let _ = fs::remove_file(&path);
+
+        let mut buffer = match Buffer::open(&path).unwrap() {
+            OpenResult::Created(buffer) => buffer,
+            OpenResult::Loaded(_) => panic!("missing file was loaded"),
+        };
+        assert!(!buffer.modified());
+        assert_eq!(buffer.path, Some(path.clone()));
+
+        buffer.insert("scribe");
+        buffer.save().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "scribe");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn open_returns_errors_for_missing_parent_directories() {
+        let path = env::temp_dir().join("scribe_missing_directory/file");
+        assert!(Buffer::open(&path).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn crlf_line_endings_are_converted_when_loading_and_saving() {
+        let path = env::temp_dir().join("scribe_crlf_line_endings_test");
+        fs::write(&path, "scribe\r\nlibrary\r\n").unwrap();
+
+        let mut buffer = Buffer::from_file(&path).unwrap();
+        assert_eq!(buffer.data(), "scribe\nlibrary\n");
+        assert_eq!(buffer.line_ending, LineEnding::Crlf);
+
+        // Joining lines removes the entire line ending.
+        buffer.cursor.move_to(Position{ line: 0, offset: 6 });
+        buffer.delete();
+        buffer.insert("\n");
+        buffer.save().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "scribe\r\nlibrary\r\n");
+
+        buffer.line_ending = LineEnding::Lf;
+        buffer.save().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "scribe\nlibrary\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn save_returns_a_distinct_error_for_buffers_without_paths() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+
+        assert_eq!(buffer.save().unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert!(buffer.modified());
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn save_refuses_read_only_files_before_normalizing() {
+        let path = env::temp_dir().join("scribe_save_read_only_file");
+        fs::write(&path, "scribe").unwrap();
+        let mut permissions = fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&path, permissions.clone()).unwrap();
+
+        let mut buffer = Buffer::from_file(&path).unwrap();
+        buffer.insert_final_newline = true;
+        assert_eq!(buffer.save().unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(buffer.data(), "scribe");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "scribe");
+
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(&path, permissions).unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    #[cfg(unix)]
+    fn save_writes_files_with_hard_links_in_place() {
+        let path = env::temp_dir().join("scribe_save_hard_linked_file");
+        let link = env::temp_dir().join("scribe_save_hard_linked_file_link");
+        let _ = fs::remove_file(&link);
+        fs::write(&path, "scribe").unwrap();
+        fs::hard_link(&path, &link).unwrap();
+
+        let mut buffer = Buffer::from_file(&path).unwrap();
+        buffer.insert("library ");
+        buffer.save().unwrap();
+        assert_eq!(fs::read_to_string(&link).unwrap(), "library scribe");
+        assert!(!buffer.modified());
+        assert!(!buffer.outdated());
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&link).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn save_as_restores_the_original_path_when_saving_fails() {
+        let original_path = env::temp_dir().join("scribe_save_as_original");
+        let mut buffer = Buffer::new();
+        buffer.path = Some(original_path.clone());
+        buffer.insert("scribe");
+
+        assert!(buffer.save_as(&env::temp_dir().join("scribe_missing_directory/file")).is_err());
+        assert_eq!(buffer.path, Some(original_path));
+        assert!(buffer.modified());
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    #[cfg(unix)]
+    fn from_file_preserves_symbolic_links_in_paths() {
+        let directory = env::temp_dir().join("scribe_from_file_preserves_symbolic_links");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("target"), "scribe").unwrap();
+        ::std::os::unix::fs::symlink(directory.join("target"), directory.join("link")).unwrap();
+
+        let mut buffer = Buffer::from_file(&directory.join("./link")).unwrap();
+        assert_eq!(buffer.path, Some(directory.join("link")));
+        assert_eq!(buffer.canonical_path(), Some(directory.join("target").canonicalize().unwrap()));
+
+        // Saving writes through the link, rather than replacing it.
+        buffer.insert("library ");
+        buffer.save().unwrap();
+        assert!(fs::symlink_metadata(directory.join("link")).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(directory.join("target")).unwrap(), "library scribe");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn from_file_range_truncates_ranges_beyond_the_end_of_the_file() {
+        let path = env::temp_dir().join("scribe_from_file_range_truncates");
+        fs::write(&path, "scribe\nlibrary").unwrap();
+
+        let buffer = Buffer::from_file_range(&path, 1, 10).unwrap();
+        assert_eq!(buffer.data(), "library");
+        assert_eq!(buffer.line_offset(), 1);
+        assert!(Buffer::from_file_range(&path, 5, 10).unwrap().data().is_empty());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn from_file_range_produces_buffers_that_cannot_be_saved() {
+        let path = env::temp_dir().join("scribe_from_file_range_cannot_be_saved");
+        fs::write(&path, "scribe\nlibrary").unwrap();
+
+        let mut buffer = Buffer::from_file_range(&path, 0, 1).unwrap();
+        assert!(!buffer.modified());
+        assert!(buffer.save().is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "scribe\nlibrary");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "syntax")]
+    fn tokens_reflect_edits_made_after_lexing() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let def = syntax_set.find_syntax_by_extension("rs").unwrap().clone();
+        let mut buffer = Buffer::new();
+        buffer.syntax_definition = Some(def.clone());
+        buffer.insert("fn scribe() {}\n".repeat(200));
+        assert_eq!(tokens_as_strings(&buffer), lexed_tokens_as_strings(&buffer, &def));
+        assert_eq!(buffer.token_cache.borrow().len(), 201);
+
+        // Opening a block comment changes the scope of every following line.
+        buffer.cursor.move_to(Position{ line: 150, offset: 0 });
+        buffer.insert("/*");
+        assert_eq!(buffer.token_cache.borrow().len(), 128);
+        assert_eq!(tokens_as_strings(&buffer), lexed_tokens_as_strings(&buffer, &def));
+
+        buffer.cursor.move_to(Position{ line: 10, offset: 0 });
+        buffer.insert("/*");
+        assert_eq!(tokens_as_strings(&buffer), lexed_tokens_as_strings(&buffer, &def));
+
+        buffer.undo();
+        buffer.undo();
+        assert_eq!(tokens_as_strings(&buffer), lexed_tokens_as_strings(&buffer, &def));
+    }
+
+    #[test]
+    #[cfg(feature = "syntax")]
+    fn tokens_share_the_buffer_content_until_it_is_modified() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let def = syntax_set.find_syntax_by_extension("rs").unwrap().clone();
+        let mut buffer = Buffer::new();
+        buffer.syntax_definition = Some(def.clone());
+        buffer.insert("fn scribe() {}\n");
+
+        // Lexemes borrow their values from the lexed content, so
+        // matching addresses show that it wasn't copied again.
+        let content_address = |tokens: TokenSet| match tokens.iter().next() {
+            Some(Token::Lexeme(lexeme)) => lexeme.value.as_ptr() as usize,
+            _ => panic!("expected a lexeme"),
+        };
+        let address = content_address(buffer.tokens().unwrap());
+        assert_eq!(content_address(buffer.tokens().unwrap()), address);
+        assert_eq!(content_address(buffer.tokens_for_lines(&LineRange::new(0, 1)).unwrap()), address);
+
+        buffer.insert("struct Buffer;\n");
+        assert_ne!(content_address(buffer.tokens().unwrap()), address);
+        assert_eq!(tokens_as_strings(&buffer), lexed_tokens_as_strings(&buffer, &def));
+    }
+
+    #[test]
+    #[cfg(feature = "syntax")]
+    fn tokens_for_lines_and_lexer_state_resume_from_cached_states() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let def = syntax_set.find_syntax_by_extension("rs").unwrap().clone();
+        let mut buffer = Buffer::new();
+        buffer.syntax_definition = Some(def.clone());
+        buffer.insert(format!("/*\n{}*/\n{}", "scribe\n".repeat(150), "fn scribe() {}\n".repeat(50)));
+        buffer.tokens().unwrap().iter().count();
+
+        let lines = ::buffer::LineRange::new(140, 160);
+        let tokens: Vec<String> = buffer.tokens_for_lines(&lines).unwrap().iter().map(|token| format!("{:?}", token)).collect();
+        let lexed: Vec<String> = TokenSet::new(buffer.data(), &def).with_lines(140, 160).iter().map(|token| format!("{:?}", token)).collect();
+        assert_eq!(tokens, lexed);
+
+        let data = buffer.read(&::buffer::Range::new(
+            Position{ line: 140, offset: 0 },
+            Position{ line: 160, offset: 0 }
+        )).unwrap();
+        let state = buffer.lexer_state(140).unwrap();
+        let mut resumed: Vec<String> = TokenSet::new(data, &def).resuming(state, 140).iter().map(|token| format!("{:?}", token)).collect();
+
+        // The data read includes the last line's newline.
+        assert_eq!(resumed.pop(), Some("Newline".to_string()));
+        assert_eq!(resumed, lexed);
+    }
+
+    #[test]
+    #[cfg(feature = "syntax")]
+    fn tokens_leave_lines_beyond_the_max_line_length_unlexed() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let def = syntax_set.find_syntax_by_extension("rs").unwrap().clone();
+        let long_line = format!("let scribe = [{}];", "1, ".repeat(1000));
+        let mut buffer = Buffer::new();
+        buffer.syntax_definition = Some(def.clone());
+        buffer.insert(format!("{}\nfn scribe() {{}}\n", long_line));
+        let lexed = tokens_as_strings(&buffer);
+
+        buffer.max_line_length = Some(100);
+        let tokens = tokens_as_strings(&buffer);
+        let mut scope = ScopeStack::new();
+        scope.push(Scope::new("source.rust").unwrap());
+        assert_eq!(tokens[0], format!("{:?}", Token::Lexeme(Lexeme{
+            value: &long_line,
+            scope,
+            position: Position{ line: 0, offset: 0 },
+        })));
+
+        // Following lines are lexed as usual, and the cache
+        // is discarded when the maximum length changes.
+        let line_start = |tokens: &[String]| tokens.iter().position(|token| token == "Newline").unwrap();
+        assert_eq!(tokens[line_start(&tokens)..], lexed[line_start(&lexed)..]);
+        assert_eq!(buffer.lexer_state(1).unwrap().scopes().as_slice().len(), 1);
+
+        buffer.max_line_length = None;
+        assert_eq!(tokens_as_strings(&buffer), lexed);
+    }
+
+    #[cfg(feature = "syntax")]
+    fn tokens_as_strings(buffer: &Buffer) -> Vec<String> {
+        buffer.tokens().unwrap().iter().map(|token| format!("{:?}", token)).collect()
+    }
+
+    // Lexes the buffer's content without using its cache.
+    #[cfg(feature = "syntax")]
+    fn lexed_tokens_as_strings(buffer: &Buffer, def: &SyntaxDefinition) -> Vec<String> {
+        TokenSet::new(buffer.data(), def).iter().map(|token| format!("{:?}", token)).collect()
+    }
 }
@@ -0,0 +1,139 @@
+//! Content changes applied when saving.
+
+use buffer::Buffer;
+
+/// A change made to a buffer's content when it's saved, as
+/// configured by its options (e.g. `insert_final_newline`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Normalization {
+    /// A newline is appended to the last line.
+    FinalNewline,
+
+    /// The file was loaded with a mix of line endings, which are replaced
+    /// with the buffer's `line_ending`.
+    LineEndings,
+}
+
+impl Buffer {
+    /// The normalizations that saving the buffer would currently apply,
+    /// altering its content. When the buffer is set to `preserve_formatting`,
+    /// none are applied, so saving a freshly loaded buffer reproduces its
+    /// file byte for byte (line endings, byte order marks, and all). Lines
+    /// of files with mixed line endings keep their own endings as they're
+    /// edited, with added lines using the buffer's `line_ending`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Normalization;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    /// buffer.insert_final_newline = true;
+    /// assert_eq!(buffer.normalizations(), vec![Normalization::FinalNewline]);
+    ///
+    /// buffer.preserve_formatting = true;
+    /// assert!(buffer.normalizations().is_empty());
+    /// ```
+    pub fn normalizations(&self) -> Vec<Normalization> {
+        let mut normalizations = Vec::new();
+        if self.read_only || self.preserve_formatting {
+            return normalizations;
+        }
+
+        if !self.mixed_line_endings.is_empty() {
+            normalizations.push(Normalization::LineEndings);
+        }
+
+        let data = self.data();
+        if self.insert_final_newline && !data.is_empty() && !data.ends_with('\n') {
+            normalizations.push(Normalization::FinalNewline);
+        }
+
+        normalizations
+    }
+
+    // Applies the normalizations that saving the buffer calls for.
+    pub(crate) fn normalize(&mut self) {
+        for normalization in self.normalizations() {
+            match normalization {
+                Normalization::FinalNewline => { self.ensure_trailing_newline(); },
+
+                // Line endings are converted as the buffer is written.
+                Normalization::LineEndings => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::Buffer;
+    #[cfg(feature = "fs")]
+    use buffer::{Normalization, Position, Range};
+    #[cfg(feature = "fs")]
+    use std::env;
+    #[cfg(feature = "fs")]
+    use std::fs;
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn saving_unedited_buffers_preserving_formatting_reproduces_their_files() {
+        let path = env::temp_dir().join("scribe_round_trip_test");
+        let content = "\u{feff}scribe\r\nlibrary\r\n\teditor  ".as_bytes();
+        fs::write(&path, content).unwrap();
+
+        let mut buffer = Buffer::from_file(&path).unwrap();
+        buffer.insert_final_newline = true;
+        buffer.preserve_formatting = true;
+        buffer.save().unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), content);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn saving_files_with_mixed_line_endings_uses_the_dominant_one() {
+        let path = env::temp_dir().join("scribe_mixed_line_endings_test");
+        fs::write(&path, "scribe\r\nlibrary\neditor\r\n").unwrap();
+
+        let mut buffer = Buffer::from_file(&path).unwrap();
+        assert_eq!(buffer.normalizations(), vec![Normalization::LineEndings]);
+
+        buffer.save().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "scribe\r\nlibrary\r\neditor\r\n");
+        assert!(buffer.normalizations().is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn saving_files_with_mixed_line_endings_preserving_formatting_keeps_them() {
+        let path = env::temp_dir().join("scribe_mixed_line_endings_preserved_test");
+        fs::write(&path, "scribe\r\nlibrary\neditor\r\n").unwrap();
+
+        let mut buffer = Buffer::from_file(&path).unwrap();
+        buffer.preserve_formatting = true;
+        assert!(buffer.normalizations().is_empty());
+
+        // Added lines use the dominant line ending, and
+        // joined lines use the ending of the last one.
+        buffer.insert("text\n");
+        buffer.delete_range(Range::new(Position{ line: 2, offset: 7 }, Position{ line: 3, offset: 0 }));
+        buffer.save().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "text\r\nscribe\r\nlibraryeditor\r\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn normalizations_ignores_empty_and_terminated_buffers() {
+        let mut buffer = Buffer::new();
+        buffer.insert_final_newline = true;
+        assert!(buffer.normalizations().is_empty());
+
+        buffer.insert("scribe\n");
+        assert!(buffer.normalizations().is_empty());
+    }
+}
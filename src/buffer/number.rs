@@ -0,0 +1,292 @@
+//! Incrementing and decrementing numbers in place.
+
+use buffer::{Buffer, Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+// Notations recognized when locating numbers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Radix {
+    Binary,
+    Octal,
+    Decimal,
+    Hexadecimal,
+}
+
+impl Radix {
+    fn base(self) -> u32 {
+        match self {
+            Radix::Binary => 2,
+            Radix::Octal => 8,
+            Radix::Decimal => 10,
+            Radix::Hexadecimal => 16,
+        }
+    }
+
+    // Maps the character following a leading zero to its radix.
+    fn from_prefix(grapheme: &str) -> Option<Radix> {
+        match grapheme {
+            "b" | "B" => Some(Radix::Binary),
+            "o" | "O" => Some(Radix::Octal),
+            "x" | "X" => Some(Radix::Hexadecimal),
+            _ => None,
+        }
+    }
+
+    fn is_digit(self, grapheme: &str) -> bool {
+        let mut chars = grapheme.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => c.is_digit(self.base()),
+            _ => false,
+        }
+    }
+}
+
+// The grapheme cluster-based location of a number on a line. The range
+// includes any sign or radix prefix, which digits_start points beyond.
+#[derive(Debug, PartialEq)]
+struct Number {
+    start: usize,
+    digits_start: usize,
+    end: usize,
+    radix: Radix,
+}
+
+impl Buffer {
+    /// Adds `delta` to the number under (or following) the cursor on its
+    /// line, moving the cursor to the number's last character. Returns
+    /// whether or not a number was found and changed.
+    ///
+    /// Hexadecimal (`0x`), octal (`0o`), and binary (`0b`) prefixes are
+    /// recognized, and their digits are adjusted as unsigned values. Decimal
+    /// values honour a leading minus sign. Numbers with leading zeros are
+    /// padded to retain their original width, and hexadecimal digits retain
+    /// their case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("width: 0x0f, height: -2");
+    ///
+    /// assert!(buffer.increment_number(1));
+    /// assert_eq!(buffer.data(), "width: 0x10, height: -2");
+    ///
+    /// buffer.cursor.move_right();
+    /// assert!(buffer.increment_number(5));
+    /// assert_eq!(buffer.data(), "width: 0x10, height: 3");
+    /// ```
+    pub fn increment_number(&mut self, delta: i64) -> bool {
+        if self.read_only { return false; }
+
+        let line = self.cursor.line;
+        let content = match self.data().lines().nth(line) {
+            Some(content) => content.to_string(),
+            None => return false,
+        };
+        let graphemes: Vec<&str> = content.graphemes(true).collect();
+
+        let number = match find_number(&graphemes, self.cursor.offset) {
+            Some(number) => number,
+            None => return false,
+        };
+        let replacement = match adjust(&graphemes, &number, delta) {
+            Some(replacement) => replacement,
+            None => return false,
+        };
+
+        let start = Position{ line, offset: number.start };
+        let end = Position{ line, offset: number.end };
+        let replacement_length = replacement.graphemes(true).count();
+
+        let result = self.try_batch(|buffer| {
+            buffer.delete_range(Range::new(start, end));
+            buffer.insert_at(replacement, start);
+        });
+        if result.is_err() { return false; }
+        self.cursor.move_to(Position{ line, offset: number.start + replacement_length - 1 });
+
+        true
+    }
+}
+
+// Finds the first number that contains or follows the specified offset.
+fn find_number(graphemes: &[&str], offset: usize) -> Option<Number> {
+    let mut index = 0;
+
+    while index < graphemes.len() {
+        let prefixed_radix = if graphemes[index] == "0" && index + 2 < graphemes.len() {
+            Radix::from_prefix(graphemes[index + 1])
+                .filter(|radix| radix.is_digit(graphemes[index + 2]))
+        } else {
+            None
+        };
+
+        let number = if let Some(radix) = prefixed_radix {
+            Number{
+                start: index,
+                digits_start: index + 2,
+                end: digits_end(graphemes, index + 2, radix),
+                radix,
+            }
+        } else if Radix::Decimal.is_digit(graphemes[index]) {
+            // Treat a preceding minus as a sign, unless it's part of a word.
+            let signed = index > 0 && graphemes[index - 1] == "-" &&
+                (index < 2 || !is_word(graphemes[index - 2]));
+            let start = if signed { index - 1 } else { index };
+
+            Number{
+                start,
+                digits_start: index,
+                end: digits_end(graphemes, index, Radix::Decimal),
+                radix: Radix::Decimal,
+            }
+        } else {
+            index += 1;
+            continue;
+        };
+
+        if number.end > offset {
+            return Some(number);
+        }
+        index = number.end;
+    }
+
+    None
+}
+
+fn digits_end(graphemes: &[&str], start: usize, radix: Radix) -> usize {
+    let mut end = start;
+    while end < graphemes.len() && radix.is_digit(graphemes[end]) {
+        end += 1;
+    }
+
+    end
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+// Builds the adjusted representation of the number, returning
+// None if it can't be represented using 64-bit values.
+fn adjust(graphemes: &[&str], number: &Number, delta: i64) -> Option<String> {
+    let prefix = graphemes[number.start..number.digits_start].concat();
+    let digits = graphemes[number.digits_start..number.end].concat();
+
+    // Retain the original width of zero-padded numbers.
+    let width = if digits.len() > 1 && digits.starts_with('0') {
+        digits.len()
+    } else {
+        0
+    };
+
+    if number.radix == Radix::Decimal {
+        let magnitude = digits.parse::<i64>().ok()?;
+        let value = if prefix == "-" { -magnitude } else { magnitude };
+        let result = value.checked_add(delta)?;
+        let sign = if result < 0 { "-" } else { "" };
+
+        return Some(format!("{}{:0width$}", sign, result.unsigned_abs(), width = width));
+    }
+
+    let value = u64::from_str_radix(&digits, number.radix.base()).ok()?;
+    let result = value.wrapping_add(delta as u64);
+    let formatted = match number.radix {
+        Radix::Binary => format!("{:0width$b}", result, width = width),
+        Radix::Octal => format!("{:0width$o}", result, width = width),
+        _ => {
+            if digits.chars().any(|c| c.is_ascii_uppercase()) {
+                format!("{:0width$X}", result, width = width)
+            } else {
+                format!("{:0width$x}", result, width = width)
+            }
+        }
+    };
+
+    Some(prefix + &formatted)
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position};
+
+    #[test]
+    fn increment_number_finds_numbers_after_the_cursor() {
+        let mut buffer = Buffer::new();
+        buffer.insert("version 9");
+        assert!(buffer.increment_number(1));
+        assert_eq!(buffer.data(), "version 10");
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 9 });
+    }
+
+    #[test]
+    fn increment_number_adjusts_the_number_under_the_cursor() {
+        let mut buffer = Buffer::new();
+        buffer.insert("1 234 5");
+        buffer.cursor.move_to(Position{ line: 0, offset: 3 });
+        assert!(buffer.increment_number(-34));
+        assert_eq!(buffer.data(), "1 200 5");
+    }
+
+    #[test]
+    fn increment_number_preserves_leading_zeros() {
+        let mut buffer = Buffer::new();
+        buffer.insert("007");
+        buffer.increment_number(1);
+        assert_eq!(buffer.data(), "008");
+    }
+
+    #[test]
+    fn increment_number_crosses_zero_with_decimal_values() {
+        let mut buffer = Buffer::new();
+        buffer.insert("x = 1");
+        buffer.increment_number(-3);
+        assert_eq!(buffer.data(), "x = -2");
+    }
+
+    #[test]
+    fn increment_number_ignores_hyphens_within_words() {
+        let mut buffer = Buffer::new();
+        buffer.insert("utf-8");
+        buffer.increment_number(1);
+        assert_eq!(buffer.data(), "utf-9");
+    }
+
+    #[test]
+    fn increment_number_preserves_hexadecimal_case() {
+        let mut buffer = Buffer::new();
+        buffer.insert("0xFE");
+        buffer.increment_number(1);
+        assert_eq!(buffer.data(), "0xFF");
+    }
+
+    #[test]
+    fn increment_number_handles_binary_and_octal_values() {
+        let mut buffer = Buffer::new();
+        buffer.insert("0b0111 0o7");
+        buffer.increment_number(1);
+        buffer.cursor.move_right();
+        buffer.increment_number(1);
+        assert_eq!(buffer.data(), "0b1000 0o10");
+    }
+
+    #[test]
+    fn increment_number_is_undone_as_a_single_operation() {
+        let mut buffer = Buffer::new();
+        buffer.insert("9");
+        buffer.increment_number(1);
+        buffer.undo();
+        assert_eq!(buffer.data(), "9");
+    }
+
+    #[test]
+    fn increment_number_returns_false_without_a_number() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe 1");
+        buffer.cursor.move_to(Position{ line: 0, offset: 8 });
+        assert!(!buffer.increment_number(1));
+        assert_eq!(buffer.data(), "scribe 1");
+    }
+}
@@ -0,0 +1,134 @@
+//! Subscriptions to buffer modifications.
+
+use buffer::{Buffer, Edit};
+
+// A function called with each modification made to a buffer.
+pub(crate) type ChangeObserver = Box<dyn Fn(&Edit)>;
+
+impl Buffer {
+    /// Registers an observer, called with a description of each modification
+    /// made to the buffer (including those made by undoing, redoing, and
+    /// reloading it), once it's been applied. Edits made by operation groups
+    /// and range-based methods are reported individually, in the order they're
+    /// made. Returns an ID that can be used to remove the observer.
+    ///
+    /// Unlike the `change_callback`, which only receives the position of a
+    /// change, the edit describes the affected range and its content,
+    /// allowing observers (e.g. renderers and linters) to update incrementally.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Edit, Position, Range};
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// let edits = Rc::new(RefCell::new(Vec::new()));
+    /// let observed_edits = edits.clone();
+    ///
+    /// let mut buffer = Buffer::new();
+    /// let id = buffer.on_change(move |edit: &Edit| observed_edits.borrow_mut().push(edit.clone()));
+    /// buffer.insert("scribe");
+    /// buffer.undo();
+    ///
+    /// let range = Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 6 });
+    /// assert_eq!(edits.borrow()[0].range, range);
+    /// assert_eq!(edits.borrow()[0].inserted, "scribe");
+    /// assert_eq!(edits.borrow()[1].removed, "scribe");
+    ///
+    /// assert!(buffer.remove_change_observer(id));
+    /// buffer.insert("library");
+    /// assert_eq!(edits.borrow().len(), 2);
+    /// ```
+    pub fn on_change<F: Fn(&Edit) + 'static>(&mut self, observer: F) -> usize {
+        let id = self.next_observer_id;
+        self.next_observer_id += 1;
+        self.change_observers.push((id, Box::new(observer)));
+
+        id
+    }
+
+    /// Removes a previously registered observer, returning
+    /// whether or not an observer with the specified ID existed.
+    pub fn remove_change_observer(&mut self, id: usize) -> bool {
+        let count = self.change_observers.len() + self.coalesced_observers.len();
+        self.change_observers.retain(|&(observer_id, _)| observer_id != id);
+        self.coalesced_observers.retain(|&(observer_id, _)| observer_id != id);
+
+        self.change_observers.len() + self.coalesced_observers.len() != count
+    }
+
+    // Calls each registered observer with the edit.
+    pub(crate) fn notify_observers(&self, edit: &Edit) {
+        for (_, observer) in &self.change_observers {
+            observer(edit);
+        }
+        self.notify_coalesced_observers(edit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Edit, Position, Range};
+    use std::cell::RefCell;
+    #[cfg(feature = "fs")]
+    use std::path::Path;
+    use std::rc::Rc;
+
+    fn observe(buffer: &mut Buffer) -> Rc<RefCell<Vec<Edit>>> {
+        let edits = Rc::new(RefCell::new(Vec::new()));
+        let observed_edits = edits.clone();
+        buffer.on_change(move |edit| observed_edits.borrow_mut().push(edit.clone()));
+
+        edits
+    }
+
+    #[test]
+    fn observers_are_notified_of_each_edit_in_a_replacement() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe library");
+        let edits = observe(&mut buffer);
+
+        let range = Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 6 });
+        buffer.replace(&range, "text");
+
+        let edits = edits.borrow();
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0], Edit{ range, inserted: String::new(), removed: "scribe".to_string() });
+        assert_eq!(edits[1].inserted, "text");
+        assert_eq!(edits[1].range.end(), Position{ line: 0, offset: 4 });
+    }
+
+    #[test]
+    fn undoing_a_deletion_reports_the_restored_content() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary");
+        let edits = observe(&mut buffer);
+
+        buffer.delete_range(Range::new(Position{ line: 0, offset: 4 }, Position{ line: 1, offset: 2 }));
+        buffer.undo();
+
+        let edits = edits.borrow();
+        assert_eq!(edits[1].inserted, "be\nli");
+        assert!(edits[1].removed.is_empty());
+        assert_eq!(edits[1].range, edits[0].range);
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn observers_survive_reloads_and_are_notified_of_them() {
+        let mut buffer = Buffer::from_file(Path::new("tests/sample/file")).unwrap();
+        buffer.insert("scribe");
+        let edits = observe(&mut buffer);
+
+        buffer.reload().unwrap();
+        buffer.insert("library");
+
+        let edits = edits.borrow();
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0].removed, "scribeit works!\n");
+        assert_eq!(edits[0].inserted, "it works!\n");
+        assert_eq!(edits[0].range.end(), Position{ line: 1, offset: 0 });
+    }
+}
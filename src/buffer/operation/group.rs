@@ -1,5 +1,7 @@
 use super::Operation;
+use super::summary::Edit;
 use buffer::Buffer;
+use errors::*;
 
 /// A collection of operations run as a single/atomic operation.
 ///
@@ -37,6 +39,11 @@ impl Operation for OperationGroup {
             operations: self.operations.iter().map(|o| (*o).clone_operation()).collect()
         })
     }
+
+    /// Collects the edits of the group's individual operations, in order.
+    fn edits(&self) -> Vec<Edit> {
+        self.operations.iter().flat_map(|o| o.edits()).collect()
+    }
 }
 
 impl OperationGroup {
@@ -54,6 +61,17 @@ impl OperationGroup {
     pub fn is_empty(&self) -> bool {
         self.operations.is_empty()
     }
+
+    // The number of operations in the group.
+    pub(crate) fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    // Removes the operations from the specified index onwards, returning
+    // them as a group of their own.
+    pub(crate) fn split_off(&mut self, index: usize) -> OperationGroup {
+        OperationGroup{ operations: self.operations.split_off(index) }
+    }
 }
 
 impl Buffer {
@@ -81,6 +99,86 @@ impl Buffer {
             }
         }
     }
+
+    /// Runs the closure, grouping any operations it runs into a single unit,
+    /// which is undone/redone as a whole. If a group is already open (see
+    /// `start_operation_group`), the operations are added to it instead, and
+    /// it's left open. Returns the closure's result.
+    ///
+    /// If the buffer is `append_only` and any of the operations would remove
+    /// content, all of them are rolled back, rather than applying the rest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe library");
+    ///
+    /// // Replace a word.
+    /// buffer.batch(|buffer| {
+    ///     buffer.delete_range(Range::new(
+    ///         Position{ line: 0, offset: 0 },
+    ///         Position{ line: 0, offset: 6 }
+    ///     ));
+    ///     buffer.insert("editor");
+    /// });
+    /// assert_eq!(buffer.data(), "editor library");
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "scribe library");
+    /// ```
+    pub fn batch<F, T>(&mut self, f: F) -> T
+        where F: FnOnce(&mut Buffer) -> T
+    {
+        self.run_batch(f).0
+    }
+
+    // Like `batch`, but returns an AppendOnly error
+    // if the operations were rolled back.
+    pub(crate) fn try_batch<F, T>(&mut self, f: F) -> Result<T>
+        where F: FnOnce(&mut Buffer) -> T
+    {
+        match self.run_batch(f) {
+            (result, false) => Ok(result),
+            (_, true) => Err(ErrorKind::AppendOnly.into()),
+        }
+    }
+
+    // Runs a batch, returning the closure's result
+    // and whether or not its operations were rolled back.
+    fn run_batch<F, T>(&mut self, f: F) -> (T, bool)
+        where F: FnOnce(&mut Buffer) -> T
+    {
+        // Don't close a group the caller has already started.
+        let existing_group = self.operation_group.is_some();
+        if !existing_group {
+            self.start_operation_group();
+        }
+        let start = self.operation_group.as_ref().map_or(0, OperationGroup::len);
+        let refused_removals = self.refused_removals();
+        let audit_log_length = self.audit_log().len();
+
+        let result = f(self);
+
+        // Roll back the batch's operations if any of them were refused.
+        let rolled_back = self.refused_removals() != refused_removals;
+        if rolled_back {
+            if let Some(mut group) = self.operation_group.take() {
+                group.split_off(start).reverse(self);
+                self.operation_group = Some(group);
+            }
+            self.truncate_audit_log(audit_log_length);
+        }
+
+        if !existing_group {
+            self.end_operation_group();
+        }
+
+        (result, rolled_back)
+    }
 }
 
 #[cfg(test)]
@@ -112,6 +210,24 @@ mod tests {
         assert_eq!(buffer.data(), "");
     }
 
+    #[test]
+    fn batch_adds_to_existing_groups_without_closing_them() {
+        let mut buffer = Buffer::new();
+        buffer.start_operation_group();
+        buffer.insert("scribe");
+
+        let result = buffer.batch(|buffer| {
+            buffer.insert(" ");
+            42
+        });
+        assert_eq!(result, 42);
+        buffer.insert("library");
+        buffer.end_operation_group();
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "");
+    }
+
     #[test]
     fn end_operation_group_drops_group_if_empty() {
         let mut buffer = Buffer::new();
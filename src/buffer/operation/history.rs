@@ -34,6 +34,18 @@ impl History {
         }
     }
 
+    /// Replaces the most recent operation with one that has already been run,
+    /// and that encompasses it (e.g. a longer insertion at the same position).
+    pub fn replace_last(&mut self, operation: Box<dyn Operation>) {
+        if self.previous.pop().is_none() { return; }
+
+        // The history no longer passes through a marked final operation.
+        if self.marked_position == Some(self.previous.len() + 1) {
+            self.marked_position = None
+        }
+        self.add(operation);
+    }
+
     /// Navigate the history backwards.
     pub fn previous(&mut self) -> Option<Box<Operation>> {
         match self.previous.pop() {
@@ -60,10 +72,60 @@ impl History {
         }
     }
 
+    /// Discards the oldest operations, such that no more than `limit`
+    /// operations remain eligible to be undone. Returns the number of
+    /// operations discarded.
+    pub fn trim(&mut self, limit: usize) -> usize {
+        let excess = self.previous.len().saturating_sub(limit);
+        self.previous.drain(..excess);
+
+        // Shift the mark to account for the discarded operations,
+        // clearing it if it referred to one of them.
+        if let Some(position) = self.marked_position {
+            self.marked_position = position.checked_sub(excess);
+        }
+
+        excess
+    }
+
+    /// The number of operations that have been applied (i.e. are eligible
+    /// to be undone), which identifies the current point in the history.
+    pub fn position(&self) -> usize {
+        self.previous.len()
+    }
+
+    /// The total number of operations in the history,
+    /// including those that can be redone.
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.next.len()
+    }
+
+    /// Retrieves the operation that moves the history from point `index` to
+    /// `index + 1`, regardless of whether it's been applied or undone.
+    pub fn operation(&self, index: usize) -> Option<&dyn Operation> {
+        let position = self.position();
+
+        if index < position {
+            Some(&*self.previous[index])
+        } else if index < self.len() {
+            // Operations that can be redone are stored as a stack,
+            // with the next operation at the end of the list.
+            Some(&*self.next[self.next.len() - 1 - (index - position)])
+        } else {
+            None
+        }
+    }
+
     pub fn mark(&mut self) {
         self.marked_position = Some(self.previous.len())
     }
 
+    /// The point in the history (see `position`) that was last marked,
+    /// if the history still passes through it.
+    pub fn marked_position(&self) -> Option<usize> {
+        self.marked_position
+    }
+
     pub fn at_mark(&self) -> bool {
         if let Some(position) = self.marked_position {
             self.previous.len() == position
@@ -226,4 +288,52 @@ mod tests {
 
         assert!(!history.at_mark());
     }
+
+    #[test]
+    fn replace_last_clears_marks_referring_to_the_replaced_operation() {
+        let mut history = History::new();
+        history.add(Box::new(Insert::new("s".to_string(), Position::new())));
+        history.mark();
+
+        history.replace_last(Box::new(Insert::new("sc".to_string(), Position::new())));
+        assert_eq!(history.len(), 1);
+        assert!(!history.at_mark());
+    }
+
+    #[test]
+    fn trim_discards_the_oldest_operations() {
+        let mut buffer = Buffer::new();
+        let mut history = History::new();
+
+        for content in ["scribe", " library"].iter() {
+            let end = Position{ line: 0, offset: buffer.data().len() };
+            let mut insert_operation = Insert::new(content.to_string(), end);
+            insert_operation.run(&mut buffer);
+            history.add(Box::new(insert_operation));
+        }
+
+        assert_eq!(history.trim(1), 1);
+        assert_eq!(history.trim(1), 0);
+
+        if let Some(mut operation) = history.previous() {
+            operation.reverse(&mut buffer);
+        }
+        assert!(history.previous().is_none());
+        assert_eq!(buffer.data(), "scribe");
+    }
+
+    #[test]
+    fn trim_clears_marks_referring_to_discarded_operations() {
+        let mut buffer = Buffer::new();
+        let mut history = History::new();
+        history.mark();
+
+        let mut insert_operation = Insert::new("scribe".to_string(), Position::new());
+        insert_operation.run(&mut buffer);
+        history.add(Box::new(insert_operation));
+        history.trim(0);
+
+        history.previous();
+        assert!(!history.at_mark());
+    }
 }
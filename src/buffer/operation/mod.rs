@@ -1,8 +1,10 @@
 use buffer::Buffer;
 pub use self::group::OperationGroup;
+use self::summary::Edit;
 
 pub mod group;
 pub mod history;
+pub mod summary;
 
 /// A reversible buffer operation.
 ///
@@ -17,4 +19,7 @@ pub trait Operation {
     fn run(&mut self, &mut Buffer);
     fn reverse(&mut self, &mut Buffer);
     fn clone_operation(&self) -> Box<Operation>;
+
+    /// Describes the changes the operation makes when run.
+    fn edits(&self) -> Vec<Edit>;
 }
@@ -0,0 +1,120 @@
+//! Descriptions of the changes recorded in a buffer's history.
+
+use buffer::{Buffer, Range};
+
+/// A single change made by a history entry: the `removed` content was
+/// replaced with the `inserted` content, which occupies `range` once the
+/// change has been applied (or occupied it before, for removals).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Edit {
+    pub range: Range,
+    pub inserted: String,
+    pub removed: String,
+}
+
+/// The changes made by an entry in a buffer's history, in the order they
+/// were applied. Entries that have been undone (but can be redone) aren't
+/// `applied`. The `history_index` is the point in the history (see
+/// `Buffer::history_index`) reached by applying the entry.
+///
+/// Summaries produced by `Buffer::preview` also describe the outcome: the
+/// `range` of the resulting content that differs from the buffer's current
+/// content (if any does), and the resulting content itself, as `data`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChangeSummary {
+    pub history_index: usize,
+    pub applied: bool,
+    pub edits: Vec<Edit>,
+    pub range: Option<Range>,
+    pub data: Option<String>,
+}
+
+impl Edit {
+    // The edit that reverses this one.
+    pub(crate) fn inverted(&self) -> Edit {
+        Edit{
+            range: self.range.clone(),
+            inserted: self.removed.clone(),
+            removed: self.inserted.clone(),
+        }
+    }
+}
+
+impl Buffer {
+    /// Summarizes each of the entries in the buffer's history, from oldest
+    /// to newest, including those that can be redone. Operations in an open
+    /// operation group haven't been added to the history yet, and are excluded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    /// buffer.delete();
+    /// buffer.undo();
+    ///
+    /// let entries: Vec<_> = buffer.history_entries().collect();
+    /// assert_eq!(entries.len(), 2);
+    /// assert_eq!(entries[0].edits[0].inserted, "scribe");
+    /// assert_eq!(
+    ///     entries[0].edits[0].range,
+    ///     Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 6 })
+    /// );
+    /// assert_eq!(entries[1].edits[0].removed, "s");
+    /// assert!(!entries[1].applied);
+    /// ```
+    pub fn history_entries(&self) -> impl Iterator<Item=ChangeSummary> + '_ {
+        let position = self.history.position();
+
+        (0..self.history.len()).filter_map(move |index| {
+            let operation = self.history.operation(index)?;
+
+            Some(ChangeSummary{
+                history_index: index + 1,
+                applied: index < position,
+                edits: operation.edits(),
+                range: None,
+                data: None,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, Range};
+
+    #[test]
+    fn history_entries_include_each_edit_in_a_group() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary");
+        buffer.start_operation_group();
+        buffer.delete_range(Range::new(Position{ line: 0, offset: 6 }, Position{ line: 1, offset: 0 }));
+        buffer.insert(" ");
+        buffer.end_operation_group();
+
+        let entries: Vec<_> = buffer.history_entries().collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].history_index, 2);
+        assert!(entries[1].applied);
+
+        let edits = &entries[1].edits;
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[0].removed, "\n");
+        assert_eq!(edits[0].range, Range::new(Position{ line: 0, offset: 6 }, Position{ line: 1, offset: 0 }));
+        assert_eq!(edits[1].inserted, " ");
+        assert_eq!(edits[1].range.start(), Position::new());
+    }
+
+    #[test]
+    fn history_entries_exclude_open_operation_groups() {
+        let mut buffer = Buffer::new();
+        buffer.start_operation_group();
+        buffer.insert("scribe");
+
+        assert_eq!(buffer.history_entries().count(), 0);
+    }
+}
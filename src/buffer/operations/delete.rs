@@ -1,4 +1,5 @@
 use buffer::operation::Operation;
+use buffer::operation::summary::Edit;
 use buffer::{Buffer, Position, Range};
 use std::clone::Clone;
 
@@ -9,7 +10,8 @@ use std::clone::Clone;
 /// the start of the specified range.
 ///
 /// If the buffer is configured with a `change_callback`, it will be called with
-/// the starting position of this operation when it is run or reversed.
+/// the starting position of this operation when it is run or reversed, and registered observers
+/// (see `Buffer::on_change`) will be notified of the resulting edit.
 #[derive(Clone)]
 pub struct Delete {
     content: Option<String>,
@@ -18,32 +20,62 @@ pub struct Delete {
 
 impl Operation for Delete {
     fn run(&mut self, buffer: &mut Buffer) {
+        // Append-only buffers don't allow content to be removed.
+        if buffer.refuse_removal(&self.range) { return; }
+
         // Fetch and store the content we're about to delete.
         self.content = buffer.data.borrow().read(&self.range);
 
         // Delete the data.
+        buffer.track_deletion(&self.range, self.content.as_deref());
+        buffer.invalidate_lexed_state(self.range.start().line);
+        buffer.record_edit();
         buffer.data.borrow_mut().delete(&self.range);
 
         // Run the change callback, if present.
         if let Some(ref callback) = buffer.change_callback {
             callback(self.range.start())
         }
+
+        // Adjust selections and collaborators, and notify any registered observers.
+        let edit = self.edits().remove(0);
+        buffer.shift_selections(&edit);
+        buffer.shift_collaborators(&edit);
+        buffer.notify_observers(&edit);
     }
 
     fn reverse(&mut self, buffer: &mut Buffer) {
         if let Some(ref content) = self.content {
+            buffer.audit_insertion(content, self.range.start());
+            buffer.track_insertion(content, &self.range.start());
+            buffer.invalidate_lexed_state(self.range.start().line);
+            buffer.record_edit();
             buffer.data.borrow_mut().insert(content, &self.range.start());
 
             // Run the change callback, if present.
             if let Some(ref callback) = buffer.change_callback {
                 callback(self.range.start())
             }
+
+            // Adjust selections and collaborators, and notify any registered observers.
+            let edit = self.edits()[0].inverted();
+            buffer.shift_selections(&edit);
+            buffer.shift_collaborators(&edit);
+            buffer.notify_observers(&edit);
         }
     }
 
     fn clone_operation(&self) -> Box<Operation> {
         Box::new(self.clone())
     }
+
+    fn edits(&self) -> Vec<Edit> {
+        vec![Edit{
+            range: self.range.clone(),
+            inserted: String::new(),
+            removed: self.content.clone().unwrap_or_default(),
+        }]
+    }
 }
 
 impl Delete {
@@ -58,6 +90,10 @@ impl Buffer {
     /// of the current line, it'll try to delete a newline character
     /// (joining the lines), succeeding if there's a line below.
     ///
+    /// Nothing is deleted if the cursor is at the end of the buffer, or the
+    /// buffer is `read_only` or `append_only`, and the call is silently
+    /// ignored; use `try_delete` to have these cases reported as errors.
+    ///
     /// # Examples
     ///
     /// ```
@@ -67,8 +103,19 @@ impl Buffer {
     /// buffer.insert("scribe");
     /// buffer.delete();
     /// assert_eq!(buffer.data(), "cribe");
+    ///
+    /// buffer.cursor.move_to_end_of_line();
+    /// buffer.delete();
+    /// assert_eq!(buffer.data(), "cribe");
+    /// assert!(buffer.try_delete().is_err());
     /// ```
     pub fn delete(&mut self) {
+        let range = self.deletion_range();
+        self.delete_range(range);
+    }
+
+    // The range deleted by `delete`, which may be out of bounds.
+    pub(crate) fn deletion_range(&self) -> Range {
         // We need to specify a range to delete, so start at
         // the current offset and delete the character to the right.
         let mut end = Position{ line: self.cursor.line, offset: self.cursor.offset + 1 };
@@ -86,12 +133,17 @@ impl Buffer {
         // so create a clone of the cursor's current position.
         let start = self.cursor.position;
 
-        // Now that we've established the range, defer.
-        self.delete_range(Range::new(start, end));
+        Range::new(start, end)
     }
 
     /// Removes a range of characters from the buffer.
     ///
+    /// Ranges starting beyond the buffer's content are silently ignored, and
+    /// those ending beyond it are truncated, removing content up to the start
+    /// of the following line (or the end of the buffer). Ranges removing
+    /// content from `read_only` or `append_only` buffers are also ignored.
+    /// Use `try_delete_range` to have these cases reported as errors.
+    ///
     /// # Examples
     ///
     /// ```
@@ -112,9 +164,13 @@ impl Buffer {
     /// assert_eq!(buffer.data(), "scribe");
     /// ```
     pub fn delete_range(&mut self, range: Range) {
+        if self.read_only { return; }
+
         // Build and run a delete operation.
+        let refused_removals = self.refused_removals();
         let mut op = Delete::new(range);
         op.run(self);
+        if self.refused_removals() != refused_removals { return; }
 
         // Store the operation in the history
         // object so that it can be undone.
@@ -1,7 +1,9 @@
 use buffer::operation::Operation;
-use buffer::{Buffer, Position, Range};
+use buffer::operation::summary::Edit;
+use buffer::{Buffer, Distance, Position, Range};
 use std::clone::Clone;
 use std::convert::Into;
+use std::time::{Duration, Instant};
 use unicode_segmentation::UnicodeSegmentation;
 
 /// A reversible buffer insert operation.
@@ -11,7 +13,8 @@ use unicode_segmentation::UnicodeSegmentation;
 /// to its inserted location, and removing said range from the underlying buffer.
 ///
 /// If the buffer is configured with a `change_callback`, it will be called with
-/// the position of this operation when it is run or reversed.
+/// the position of this operation when it is run or reversed, and registered observers
+/// (see `Buffer::on_change`) will be notified of the resulting edit.
 #[derive(Clone)]
 pub struct Insert {
     content: String,
@@ -20,12 +23,22 @@ pub struct Insert {
 
 impl Operation for Insert {
     fn run(&mut self, buffer: &mut Buffer) {
+        buffer.audit_insertion(&self.content, self.position);
+        buffer.track_insertion(&self.content, &self.position);
+        buffer.invalidate_lexed_state(self.position.line);
+        buffer.record_edit();
         buffer.data.borrow_mut().insert(&self.content, &self.position);
 
         // Run the change callback, if present.
         if let Some(ref callback) = buffer.change_callback {
             callback(self.position)
         }
+
+        // Adjust selections and collaborators, and notify any registered observers.
+        let edit = self.edits().remove(0);
+        buffer.shift_selections(&edit);
+        buffer.shift_collaborators(&edit);
+        buffer.notify_observers(&edit);
     }
 
     // We need to calculate the range of the inserted content.
@@ -62,17 +75,34 @@ impl Operation for Insert {
         );
 
         // Remove the content we'd previously inserted.
+        buffer.track_deletion(&range, Some(&self.content));
+        buffer.invalidate_lexed_state(self.position.line);
+        buffer.record_edit();
         buffer.data.borrow_mut().delete(&range);
 
         // Run the change callback, if present.
         if let Some(ref callback) = buffer.change_callback {
             callback(self.position)
         }
+
+        // Adjust selections and collaborators, and notify any registered observers.
+        let edit = self.edits()[0].inverted();
+        buffer.shift_selections(&edit);
+        buffer.shift_collaborators(&edit);
+        buffer.notify_observers(&edit);
     }
 
     fn clone_operation(&self) -> Box<Operation> {
         Box::new(self.clone())
     }
+
+    fn edits(&self) -> Vec<Edit> {
+        vec![Edit{
+            range: Range::new(self.position, self.position + Distance::of_str(&self.content)),
+            inserted: self.content.clone(),
+            removed: String::new(),
+        }]
+    }
 }
 
 impl Insert {
@@ -82,8 +112,41 @@ impl Insert {
     }
 }
 
+// Consecutive single-grapheme insertions (i.e. typing), tracked so
+// that they can be coalesced into a single history entry.
+pub(crate) struct TypingRun {
+    insert: Insert,
+    end: Position,
+    history_position: usize,
+    last_typed: Instant,
+}
+
+impl TypingRun {
+    // Whether the grapheme typed at the position continues the run. Runs are
+    // broken by pauses longer than the window and by the start of a new word,
+    // so that text is undone a word (and its trailing whitespace) at a time.
+    fn continued_by(&self, grapheme: &str, position: &Position, window: Duration) -> bool {
+        let starts_word = is_word(grapheme) &&
+            !self.insert.content.graphemes(true).next_back().is_some_and(is_word);
+
+        *position == self.end && self.last_typed.elapsed() < window && !starts_word
+    }
+}
+
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
 impl Buffer {
-    /// Inserts `data` into the buffer at the cursor position.
+    /// Inserts `data` into the buffer at the cursor position, after applying
+    /// any registered transforms (see `add_insert_transform`).
+    ///
+    /// If the buffer is configured with `smart_punctuation`, typed (i.e.
+    /// single-character) straight quotes are replaced with curly quotes, and
+    /// consecutive hyphens with en and em dashes. Each substitution can be
+    /// undone separately, restoring the straight character. Dashes replace
+    /// the preceding hyphen, too, so the cursor is moved back by one, if it's
+    /// at the insertion point.
     ///
     /// # Examples
     ///
@@ -93,18 +156,83 @@ impl Buffer {
     /// let mut buffer = Buffer::new();
     /// buffer.insert("scribe");
     /// assert_eq!(buffer.data(), "scribe");
+    ///
+    /// buffer.smart_punctuation = true;
+    /// buffer.insert("\"");
+    /// assert_eq!(buffer.data(), "\u{201C}scribe");
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "\"scribe");
     /// ```
     pub fn insert<T: Into<String>>(&mut self, data: T) {
+        let position = self.cursor.position;
+        let data = self.insert_transformed(data.into(), position);
+        if data.is_empty() { return; }
+
+        if self.smart_punctuation && data.graphemes(true).count() == 1 {
+            self.substitute_punctuation(position);
+        }
+    }
+
+    /// Inserts `data` into the buffer at the specified position.
+    pub(crate) fn insert_at<T: Into<String>>(&mut self, data: T, position: Position) {
+        if self.read_only { return; }
+
         // Build and run an insert operation.
-        let mut op = Insert::new(data.into(), self.cursor.position);
+        let mut op = Insert::new(data.into(), position);
         op.run(self);
 
         // Store the operation in the history
         // object so that it can be undone.
         match self.operation_group {
             Some(ref mut group) => group.add(Box::new(op)),
-            None => self.history.add(Box::new(op)),
+            None => self.record_typing(op),
+        };
+    }
+
+    // Adds an insertion to the history, coalescing it with
+    // the preceding insertion if they're part of a typing run.
+    fn record_typing(&mut self, op: Insert) {
+        let run = self.typing_run.take();
+        let window = match self.undo_coalescing_window {
+            Some(window) if op.content != "\n" && op.content.graphemes(true).count() == 1 => window,
+            _ => return self.history.add(Box::new(op)),
+        };
+        let end = op.position + Distance::of_str(&op.content);
+
+        let continued = run.filter(|run| {
+            run.history_position == self.history.position() && !self.history.at_mark() &&
+                run.continued_by(&op.content, &op.position, window)
+        });
+        let insert = match continued {
+            Some(mut run) => {
+                // Inserting the combined content at the start of the run
+                // is equivalent to the individual insertions.
+                run.insert.content.push_str(&op.content);
+                self.history.replace_last(Box::new(run.insert.clone()));
+                run.insert
+            },
+            None => {
+                self.history.add(Box::new(op.clone()));
+                op
+            },
         };
+
+        self.typing_run = Some(TypingRun{
+            insert,
+            end,
+            history_position: self.history.position(),
+            last_typed: Instant::now(),
+        });
+    }
+
+    /// Appends `data` to the end of the buffer, without recording the change in
+    /// its history. Used for output streamed into buffers, even if read-only.
+    #[cfg(feature = "workspace")]
+    pub(crate) fn append_unrecorded(&mut self, data: &str) {
+        let end = self.end_position();
+
+        Insert::new(data.to_string(), end).run(self);
     }
 }
 
@@ -113,9 +241,10 @@ mod tests {
     use std::cell::RefCell;
     use std::rc::Rc;
     use super::Insert;
-    use buffer::Buffer;
+    use buffer::{Buffer, Distance};
     use buffer::position::Position;
     use buffer::operation::Operation;
+    use std::time::Duration;
 
     #[test]
     fn run_and_reverse_add_and_remove_content_without_newlines_at_cursor_position() {
@@ -268,4 +397,68 @@ mod tests {
         // Verify that the callback received the correct position.
         assert_eq!(*tracked_position.borrow(), Position{ line: 0, offset: 9});
     }
+
+    // Types the content a grapheme at a time, as a user would.
+    fn type_into(buffer: &mut Buffer, content: &str) {
+        for c in content.chars() {
+            let position = *buffer.cursor + Distance::of_str(&c.to_string());
+            buffer.insert(c.to_string());
+            buffer.cursor.move_to(position);
+        }
+    }
+
+    #[test]
+    fn typing_is_undone_a_word_at_a_time_within_the_coalescing_window() {
+        let mut buffer = Buffer::new();
+        buffer.undo_coalescing_window = Some(Duration::from_secs(60));
+        type_into(&mut buffer, "scribe library, editor");
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "scribe library, ");
+        buffer.undo();
+        assert_eq!(buffer.data(), "scribe ");
+
+        buffer.redo();
+        assert_eq!(buffer.data(), "scribe library, ");
+    }
+
+    #[test]
+    fn typing_is_not_coalesced_across_newlines_or_cursor_movement() {
+        let mut buffer = Buffer::new();
+        buffer.undo_coalescing_window = Some(Duration::from_secs(60));
+        type_into(&mut buffer, "ab\ncd");
+        buffer.cursor.move_left();
+        type_into(&mut buffer, "e");
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "ab\ncd");
+        buffer.undo();
+        assert_eq!(buffer.data(), "ab\n");
+        buffer.undo();
+        assert_eq!(buffer.data(), "ab");
+    }
+
+    #[test]
+    fn typing_is_not_coalesced_once_the_window_elapses() {
+        let mut buffer = Buffer::new();
+        buffer.undo_coalescing_window = Some(Duration::from_secs(0));
+        type_into(&mut buffer, "ab");
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "a");
+    }
+
+    #[test]
+    fn typing_is_not_coalesced_with_changes_preceding_a_save() {
+        let mut buffer = Buffer::new();
+        buffer.undo_coalescing_window = Some(Duration::from_secs(60));
+        type_into(&mut buffer, "ab");
+        buffer.history.mark();
+        type_into(&mut buffer, "c");
+        assert!(buffer.modified());
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "ab");
+        assert!(!buffer.modified());
+    }
 }
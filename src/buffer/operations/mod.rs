@@ -2,6 +2,7 @@
 //! un/redone using the buffer type's undo/redo methods.
 pub use self::delete::Delete;
 pub use self::insert::Insert;
+pub(crate) use self::insert::TypingRun;
 
 mod insert;
 mod delete;
@@ -0,0 +1,250 @@
+//! Composition of overlays (e.g. selections, search matches, and
+//! diagnostics) drawn over buffer content.
+
+use buffer::{Buffer, LineRange, Position, Range};
+use std::cmp::Ordering;
+
+// A function producing the overlays on the specified lines.
+pub(crate) type OverlayProvider = Box<dyn Fn(&Buffer, &LineRange) -> Vec<Overlay>>;
+
+/// A styled range to be drawn over buffer content, produced by an overlay
+/// provider (see `Buffer::add_overlay_provider`). The style is a name
+/// understood by the frontend (e.g. "selection" or "diagnostic.error").
+#[derive(Clone, Debug, PartialEq)]
+pub struct Overlay {
+    pub range: Range,
+    pub style: String,
+}
+
+impl Overlay {
+    pub fn new<T: Into<String>>(range: Range, style: T) -> Overlay {
+        Overlay{ range, style: style.into() }
+    }
+}
+
+/// The standard overlay layers, listed from bottom to top. Their priorities
+/// are spaced apart, so that custom providers can be placed between them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OverlayLayer {
+    Search,
+    Diagnostics,
+    Selection,
+    Collaborators,
+}
+
+impl OverlayLayer {
+    /// The layer's priority, for use with `Buffer::add_overlay_provider`.
+    pub fn priority(&self) -> i32 {
+        match *self {
+            OverlayLayer::Search => 100,
+            OverlayLayer::Diagnostics => 200,
+            OverlayLayer::Selection => 300,
+            OverlayLayer::Collaborators => 400,
+        }
+    }
+}
+
+/// A stretch of content covered by the same overlays, produced by
+/// `Buffer::overlays`. Its styles are ordered from bottom to top, so that
+/// frontends drawing them in order leave the highest priority style on top.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OverlaySpan {
+    pub range: Range,
+    pub styles: Vec<String>,
+}
+
+impl Buffer {
+    /// Registers an overlay provider, which is called with the lines being
+    /// displayed, and returns the overlays on them. Overlays are stacked by
+    /// priority (see `OverlayLayer`), with higher priorities drawn on top;
+    /// those with the same priority are stacked in the order their providers
+    /// were registered, and then in the order they were returned. Returns an
+    /// ID that can be used to remove the provider.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{LineRange, Overlay, OverlayLayer, Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe library");
+    /// buffer.add_overlay_provider(OverlayLayer::Selection.priority(), |buffer, _| {
+    ///     buffer.selections().iter().map(|range| Overlay::new(range.clone(), "selection")).collect()
+    /// });
+    /// let id = buffer.add_overlay_provider(OverlayLayer::Search.priority(), |buffer, _| {
+    ///     buffer.search("library").into_iter().map(|start| {
+    ///         let end = Position{ line: start.line, offset: start.offset + 7 };
+    ///         Overlay::new(Range::new(start, end), "search")
+    ///     }).collect()
+    /// });
+    /// buffer.cursors_from_ranges(vec![Range::new(
+    ///     Position{ line: 0, offset: 10 },
+    ///     Position{ line: 0, offset: 14 }
+    /// )]).unwrap();
+    ///
+    /// let spans = buffer.overlays(&LineRange::new(0, 1));
+    /// assert_eq!(spans.len(), 2);
+    /// assert_eq!(spans[0].styles, vec!["search"]);
+    /// assert_eq!(spans[1].range.start(), Position{ line: 0, offset: 10 });
+    /// assert_eq!(spans[1].styles, vec!["search", "selection"]);
+    ///
+    /// assert!(buffer.remove_overlay_provider(id));
+    /// assert_eq!(buffer.overlays(&LineRange::new(0, 1))[0].styles, vec!["selection"]);
+    /// ```
+    pub fn add_overlay_provider<F>(&mut self, priority: i32, provider: F) -> usize
+        where F: Fn(&Buffer, &LineRange) -> Vec<Overlay> + 'static {
+        let id = self.next_overlay_provider_id;
+        self.next_overlay_provider_id += 1;
+        self.overlay_providers.push((id, priority, Box::new(provider)));
+
+        id
+    }
+
+    /// Removes a previously registered overlay provider, returning
+    /// whether or not a provider with the specified ID existed.
+    pub fn remove_overlay_provider(&mut self, id: usize) -> bool {
+        let count = self.overlay_providers.len();
+        self.overlay_providers.retain(|&(provider_id, _, _)| provider_id != id);
+
+        self.overlay_providers.len() != count
+    }
+
+    /// Composes the overlays on the specified lines from each registered
+    /// provider (and the buffer's collaborators), splitting them into spans
+    /// that don't overlap, in the order they appear. Content without any
+    /// overlays isn't included. Overlays extending beyond the lines are
+    /// clipped, and empty overlays (e.g. cursors) produce empty spans, which
+    /// precede those starting at the same position.
+    pub fn overlays(&self, lines: &LineRange) -> Vec<OverlaySpan> {
+        let bounds = lines.to_range();
+
+        // Collect every overlay, along with its stacking order. Collaborators
+        // are stacked above providers sharing their priority.
+        let mut overlays = Vec::new();
+        for (index, &(_, priority, ref provider)) in self.overlay_providers.iter().enumerate() {
+            overlays.extend(provider(self, lines).into_iter().map(|overlay| (priority, index, overlay)));
+        }
+        let collaborators = (OverlayLayer::Collaborators.priority(), self.overlay_providers.len());
+        for collaborator in &self.collaborators {
+            if let Some(ref selection) = collaborator.selection {
+                let overlay = Overlay::new(selection.clone(), collaborator.selection_style());
+                overlays.push((collaborators.0, collaborators.1, overlay));
+            }
+            let cursor = Range::new(collaborator.cursor, collaborator.cursor);
+            overlays.push((collaborators.0, collaborators.1, Overlay::new(cursor, collaborator.cursor_style())));
+        }
+        overlays.sort_by_key(|overlay| (overlay.0, overlay.1));
+
+        // Clip the overlays, separating the empty ones.
+        let mut empty_overlays = Vec::new();
+        let overlays: Vec<(Range, String)> = overlays.into_iter().filter_map(|(_, _, overlay)| {
+            let start = latest(overlay.range.start(), bounds.start());
+            let end = earliest(overlay.range.end(), bounds.end());
+            if overlay.range.start() == overlay.range.end() && start == end && end < bounds.end() {
+                empty_overlays.push((start, overlay.style));
+                None
+            } else if start < end {
+                Some((Range::new(start, end), overlay.style))
+            } else {
+                None
+            }
+        }).collect();
+
+        let mut boundaries: Vec<Position> = overlays.iter()
+            .flat_map(|overlay| vec![overlay.0.start(), overlay.0.end()])
+            .collect();
+        boundaries.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        boundaries.dedup();
+
+        let mut spans: Vec<OverlaySpan> = Vec::new();
+        for pair in boundaries.windows(2) {
+            let styles: Vec<String> = overlays.iter()
+                .filter(|overlay| overlay.0.start() <= pair[0] && pair[1] <= overlay.0.end())
+                .map(|overlay| overlay.1.clone())
+                .collect();
+            if styles.is_empty() { continue; }
+
+            // Extend the previous span if it's adjacent and styled identically.
+            if let Some(span) = spans.last_mut() {
+                if span.range.end() == pair[0] && span.styles == styles {
+                    span.range = Range::new(span.range.start(), pair[1]);
+                    continue;
+                }
+            }
+            spans.push(OverlaySpan{ range: Range::new(pair[0], pair[1]), styles });
+        }
+
+        // Add the empty spans, grouping overlays at the same position.
+        let mut positions: Vec<Position> = empty_overlays.iter().map(|overlay| overlay.0).collect();
+        positions.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        positions.dedup();
+        for position in positions {
+            let styles = empty_overlays.iter()
+                .filter(|overlay| overlay.0 == position)
+                .map(|overlay| overlay.1.clone())
+                .collect();
+            let index = spans.iter()
+                .position(|span| span.range.start() >= position)
+                .unwrap_or(spans.len());
+            spans.insert(index, OverlaySpan{ range: Range::new(position, position), styles });
+        }
+
+        spans
+    }
+}
+
+fn earliest(first: Position, second: Position) -> Position {
+    if second < first { second } else { first }
+}
+
+fn latest(first: Position, second: Position) -> Position {
+    if second > first { second } else { first }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, LineRange, Overlay, OverlaySpan, Position, Range};
+
+    fn range(start: (usize, usize), end: (usize, usize)) -> Range {
+        Range::new(
+            Position{ line: start.0, offset: start.1 },
+            Position{ line: end.0, offset: end.1 }
+        )
+    }
+
+    #[test]
+    fn equal_priorities_are_stacked_in_registration_order() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        buffer.add_overlay_provider(0, |_, _| vec![
+            Overlay::new(range((0, 0), (0, 4)), "first"),
+            Overlay::new(range((0, 2), (0, 6)), "second"),
+        ]);
+        buffer.add_overlay_provider(0, |_, _| vec![Overlay::new(range((0, 0), (0, 6)), "third")]);
+        buffer.add_overlay_provider(-1, |_, _| vec![Overlay::new(range((0, 0), (0, 6)), "bottom")]);
+
+        assert_eq!(buffer.overlays(&LineRange::new(0, 1)), vec![
+            OverlaySpan{ range: range((0, 0), (0, 2)), styles: vec!["bottom".to_string(), "first".to_string(), "third".to_string()] },
+            OverlaySpan{ range: range((0, 2), (0, 4)), styles: vec!["bottom".to_string(), "first".to_string(), "second".to_string(), "third".to_string()] },
+            OverlaySpan{ range: range((0, 4), (0, 6)), styles: vec!["bottom".to_string(), "second".to_string(), "third".to_string()] },
+        ]);
+    }
+
+    #[test]
+    fn overlays_are_clipped_to_the_requested_lines() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary\nlast");
+        buffer.add_overlay_provider(0, |_, _| vec![
+            Overlay::new(range((0, 2), (2, 2)), "diagnostic"),
+            Overlay::new(range((1, 3), (1, 3)), "cursor"),
+            Overlay::new(range((2, 0), (2, 0)), "next line"),
+            Overlay::new(range((2, 0), (2, 4)), "outside"),
+        ]);
+
+        assert_eq!(buffer.overlays(&LineRange::new(1, 2)), vec![
+            OverlaySpan{ range: range((1, 0), (2, 0)), styles: vec!["diagnostic".to_string()] },
+            OverlaySpan{ range: range((1, 3), (1, 3)), styles: vec!["cursor".to_string()] },
+        ]);
+    }
+}
@@ -0,0 +1,142 @@
+//! Provisional text, such as input method compositions.
+
+use buffer::{Buffer, Position};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Text shown at a position in a buffer, but not yet part of its data
+/// or history (e.g. an in-progress input method composition, or an
+/// inline completion preview).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingText {
+    pub position: Position,
+    pub content: String,
+}
+
+impl Buffer {
+    /// Sets the buffer's pending text to `content`, at the cursor position,
+    /// replacing any existing pending text. Pending text isn't included in
+    /// the buffer's data, and its position isn't adjusted by edits made
+    /// before it's committed or cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    /// buffer.set_pending("わ");
+    ///
+    /// assert_eq!(buffer.data(), "scribe");
+    /// assert_eq!(buffer.data_with_pending(), "わscribe");
+    /// assert_eq!(buffer.pending().unwrap().position, Position{ line: 0, offset: 0 });
+    /// ```
+    pub fn set_pending(&mut self, content: &str) {
+        self.pending = Some(PendingText{
+            position: *self.cursor,
+            content: content.to_string(),
+        });
+    }
+
+    /// The buffer's pending text, if any.
+    pub fn pending(&self) -> Option<&PendingText> {
+        self.pending.as_ref()
+    }
+
+    /// Inserts the pending text at its position, as a regular (undoable)
+    /// insert, and clears it. Returns whether there was pending text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.set_pending("scribe");
+    ///
+    /// assert!(buffer.commit_pending());
+    /// assert_eq!(buffer.data(), "scribe");
+    /// assert!(buffer.pending().is_none());
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "");
+    /// ```
+    pub fn commit_pending(&mut self) -> bool {
+        match self.pending.take() {
+            Some(pending) => {
+                self.insert_transformed(pending.content, pending.position);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Discards the pending text. Returns whether there was pending text.
+    pub fn cancel_pending(&mut self) -> bool {
+        self.pending.take().is_some()
+    }
+
+    /// The buffer's data, with its pending text inserted, for display.
+    /// Pending text positioned beyond the end of its line is appended to it.
+    pub fn data_with_pending(&self) -> String {
+        let data = self.data();
+        let pending = match self.pending {
+            Some(ref pending) => pending,
+            None => return data,
+        };
+
+        let mut lines: Vec<String> = data.split('\n').map(String::from).collect();
+        if let Some(line) = lines.get_mut(pending.position.line) {
+            let index: usize = line
+                .graphemes(true)
+                .take(pending.position.offset)
+                .map(str::len)
+                .sum();
+            line.insert_str(index, &pending.content);
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position};
+
+    #[test]
+    fn set_pending_replaces_existing_pending_text() {
+        let mut buffer = Buffer::new();
+        buffer.insert("ab");
+        buffer.cursor.move_to(Position{ line: 0, offset: 1 });
+        buffer.set_pending("x");
+        buffer.set_pending("yz");
+
+        assert_eq!(buffer.data_with_pending(), "ayzb");
+    }
+
+    #[test]
+    fn cancel_pending_leaves_data_and_history_unchanged() {
+        let mut buffer = Buffer::new();
+        buffer.set_pending("scribe");
+
+        assert!(buffer.cancel_pending());
+        assert!(!buffer.cancel_pending());
+        assert!(!buffer.commit_pending());
+        assert_eq!(buffer.data_with_pending(), "");
+        assert_eq!(buffer.history_index(), 0);
+    }
+
+    #[test]
+    fn commit_pending_uses_the_position_the_text_was_set_at() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\n");
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        buffer.set_pending("library");
+        buffer.cursor.move_to(Position{ line: 0, offset: 0 });
+
+        assert_eq!(buffer.data_with_pending(), "scribe\nlibrary");
+        buffer.commit_pending();
+        assert_eq!(buffer.data(), "scribe\nlibrary");
+    }
+}
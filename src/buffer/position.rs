@@ -1,7 +1,7 @@
 use buffer::Distance;
-use std::cmp::{PartialOrd, Ordering};
-use std::default::Default;
-use std::ops::{Add, AddAssign};
+use core::cmp::{PartialOrd, Ordering};
+use core::default::Default;
+use core::ops::{Add, AddAssign};
 
 /// A two (zero-based) coordinate value representing a location in a buffer.
 /// The `offset` field is so named to emphasize that positions point to
@@ -0,0 +1,117 @@
+//! Buffer configuration, applied when buffers are opened.
+
+use buffer::{Buffer, UnicodeForm, DEFAULT_MAX_LINE_LENGTH};
+#[cfg(feature = "workspace")]
+use editor_config;
+#[cfg(feature = "workspace")]
+use std::path::Path;
+use std::time::Duration;
+
+/// Options applied to buffers opened by a `Workspace` (see its
+/// `buffer_preferences` field), letting applications configure them once,
+/// rather than for each buffer. Options can still be changed on individual
+/// buffers once they've been opened.
+///
+/// If a `unicode_form` is set, buffers' content is normalized to it when
+/// they're opened (see `Buffer::normalize_unicode`), unless they're set to
+/// `preserve_formatting`. Buffers whose content was changed by this are
+/// left modified, so that the original content can be restored by undoing.
+///
+/// The `max_line_length` defaults to the same limit as new buffers (see
+/// `Buffer`), so that minified files opened by a `Workspace` aren't lexed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BufferPreferences {
+    pub insert_final_newline: bool,
+    pub preserve_formatting: bool,
+    pub history_limit: Option<usize>,
+    pub undo_coalescing_window: Option<Duration>,
+    pub unicode_form: Option<UnicodeForm>,
+    pub max_line_length: Option<usize>,
+}
+
+impl Default for BufferPreferences {
+    fn default() -> BufferPreferences {
+        BufferPreferences{
+            insert_final_newline: false,
+            preserve_formatting: false,
+            history_limit: None,
+            undo_coalescing_window: None,
+            unicode_form: None,
+            max_line_length: Some(DEFAULT_MAX_LINE_LENGTH),
+        }
+    }
+}
+
+impl BufferPreferences {
+    /// Returns a copy of these preferences, overridden by any EditorConfig
+    /// properties that apply to the specified (absolute) file path. The
+    /// `insert_final_newline` property is supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::buffer::BufferPreferences;
+    /// use std::env;
+    /// use std::fs;
+    ///
+    /// let directory = env::temp_dir().join("scribe_preferences_for_path_example");
+    /// fs::create_dir_all(&directory).unwrap();
+    /// fs::write(directory.join(".editorconfig"), "root = true\n[*.rs]\ninsert_final_newline = true\n").unwrap();
+    ///
+    /// let preferences = BufferPreferences::default();
+    /// assert!(preferences.for_path(&directory.join("lib.rs")).insert_final_newline);
+    /// assert!(!preferences.for_path(&directory.join("README")).insert_final_newline);
+    /// # fs::remove_dir_all(&directory).unwrap();
+    /// ```
+    #[cfg(feature = "workspace")]
+    pub fn for_path(&self, path: &Path) -> BufferPreferences {
+        let mut preferences = self.clone();
+        let properties = editor_config::properties(path);
+
+        match properties.get("insert_final_newline").map(String::as_str) {
+            Some("true") => preferences.insert_final_newline = true,
+            Some("false") => preferences.insert_final_newline = false,
+            _ => (),
+        }
+
+        preferences
+    }
+
+    /// Configures the buffer using these preferences.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::BufferPreferences;
+    /// use std::time::Duration;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// let preferences = BufferPreferences{
+    ///     insert_final_newline: true,
+    ///     preserve_formatting: false,
+    ///     history_limit: Some(100),
+    ///     undo_coalescing_window: Some(Duration::from_secs(1)),
+    ///     unicode_form: None,
+    ///     max_line_length: Some(1000),
+    /// };
+    /// preferences.apply(&mut buffer);
+    ///
+    /// assert!(buffer.insert_final_newline);
+    /// assert_eq!(buffer.history_limit, Some(100));
+    /// assert_eq!(buffer.max_line_length, Some(1000));
+    /// ```
+    pub fn apply(&self, buffer: &mut Buffer) {
+        buffer.insert_final_newline = self.insert_final_newline;
+        buffer.preserve_formatting = self.preserve_formatting;
+        buffer.history_limit = self.history_limit;
+        buffer.undo_coalescing_window = self.undo_coalescing_window;
+        buffer.max_line_length = self.max_line_length;
+
+        if let Some(form) = self.unicode_form {
+            if !self.preserve_formatting {
+                buffer.normalize_unicode(form);
+            }
+        }
+    }
+}
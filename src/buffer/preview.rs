@@ -0,0 +1,150 @@
+//! Computing the changes an operation would make, without making them.
+
+use buffer::{Buffer, ChangeSummary, Distance, Position, Range};
+use std::cell::RefCell;
+use std::rc::Rc;
+use unicode_segmentation::UnicodeSegmentation;
+
+impl Buffer {
+    /// Describes the changes the specified function would make to the buffer
+    /// (e.g. by calling `replace_all` or `reformat`), without applying them,
+    /// so that they can be confirmed first. The function is run against a
+    /// copy of the buffer, sharing its content, cursor, selections, syntax
+    /// definition, and editing settings (but not its insert transforms,
+    /// observers, or history).
+    ///
+    /// The summary's edits are those the function made, in order; it isn't
+    /// `applied`, and its `history_index` is the one the buffer would reach by
+    /// running the function (as a single operation, using `batch`). Its `data`
+    /// is the content the buffer would have, and its `range` spans the part of
+    /// that content that differs from the buffer's current content.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary scribe");
+    ///
+    /// let summary = buffer.preview(|buffer| { buffer.replace_all("scribe", "editor"); });
+    /// assert_eq!(summary.edits.len(), 4);
+    ///
+    /// // Matches are replaced from last to first.
+    /// assert_eq!(summary.edits[1].inserted, "editor");
+    /// assert_eq!(summary.edits[1].range, Range::new(
+    ///     Position{ line: 1, offset: 8 },
+    ///     Position{ line: 1, offset: 14 }
+    /// ));
+    ///
+    /// // The outcome spans from the first change to the last.
+    /// assert_eq!(summary.data.unwrap(), "editor\nlibrary editor");
+    /// assert_eq!(summary.range, Some(Range::new(
+    ///     Position{ line: 0, offset: 0 },
+    ///     Position{ line: 1, offset: 14 }
+    /// )));
+    ///
+    /// // The buffer itself is left as-is.
+    /// assert_eq!(buffer.data(), "scribe\nlibrary scribe");
+    /// ```
+    pub fn preview<F: FnOnce(&mut Buffer)>(&self, f: F) -> ChangeSummary {
+        let mut scratch = self.derive(self.data());
+        scratch.cursor.move_to(*self.cursor);
+        scratch.selections = self.selections.clone();
+        scratch.read_only = self.read_only;
+        scratch.append_only = self.append_only;
+        scratch.insert_final_newline = self.insert_final_newline;
+        scratch.preserve_formatting = self.preserve_formatting;
+        scratch.smart_punctuation = self.smart_punctuation;
+        scratch.line_ending = self.line_ending;
+        scratch.abbreviations = self.abbreviations.clone();
+        scratch.tab_width = self.tab_width;
+        scratch.soft_tabs = self.soft_tabs;
+        scratch.indent_rules = self.indent_rules.clone();
+        scratch.word_boundaries = self.word_boundaries;
+        scratch.max_line_length = self.max_line_length;
+
+        let edits = Rc::new(RefCell::new(Vec::new()));
+        let observed_edits = edits.clone();
+        scratch.on_change(move |edit| observed_edits.borrow_mut().push(edit.clone()));
+        scratch.batch(f);
+
+        let edits = edits.replace(Vec::new());
+        let history_index = if edits.is_empty() {
+            self.history.position()
+        } else {
+            self.history.position() + 1
+        };
+
+        let data = scratch.data();
+        let range = changed_range(&self.data(), &data);
+
+        ChangeSummary{ history_index, applied: false, edits, range, data: Some(data) }
+    }
+}
+
+// The range of the updated content that differs from the original content,
+// found by skipping the graphemes they start and end with in common.
+fn changed_range(original: &str, updated: &str) -> Option<Range> {
+    let original: Vec<&str> = original.graphemes(true).collect();
+    let updated: Vec<&str> = updated.graphemes(true).collect();
+    if original == updated { return None; }
+
+    let prefix = original.iter().zip(&updated).take_while(|(a, b)| a == b).count();
+    let suffix = original[prefix..].iter().rev()
+        .zip(updated[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let start = Position::new() + Distance::of_str(&updated[..prefix].concat());
+    let end = start + Distance::of_str(&updated[prefix..updated.len() - suffix].concat());
+
+    Some(Range::new(start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, Range};
+
+    #[test]
+    fn preview_leaves_the_history_intact() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        buffer.insert("library ");
+        buffer.undo();
+
+        let summary = buffer.preview(|buffer| buffer.insert("editor "));
+        assert_eq!(summary.history_index, 2);
+        assert_eq!(summary.edits[0].inserted, "editor ");
+
+        buffer.redo();
+        assert_eq!(buffer.data(), "library scribe");
+    }
+
+    #[test]
+    fn preview_of_read_only_buffers_is_empty() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        buffer.read_only = true;
+
+        let summary = buffer.preview(|buffer| buffer.insert("library "));
+        assert!(summary.edits.is_empty());
+        assert_eq!(summary.history_index, 1);
+    }
+
+    #[test]
+    fn preview_range_covers_the_resulting_content() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary\neditor");
+
+        let summary = buffer.preview(|buffer| {
+            buffer.delete_range(Range::new(Position{ line: 1, offset: 0 }, Position{ line: 2, offset: 0 }));
+        });
+        assert_eq!(summary.data.unwrap(), "scribe\neditor");
+        assert_eq!(summary.range, Some(Range::new(Position{ line: 1, offset: 0 }, Position{ line: 1, offset: 0 })));
+
+        let summary = buffer.preview(|buffer| { buffer.replace_all("library", "library"); });
+        assert_eq!(summary.range, None);
+    }
+}
@@ -0,0 +1,544 @@
+//! Pretty-printing and minification for JSON and XML buffers.
+
+use buffer::structure::Format;
+use buffer::{Buffer, Position};
+use errors::*;
+use std::iter::Peekable;
+use std::str::CharIndices;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Options controlling the output of `Buffer::reformat`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReformatOptions {
+    /// The string used for each level of indentation.
+    pub indent: String,
+
+    /// Whether JSON object members are sorted by key.
+    pub sort_keys: bool,
+
+    /// Whether to remove all insignificant whitespace, rather than indenting.
+    pub minify: bool,
+}
+
+impl Default for ReformatOptions {
+    fn default() -> ReformatOptions {
+        ReformatOptions{ indent: "  ".to_string(), sort_keys: false, minify: false }
+    }
+}
+
+impl Buffer {
+    /// Pretty-prints or minifies the buffer's content, based on its JSON or
+    /// XML syntax definition. The change is undoable, and the cursor is kept
+    /// alongside the content it preceded. Whitespace surrounding XML text
+    /// is not preserved, and a trailing newline is kept if present.
+    ///
+    /// Returns an error if the buffer's syntax definition isn't
+    /// JSON or XML, or if the content can't be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate syntect;
+    /// extern crate scribe;
+    ///
+    /// use scribe::Buffer;
+    /// use scribe::buffer::ReformatOptions;
+    /// use syntect::parsing::SyntaxSet;
+    ///
+    /// let syntax_set = SyntaxSet::load_defaults_newlines();
+    /// let mut buffer = Buffer::new();
+    /// buffer.syntax_definition = syntax_set.find_syntax_by_name("JSON").cloned();
+    /// buffer.insert("{\"b\": [1, 2], \"a\": {}}\n");
+    ///
+    /// let options = ReformatOptions{ sort_keys: true, ..Default::default() };
+    /// buffer.reformat(&options).unwrap();
+    /// assert_eq!(buffer.data(), "{\n  \"a\": {},\n  \"b\": [\n    1,\n    2\n  ]\n}\n");
+    ///
+    /// let options = ReformatOptions{ minify: true, ..Default::default() };
+    /// buffer.reformat(&options).unwrap();
+    /// assert_eq!(buffer.data(), "{\"a\":{},\"b\":[1,2]}\n");
+    ///
+    /// buffer.undo();
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "{\"b\": [1, 2], \"a\": {}}\n");
+    /// ```
+    pub fn reformat(&mut self, options: &ReformatOptions) -> Result<()> {
+        let format = self.structure_format().ok_or_else(|| {
+            let syntax = self.syntax_definition.as_ref().map(|def| def.name.clone());
+            ErrorKind::UnsupportedFormat(syntax.unwrap_or_default())
+        })?;
+        let data = self.data();
+        let mut content = match format {
+            Format::Json => format_json(&data, options)?,
+            Format::Xml => format_xml(&data, options)?,
+        };
+        if data.ends_with('\n') {
+            content.push('\n');
+        }
+        if content == data {
+            return Ok(());
+        }
+
+        // Keep the cursor in front of the same content.
+        let preceding_content = non_whitespace_before(&data, &self.cursor);
+        self.replace_contents(&content);
+        self.cursor.move_to(position_of_non_whitespace(&content, preceding_content));
+
+        Ok(())
+    }
+}
+
+// Counts the non-whitespace graphemes preceding the position.
+fn non_whitespace_before(data: &str, position: &Position) -> usize {
+    let mut current_position = Position::new();
+    let mut count = 0;
+
+    for grapheme in data.graphemes(true) {
+        if current_position >= *position { break; }
+        if !grapheme.trim().is_empty() { count += 1; }
+        current_position = advance(current_position, grapheme);
+    }
+
+    count
+}
+
+// Finds the position of the non-whitespace grapheme preceded by `count` others.
+fn position_of_non_whitespace(data: &str, count: usize) -> Position {
+    let mut position = Position::new();
+    let mut seen = 0;
+
+    for grapheme in data.graphemes(true) {
+        if !grapheme.trim().is_empty() {
+            if seen == count { break; }
+            seen += 1;
+        }
+        position = advance(position, grapheme);
+    }
+
+    position
+}
+
+fn advance(position: Position, grapheme: &str) -> Position {
+    if grapheme == "\n" || grapheme == "\r\n" {
+        Position{ line: position.line + 1, offset: 0 }
+    } else {
+        Position{ line: position.line, offset: position.offset + 1 }
+    }
+}
+
+enum JsonValue {
+    Object(Vec<(String, JsonValue)>),
+    Array(Vec<JsonValue>),
+    // Strings, numbers, and literals, as they appear in the source.
+    Scalar(String),
+}
+
+// Parses JSON data, retaining the original
+// representation of its strings and numbers.
+struct JsonParser<'a> {
+    data: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> JsonParser<'a> {
+    fn parse(data: &str) -> Result<JsonValue> {
+        let mut parser = JsonParser{ data, chars: data.char_indices().peekable() };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+
+        match parser.chars.peek() {
+            Some(&(index, c)) => Err(parser.unexpected(index, c)),
+            None => Ok(value),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue> {
+        self.skip_whitespace();
+
+        match self.chars.peek().cloned() {
+            Some((_, '{')) => {
+                self.chars.next();
+                let mut members = Vec::new();
+                self.skip_whitespace();
+                if self.consume('}') { return Ok(JsonValue::Object(members)); }
+
+                loop {
+                    self.skip_whitespace();
+                    let key = match self.chars.peek().cloned() {
+                        Some((index, '"')) => self.parse_string(index)?,
+                        Some((index, c)) => return Err(self.unexpected(index, c)),
+                        None => return Err(self.unexpected_end()),
+                    };
+                    self.expect(':')?;
+                    members.push((key, self.parse_value()?));
+                    if !self.consume(',') {
+                        self.expect('}')?;
+                        return Ok(JsonValue::Object(members));
+                    }
+                }
+            },
+            Some((_, '[')) => {
+                self.chars.next();
+                let mut elements = Vec::new();
+                self.skip_whitespace();
+                if self.consume(']') { return Ok(JsonValue::Array(elements)); }
+
+                loop {
+                    elements.push(self.parse_value()?);
+                    if !self.consume(',') {
+                        self.expect(']')?;
+                        return Ok(JsonValue::Array(elements));
+                    }
+                }
+            },
+            Some((index, '"')) => Ok(JsonValue::Scalar(self.parse_string(index)?)),
+            Some((start, c)) if c == '-' || c.is_ascii_alphanumeric() => {
+                let mut end = start;
+                while let Some(&(index, c)) = self.chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || "+-.".contains(c)) { break; }
+                    end = index + c.len_utf8();
+                    self.chars.next();
+                }
+
+                Ok(JsonValue::Scalar(self.data[start..end].to_string()))
+            },
+            Some((index, c)) => Err(self.unexpected(index, c)),
+            None => Err(self.unexpected_end()),
+        }
+    }
+
+    // Returns the string, including its quotes and escape sequences.
+    fn parse_string(&mut self, start: usize) -> Result<String> {
+        self.chars.next();
+
+        while let Some((index, c)) = self.chars.next() {
+            match c {
+                '"' => return Ok(self.data[start..=index].to_string()),
+                '\\' => { self.chars.next(); },
+                _ => (),
+            }
+        }
+
+        Err(self.unexpected_end())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().map(|&(_, c)| c.is_whitespace()).unwrap_or(false) {
+            self.chars.next();
+        }
+    }
+
+    // Consumes the character if it's next, ignoring whitespace.
+    fn consume(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some(&(_, c)) if c == expected => { self.chars.next(); true },
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        if self.consume(expected) { return Ok(()); }
+
+        match self.chars.peek().cloned() {
+            Some((index, c)) => Err(self.unexpected(index, c)),
+            None => Err(self.unexpected_end()),
+        }
+    }
+
+    fn unexpected(&self, index: usize, c: char) -> Error {
+        invalid_document(self.data, index, &format!("unexpected character '{}'", c))
+    }
+
+    fn unexpected_end(&self) -> Error {
+        ErrorKind::InvalidDocument("unexpected end of document".to_string()).into()
+    }
+}
+
+fn format_json(data: &str, options: &ReformatOptions) -> Result<String> {
+    let mut value = JsonParser::parse(data)?;
+    if options.sort_keys {
+        sort_keys(&mut value);
+    }
+
+    let mut output = String::new();
+    write_json(&value, options, 0, &mut output);
+
+    Ok(output)
+}
+
+fn sort_keys(value: &mut JsonValue) {
+    match *value {
+        JsonValue::Object(ref mut members) => {
+            members.sort_by(|a, b| a.0.cmp(&b.0));
+            for member in members.iter_mut() {
+                sort_keys(&mut member.1);
+            }
+        },
+        JsonValue::Array(ref mut elements) => elements.iter_mut().for_each(sort_keys),
+        JsonValue::Scalar(_) => (),
+    }
+}
+
+fn write_json(value: &JsonValue, options: &ReformatOptions, depth: usize, output: &mut String) {
+    let (open, close, length) = match *value {
+        JsonValue::Object(ref members) => ('{', '}', members.len()),
+        JsonValue::Array(ref elements) => ('[', ']', elements.len()),
+        JsonValue::Scalar(ref content) => return output.push_str(content),
+    };
+
+    output.push(open);
+    for index in 0..length {
+        if index > 0 { output.push(','); }
+        if !options.minify { new_line(options, depth + 1, output); }
+
+        match *value {
+            JsonValue::Object(ref members) => {
+                output.push_str(&members[index].0);
+                output.push_str(if options.minify { ":" } else { ": " });
+                write_json(&members[index].1, options, depth + 1, output);
+            },
+            JsonValue::Array(ref elements) => write_json(&elements[index], options, depth + 1, output),
+            JsonValue::Scalar(_) => (),
+        }
+    }
+    if length > 0 && !options.minify { new_line(options, depth, output); }
+    output.push(close);
+}
+
+fn new_line(options: &ReformatOptions, depth: usize, output: &mut String) {
+    output.push('\n');
+    for _ in 0..depth {
+        output.push_str(&options.indent);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum XmlItem<'a> {
+    Open(&'a str, &'a str),
+    Close(&'a str, &'a str),
+    // Self-closing elements, comments, declarations, and CDATA sections.
+    Standalone(&'a str),
+    Text(&'a str),
+}
+
+// Splits XML data into tags and (trimmed, non-empty) text.
+fn xml_items(data: &str) -> Result<Vec<XmlItem<'_>>> {
+    let mut items = Vec::new();
+    let mut index = 0;
+
+    while index < data.len() {
+        let remaining = &data[index..];
+        if !remaining.starts_with('<') {
+            let length = remaining.find('<').unwrap_or(remaining.len());
+            let text = remaining[..length].trim();
+            if !text.is_empty() { items.push(XmlItem::Text(text)); }
+            index += length;
+            continue;
+        }
+
+        let terminator = if remaining.starts_with("<!--") {
+            "-->"
+        } else if remaining.starts_with("<![CDATA[") {
+            "]]>"
+        } else if remaining.starts_with("<?") {
+            "?>"
+        } else {
+            ">"
+        };
+        let length = tag_length(remaining, terminator).ok_or_else(|| {
+            invalid_document(data, index, "unterminated tag")
+        })?;
+        let tag = &remaining[..length];
+        let name = tag.trim_start_matches("</").trim_start_matches('<')
+            .split(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .next()
+            .unwrap_or("");
+
+        items.push(if terminator != ">" || tag.starts_with("<!") || tag.ends_with("/>") {
+            XmlItem::Standalone(tag)
+        } else if tag.starts_with("</") {
+            XmlItem::Close(name, tag)
+        } else {
+            XmlItem::Open(name, tag)
+        });
+        index += length;
+    }
+
+    Ok(items)
+}
+
+// Finds the length of the tag at the start of the data, skipping
+// over quoted attribute values in the case of regular tags.
+fn tag_length(data: &str, terminator: &str) -> Option<usize> {
+    if terminator != ">" {
+        return data.find(terminator).map(|index| index + terminator.len());
+    }
+
+    let mut quote = None;
+    for (index, c) in data.char_indices() {
+        match quote {
+            Some(q) if q == c => quote = None,
+            Some(_) => (),
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return Some(index + 1),
+            None => (),
+        }
+    }
+
+    None
+}
+
+fn format_xml(data: &str, options: &ReformatOptions) -> Result<String> {
+    let items = xml_items(data)?;
+    let mut output = String::new();
+    let mut open_elements = Vec::new();
+    let mut index = 0;
+
+    while index < items.len() {
+        let depth = open_elements.len();
+        if !options.minify && !output.is_empty() {
+            let closing = match items[index] { XmlItem::Close(..) => 1, _ => 0 };
+            new_line(options, depth - closing.min(depth), &mut output);
+        }
+
+        match items[index] {
+            XmlItem::Open(name, tag) => {
+                output.push_str(tag);
+
+                // Keep elements containing nothing but text on a single line.
+                match (items.get(index + 1), items.get(index + 2)) {
+                    (Some(&XmlItem::Close(close_name, close_tag)), _) if close_name == name => {
+                        output.push_str(close_tag);
+                        index += 1;
+                    },
+                    (Some(&XmlItem::Text(text)), Some(&XmlItem::Close(close_name, close_tag))) if close_name == name => {
+                        output.push_str(text);
+                        output.push_str(close_tag);
+                        index += 2;
+                    },
+                    _ => open_elements.push(name),
+                }
+            },
+            XmlItem::Close(name, tag) => {
+                if open_elements.pop() != Some(name) {
+                    return Err(ErrorKind::InvalidDocument(format!("unexpected closing tag {}", tag)).into());
+                }
+                output.push_str(tag);
+            },
+            XmlItem::Standalone(content) | XmlItem::Text(content) => output.push_str(content),
+        }
+
+        index += 1;
+    }
+
+    match open_elements.last() {
+        Some(name) => Err(ErrorKind::InvalidDocument(format!("unclosed element {}", name)).into()),
+        None => Ok(output),
+    }
+}
+
+// Builds an error describing a problem at the specified byte index.
+fn invalid_document(data: &str, index: usize, reason: &str) -> Error {
+    let line = data[..index].matches('\n').count() + 1;
+
+    ErrorKind::InvalidDocument(format!("{} on line {}", reason, line)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, ReformatOptions};
+    use errors::*;
+    use syntect::parsing::SyntaxSet;
+
+    fn buffer_with_syntax(name: &str, content: &str) -> Buffer {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let mut buffer = Buffer::new();
+        buffer.syntax_definition = syntax_set.find_syntax_by_name(name).cloned();
+        buffer.insert(content);
+
+        buffer
+    }
+
+    #[test]
+    fn reformat_rejects_unsupported_syntaxes() {
+        let mut buffer = buffer_with_syntax("Rust", "fn main() {}");
+
+        match buffer.reformat(&ReformatOptions::default()) {
+            Err(Error(ErrorKind::UnsupportedFormat(ref syntax), _)) => assert_eq!(syntax, "Rust"),
+            _ => panic!("expected an unsupported format error"),
+        }
+    }
+
+    #[test]
+    fn reformat_reports_invalid_json_without_changing_the_buffer() {
+        let mut buffer = buffer_with_syntax("JSON", "{\n  \"a\": [1,, 2]\n}");
+
+        match buffer.reformat(&ReformatOptions::default()) {
+            Err(Error(ErrorKind::InvalidDocument(ref reason), _)) => {
+                assert_eq!(reason, "unexpected character ',' on line 2")
+            },
+            _ => panic!("expected an invalid document error"),
+        }
+        assert_eq!(buffer.data(), "{\n  \"a\": [1,, 2]\n}");
+    }
+
+    #[test]
+    fn reformat_preserves_json_strings_and_nested_sort_order() {
+        let mut buffer = buffer_with_syntax("JSON", "{\"z\": {\"y\": \"a, \\\"b\\\"\", \"x\": -1.5e3}}");
+        let options = ReformatOptions{ sort_keys: true, minify: true, ..Default::default() };
+        buffer.reformat(&options).unwrap();
+
+        assert_eq!(buffer.data(), "{\"z\":{\"x\":-1.5e3,\"y\":\"a, \\\"b\\\"\"}}");
+    }
+
+    #[test]
+    fn reformat_indents_xml() {
+        let mut buffer = buffer_with_syntax(
+            "XML",
+            "<?xml version=\"1.0\"?><list a=\"x>y\"><!-- items --><item>  a </item><item/><group><item></item></group></list>"
+        );
+        let options = ReformatOptions{ indent: "\t".to_string(), ..Default::default() };
+        buffer.reformat(&options).unwrap();
+
+        assert_eq!(
+            buffer.data(),
+            "<?xml version=\"1.0\"?>\n<list a=\"x>y\">\n\t<!-- items -->\n\t<item>a</item>\n\t<item/>\n\t<group>\n\t\t<item></item>\n\t</group>\n</list>"
+        );
+
+        buffer.reformat(&ReformatOptions{ minify: true, ..Default::default() }).unwrap();
+        assert_eq!(
+            buffer.data(),
+            "<?xml version=\"1.0\"?><list a=\"x>y\"><!-- items --><item>a</item><item/><group><item></item></group></list>"
+        );
+    }
+
+    #[test]
+    fn reformat_reports_mismatched_xml_elements() {
+        let mut buffer = buffer_with_syntax("XML", "<a><b></a>");
+        assert!(buffer.reformat(&ReformatOptions::default()).is_err());
+
+        let mut buffer = buffer_with_syntax("XML", "<a><b></b>");
+        assert!(buffer.reformat(&ReformatOptions::default()).is_err());
+    }
+
+    #[test]
+    fn reformat_keeps_the_cursor_in_front_of_the_same_content() {
+        let mut buffer = buffer_with_syntax("JSON", "{\"a\": 1, \"b\": 2}");
+        buffer.cursor.move_to(Position{ line: 0, offset: 9 });
+        buffer.reformat(&ReformatOptions::default()).unwrap();
+
+        assert_eq!(buffer.data(), "{\n  \"a\": 1,\n  \"b\": 2\n}");
+        assert_eq!(*buffer.cursor, Position{ line: 2, offset: 2 });
+    }
+
+    #[test]
+    fn reformat_does_not_add_history_when_content_is_unchanged() {
+        let mut buffer = buffer_with_syntax("JSON", "[1]");
+        buffer.reformat(&ReformatOptions{ minify: true, ..Default::default() }).unwrap();
+        buffer.undo();
+
+        assert_eq!(buffer.data(), "");
+    }
+}
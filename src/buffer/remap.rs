@@ -0,0 +1,219 @@
+//! Position remapping for wholesale content replacements.
+
+use buffer::{Buffer, Collaborator, Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+use util::approximate_matching_elements;
+
+// The number of edits beyond which `DiffRemap` stops searching a section
+// of content for the shortest edit script (see `approximate_matching_elements`).
+const MAX_DIFF_COST: usize = 1000;
+
+/// Maps positions in a buffer's previous content to the corresponding
+/// positions in the content replacing it. Buffers use their `remap_policy`
+/// to move their cursor and selections when their content is swapped out
+/// wholesale (i.e. by `reload` and `replace_contents`).
+pub trait RemapPolicy {
+    /// Returns the new position of each of the specified positions,
+    /// in the same order. Out-of-bounds results are clamped by the buffer.
+    fn remap(&self, previous: &str, current: &str, positions: &[Position]) -> Vec<Position>;
+}
+
+/// Keeps positions on the text they preceded, by diffing the previous and
+/// current content: lines are matched using a minimal diff, and offsets on
+/// lines that changed are matched using a diff of their graphemes. Positions
+/// within removed content move to where it was. This is the default policy.
+///
+/// To bound the cost of remapping, sections of content that differ by more
+/// than a few thousand lines (or graphemes) are treated as having been
+/// replaced outright, rather than searching them for common lines.
+///
+/// # Examples
+///
+/// ```
+/// use scribe::buffer::{DiffRemap, Position, RemapPolicy};
+///
+/// let positions = DiffRemap.remap(
+///     "scribe\nlibrary",
+///     "a text editor\nscribe\nrust library",
+///     &[Position{ line: 1, offset: 2 }]
+/// );
+/// assert_eq!(positions, vec![Position{ line: 2, offset: 7 }]);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DiffRemap;
+
+impl RemapPolicy for DiffRemap {
+    fn remap(&self, previous: &str, current: &str, positions: &[Position]) -> Vec<Position> {
+        let previous_lines: Vec<&str> = previous.split('\n').collect();
+        let current_lines: Vec<&str> = current.split('\n').collect();
+        let line_matches = approximate_matching_elements(&previous_lines, &current_lines, MAX_DIFF_COST);
+
+        positions.iter().map(|position| {
+            let line = map_index(&line_matches, position.line, current_lines.len())
+                .min(current_lines.len() - 1);
+
+            let offset = match line_matches.get(position.line) {
+                Some(&Some(_)) => position.offset,
+
+                // The line was removed; move to the start of the line that followed it.
+                _ if line_matches.contains(&Some(line)) => 0,
+
+                _ => {
+                    let previous_graphemes: Vec<&str> = previous_lines
+                        .get(position.line)
+                        .map(|content| content.graphemes(true).collect())
+                        .unwrap_or_default();
+                    let current_graphemes: Vec<&str> = current_lines[line].graphemes(true).collect();
+                    let grapheme_matches = approximate_matching_elements(
+                        &previous_graphemes, &current_graphemes, MAX_DIFF_COST
+                    );
+
+                    map_index(&grapheme_matches, position.offset, current_graphemes.len())
+                },
+            };
+
+            Position{ line, offset }
+        }).collect()
+    }
+}
+
+/// Leaves positions as they are, clamping them to the current content.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClampRemap;
+
+impl RemapPolicy for ClampRemap {
+    fn remap(&self, _: &str, _: &str, positions: &[Position]) -> Vec<Position> {
+        positions.to_vec()
+    }
+}
+
+impl Buffer {
+    // Moves the cursor, selections, and collaborators from their positions
+    // in the previous content to the corresponding positions in the current
+    // content.
+    pub(crate) fn remap_positions(&mut self, previous_data: &str, cursor: Position, selections: &[Range], mut collaborators: Vec<Collaborator>) {
+        let data = self.data();
+        let mut positions = vec![cursor];
+        for selection in selections {
+            positions.push(selection.start());
+            positions.push(selection.end());
+        }
+        for collaborator in &collaborators {
+            positions.push(collaborator.cursor);
+            if let Some(ref selection) = collaborator.selection {
+                positions.push(selection.start());
+                positions.push(selection.end());
+            }
+        }
+
+        let mut positions = self.remap_policy
+            .remap(previous_data, &data, &positions)
+            .into_iter()
+            .map(|position| clamp(position, &data));
+
+        if let Some(position) = positions.next() {
+            self.cursor.move_to(position);
+        }
+        self.selections.clear();
+        for _ in selections {
+            if let (Some(start), Some(end)) = (positions.next(), positions.next()) {
+                self.selections.push(Range::new(start, end));
+            }
+        }
+        self.selections.dedup();
+        for collaborator in &mut collaborators {
+            if let Some(position) = positions.next() {
+                collaborator.cursor = position;
+            }
+            if collaborator.selection.is_some() {
+                if let (Some(start), Some(end)) = (positions.next(), positions.next()) {
+                    collaborator.selection = Some(Range::new(start, end));
+                }
+            }
+        }
+        self.collaborators = collaborators;
+    }
+}
+
+// Maps an index into one sequence to another, using the matches between them.
+// Matched elements map directly; others keep their distance from the preceding
+// match, without passing the element matching the next one.
+fn map_index(matches: &[Option<usize>], index: usize, target_length: usize) -> usize {
+    if let Some(&Some(target)) = matches.get(index) {
+        return target;
+    }
+
+    let split = index.min(matches.len());
+    let (base, target_base) = matches[..split]
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(index, target)| target.map(|target| (index + 1, target + 1)))
+        .unwrap_or((0, 0));
+    let limit = matches[split..].iter().find_map(|&target| target).unwrap_or(target_length);
+
+    (target_base + index - base).min(limit)
+}
+
+// Moves the position to the end of its line, or to the last
+// line, if it's beyond the end of the data.
+fn clamp(position: Position, data: &str) -> Position {
+    let lines: Vec<&str> = data.split('\n').collect();
+    let line = position.line.min(lines.len() - 1);
+    let length = lines[line].graphemes(true).count();
+
+    Position{ line, offset: position.offset.min(length) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClampRemap, DiffRemap, RemapPolicy};
+    use buffer::{Buffer, Position};
+    #[cfg(feature = "fs")]
+    use buffer::Range;
+    #[cfg(feature = "fs")]
+    use std::env;
+    #[cfg(feature = "fs")]
+    use std::fs;
+
+    #[test]
+    fn diff_remap_moves_positions_in_removed_lines_to_where_they_were() {
+        let positions = DiffRemap.remap(
+            "scribe\nlibrary\neditor",
+            "scribe\neditor",
+            &[Position{ line: 1, offset: 3 }, Position{ line: 2, offset: 2 }]
+        );
+
+        assert_eq!(positions, vec![Position{ line: 1, offset: 0 }, Position{ line: 1, offset: 2 }]);
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn reload_keeps_the_cursor_and_selections_on_the_same_text() {
+        let path = env::temp_dir().join("scribe_reload_remaps_positions");
+        fs::write(&path, "scribe\nlibrary\n").unwrap();
+        let mut buffer = Buffer::from_file(&path).unwrap();
+        buffer.cursor.move_to(Position{ line: 1, offset: 3 });
+        buffer.select_all_occurrences("scribe");
+
+        fs::write(&path, "a text editor\nscribe\nlibrary\n").unwrap();
+        buffer.reload().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(*buffer.cursor, Position{ line: 2, offset: 3 });
+        assert_eq!(buffer.selections(), &[
+            Range::new(Position{ line: 1, offset: 0 }, Position{ line: 1, offset: 6 })
+        ]);
+    }
+
+    #[test]
+    fn replace_contents_uses_the_buffer_remap_policy() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary");
+        buffer.cursor.move_to(Position{ line: 1, offset: 3 });
+        buffer.remap_policy = Box::new(ClampRemap);
+
+        buffer.replace_contents("a text editor\nscribe\nlibrary");
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 3 });
+    }
+}
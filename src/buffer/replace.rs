@@ -0,0 +1,300 @@
+//! Search and replace functionality.
+
+use buffer::{Buffer, Distance, Position, Range};
+use errors::*;
+use unicode_segmentation::UnicodeSegmentation;
+use util::mirror_case;
+
+impl Buffer {
+    /// Replaces the content in the specified range with `data`. The
+    /// change is undone/redone as a single operation.
+    ///
+    /// Out of bounds ranges are handled as they are by `delete_range`, and
+    /// changes to `read_only` buffers (or removals from `append_only` ones)
+    /// are silently ignored; use `try_replace` to have these cases reported
+    /// as errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary");
+    ///
+    /// let range = Range::new(Position{ line: 0, offset: 3 }, Position{ line: 1, offset: 3 });
+    /// buffer.replace(&range, "amp\neditor\nlib");
+    /// assert_eq!(buffer.data(), "scramp\neditor\nlibrary");
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "scribe\nlibrary");
+    ///
+    /// let range = Range::new(Position{ line: 2, offset: 0 }, Position{ line: 2, offset: 3 });
+    /// buffer.replace(&range, "amp");
+    /// assert_eq!(buffer.data(), "scribe\nlibrary");
+    /// assert!(buffer.try_replace(&range, "amp").is_err());
+    /// ```
+    pub fn replace(&mut self, range: &Range, data: &str) {
+        if self.read_only { return; }
+
+        self.batch(|buffer| {
+            buffer.delete_range(range.clone());
+            buffer.insert_transformed(data.to_string(), range.start());
+        });
+    }
+
+    // Like replace, but without applying the insert transforms,
+    // for content the buffer produces itself (e.g. indentation).
+    pub(crate) fn replace_verbatim(&mut self, range: &Range, data: &str) {
+        if self.read_only { return; }
+
+        self.batch(|buffer| {
+            buffer.delete_range(range.clone());
+            buffer.insert_at(data, range.start());
+        });
+    }
+
+    /// Replaces every occurrence of `needle` with `replacement`,
+    /// returning the number of replacements made. All of the
+    /// replacements are undone/redone as a single operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nscribe library");
+    ///
+    /// assert_eq!(buffer.replace_all("scribe", "amp"), 2);
+    /// assert_eq!(buffer.data(), "amp\namp library");
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "scribe\nscribe library");
+    /// ```
+    pub fn replace_all(&mut self, needle: &str, replacement: &str) -> usize {
+        let matches = find_matches(&self.data(), needle, false);
+
+        self.replace_matches(matches, |_| replacement.to_string())
+    }
+
+    /// Replaces every case-insensitive occurrence of `needle` with
+    /// `replacement`, mirroring the case pattern of each match (see
+    /// `util::mirror_case`), and returning the number of replacements made.
+    /// All of the replacements are undone/redone as a single operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("foo Foo FOO");
+    ///
+    /// assert_eq!(buffer.replace_all_preserving_case("foo", "bar"), 3);
+    /// assert_eq!(buffer.data(), "bar Bar BAR");
+    /// ```
+    pub fn replace_all_preserving_case(&mut self, needle: &str, replacement: &str) -> usize {
+        let matches = find_matches(&self.data(), needle, true);
+
+        self.replace_matches(matches, |matched| mirror_case(matched, replacement))
+    }
+
+    /// Replaces the buffer's content with `content`, as a single operation
+    /// that can be undone/redone. The cursor and selections are moved to the
+    /// corresponding positions in the new content, using the buffer's
+    /// `remap_policy`, and are clamped to its bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary");
+    /// buffer.cursor.move_to(Position{ line: 1, offset: 4 });
+    ///
+    /// buffer.replace_contents("a text editor\nscribe\nlibrary");
+    /// assert_eq!(buffer.data(), "a text editor\nscribe\nlibrary");
+    /// assert_eq!(*buffer.cursor, Position{ line: 2, offset: 4 });
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "scribe\nlibrary");
+    /// ```
+    pub fn replace_contents(&mut self, content: &str) {
+        let _ = self.try_replace_contents(content);
+    }
+
+    // Like `replace_contents`, but returns an AppendOnly
+    // error if the buffer's content can't be removed.
+    pub(crate) fn try_replace_contents(&mut self, content: &str) -> Result<()> {
+        if self.read_only { return Ok(()); }
+
+        let previous_data = self.data();
+        let cursor = *self.cursor;
+        let selections = self.selections.clone();
+        let collaborators = self.collaborators.clone();
+        let end = Position::new() + Distance::of_str(&previous_data);
+        self.try_batch(|buffer| {
+            buffer.delete_range(Range::new(Position::new(), end));
+            buffer.insert_at(content, Position::new());
+        })?;
+
+        self.remap_positions(&previous_data, cursor, &selections, collaborators);
+
+        Ok(())
+    }
+
+    // Replaces the matched ranges with the output of the replacement closure,
+    // grouping the changes so that they're undone/redone together.
+    fn replace_matches<F>(&mut self, matches: Vec<(Range, String)>, replacement: F) -> usize
+        where F: Fn(&str) -> String
+    {
+        if self.read_only { return 0; }
+
+        let result = self.try_batch(|buffer| {
+            // Replace from the end of the buffer, so that earlier
+            // match positions aren't invalidated by the changes.
+            for (range, matched) in matches.iter().rev() {
+                buffer.delete_range(range.clone());
+                buffer.insert_transformed(replacement(matched), range.start());
+            }
+        });
+
+        if result.is_ok() { matches.len() } else { 0 }
+    }
+}
+
+// Finds non-overlapping occurrences of the needle on each line, returning
+// their (grapheme cluster-based) ranges, alongside the matched content.
+fn find_matches(data: &str, needle: &str, ignore_case: bool) -> Vec<(Range, String)> {
+    let normalize = |grapheme: &str| if ignore_case {
+        grapheme.to_lowercase()
+    } else {
+        grapheme.to_string()
+    };
+    let needle: Vec<String> = needle.graphemes(true).map(&normalize).collect();
+    let mut matches = Vec::new();
+
+    if needle.is_empty() {
+        return matches;
+    }
+
+    for (line, content) in data.lines().enumerate() {
+        let graphemes: Vec<&str> = content.graphemes(true).collect();
+        let mut offset = 0;
+
+        while offset + needle.len() <= graphemes.len() {
+            let candidate = &graphemes[offset..offset + needle.len()];
+
+            if candidate.iter().zip(needle.iter()).all(|(g, n)| normalize(g) == *n) {
+                let range = Range::new(
+                    Position{ line, offset },
+                    Position{ line, offset: offset + needle.len() }
+                );
+                matches.push((range, candidate.concat()));
+                offset += needle.len();
+            } else {
+                offset += 1;
+            }
+        }
+    }
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, Range};
+
+    #[test]
+    fn replace_inserts_at_the_start_of_empty_ranges() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+
+        let position = Position{ line: 0, offset: 6 };
+        buffer.replace(&Range::new(position, position), " library");
+        assert_eq!(buffer.data(), "scribe library");
+    }
+
+    #[test]
+    fn replace_does_nothing_to_read_only_buffers() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        buffer.read_only = true;
+
+        buffer.replace(&Range::new(Position::new(), Position{ line: 0, offset: 6 }), "amp");
+        assert_eq!(buffer.data(), "scribe");
+    }
+
+    #[test]
+    fn replace_contents_moves_out_of_bounds_cursors_to_the_end_of_their_line() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary\neditor");
+        buffer.cursor.move_to(Position{ line: 1, offset: 6 });
+
+        buffer.replace_contents("a\nb");
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 1 });
+
+        buffer.cursor.move_to(Position{ line: 1, offset: 1 });
+        buffer.replace_contents("");
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 0 });
+    }
+
+    #[test]
+    fn replace_all_preserving_case_is_undone_as_a_single_operation() {
+        let mut buffer = Buffer::new();
+        buffer.insert("foo\nFoo");
+        buffer.replace_all_preserving_case("foo", "bar");
+        assert_eq!(buffer.data(), "bar\nBar");
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "foo\nFoo");
+    }
+
+    #[test]
+    fn replace_all_handles_grapheme_clusters() {
+        let mut buffer = Buffer::new();
+        buffer.insert("नी scribe नी");
+        buffer.replace_all("नी", "x");
+        assert_eq!(buffer.data(), "x scribe x");
+    }
+
+    #[test]
+    fn replace_all_does_not_replace_overlapping_matches() {
+        let mut buffer = Buffer::new();
+        buffer.insert("aaa");
+        assert_eq!(buffer.replace_all("aa", "b"), 1);
+        assert_eq!(buffer.data(), "ba");
+    }
+
+    #[test]
+    fn replace_all_ignores_empty_needles() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        assert_eq!(buffer.replace_all("", "x"), 0);
+        assert_eq!(buffer.data(), "scribe");
+    }
+
+    #[test]
+    fn replace_all_is_case_sensitive() {
+        let mut buffer = Buffer::new();
+        buffer.insert("foo Foo");
+        assert_eq!(buffer.replace_all("foo", "bar"), 1);
+        assert_eq!(buffer.data(), "bar Foo");
+    }
+
+    #[test]
+    fn replace_all_adds_to_an_existing_operation_group() {
+        let mut buffer = Buffer::new();
+        buffer.start_operation_group();
+        buffer.insert("foo");
+        buffer.replace_all("foo", "bar");
+        buffer.end_operation_group();
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "");
+    }
+}
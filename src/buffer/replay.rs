@@ -0,0 +1,300 @@
+//! A versioned file format for recorded command streams.
+//!
+//! Replay files let a sequence of edits (e.g. one produced by a `Fuzzer`, or
+//! recorded by a frontend) be saved and run again exactly, so that bug
+//! reports can include a reproduction. They're plain text, starting with a
+//! header naming the format version:
+//!
+//! ```text
+//! scribe-replay 1
+//! insert "scribe\nlibrary"
+//! move_to 1 3
+//! increment_number -2
+//! ```
+//!
+//! Each following line is a command, named as in `scripting::FUNCTIONS`,
+//! with its arguments separated by spaces. Integers are written in decimal,
+//! and strings are double-quoted, using `\\`, `\"`, `\n`, `\r`, `\t`, and
+//! `\u{...}` escapes. Blank lines are skipped.
+
+use buffer::{Buffer, Command};
+use scripting::{self, Value};
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The replay format version written by `write_replay`. Files using
+/// later versions are rejected by `read_replay`.
+pub const REPLAY_VERSION: u32 = 1;
+
+const HEADER: &str = "scribe-replay";
+
+/// Writes the commands to a replay file at the specified path.
+///
+/// # Examples
+///
+/// ```
+/// use scribe::Buffer;
+/// use scribe::buffer::{read_replay, write_replay, Command, Position};
+/// use std::env;
+///
+/// let commands = vec![
+///     Command::Insert("scribe \"library\"\n".to_string()),
+///     Command::MoveTo(Position{ line: 0, offset: 2 }),
+///     Command::IncrementNumber(-1),
+/// ];
+/// let path = env::temp_dir().join("scribe_write_replay_doctest");
+/// write_replay(&path, &commands).unwrap();
+///
+/// assert_eq!(read_replay(&path).unwrap(), commands);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn write_replay(path: &Path, commands: &[Command]) -> io::Result<()> {
+    fs::write(path, encode(commands))
+}
+
+/// Reads the commands from the replay file at the specified path. Returns
+/// an `InvalidData` error, describing the problem and its line number, if
+/// the file isn't a replay file, uses a later version of the format, or
+/// contains an invalid command.
+pub fn read_replay(path: &Path) -> io::Result<Vec<Command>> {
+    decode(&fs::read_to_string(path)?)
+        .map_err(|reason| io::Error::new(io::ErrorKind::InvalidData, reason))
+}
+
+impl Buffer {
+    /// Reads the commands from the replay file at the specified path (see
+    /// `read_replay`) and runs them against the buffer, returning how many
+    /// were run. If the file can't be read, no commands are run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{write_replay, Command};
+    /// use std::env;
+    ///
+    /// let path = env::temp_dir().join("scribe_replay_file_doctest");
+    /// write_replay(&path, &[
+    ///     Command::Insert("library".to_string()),
+    ///     Command::Insert("scribe ".to_string()),
+    /// ]).unwrap();
+    ///
+    /// let mut buffer = Buffer::new();
+    /// assert_eq!(buffer.replay_file(&path).unwrap(), 2);
+    /// assert_eq!(buffer.data(), "scribe library");
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn replay_file(&mut self, path: &Path) -> io::Result<usize> {
+        let commands = read_replay(path)?;
+        let count = commands.len();
+        for command in commands {
+            self.execute(command);
+        }
+
+        Ok(count)
+    }
+}
+
+fn encode(commands: &[Command]) -> String {
+    let mut data = format!("{} {}\n", HEADER, REPLAY_VERSION);
+
+    for command in commands {
+        let (function, arguments) = scripting::function(command);
+        data.push_str(function);
+        for argument in arguments {
+            data.push(' ');
+            match argument {
+                Value::Integer(value) => { let _ = write!(data, "{}", value); },
+                Value::String(value) => data.push_str(&quote(&value)),
+                _ => unreachable!("commands only take integers and strings"),
+            }
+        }
+        data.push('\n');
+    }
+
+    data
+}
+
+fn decode(data: &str) -> Result<Vec<Command>, String> {
+    let mut lines = data.lines().enumerate();
+
+    let version = lines.next()
+        .and_then(|(_, line)| line.strip_prefix(HEADER))
+        .and_then(|version| version.trim().parse::<u32>().ok())
+        .ok_or_else(|| "missing replay header on line 1".to_string())?;
+    if version > REPLAY_VERSION {
+        return Err(format!(
+            "unsupported replay version {} (the latest supported is {})",
+            version, REPLAY_VERSION
+        ));
+    }
+
+    let mut commands = Vec::new();
+    for (index, line) in lines {
+        let fail = |reason: String| format!("{} on line {}", reason, index + 1);
+
+        let line = line.trim_start_matches(' ');
+        if line.is_empty() { continue; }
+        let (function, arguments) = match line.find(' ') {
+            Some(space) => line.split_at(space),
+            None => (line, ""),
+        };
+        if function.starts_with('"') {
+            return Err(fail("expected a command name".to_string()));
+        }
+
+        let values = parse_values(arguments).map_err(fail)?;
+        let command = scripting::command(function, &values)
+            .map_err(|error| fail(error.to_string()))?;
+        commands.push(command);
+    }
+
+    Ok(commands)
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for character in value.chars() {
+        match character {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => { let _ = write!(quoted, "\\u{{{:x}}}", c as u32); },
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+// Splits a command's arguments into values.
+fn parse_values(arguments: &str) -> Result<Vec<Value>, String> {
+    let mut values = Vec::new();
+    let mut chars = arguments.chars().peekable();
+
+    loop {
+        while chars.peek() == Some(&' ') {
+            chars.next();
+        }
+        match chars.peek() {
+            None => return Ok(values),
+            Some(&'"') => {
+                chars.next();
+                values.push(Value::String(parse_string(&mut chars)?));
+            },
+            Some(_) => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == ' ' { break; }
+                    word.push(c);
+                    chars.next();
+                }
+                match word.parse::<i64>() {
+                    Ok(value) => values.push(Value::Integer(value)),
+                    Err(_) => return Err(format!("invalid argument {}", word)),
+                }
+            },
+        }
+    }
+}
+
+// Reads a quoted string's content, following its opening quote.
+fn parse_string<I: Iterator<Item=char>>(chars: &mut I) -> Result<String, String> {
+    let mut value = String::new();
+
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(value),
+            Some('\\') => value.push(match chars.next() {
+                Some('\\') => '\\',
+                Some('"') => '"',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('t') => '\t',
+                Some('u') => parse_code_point(chars)?,
+                _ => return Err("invalid escape sequence".to_string()),
+            }),
+            Some(c) => value.push(c),
+            None => return Err("unterminated string".to_string()),
+        }
+    }
+}
+
+// Reads the braced hexadecimal code point following a \u escape.
+fn parse_code_point<I: Iterator<Item=char>>(chars: &mut I) -> Result<char, String> {
+    if chars.next() != Some('{') {
+        return Err("invalid escape sequence".to_string());
+    }
+    let digits: String = chars.by_ref().take_while(|&c| c != '}').collect();
+
+    u32::from_str_radix(&digits, 16).ok()
+        .and_then(::std::char::from_u32)
+        .ok_or_else(|| format!("invalid code point {}", digits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode};
+    use buffer::{Buffer, Command, Position, Range};
+    use fuzz::Fuzzer;
+
+    #[test]
+    fn encoded_commands_are_decoded_identically() {
+        let commands = vec![
+            Command::Insert("\"scribe\"\\\n\r\t\u{7}नी".to_string()),
+            Command::Replace{
+                range: Range::new(Position{ line: 0, offset: 1 }, Position{ line: 2, offset: 0 }),
+                data: String::new(),
+            },
+            Command::IncrementNumber(-3),
+            Command::MoveToNextCell,
+        ];
+
+        let data = encode(&commands);
+        assert!(data.starts_with("scribe-replay 1\ninsert \"\\\"scribe\\\"\\\\\\n\\r\\t\\u{7}नी\"\n"));
+        assert_eq!(decode(&data).unwrap(), commands);
+    }
+
+    #[test]
+    fn fuzzed_command_streams_replay_identically() {
+        let mut buffer = Buffer::new();
+        let commands = Fuzzer::new(7).run(&mut buffer, 200);
+
+        let mut replayed = Buffer::new();
+        for command in decode(&encode(&commands)).unwrap() {
+            replayed.execute(command);
+        }
+        assert_eq!(replayed.data(), buffer.data());
+        assert_eq!(*replayed.cursor, *buffer.cursor);
+    }
+
+    #[test]
+    fn decode_rejects_invalid_files() {
+        assert_eq!(decode("insert \"scribe\"").unwrap_err(), "missing replay header on line 1");
+        assert_eq!(
+            decode("scribe-replay 2\n").unwrap_err(),
+            "unsupported replay version 2 (the latest supported is 1)"
+        );
+        assert_eq!(
+            decode("scribe-replay 1\n\ninsert \"scribe").unwrap_err(),
+            "unterminated string on line 3"
+        );
+        assert_eq!(
+            decode("scribe-replay 1\nmove_to 1").unwrap_err(),
+            "invalid arguments to move_to on line 2"
+        );
+        assert_eq!(
+            decode("scribe-replay 1\nexplode").unwrap_err(),
+            "unknown function: explode on line 2"
+        );
+        assert_eq!(
+            decode("scribe-replay 1\n\"insert\" \"x\"").unwrap_err(),
+            "expected a command name on line 2"
+        );
+    }
+}
@@ -0,0 +1,454 @@
+//! Interruptible search.
+
+use buffer::{Buffer, GapBuffer, LineRange, Position, Range};
+use buffer::gap_buffer::breaks_within;
+use std::borrow::Cow;
+#[cfg(feature = "parallel-search")]
+use std::thread;
+use unicode_segmentation::UnicodeSegmentation;
+use util::Budget;
+
+// The size (in bytes) beyond which searches are split across threads.
+#[cfg(feature = "parallel-search")]
+const PARALLEL_SEARCH_THRESHOLD: usize = 1 << 20;
+
+/// An opaque token used to resume a search that exhausted its budget.
+/// Tokens are only meaningful for the buffer and needle that produced
+/// them, and are invalidated by any modification to the buffer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchResumption {
+    line: usize,
+}
+
+/// The results of a budgeted search, along with a token that can be used to
+/// continue it, if it was interrupted before the end of the buffer.
+#[derive(Debug, PartialEq)]
+pub struct PartialSearch {
+    pub results: Vec<Position>,
+    pub resumption: Option<SearchResumption>,
+}
+
+impl Buffer {
+    /// Searches the buffer for occurrences of `needle` (see `search`),
+    /// returning the range each one occupies, in document order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary");
+    ///
+    /// assert_eq!(
+    ///     buffer.search_ranges("ib"),
+    ///     vec![
+    ///         Range::new(Position{ line: 0, offset: 3 }, Position{ line: 0, offset: 5 }),
+    ///         Range::new(Position{ line: 1, offset: 1 }, Position{ line: 1, offset: 3 })
+    ///     ]
+    /// );
+    /// ```
+    pub fn search_ranges(&self, needle: &str) -> Vec<Range> {
+        // Needles never span lines, so matches end on the line they start on.
+        let length = needle.graphemes(true).count();
+
+        self.search(needle).into_iter().map(|start| {
+            Range::new(start, Position{ line: start.line, offset: start.offset + length })
+        }).collect()
+    }
+
+    /// Searches the buffer for occurrences of `needle` (see `search`), doing
+    /// no more work than the specified budget allows, in lines searched or
+    /// elapsed time. When the budget runs out, the results found so far are
+    /// returned with a resumption token, which can be passed to a subsequent
+    /// call to pick up where the search left off, allowing interactive
+    /// applications to spread searches of large buffers across their event
+    /// loop iterations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    /// use scribe::util::Budget;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary\nscribe");
+    ///
+    /// let first = buffer.search_with_budget("scribe", Budget::Units(2), None);
+    /// assert_eq!(first.results, vec![Position{ line: 0, offset: 0 }]);
+    ///
+    /// let second = buffer.search_with_budget("scribe", Budget::Units(2), first.resumption);
+    /// assert_eq!(second.results, vec![Position{ line: 2, offset: 0 }]);
+    /// assert!(second.resumption.is_none());
+    /// ```
+    pub fn search_with_budget(&self, needle: &str, budget: Budget,
+                              resumption: Option<SearchResumption>) -> PartialSearch {
+        let start = resumption.map(|resumption| resumption.line).unwrap_or(0);
+        let lines = self.lines(&LineRange::new(start, self.line_count()));
+        let mut meter = budget.meter();
+        let mut results = Vec::new();
+
+        for (line, content) in (start..).zip(lines.into_iter().flatten()) {
+            if !meter.permit() {
+                return PartialSearch{ results, resumption: Some(SearchResumption{ line }) };
+            }
+
+            search_line(&content, line, needle, &mut results);
+        }
+
+        PartialSearch{ results, resumption: None }
+    }
+}
+
+// Finds the positions of all occurrences of the needle in the data, searching
+// it in place. With the parallel-search feature enabled, large data is instead
+// searched using a thread per available core, each handling a contiguous chunk
+// of lines.
+pub(crate) fn search_data(data: &GapBuffer, needle: &str) -> Vec<Position> {
+    #[cfg(feature = "parallel-search")]
+    {
+        if data.len() >= PARALLEL_SEARCH_THRESHOLD {
+            return parallel_search(&data.to_string(), needle);
+        }
+    }
+
+    let (first, second) = data.byte_slices(0, data.len());
+    search_halves(first, second, needle)
+}
+
+// Finds the positions of all occurrences of the needle in the content on
+// either side of a gap buffer's gap, including those straddling it. Needles
+// spanning multiple lines never match, as with line-by-line searches.
+fn search_halves(first: &str, second: &str, needle: &str) -> Vec<Position> {
+    let mut offsets = Vec::new();
+    if needle.is_empty() || needle.contains('\n') {
+        return Vec::new();
+    }
+
+    find_overlapping(first, needle, 0, &mut offsets);
+
+    // Matches straddling the gap start within the needle's length of it.
+    for start in first.len().saturating_sub(needle.len() - 1)..first.len() {
+        let length = first.len() - start;
+        if !first.is_char_boundary(start) || !needle.is_char_boundary(length) { continue; }
+
+        let (head, tail) = needle.split_at(length);
+        if first[start..] == *head && second.starts_with(tail) {
+            offsets.push(start);
+        }
+    }
+
+    find_overlapping(second, needle, first.len(), &mut offsets);
+    offsets = whole_grapheme_matches(first, second, offsets, needle.len());
+
+    positions_of(first, second, &offsets)
+}
+
+// Discards matches that start or end partway through a grapheme cluster,
+// consistent with line-by-line searches (see search_line). The boundaries
+// of each line containing a match are only found once, and ASCII lines
+// (whose characters are all their own graphemes) aren't segmented at all.
+fn whole_grapheme_matches(first: &str, second: &str, offsets: Vec<usize>, length: usize) -> Vec<usize> {
+    let mut line: Option<(usize, usize, Option<Vec<usize>>)> = None;
+
+    offsets.into_iter().filter(|&offset| {
+        if !line.as_ref().is_some_and(|&(start, end, _)| start <= offset && offset <= end) {
+            let (start, end) = line_around(first, second, offset);
+            let content = segment(first, second, start, end);
+            let boundaries = if content.is_ascii() {
+                None
+            } else {
+                Some(content.grapheme_indices(true).map(|(index, _)| start + index).chain(Some(end)).collect())
+            };
+            line = Some((start, end, boundaries));
+        }
+
+        match line {
+            Some((_, _, Some(ref boundaries))) => {
+                boundaries.binary_search(&offset).is_ok() && boundaries.binary_search(&(offset + length)).is_ok()
+            },
+            _ => true,
+        }
+    }).collect()
+}
+
+// The byte offsets of the start and end (excluding its
+// line break) of the line containing the specified offset.
+fn line_around(first: &str, second: &str, offset: usize) -> (usize, usize) {
+    let split = first.len();
+    let (before, after) = if offset <= split {
+        (first[..offset].rfind('\n'), first[offset..].find('\n').map(|index| offset + index))
+    } else {
+        let before = second[..offset - split].rfind('\n').map(|index| split + index);
+        (before.or_else(|| first.rfind('\n')), second[offset - split..].find('\n').map(|index| offset + index))
+    };
+    let after = after.or_else(|| {
+        if offset <= split { second.find('\n').map(|index| split + index) } else { None }
+    });
+
+    (before.map_or(0, |index| index + 1), after.unwrap_or(split + second.len()))
+}
+
+// Adds the offsets of all (possibly overlapping) occurrences of the needle.
+fn find_overlapping(haystack: &str, needle: &str, base: usize, offsets: &mut Vec<usize>) {
+    let mut from = 0;
+
+    while let Some(index) = haystack[from..].find(needle) {
+        let start = from + index;
+        offsets.push(base + start);
+
+        // Resume from the next character, to find overlapping matches.
+        from = start + haystack[start..].chars().next().map_or(1, char::len_utf8);
+    }
+}
+
+// Converts ordered byte offsets spanning the halves into positions.
+pub(crate) fn positions_of(first: &str, second: &str, offsets: &[usize]) -> Vec<Position> {
+    let mut positions = Vec::with_capacity(offsets.len());
+    let mut line = 0;
+    let mut line_start = 0;
+    let mut scanned = 0;
+
+    for &offset in offsets {
+        // Advance past any newlines preceding the match.
+        for index in breaks_within(&segment(first, second, scanned, offset)) {
+            line += 1;
+            line_start = scanned + index + 1;
+        }
+        scanned = offset;
+
+        // Only the line containing the gap needs to be copied
+        // to count its graphemes, and only if the gap precedes the match.
+        let prefix = segment(first, second, line_start, offset);
+        let offset = if prefix.is_ascii() && !prefix.contains("\r\n") {
+            // Every other ASCII character is its own grapheme.
+            prefix.len()
+        } else {
+            prefix.graphemes(true).count()
+        };
+        positions.push(Position{ line, offset });
+    }
+
+    positions
+}
+
+// Borrows the content between the byte offsets, copying it only if it spans the gap.
+fn segment<'a>(first: &'a str, second: &'a str, start: usize, end: usize) -> Cow<'a, str> {
+    let split = first.len();
+
+    if end <= split {
+        Cow::Borrowed(&first[start..end])
+    } else if start >= split {
+        Cow::Borrowed(&second[start - split..end - split])
+    } else {
+        Cow::Owned(format!("{}{}", &first[start..], &second[..end - split]))
+    }
+}
+
+#[cfg(feature = "parallel-search")]
+fn parallel_search(data: &str, needle: &str) -> Vec<Position> {
+    let mut start = 0;
+    let mut lines: Vec<&str> = breaks_within(data).map(|end| {
+        let line = &data[start..end];
+        start = end + 1;
+        line
+    }).collect();
+    lines.push(&data[start..]);
+    let threads = thread::available_parallelism().map(|count| count.get()).unwrap_or(1);
+    let chunk_size = lines.len().div_ceil(threads).max(1);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = lines.chunks(chunk_size).enumerate().map(|(index, chunk)| {
+            scope.spawn(move || {
+                let mut results = Vec::new();
+                for (chunk_line, content) in chunk.iter().enumerate() {
+                    search_line(content, index * chunk_size + chunk_line, needle, &mut results);
+                }
+
+                results
+            })
+        }).collect();
+
+        // Joining the threads in the order they were spawned
+        // keeps the merged results in document order.
+        handles.into_iter().flat_map(|handle| {
+            handle.join().expect("search thread panicked")
+        }).collect()
+    })
+}
+
+// Adds the positions of any occurrences of the needle within
+// the specified line's content to the list of results.
+pub(crate) fn search_line(content: &str, line: usize, needle: &str, results: &mut Vec<Position>) {
+    if needle.is_empty() { return; }
+
+    // Matches must also end on a grapheme cluster boundary.
+    let boundaries: Vec<usize> = content.grapheme_indices(true)
+        .map(|(index, _)| index)
+        .chain(Some(content.len()))
+        .collect();
+
+    for (offset, &index) in boundaries[..boundaries.len() - 1].iter().enumerate() {
+        let ends_on_boundary = boundaries.binary_search(&(index + needle.len())).is_ok();
+        if content[index..].starts_with(needle) && ends_on_boundary {
+            results.push(
+                Position{
+                    line,
+                    offset
+                }
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position};
+    use std::time::Duration;
+    use util::Budget;
+
+    #[test]
+    fn search_ranges_measures_matches_in_graphemes() {
+        let mut buffer = Buffer::new();
+        buffer.insert("ñañ\nañ");
+
+        let ranges = buffer.search_ranges("añ");
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[0].start(), Position{ line: 0, offset: 1 });
+        assert_eq!(ranges[0].end(), Position{ line: 0, offset: 3 });
+        assert_eq!(ranges[1].start(), Position{ line: 1, offset: 0 });
+        assert_eq!(ranges[1].end(), Position{ line: 1, offset: 2 });
+    }
+
+    #[test]
+    fn search_with_budget_matches_search_when_resumed_to_completion() {
+        let mut buffer = Buffer::new();
+        buffer.insert("ab\nab ab\n\nb ab");
+
+        let mut results = Vec::new();
+        let mut resumption = None;
+        loop {
+            let partial = buffer.search_with_budget("ab", Budget::Units(1), resumption);
+            results.extend(partial.results);
+            resumption = partial.resumption;
+
+            if resumption.is_none() { break; }
+        }
+
+        assert_eq!(results, buffer.search("ab"));
+    }
+
+    #[test]
+    fn search_with_budget_always_makes_progress() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nscribe");
+
+        let partial = buffer.search_with_budget("scribe", Budget::Time(Duration::from_secs(0)), None);
+        assert_eq!(partial.results, vec![Position{ line: 0, offset: 0 }]);
+        assert!(partial.resumption.is_some());
+    }
+
+    #[test]
+    fn search_finds_matches_straddling_the_gap() {
+        let mut buffer = Buffer::new();
+        buffer.insert("library\nscribe scr-ibe");
+        // Move the gap into the middle of the first "scr-ibe".
+        buffer.cursor.move_to(Position{ line: 1, offset: 3 });
+        buffer.insert("-");
+
+        assert_eq!(
+            buffer.search("scr-ibe"),
+            vec![Position{ line: 1, offset: 0 }, Position{ line: 1, offset: 8 }]
+        );
+    }
+
+    #[test]
+    fn search_finds_overlapping_matches() {
+        let mut buffer = Buffer::new();
+        buffer.insert("aaa");
+
+        assert_eq!(
+            buffer.search("aa"),
+            vec![Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 1 }]
+        );
+    }
+
+    #[test]
+    fn search_returns_grapheme_offsets() {
+        let mut buffer = Buffer::new();
+        buffer.insert("ñandú scribe\nnaïve scribe");
+        buffer.cursor.move_to(Position{ line: 1, offset: 2 });
+        buffer.insert("-");
+
+        assert_eq!(
+            buffer.search("scribe"),
+            vec![Position{ line: 0, offset: 6 }, Position{ line: 1, offset: 7 }]
+        );
+        assert_eq!(
+            buffer.search_with_budget("scribe", Budget::Units(2), None).results,
+            buffer.search("scribe")
+        );
+    }
+
+    #[test]
+    fn search_only_matches_whole_graphemes() {
+        let mut buffer = Buffer::new();
+        buffer.insert("cafe\u{301} caf\u{301}e\ncafe");
+        // Move the gap to the start of the buffer, so that it precedes the matches.
+        buffer.cursor.move_to(Position::new());
+        buffer.insert("-");
+
+        // Neither the unaccented "e" nor the lone accent are whole graphemes.
+        assert_eq!(buffer.search("cafe"), vec![Position{ line: 1, offset: 0 }]);
+        assert!(buffer.search("\u{301}").is_empty());
+        assert_eq!(buffer.search("e\u{301}"), vec![Position{ line: 0, offset: 4 }]);
+
+        // Line-by-line searches apply the same rule.
+        assert_eq!(
+            buffer.search_with_budget("cafe", Budget::Units(2), None).results,
+            buffer.search("cafe")
+        );
+        assert_eq!(
+            buffer.search_with_budget("\u{301}", Budget::Units(2), None).results,
+            buffer.search("\u{301}")
+        );
+    }
+
+    #[test]
+    fn search_positions_treat_carriage_return_line_feeds_as_graphemes() {
+        let mut buffer = Buffer::new();
+        buffer.insert("a\r\nb\nb");
+
+        let expected = vec![Position{ line: 0, offset: 2 }, Position{ line: 1, offset: 0 }];
+        assert_eq!(buffer.search("b"), expected);
+        assert_eq!(buffer.search_with_budget("b", Budget::Units(1), None).results, expected[..1]);
+    }
+
+    #[test]
+    fn search_does_not_match_across_lines() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary");
+
+        assert!(buffer.search("e\nl").is_empty());
+        assert!(buffer.search("").is_empty());
+    }
+
+    #[cfg(feature = "parallel-search")]
+    #[test]
+    fn search_returns_ordered_results_for_parallelized_searches() {
+        use super::PARALLEL_SEARCH_THRESHOLD;
+
+        let line = "scribe library\n";
+        let line_count = PARALLEL_SEARCH_THRESHOLD / line.len() + 1;
+        let mut buffer = Buffer::new();
+        buffer.insert(line.repeat(line_count));
+
+        let results = buffer.search("library");
+        assert_eq!(results.len(), line_count);
+        assert!(results.iter().enumerate().all(|(line, position)| {
+            *position == Position{ line, offset: 7 }
+        }));
+    }
+}
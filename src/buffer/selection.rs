@@ -0,0 +1,254 @@
+//! Multiple selections, edited simultaneously.
+
+use buffer::{Buffer, Distance, Edit, Position, Range};
+use errors::*;
+
+impl Buffer {
+    /// The buffer's selections, in the order they appear in the buffer. They
+    /// never overlap, and are adjusted as the buffer is modified, so that
+    /// they continue to cover the same content.
+    pub fn selections(&self) -> &[Range] {
+        &self.selections
+    }
+
+    /// Selects every occurrence of `needle` (typically the word under the
+    /// cursor), replacing any existing selections. Occurrences overlapping a
+    /// preceding occurrence are skipped. Returns the number of selections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("let value = value + 1;\nvalue");
+    ///
+    /// assert_eq!(buffer.select_all_occurrences("value"), 3);
+    /// buffer.replace_selections("total");
+    /// assert_eq!(buffer.data(), "let total = total + 1;\ntotal");
+    ///
+    /// // The renamed occurrences remain selected.
+    /// buffer.replace_selections("sum");
+    /// assert_eq!(buffer.data(), "let sum = sum + 1;\nsum");
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "let total = total + 1;\ntotal");
+    /// ```
+    pub fn select_all_occurrences(&mut self, needle: &str) -> usize {
+        self.selections.clear();
+        for range in self.search_ranges(needle) {
+            let overlapping = self.selections.last().is_some_and(|previous| previous.end() > range.start());
+
+            if !overlapping {
+                self.selections.push(range);
+            }
+        }
+
+        self.selections.len()
+    }
+
+    /// Replaces the buffer's selections with the specified ranges (e.g. rename
+    /// locations reported by a language server, or search results produced
+    /// by another tool), and moves the cursor to the start of the first.
+    /// Ranges are sorted, and those that overlap are merged. Returns the
+    /// number of selections.
+    ///
+    /// If any of the ranges are out of bounds, an `OutOfBounds` error is
+    /// returned for the first of them (see `check_position`), and the
+    /// selections are left as-is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("let value = 1;\nvalue");
+    ///
+    /// let ranges = vec![
+    ///     Range::new(Position{ line: 1, offset: 0 }, Position{ line: 1, offset: 5 }),
+    ///     Range::new(Position{ line: 0, offset: 4 }, Position{ line: 0, offset: 9 }),
+    /// ];
+    /// assert_eq!(buffer.cursors_from_ranges(ranges).unwrap(), 2);
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 4 });
+    ///
+    /// buffer.replace_selections("total");
+    /// assert_eq!(buffer.data(), "let total = 1;\ntotal");
+    ///
+    /// let out_of_bounds = Range::new(Position{ line: 0, offset: 4 }, Position{ line: 2, offset: 0 });
+    /// assert!(buffer.cursors_from_ranges(vec![out_of_bounds]).is_err());
+    /// assert_eq!(buffer.selections().len(), 2);
+    /// ```
+    pub fn cursors_from_ranges(&mut self, mut ranges: Vec<Range>) -> Result<usize> {
+        for range in &ranges {
+            self.check_position(&range.start())?;
+            self.check_position(&range.end())?;
+        }
+
+        ranges.sort_by(|a, b| a.start().partial_cmp(&b.start()).unwrap());
+        let mut selections: Vec<Range> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match selections.last_mut() {
+                Some(previous) if range.start() < previous.end() || range == *previous => {
+                    if range.end() > previous.end() {
+                        *previous = Range::new(previous.start(), range.end());
+                    }
+                },
+                _ => selections.push(range),
+            }
+        }
+
+        if let Some(first) = selections.first() {
+            self.cursor.move_to(first.start());
+        }
+        self.selections = selections;
+
+        Ok(self.selections.len())
+    }
+
+    /// Discards the buffer's selections.
+    pub fn clear_selections(&mut self) {
+        self.selections.clear();
+    }
+
+    /// Replaces the content of every selection with `data`, as a single
+    /// undoable operation. The selections are updated to cover the
+    /// replacement content, so that they can be replaced again.
+    pub fn replace_selections(&mut self, data: &str) {
+        if self.read_only { return; }
+
+        self.batch(|buffer| {
+            // Work backwards, so that replacements don't move
+            // the selections that have yet to be replaced.
+            for index in (0..buffer.selections.len()).rev() {
+                let range = buffer.selections[index].clone();
+                buffer.delete_range(range.clone());
+                let inserted = buffer.insert_transformed(data.to_string(), range.start());
+
+                let start = range.start();
+                buffer.selections[index] = Range::new(start, start + Distance::of_str(&inserted));
+            }
+        });
+    }
+
+    // Adjusts the selections to account for the edit, collapsing
+    // those whose content is removed, and discarding duplicates.
+    pub(crate) fn shift_selections(&mut self, edit: &Edit) {
+        if self.selections.is_empty() { return; }
+
+        for selection in &mut self.selections {
+            let start = shift_position(selection.start(), edit, false);
+            let end = if selection.end() == selection.start() {
+                start
+            } else {
+                shift_position(selection.end(), edit, true)
+            };
+
+            *selection = Range::new(start, end);
+        }
+        self.selections.dedup();
+    }
+}
+
+// Moves the position to account for the edit. Edits either insert or
+// remove content; positions within removed content are moved to its start.
+// Content inserted at a selection's end isn't considered part of it.
+pub(crate) fn shift_position(position: Position, edit: &Edit, selection_end: bool) -> Position {
+    let (start, end) = (edit.range.start(), edit.range.end());
+
+    if !edit.removed.is_empty() {
+        if position <= start {
+            position
+        } else if position < end {
+            start
+        } else if position.line == end.line {
+            Position{ line: start.line, offset: start.offset + position.offset - end.offset }
+        } else {
+            Position{ line: position.line - (end.line - start.line), offset: position.offset }
+        }
+    } else if position < start || (selection_end && position == start) {
+        position
+    } else if position.line == start.line {
+        Position{ line: end.line, offset: end.offset + position.offset - start.offset }
+    } else {
+        Position{ line: position.line + end.line - start.line, offset: position.offset }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, Range};
+
+    #[test]
+    fn select_all_occurrences_skips_overlapping_matches() {
+        let mut buffer = Buffer::new();
+        buffer.insert("aaaa");
+
+        assert_eq!(buffer.select_all_occurrences("aa"), 2);
+        assert_eq!(buffer.selections()[1], Range::new(
+            Position{ line: 0, offset: 2 },
+            Position{ line: 0, offset: 4 }
+        ));
+    }
+
+    #[test]
+    fn selections_follow_their_content_through_other_edits() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nlibrary scribe");
+        buffer.select_all_occurrences("scribe");
+
+        buffer.insert("a\nb");
+        assert_eq!(buffer.selections(), &[
+            Range::new(Position{ line: 1, offset: 1 }, Position{ line: 1, offset: 7 }),
+            Range::new(Position{ line: 2, offset: 8 }, Position{ line: 2, offset: 14 }),
+        ]);
+
+        buffer.undo();
+        assert_eq!(buffer.selections()[0], Range::new(
+            Position{ line: 0, offset: 0 },
+            Position{ line: 0, offset: 6 }
+        ));
+
+        buffer.delete_range(Range::new(Position{ line: 0, offset: 3 }, Position{ line: 1, offset: 2 }));
+        assert_eq!(buffer.selections(), &[
+            Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 3 }),
+            Range::new(Position{ line: 0, offset: 9 }, Position{ line: 0, offset: 15 }),
+        ]);
+    }
+
+    #[test]
+    fn replace_selections_handles_adjacent_and_multi_line_replacements() {
+        let mut buffer = Buffer::new();
+        buffer.insert("abab\nab");
+        buffer.select_all_occurrences("ab");
+
+        buffer.replace_selections("x\ny");
+        assert_eq!(buffer.data(), "x\nyx\ny\nx\ny");
+        assert_eq!(buffer.selections()[1], Range::new(
+            Position{ line: 1, offset: 1 },
+            Position{ line: 2, offset: 1 }
+        ));
+
+        buffer.replace_selections("");
+        assert_eq!(buffer.data(), "\n");
+    }
+
+    #[test]
+    fn cursors_from_ranges_merges_overlapping_ranges() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe library");
+
+        let ranges = vec![
+            Range::new(Position{ line: 0, offset: 4 }, Position{ line: 0, offset: 8 }),
+            Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 6 }),
+            Range::new(Position{ line: 0, offset: 9 }, Position{ line: 0, offset: 9 }),
+            Range::new(Position{ line: 0, offset: 9 }, Position{ line: 0, offset: 9 }),
+        ];
+        assert_eq!(buffer.cursors_from_ranges(ranges).unwrap(), 2);
+        assert_eq!(buffer.selections(), &[
+            Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 8 }),
+            Range::new(Position{ line: 0, offset: 9 }, Position{ line: 0, offset: 9 }),
+        ]);
+    }
+}
@@ -0,0 +1,167 @@
+//! Characters that should be rendered visibly, rather than as-is.
+
+use buffer::{Buffer, LineRange, Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+// Invisible formatting characters (e.g. zero-width spaces,
+// byte order marks, and bidirectional overrides).
+const FORMAT_CHARACTERS: &[(char, char)] = &[
+    ('\u{AD}', '\u{AD}'),
+    ('\u{61C}', '\u{61C}'),
+    ('\u{200B}', '\u{200F}'),
+    ('\u{202A}', '\u{202E}'),
+    ('\u{2060}', '\u{2064}'),
+    ('\u{2066}', '\u{2069}'),
+    ('\u{FEFF}', '\u{FEFF}'),
+];
+
+/// The category of a special character.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpecialCharacterKind {
+    /// A control character other than a newline or tab (e.g. a carriage return).
+    Control,
+
+    /// An invisible formatting character (e.g. a zero-width space).
+    Format,
+
+    /// The replacement character, which stands in for invalid byte sequences.
+    Replacement,
+}
+
+/// A character that would corrupt or be missing from a buffer's display if
+/// rendered as-is, produced by `Buffer::special_characters`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpecialCharacter {
+    pub range: Range,
+    pub kind: SpecialCharacterKind,
+    pub character: char,
+}
+
+impl SpecialCharacter {
+    /// A visible representation of the character: caret notation for ASCII
+    /// control characters (e.g. "^M"), and its code point otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::LineRange;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\r\u{200B}");
+    ///
+    /// let representations: Vec<String> = buffer
+    ///     .special_characters(&LineRange::new(0, 1))
+    ///     .iter()
+    ///     .map(|character| character.representation())
+    ///     .collect();
+    /// assert_eq!(representations, vec!["^M", "<U+200B>"]);
+    /// ```
+    pub fn representation(&self) -> String {
+        match self.character as u32 {
+            code @ 0..=0x1F => format!("^{}", (code as u8 + b'@') as char),
+            0x7F => "^?".to_string(),
+            code => format!("<U+{:04X}>", code),
+        }
+    }
+}
+
+impl Buffer {
+    /// Finds the control characters, invisible formatting characters, and
+    /// replacement characters (see `SpecialCharacterKind`) on the specified
+    /// lines, reading only those lines. Frontends can use these to render the
+    /// characters' `representation` in place of tokens' content, so that
+    /// they're visible, and don't disrupt the display's layout.
+    ///
+    /// Formatting characters within grapheme clusters (e.g. zero-width joiners
+    /// in emoji sequences) are part of a visible character, and are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{LineRange, Position, Range, SpecialCharacterKind};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nli\u{FFFD}rary\u{7}");
+    ///
+    /// let characters = buffer.special_characters(&LineRange::new(1, 2));
+    /// assert_eq!(characters.len(), 2);
+    /// assert_eq!(characters[0].kind, SpecialCharacterKind::Replacement);
+    /// assert_eq!(characters[0].range, Range::new(
+    ///     Position{ line: 1, offset: 2 },
+    ///     Position{ line: 1, offset: 3 }
+    /// ));
+    /// assert_eq!(characters[1].kind, SpecialCharacterKind::Control);
+    /// ```
+    pub fn special_characters(&self, lines: &LineRange) -> Vec<SpecialCharacter> {
+        let mut characters = Vec::new();
+        let lines_iter = match self.lines(lines) {
+            Some(lines_iter) => lines_iter,
+            None => return characters,
+        };
+
+        for (index, content) in lines_iter.enumerate() {
+            let line = lines.start() + index;
+
+            for (offset, grapheme) in content.graphemes(true).enumerate() {
+                let mut chars = grapheme.chars();
+                let character = match (chars.next(), chars.next()) {
+                    (Some(character), None) => character,
+                    _ => continue,
+                };
+
+                if let Some(kind) = kind_of(character) {
+                    characters.push(SpecialCharacter{
+                        range: Range::new(
+                            Position{ line, offset },
+                            Position{ line, offset: offset + 1 }
+                        ),
+                        kind,
+                        character,
+                    });
+                }
+            }
+        }
+
+        characters
+    }
+}
+
+// Categorizes the character, if it should be rendered visibly.
+fn kind_of(character: char) -> Option<SpecialCharacterKind> {
+    if character == '\u{FFFD}' {
+        Some(SpecialCharacterKind::Replacement)
+    } else if character.is_control() && character != '\t' && character != '\n' {
+        Some(SpecialCharacterKind::Control)
+    } else if FORMAT_CHARACTERS.iter().any(|&(start, end)| start <= character && character <= end) {
+        Some(SpecialCharacterKind::Format)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, LineRange, SpecialCharacterKind};
+
+    #[test]
+    fn special_characters_skips_tabs_and_joined_graphemes() {
+        let mut buffer = Buffer::new();
+        buffer.insert("\tscribe \u{1F468}\u{200D}\u{1F469}\n");
+
+        assert!(buffer.special_characters(&LineRange::new(0, 2)).is_empty());
+    }
+
+    #[test]
+    fn special_characters_reports_positions_in_graphemes() {
+        let mut buffer = Buffer::new();
+        buffer.insert("नी\u{FEFF}\u{7F}");
+
+        let characters = buffer.special_characters(&LineRange::new(0, 2));
+        assert_eq!(characters[0].kind, SpecialCharacterKind::Format);
+        assert_eq!(characters[0].range.start().offset, 1);
+        assert_eq!(characters[1].representation(), "^?");
+        assert_eq!(characters[1].range.start().offset, 2);
+    }
+}
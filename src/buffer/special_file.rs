@@ -0,0 +1,119 @@
+//! Detection of files that can't be edited as text.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Types of files that buffers refuse to load, as their contents either
+/// aren't text (directories and devices) or can't be read without blocking
+/// or consuming them (FIFOs and sockets). Returned by `Buffer::from_file`
+/// as the inner error of an `io::Error`.
+///
+/// # Examples
+///
+/// ```
+/// use scribe::Buffer;
+/// use scribe::buffer::SpecialFile;
+/// use std::path::Path;
+///
+/// let error = Buffer::from_file(Path::new("tests/sample")).err().unwrap();
+/// let special_file = error.get_ref().and_then(|e| e.downcast_ref::<SpecialFile>());
+/// assert_eq!(special_file, Some(&SpecialFile::Directory));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpecialFile {
+    Directory,
+    Fifo,
+    Device,
+    Socket,
+}
+
+impl fmt::Display for SpecialFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match *self {
+            SpecialFile::Directory => "path is a directory",
+            SpecialFile::Fifo => "path is a FIFO",
+            SpecialFile::Device => "path is a device file",
+            SpecialFile::Socket => "path is a socket",
+        };
+
+        f.write_str(description)
+    }
+}
+
+impl Error for SpecialFile {}
+
+// Returns an error if the path refers to a special file. Paths that
+// can't be inspected (e.g. because they don't exist) are allowed.
+pub(crate) fn check(path: &Path) -> io::Result<()> {
+    let file_type = match fs::metadata(path) {
+        Ok(metadata) => metadata.file_type(),
+        Err(_) => return Ok(()),
+    };
+
+    match special_file(&file_type) {
+        Some(SpecialFile::Directory) => Err(io::Error::new(io::ErrorKind::IsADirectory, SpecialFile::Directory)),
+        Some(special_file) => Err(io::Error::new(io::ErrorKind::InvalidInput, special_file)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+fn special_file(file_type: &fs::FileType) -> Option<SpecialFile> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if file_type.is_dir() {
+        Some(SpecialFile::Directory)
+    } else if file_type.is_fifo() {
+        Some(SpecialFile::Fifo)
+    } else if file_type.is_block_device() || file_type.is_char_device() {
+        Some(SpecialFile::Device)
+    } else if file_type.is_socket() {
+        Some(SpecialFile::Socket)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn special_file(file_type: &fs::FileType) -> Option<SpecialFile> {
+    if file_type.is_dir() {
+        Some(SpecialFile::Directory)
+    } else {
+        None
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use buffer::{Buffer, SpecialFile};
+    use std::io;
+    use std::path::Path;
+    use std::process::Command;
+    use std::env;
+    use std::fs;
+
+    fn special_file(error: &io::Error) -> Option<SpecialFile> {
+        error.get_ref().and_then(|e| e.downcast_ref::<SpecialFile>()).cloned()
+    }
+
+    #[test]
+    fn from_file_refuses_fifos_without_blocking() {
+        let path = env::temp_dir().join("scribe_from_file_refuses_fifos");
+        let _ = fs::remove_file(&path);
+        assert!(Command::new("mkfifo").arg(&path).status().unwrap().success());
+
+        let error = Buffer::from_file(&path).err().unwrap();
+        assert_eq!(special_file(&error), Some(SpecialFile::Fifo));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn from_file_refuses_device_files() {
+        let error = Buffer::from_file(Path::new("/dev/null")).err().unwrap();
+        assert_eq!(special_file(&error), Some(SpecialFile::Device));
+    }
+}
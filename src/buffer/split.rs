@@ -0,0 +1,132 @@
+//! Splitting buffers apart and joining them together.
+
+use buffer::{Buffer, GapBuffer, Position, Range};
+
+impl Buffer {
+    /// Splits the buffer's contents at the specified position, returning two
+    /// new buffers holding the content before and after it, respectively.
+    /// Neither buffer is bound to a path, but both inherit the original
+    /// buffer's syntax definition. Returns None if the position is
+    /// out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\nlibrary");
+    ///
+    /// let (first, second) = buffer.split_at(&Position{ line: 1, offset: 0 }).unwrap();
+    /// assert_eq!(first.data(), "scribe\n");
+    /// assert_eq!(second.data(), "library");
+    /// ```
+    pub fn split_at(&self, position: &Position) -> Option<(Buffer, Buffer)> {
+        let first_range = Range::new(Position::new(), *position);
+        let second_range = Range::new(*position, self.end_position());
+        let first_data = self.read(&first_range)?;
+        let second_data = self.read(&second_range)?;
+
+        Some((self.derive(first_data), self.derive(second_data)))
+    }
+
+    /// Appends the contents of another buffer to the end of this one, as a
+    /// single undoable operation. If this buffer doesn't have a syntax
+    /// definition, it adopts the other buffer's definition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// let mut other_buffer = Buffer::new();
+    /// buffer.insert("scribe\n");
+    /// other_buffer.insert("library");
+    ///
+    /// buffer.append_buffer(&other_buffer);
+    /// assert_eq!(buffer.data(), "scribe\nlibrary");
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "scribe\n");
+    /// ```
+    pub fn append_buffer(&mut self, other: &Buffer) {
+        if self.read_only { return; }
+
+        let end = self.end_position();
+        self.insert_at(other.data(), end);
+
+        #[cfg(feature = "syntax")]
+        {
+            if self.syntax_definition.is_none() {
+                self.syntax_definition = other.syntax_definition.clone();
+            }
+        }
+    }
+
+    // Builds a new, unbound buffer with the specified data,
+    // sharing this buffer's syntax definition.
+    pub(crate) fn derive(&self, data: String) -> Buffer {
+        #[cfg_attr(not(feature = "syntax"), allow(unused_mut))]
+        let mut buffer = Buffer::new();
+        *buffer.data.borrow_mut() = GapBuffer::new(data);
+        #[cfg(feature = "syntax")]
+        {
+            buffer.syntax_definition = self.syntax_definition.clone();
+        }
+
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position};
+    #[cfg(feature = "syntax")]
+    use syntect::parsing::SyntaxSet;
+
+    #[test]
+    fn split_at_returns_none_for_out_of_bounds_positions() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        assert!(buffer.split_at(&Position{ line: 1, offset: 0 }).is_none());
+    }
+
+    #[test]
+    fn split_at_handles_grapheme_clusters() {
+        let mut buffer = Buffer::new();
+        buffer.insert("नी scribe नी");
+
+        let (first, second) = buffer.split_at(&Position{ line: 0, offset: 1 }).unwrap();
+        assert_eq!(first.data(), "नी");
+        assert_eq!(second.data(), " scribe नी");
+    }
+
+    #[test]
+    #[cfg(feature = "syntax")]
+    fn split_at_produces_buffers_without_history_that_share_syntax() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        buffer.syntax_definition = Some(syntax_set.find_syntax_plain_text().clone());
+
+        let (mut first, _) = buffer.split_at(&Position{ line: 0, offset: 3 }).unwrap();
+        assert!(first.syntax_definition.is_some());
+
+        first.undo();
+        assert_eq!(first.data(), "scr");
+    }
+
+    #[test]
+    #[cfg(feature = "syntax")]
+    fn append_buffer_adopts_missing_syntax_definitions() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let mut buffer = Buffer::new();
+        let mut other_buffer = Buffer::new();
+        other_buffer.syntax_definition = Some(syntax_set.find_syntax_plain_text().clone());
+
+        buffer.append_buffer(&other_buffer);
+        assert!(buffer.syntax_definition.is_some());
+    }
+}
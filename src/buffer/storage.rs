@@ -0,0 +1,135 @@
+//! Pluggable persistence for buffer content.
+
+use buffer::{conflict, line_ending, Buffer, Cursor, GapBuffer, Position};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// A place buffer content can be loaded from and saved to, using
+/// `Buffer::from_storage` and `Buffer::save_to`. Implementing it allows
+/// buffers to be backed by something other than the local file system
+/// (e.g. a browser's local storage, or a remote service), without relying
+/// on the file-based methods (e.g. `from_file` and `save`).
+pub trait Storage {
+    /// Reads the content stored at the path.
+    fn read(&self, path: &Path) -> io::Result<String>;
+
+    /// Replaces the content stored at the path.
+    fn write(&mut self, path: &Path, data: &[u8]) -> io::Result<()>;
+}
+
+/// A `Storage` implementation that holds content in memory, keyed by path.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    pub files: HashMap<PathBuf, String>,
+}
+
+impl MemoryStorage {
+    /// Creates empty storage.
+    pub fn new() -> MemoryStorage {
+        Default::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, path: &Path) -> io::Result<String> {
+        self.files.get(path).cloned().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "no content stored at path")
+        })
+    }
+
+    fn write(&mut self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let data = String::from_utf8(data.to_vec()).map_err(|error| {
+            io::Error::new(io::ErrorKind::InvalidData, error)
+        })?;
+        self.files.insert(path.to_path_buf(), data);
+
+        Ok(())
+    }
+}
+
+impl Buffer {
+    /// Creates a new buffer holding the content stored at the path, using
+    /// the specified storage rather than the file system. Unlike `from_file`,
+    /// the path is stored as-is, since it may not refer to a local file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::MemoryStorage;
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// let mut storage = MemoryStorage::new();
+    /// storage.files.insert(PathBuf::from("notes"), "scribe".to_string());
+    ///
+    /// let mut buffer = Buffer::from_storage(&storage, Path::new("notes")).unwrap();
+    /// buffer.insert("edited ");
+    /// buffer.save_to(&mut storage).unwrap();
+    ///
+    /// assert!(!buffer.modified());
+    /// assert_eq!(storage.files[Path::new("notes")], "edited scribe");
+    /// ```
+    pub fn from_storage<S: Storage + ?Sized>(storage: &S, path: &Path) -> io::Result<Buffer> {
+        let decoded = line_ending::decode(storage.read(path)?);
+        let loaded_with_conflicts = !conflict::find_conflicts(&decoded.data).is_empty();
+
+        let data = Rc::new(RefCell::new(GapBuffer::new(decoded.data)));
+        let cursor = Cursor::new(data.clone(), Position{ line: 0, offset: 0 });
+        let mut buffer = Buffer{
+            data,
+            path: Some(path.to_path_buf()),
+            cursor,
+            line_ending: decoded.line_ending,
+            loaded_with_conflicts,
+            mixed_line_endings: decoded.mixed,
+            ..Default::default()
+        };
+        buffer.history.mark();
+
+        Ok(buffer)
+    }
+
+    /// Like `save`, but writes the buffer's content to its
+    /// path in the specified storage, rather than the file system.
+    pub fn save_to<S: Storage + ?Sized>(&mut self, storage: &mut S) -> io::Result<()> {
+        self.save_with(|path, data| {
+            storage.write(path, data)?;
+
+            Ok(None)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MemoryStorage, Storage};
+    use buffer::Buffer;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn from_storage_returns_read_errors() {
+        let storage = MemoryStorage::new();
+
+        let error = Buffer::from_storage(&storage, Path::new("missing")).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn save_to_respects_buffer_preferences() {
+        let mut storage = MemoryStorage::new();
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("notes"));
+        buffer.insert("scribe");
+        buffer.insert_final_newline = true;
+        buffer.save_to(&mut storage).unwrap();
+
+        assert_eq!(storage.read(Path::new("notes")).unwrap(), "scribe\n");
+
+        buffer.read_only = true;
+        assert!(buffer.save_to(&mut storage).is_err());
+    }
+}
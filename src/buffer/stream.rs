@@ -0,0 +1,112 @@
+//! Appending text produced incrementally by other threads.
+
+use buffer::Buffer;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+impl Buffer {
+    /// Appends the chunks of text waiting in the receiver to the end of the
+    /// buffer, without blocking. Intended to be called repeatedly (e.g. once
+    /// per frame) while a long-running producer, such as a command or a text
+    /// generator, sends its output over the channel.
+    ///
+    /// The waiting chunks are combined into a single insertion, so observers,
+    /// cached tokens, and the undo history are updated once per call, rather
+    /// than once per chunk. The cursor is left in place. Read-only buffers
+    /// discard the chunks.
+    ///
+    /// Returns whether or not more text may arrive; false once the
+    /// sending side of the channel has been dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use std::sync::mpsc;
+    ///
+    /// let (sender, receiver) = mpsc::channel();
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    ///
+    /// sender.send(String::from(" library")).unwrap();
+    /// sender.send(String::from("\n")).unwrap();
+    /// assert!(buffer.append_stream(&receiver));
+    /// assert_eq!(buffer.data(), "scribe library\n");
+    ///
+    /// drop(sender);
+    /// assert!(!buffer.append_stream(&receiver));
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "scribe");
+    /// ```
+    pub fn append_stream(&mut self, receiver: &Receiver<String>) -> bool {
+        let mut data = String::new();
+        let open = loop {
+            match receiver.try_recv() {
+                Ok(chunk) => data.push_str(&chunk),
+                Err(TryRecvError::Empty) => break true,
+                Err(TryRecvError::Disconnected) => break false,
+            }
+        };
+
+        if !data.is_empty() {
+            let end = self.end_position();
+            self.insert_at(data, end);
+        }
+
+        open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position};
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn append_stream_appends_chunks_from_other_threads() {
+        let (sender, receiver) = mpsc::channel();
+        let producer = thread::spawn(move || {
+            for chunk in &["scribe", "\nlib", "rary"] {
+                sender.send(chunk.to_string()).unwrap();
+            }
+        });
+        producer.join().unwrap();
+
+        let mut buffer = Buffer::new();
+        while buffer.append_stream(&receiver) {}
+
+        assert_eq!(buffer.data(), "scribe\nlibrary");
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 0 });
+    }
+
+    #[test]
+    fn append_stream_notifies_observers_once_per_call() {
+        let (sender, receiver) = mpsc::channel();
+        let mut buffer = Buffer::new();
+        let edits = Rc::new(Cell::new(0));
+        let observed_edits = edits.clone();
+        buffer.on_change(move |_| observed_edits.set(observed_edits.get() + 1));
+
+        for _ in 0..100 {
+            sender.send(String::from("scribe ")).unwrap();
+        }
+        buffer.append_stream(&receiver);
+        assert_eq!(edits.get(), 1);
+        assert!(buffer.append_stream(&receiver));
+        assert_eq!(edits.get(), 1);
+    }
+
+    #[test]
+    fn append_stream_discards_chunks_in_read_only_buffers() {
+        let (sender, receiver) = mpsc::channel();
+        let mut buffer = Buffer::new();
+        buffer.read_only = true;
+
+        sender.send(String::from("scribe")).unwrap();
+        assert!(buffer.append_stream(&receiver));
+        assert_eq!(buffer.data(), "");
+    }
+}
@@ -0,0 +1,583 @@
+//! Structural navigation for JSON and XML buffers.
+
+use buffer::{Buffer, Position, Range};
+use std::ptr;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Identifies a structural node relative to its parent: object members and
+/// XML elements are named, while array elements are indexed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeKey {
+    Name(String),
+    Index(usize),
+}
+
+/// A JSON value or XML element. JSON object members span their key as well
+/// as their value, and XML elements span their opening and closing tags.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructuralNode {
+    pub key: Option<NodeKey>,
+    pub range: Range,
+}
+
+// The formats for which a structure can be derived.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Format {
+    Json,
+    Xml,
+}
+
+// A node along with its descendants.
+struct Node {
+    key: Option<NodeKey>,
+    range: Range,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn new(key: Option<NodeKey>, start: Position) -> Node {
+        Node{ key, range: Range::new(start, start), children: Vec::new() }
+    }
+
+    fn to_structural_node(&self) -> StructuralNode {
+        StructuralNode{ key: self.key.clone(), range: self.range.clone() }
+    }
+}
+
+impl Buffer {
+    /// Finds the innermost JSON value or XML element containing the
+    /// specified position. Returns None if the buffer's syntax definition
+    /// isn't JSON or XML, or if the position is outside of all nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate syntect;
+    /// extern crate scribe;
+    ///
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{NodeKey, Position, Range};
+    /// use syntect::parsing::SyntaxSet;
+    ///
+    /// let syntax_set = SyntaxSet::load_defaults_newlines();
+    /// let mut buffer = Buffer::new();
+    /// buffer.syntax_definition = syntax_set.find_syntax_by_name("JSON").cloned();
+    /// buffer.insert("{\n  \"name\": \"scribe\"\n}\n");
+    ///
+    /// let node = buffer.enclosing_node(&Position{ line: 1, offset: 12 }).unwrap();
+    /// assert_eq!(node.key, Some(NodeKey::Name("name".to_string())));
+    /// assert_eq!(node.range, Range::new(
+    ///     Position{ line: 1, offset: 2 },
+    ///     Position{ line: 1, offset: 18 }
+    /// ));
+    /// ```
+    pub fn enclosing_node(&self, position: &Position) -> Option<StructuralNode> {
+        let roots = self.structure()?;
+        let ancestors = ancestors(&roots, position);
+
+        ancestors.last().map(|(node, _)| node.to_structural_node())
+    }
+
+    /// Finds the sibling following the innermost JSON value or
+    /// XML element containing the cursor, if there is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate syntect;
+    /// extern crate scribe;
+    ///
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{NodeKey, Position};
+    /// use syntect::parsing::SyntaxSet;
+    ///
+    /// let syntax_set = SyntaxSet::load_defaults_newlines();
+    /// let mut buffer = Buffer::new();
+    /// buffer.syntax_definition = syntax_set.find_syntax_by_name("XML").cloned();
+    /// buffer.insert("<list>\n  <item>a</item>\n  <item>b</item>\n</list>\n");
+    /// buffer.cursor.move_to(Position{ line: 1, offset: 3 });
+    ///
+    /// let sibling = buffer.next_sibling().unwrap();
+    /// assert_eq!(sibling.key, Some(NodeKey::Name("item".to_string())));
+    /// assert_eq!(sibling.range.start(), Position{ line: 2, offset: 2 });
+    /// ```
+    pub fn next_sibling(&self) -> Option<StructuralNode> {
+        let roots = self.structure()?;
+        let ancestors = ancestors(&roots, &self.cursor);
+        let &(_, index) = ancestors.last()?;
+        let siblings = match ancestors.len() {
+            1 => &roots,
+            length => &ancestors[length - 2].0.children,
+        };
+
+        siblings.get(index + 1).map(Node::to_structural_node)
+    }
+
+    /// Describes the location of the specified position within the buffer's
+    /// JSON or XML structure, using dots to separate names and brackets for
+    /// indices (e.g. `object.items[3].name`). XML elements are only indexed
+    /// when they share their name with a sibling. Returns None if the
+    /// buffer's syntax definition isn't JSON or XML, or if the position is
+    /// outside of all nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate syntect;
+    /// extern crate scribe;
+    ///
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    /// use syntect::parsing::SyntaxSet;
+    ///
+    /// let syntax_set = SyntaxSet::load_defaults_newlines();
+    /// let mut buffer = Buffer::new();
+    /// buffer.syntax_definition = syntax_set.find_syntax_by_name("JSON").cloned();
+    /// buffer.insert("{\"object\": {\"items\": [1, {\"name\": \"scribe\"}]}}");
+    ///
+    /// assert_eq!(
+    ///     buffer.structural_path(&Position{ line: 0, offset: 36 }),
+    ///     Some("object.items[1].name".to_string())
+    /// );
+    /// ```
+    pub fn structural_path(&self, position: &Position) -> Option<String> {
+        let roots = self.structure()?;
+        let ancestors = ancestors(&roots, position);
+        if ancestors.is_empty() { return None; }
+
+        let format = self.structure_format()?;
+        let mut path = String::new();
+        let mut siblings = &roots;
+        for &(node, _) in &ancestors {
+            match node.key {
+                Some(NodeKey::Name(ref name)) => {
+                    if is_identifier(name) {
+                        if !path.is_empty() { path.push('.'); }
+                        path.push_str(name);
+                    } else {
+                        path.push_str(&format!("[{:?}]", name));
+                    }
+
+                    // Distinguish between identically-named XML elements.
+                    if format == Format::Xml {
+                        let mut same_named = siblings.iter().filter(|sibling| sibling.key == node.key);
+                        if same_named.clone().count() > 1 {
+                            let index = same_named.position(|sibling| ptr::eq(sibling, node)).unwrap_or(0);
+                            path.push_str(&format!("[{}]", index));
+                        }
+                    }
+                },
+                Some(NodeKey::Index(index)) => path.push_str(&format!("[{}]", index)),
+                None => (),
+            }
+
+            siblings = &node.children;
+        }
+
+        Some(path)
+    }
+
+    pub(crate) fn structure_format(&self) -> Option<Format> {
+        match self.syntax_definition.as_ref()?.name.as_str() {
+            "JSON" => Some(Format::Json),
+            "XML" => Some(Format::Xml),
+            _ => None,
+        }
+    }
+
+    // Parses the buffer's content into a list of root nodes.
+    fn structure(&self) -> Option<Vec<Node>> {
+        let format = self.structure_format()?;
+        let data = self.data();
+        let mut scanner = Scanner::new(&data);
+
+        Some(match format {
+            Format::Json => scanner.parse_json(),
+            Format::Xml => scanner.parse_xml(),
+        })
+    }
+}
+
+// Returns the chain of nodes containing the position, outermost first,
+// along with the index of each node amongst its siblings.
+fn ancestors<'a>(roots: &'a [Node], position: &Position) -> Vec<(&'a Node, usize)> {
+    let mut ancestors = Vec::new();
+    let mut nodes = roots;
+
+    while let Some(index) = nodes.iter().position(|node| node.range.includes(position)) {
+        ancestors.push((&nodes[index], index));
+        nodes = &nodes[index].children;
+    }
+
+    ancestors
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == ':')
+}
+
+// Walks the buffer's graphemes, tracking their positions.
+struct Scanner<'a> {
+    graphemes: Vec<&'a str>,
+    positions: Vec<Position>,
+    index: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(data: &'a str) -> Scanner<'a> {
+        let graphemes: Vec<&str> = data.graphemes(true).collect();
+        let mut positions = Vec::with_capacity(graphemes.len() + 1);
+        let mut position = Position::new();
+
+        for grapheme in &graphemes {
+            positions.push(position);
+            if *grapheme == "\n" || *grapheme == "\r\n" {
+                position = Position{ line: position.line + 1, offset: 0 };
+            } else {
+                position.offset += 1;
+            }
+        }
+        positions.push(position);
+
+        Scanner{ graphemes, positions, index: 0 }
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.graphemes.get(self.index).cloned()
+    }
+
+    fn position(&self) -> Position {
+        self.positions[self.index]
+    }
+
+    fn advance(&mut self) {
+        if self.index < self.graphemes.len() {
+            self.index += 1;
+        }
+    }
+
+    fn at(&self, pattern: &str) -> bool {
+        pattern.graphemes(true).enumerate().all(|(index, grapheme)| {
+            self.graphemes.get(self.index + index) == Some(&grapheme)
+        })
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().map(|g| g.trim().is_empty()).unwrap_or(false) {
+            self.advance();
+        }
+    }
+
+    // Advances past the next occurrence of the pattern, or to the end of the data.
+    fn skip_past(&mut self, pattern: &str) {
+        while self.peek().is_some() && !self.at(pattern) {
+            self.advance();
+        }
+        for _ in pattern.graphemes(true) {
+            self.advance();
+        }
+    }
+
+    fn parse_json(&mut self) -> Vec<Node> {
+        let mut roots = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            if self.peek().is_none() { break; }
+
+            let start = self.index;
+            roots.push(self.parse_json_value(None, self.position()));
+            if self.index == start { self.advance(); }
+        }
+
+        roots
+    }
+
+    // Parses a value; incomplete values extend to the end of the data.
+    fn parse_json_value(&mut self, key: Option<NodeKey>, start: Position) -> Node {
+        let mut node = Node::new(key, start);
+
+        match self.peek() {
+            Some("{") => {
+                self.advance();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some("}") => { self.advance(); break; },
+                        Some("\"") => {
+                            let member_start = self.position();
+                            let name = self.parse_json_string();
+                            self.skip_whitespace();
+                            if self.peek() == Some(":") {
+                                self.advance();
+                                self.skip_whitespace();
+                                let member = self.parse_json_value(Some(NodeKey::Name(name)), member_start);
+                                node.children.push(member);
+                            }
+                        },
+                        Some(",") => self.advance(),
+                        Some(_) => {
+                            // Tolerate invalid content without losing our place.
+                            let index = self.index;
+                            self.parse_json_value(None, self.position());
+                            if self.index == index { self.advance(); }
+                        },
+                        None => break,
+                    }
+                }
+            },
+            Some("[") => {
+                self.advance();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some("]") => { self.advance(); break; },
+                        Some(",") => self.advance(),
+                        Some("}") | None => break,
+                        Some(_) => {
+                            let index = self.index;
+                            let key = NodeKey::Index(node.children.len());
+                            let element = self.parse_json_value(Some(key), self.position());
+                            node.children.push(element);
+                            if self.index == index { self.advance(); }
+                        },
+                    }
+                }
+            },
+            Some("\"") => { self.parse_json_string(); },
+            _ => {
+                while let Some(grapheme) = self.peek() {
+                    if grapheme.trim().is_empty() || ",:[]{}\"".contains(grapheme) { break; }
+                    self.advance();
+                }
+            },
+        }
+
+        node.range = Range::new(start, self.position());
+        node
+    }
+
+    fn parse_json_string(&mut self) -> String {
+        let mut content = String::new();
+        self.advance();
+
+        while let Some(grapheme) = self.peek() {
+            self.advance();
+            match grapheme {
+                "\"" => break,
+                "\\" => {
+                    if let Some(escaped) = self.peek() {
+                        content.push_str(escaped);
+                        self.advance();
+                    }
+                },
+                _ => content.push_str(grapheme),
+            }
+        }
+
+        content
+    }
+
+    fn parse_xml(&mut self) -> Vec<Node> {
+        let mut roots = Vec::new();
+        let mut open_elements: Vec<(String, Node)> = Vec::new();
+
+        while self.peek().is_some() {
+            if self.at("<!--") {
+                self.skip_past("-->");
+            } else if self.at("<![CDATA[") {
+                self.skip_past("]]>");
+            } else if self.at("<?") {
+                self.skip_past("?>");
+            } else if self.at("<!") {
+                self.skip_past(">");
+            } else if self.at("</") {
+                self.advance();
+                self.advance();
+                let name = self.parse_xml_name();
+                self.skip_past(">");
+
+                // Close the matching element, along with any unclosed descendants.
+                if open_elements.iter().any(|(open_name, _)| *open_name == name) {
+                    while let Some((open_name, mut element)) = open_elements.pop() {
+                        element.range = Range::new(element.range.start(), self.position());
+                        let matched = open_name == name;
+                        push_child(&mut roots, &mut open_elements, element);
+                        if matched { break; }
+                    }
+                }
+            } else if self.at("<") {
+                let start = self.position();
+                self.advance();
+                let name = self.parse_xml_name();
+                let self_closing = self.skip_xml_tag();
+                let mut element = Node::new(Some(NodeKey::Name(name.clone())), start);
+
+                if self_closing {
+                    element.range = Range::new(start, self.position());
+                    push_child(&mut roots, &mut open_elements, element);
+                } else {
+                    open_elements.push((name, element));
+                }
+            } else {
+                self.advance();
+            }
+        }
+
+        // Unclosed elements extend to the end of the data.
+        while let Some((_, mut element)) = open_elements.pop() {
+            element.range = Range::new(element.range.start(), self.position());
+            push_child(&mut roots, &mut open_elements, element);
+        }
+
+        roots
+    }
+
+    fn parse_xml_name(&mut self) -> String {
+        let mut name = String::new();
+
+        while let Some(grapheme) = self.peek() {
+            if grapheme.trim().is_empty() || "/>".contains(grapheme) { break; }
+            name.push_str(grapheme);
+            self.advance();
+        }
+
+        name
+    }
+
+    // Advances past the remainder of a tag, returning whether it was self-closing.
+    fn skip_xml_tag(&mut self) -> bool {
+        let mut quote = None;
+        let mut previous = None;
+
+        while let Some(grapheme) = self.peek() {
+            self.advance();
+            match quote {
+                Some(q) if q == grapheme => quote = None,
+                Some(_) => (),
+                None => match grapheme {
+                    "\"" | "'" => quote = Some(grapheme),
+                    ">" => return previous == Some("/"),
+                    _ => (),
+                },
+            }
+            previous = Some(grapheme);
+        }
+
+        false
+    }
+}
+
+fn push_child(roots: &mut Vec<Node>, open_elements: &mut [(String, Node)], element: Node) {
+    match open_elements.last_mut() {
+        Some((_, parent)) => parent.children.push(element),
+        None => roots.push(element),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, NodeKey, Position, Range};
+    use syntect::parsing::SyntaxSet;
+
+    fn buffer_with_syntax(name: &str, content: &str) -> Buffer {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let mut buffer = Buffer::new();
+        buffer.syntax_definition = syntax_set.find_syntax_by_name(name).cloned();
+        buffer.insert(content);
+
+        buffer
+    }
+
+    #[test]
+    fn enclosing_node_returns_none_for_other_syntaxes() {
+        let buffer = buffer_with_syntax("Rust", "{\"key\": 1}");
+        assert!(buffer.enclosing_node(&Position{ line: 0, offset: 2 }).is_none());
+    }
+
+    #[test]
+    fn enclosing_node_returns_containers_between_members() {
+        let buffer = buffer_with_syntax("JSON", "{\"items\": [1, 2]}");
+        let node = buffer.enclosing_node(&Position{ line: 0, offset: 12 }).unwrap();
+
+        assert_eq!(node.key, Some(NodeKey::Name("items".to_string())));
+        assert_eq!(node.range, Range::new(
+            Position{ line: 0, offset: 1 },
+            Position{ line: 0, offset: 16 }
+        ));
+    }
+
+    #[test]
+    fn enclosing_node_ignores_delimiters_in_strings() {
+        let buffer = buffer_with_syntax("JSON", "{\"a\": \"}]\\\"\", \"b\": 2}");
+        let node = buffer.enclosing_node(&Position{ line: 0, offset: 19 }).unwrap();
+
+        assert_eq!(node.key, Some(NodeKey::Name("b".to_string())));
+    }
+
+    #[test]
+    fn enclosing_node_tolerates_incomplete_json() {
+        let buffer = buffer_with_syntax("JSON", "{\"items\": [1, ");
+        let node = buffer.enclosing_node(&Position{ line: 0, offset: 13 });
+
+        assert_eq!(node.unwrap().key, Some(NodeKey::Name("items".to_string())));
+    }
+
+    #[test]
+    fn enclosing_node_handles_xml_comments_and_self_closing_elements() {
+        let buffer = buffer_with_syntax(
+            "XML",
+            "<?xml version=\"1.0\"?>\n<root>\n  <!-- <ignored> -->\n  <empty attr=\"a>b\"/>\n</root>\n"
+        );
+
+        let node = buffer.enclosing_node(&Position{ line: 3, offset: 4 }).unwrap();
+        assert_eq!(node.key, Some(NodeKey::Name("empty".to_string())));
+        assert_eq!(node.range, Range::new(
+            Position{ line: 3, offset: 2 },
+            Position{ line: 3, offset: 21 }
+        ));
+
+        let node = buffer.enclosing_node(&Position{ line: 2, offset: 8 }).unwrap();
+        assert_eq!(node.key, Some(NodeKey::Name("root".to_string())));
+    }
+
+    #[test]
+    fn next_sibling_returns_none_for_the_last_sibling() {
+        let mut buffer = buffer_with_syntax("JSON", "[1, 2]");
+        buffer.cursor.move_to(Position{ line: 0, offset: 1 });
+        assert_eq!(
+            buffer.next_sibling().map(|node| node.key),
+            Some(Some(NodeKey::Index(1)))
+        );
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 4 });
+        assert!(buffer.next_sibling().is_none());
+    }
+
+    #[test]
+    fn structural_path_indexes_identically_named_xml_elements() {
+        let buffer = buffer_with_syntax(
+            "XML",
+            "<list>\n  <name>a</name>\n  <item>a</item>\n  <item><name>b</name></item>\n</list>\n"
+        );
+
+        assert_eq!(
+            buffer.structural_path(&Position{ line: 1, offset: 3 }),
+            Some("list.name".to_string())
+        );
+        assert_eq!(
+            buffer.structural_path(&Position{ line: 3, offset: 9 }),
+            Some("list.item[1].name".to_string())
+        );
+    }
+
+    #[test]
+    fn structural_path_quotes_non_identifier_names() {
+        let buffer = buffer_with_syntax("JSON", "{\"a b\": {\"c.d\": 1}}");
+
+        assert_eq!(
+            buffer.structural_path(&Position{ line: 0, offset: 16 }),
+            Some("[\"a b\"][\"c.d\"]".to_string())
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Mapping lexeme scopes to application-defined styles.
+
+use buffer::{Buffer, Lexeme};
+use errors::*;
+use std::collections::HashMap;
+use syntect::parsing::{Scope, ScopeStack};
+
+/// Maps lexeme scopes (see `Buffer::tokens`) to application-defined style
+/// keys, so that themes can be expressed in terms of a small set of styles,
+/// rather than the wide (and varied) set of scopes produced by each syntax.
+///
+/// Rules are keyed by scope selectors (e.g. `"entity.name"`), which match any
+/// scope they're a prefix of. A lexeme is styled using its innermost scope
+/// that matches a rule, preferring the most specific selector matching it.
+/// Rules registered for a particular syntax take precedence over general
+/// rules when styling buffers using that syntax.
+pub struct StyleMap<K> {
+    rules: Vec<(Scope, K)>,
+    syntax_rules: HashMap<String, Vec<(Scope, K)>>,
+}
+
+impl<K> StyleMap<K> {
+    /// Creates an empty style map.
+    pub fn new() -> StyleMap<K> {
+        StyleMap{ rules: Vec::new(), syntax_rules: HashMap::new() }
+    }
+
+    /// Styles scopes matching the selector with the specified key.
+    /// Returns an error if the selector isn't a valid scope.
+    pub fn insert(&mut self, selector: &str, key: K) -> Result<()> {
+        let scope = parse_selector(selector)?;
+        self.rules.push((scope, key));
+
+        Ok(())
+    }
+
+    /// Like `insert`, but only applies to the named syntax (e.g. "Rust"),
+    /// taking precedence over general rules.
+    pub fn insert_for_syntax(&mut self, syntax: &str, selector: &str, key: K) -> Result<()> {
+        let scope = parse_selector(selector)?;
+        self.syntax_rules.entry(syntax.to_string()).or_default().push((scope, key));
+
+        Ok(())
+    }
+
+    /// Finds the style key for the specified scopes, using
+    /// rules for the named syntax, if provided.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::buffer::{Scope, ScopeStack, StyleMap};
+    ///
+    /// let mut map = StyleMap::new();
+    /// map.insert("string", "green").unwrap();
+    /// map.insert("string.quoted.double", "yellow").unwrap();
+    /// map.insert_for_syntax("Rust", "string", "cyan").unwrap();
+    ///
+    /// let mut scopes = ScopeStack::new();
+    /// scopes.push(Scope::new("source.rust").unwrap());
+    /// scopes.push(Scope::new("string.quoted.double.rust").unwrap());
+    ///
+    /// assert_eq!(map.style(&scopes, None), Some(&"yellow"));
+    /// assert_eq!(map.style(&scopes, Some("Rust")), Some(&"cyan"));
+    /// ```
+    pub fn style(&self, scopes: &ScopeStack, syntax: Option<&str>) -> Option<&K> {
+        let syntax_rules = syntax.and_then(|name| self.syntax_rules.get(name));
+
+        for &scope in scopes.as_slice().iter().rev() {
+            if let Some(key) = syntax_rules.and_then(|rules| best_match(rules, scope)) {
+                return Some(key);
+            }
+            if let Some(key) = best_match(&self.rules, scope) {
+                return Some(key);
+            }
+        }
+
+        None
+    }
+}
+
+impl<K> Default for StyleMap<K> {
+    fn default() -> Self {
+        StyleMap::new()
+    }
+}
+
+impl Buffer {
+    /// Finds the style key for the lexeme (see `StyleMap`), using
+    /// rules specific to the buffer's syntax definition, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::buffer::{StyleMap, Token};
+    /// use scribe::Workspace;
+    /// use std::path::Path;
+    ///
+    /// let mut workspace = Workspace::new(Path::new(".")).unwrap();
+    /// workspace.open_buffer(Path::new("src/buffer/style_map.rs")).unwrap();
+    /// let buffer = workspace.current_buffer().unwrap();
+    ///
+    /// let mut map = StyleMap::new();
+    /// map.insert("comment", "grey").unwrap();
+    ///
+    /// let tokens = buffer.tokens().unwrap();
+    /// let first_style = tokens.iter().filter_map(|token| match token {
+    ///     Token::Lexeme(lexeme) => buffer.token_style(&map, &lexeme).cloned(),
+    ///     Token::Newline => None,
+    /// }).next();
+    /// assert_eq!(first_style, Some("grey"));
+    /// ```
+    pub fn token_style<'a, K>(&self, map: &'a StyleMap<K>, lexeme: &Lexeme) -> Option<&'a K> {
+        let syntax = self.syntax_definition.as_ref().map(|def| def.name.as_str());
+
+        map.style(&lexeme.scope, syntax)
+    }
+}
+
+fn parse_selector(selector: &str) -> Result<Scope> {
+    Scope::new(selector).map_err(|_| ErrorKind::InvalidScope(selector.to_string()).into())
+}
+
+// Finds the rule with the most specific selector matching the scope.
+fn best_match<K>(rules: &[(Scope, K)], scope: Scope) -> Option<&K> {
+    rules.iter()
+        .filter(|&&(selector, _)| selector.is_prefix_of(scope))
+        .max_by_key(|&&(selector, _)| selector.len())
+        .map(|(_, key)| key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StyleMap;
+    use syntect::parsing::{Scope, ScopeStack};
+
+    fn scopes(names: &[&str]) -> ScopeStack {
+        let mut stack = ScopeStack::new();
+        for name in names {
+            stack.push(Scope::new(name).unwrap());
+        }
+
+        stack
+    }
+
+    #[test]
+    fn style_prefers_inner_scopes() {
+        let mut map = StyleMap::new();
+        map.insert("meta.function", 1).unwrap();
+        map.insert("entity", 2).unwrap();
+
+        assert_eq!(map.style(&scopes(&["meta.function.rust", "entity.name.function.rust"]), None), Some(&2));
+    }
+
+    #[test]
+    fn style_falls_back_to_outer_scopes() {
+        let mut map = StyleMap::new();
+        map.insert("meta.function", 1).unwrap();
+
+        assert_eq!(map.style(&scopes(&["meta.function.rust", "entity.name.function.rust"]), None), Some(&1));
+        assert_eq!(map.style(&scopes(&["source.rust"]), None), None);
+    }
+
+    #[test]
+    fn style_ignores_rules_for_other_syntaxes() {
+        let mut map = StyleMap::new();
+        map.insert("keyword", 1).unwrap();
+        map.insert_for_syntax("Ruby", "keyword", 2).unwrap();
+
+        assert_eq!(map.style(&scopes(&["keyword.control.rust"]), Some("Rust")), Some(&1));
+    }
+
+    #[test]
+    fn insert_rejects_invalid_selectors() {
+        let mut map = StyleMap::new();
+        let selector = ["atom"; 10].join(".");
+
+        assert!(map.insert(&selector, 1).is_err());
+    }
+}
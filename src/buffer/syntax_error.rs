@@ -0,0 +1,236 @@
+//! Unparseable regions identified by the lexer.
+
+use buffer::{Buffer, Distance, Overlay, OverlayLayer, Position, Range, Token};
+use errors::*;
+use std::time::Duration;
+use syntect::parsing::Scope;
+
+/// The type of problem found in a `SyntaxError`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SyntaxErrorKind {
+    /// Content the syntax marks as invalid (e.g. a misplaced separator).
+    InvalidToken,
+
+    /// A string that isn't closed before the end of its line (in languages
+    /// that don't allow multi-line strings) or the buffer.
+    UnterminatedString,
+}
+
+/// A region the lexer couldn't make sense of, produced by
+/// `Buffer::syntax_errors`. Its scope is the one the syntax used to mark
+/// it as invalid (e.g. "invalid.illegal.unclosed-string.python"), or for
+/// strings left open at the end of the buffer, the string's scope.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxError {
+    pub range: Range,
+    pub kind: SyntaxErrorKind,
+    pub scope: Scope,
+}
+
+impl Buffer {
+    /// Finds the regions the buffer's syntax definition marks as invalid, as
+    /// well as strings that are never closed, using the buffer's tokens. This
+    /// provides quick feedback on broken syntax, without a language server.
+    /// Returns an error if the buffer doesn't have a syntax definition.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate syntect;
+    /// # extern crate scribe;
+    /// # fn main() {
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range, SyntaxErrorKind};
+    /// use syntect::parsing::SyntaxSet;
+    ///
+    /// let syntax_set = SyntaxSet::load_defaults_newlines();
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("x = 'scribe\ny = 2");
+    /// buffer.syntax_definition = syntax_set.find_syntax_by_name("Python").cloned();
+    ///
+    /// let errors = buffer.syntax_errors().unwrap();
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].kind, SyntaxErrorKind::UnterminatedString);
+    /// assert_eq!(errors[0].range, Range::new(
+    ///     Position{ line: 0, offset: 4 },
+    ///     Position{ line: 0, offset: 11 }
+    /// ));
+    /// # }
+    /// ```
+    pub fn syntax_errors(&self) -> Result<Vec<SyntaxError>> {
+        let tokens = self.tokens()?;
+        let invalid = Scope::new("invalid").unwrap();
+        let string = Scope::new("string").unwrap();
+        let string_end = Scope::new("punctuation.definition.string.end").unwrap();
+
+        let mut errors: Vec<SyntaxError> = Vec::new();
+        let mut open_string: Option<(Position, Scope)> = None;
+        let mut end = Position::new();
+        for token in tokens.iter() {
+            let lexeme = match token {
+                Token::Lexeme(lexeme) => lexeme,
+                Token::Newline => continue,
+            };
+            let scopes = lexeme.scope.as_slice();
+            let start = lexeme.position;
+            end = start + Distance::of_str(lexeme.value);
+
+            // Track the start of the string the lexeme is part of, if any.
+            match scopes.iter().rev().find(|&&scope| string.is_prefix_of(scope)) {
+                Some(&scope) => if open_string.is_none() { open_string = Some((start, scope)) },
+                None => open_string = None,
+            }
+
+            // Strings are closed by their ending punctuation, which
+            // may be the last lexeme in the buffer.
+            if scopes.iter().any(|&scope| string_end.is_prefix_of(scope)) {
+                open_string = None;
+            }
+
+            let scope = match scopes.iter().rev().find(|&&scope| invalid.is_prefix_of(scope)) {
+                Some(&scope) => scope,
+                None => continue,
+            };
+            let error = match open_string {
+                // Syntaxes mark unterminated strings using an empty lexeme
+                // at their end, so the error covers the string itself.
+                Some((string_start, _)) if lexeme.value.is_empty() => {
+                    open_string = None;
+                    SyntaxError{
+                        range: Range::new(string_start, end),
+                        kind: SyntaxErrorKind::UnterminatedString,
+                        scope,
+                    }
+                },
+                _ => SyntaxError{ range: Range::new(start, end), kind: SyntaxErrorKind::InvalidToken, scope },
+            };
+
+            // Extend the previous error if this one continues it.
+            if let Some(previous) = errors.last_mut() {
+                if previous.scope == error.scope && previous.range.end() == error.range.start() {
+                    previous.range = Range::new(previous.range.start(), error.range.end());
+                    continue;
+                }
+            }
+            errors.push(error);
+        }
+
+        if let Some((start, scope)) = open_string {
+            errors.push(SyntaxError{
+                range: Range::new(start, end),
+                kind: SyntaxErrorKind::UnterminatedString,
+                scope,
+            });
+        }
+
+        Ok(errors)
+    }
+
+    /// Registers an overlay provider (see `add_overlay_provider`) that
+    /// reports the buffer's `syntax_errors` at the `OverlayLayer::Diagnostics`
+    /// priority, styled "syntax_error". To avoid flagging content that's
+    /// still being typed, nothing is reported until the buffer has gone
+    /// unmodified for the specified delay; frontends should keep rendering
+    /// (or schedule a render) once it's elapsed. Returns the provider's ID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate syntect;
+    /// # extern crate scribe;
+    /// # fn main() {
+    /// use scribe::Buffer;
+    /// use scribe::buffer::LineRange;
+    /// use std::time::Duration;
+    /// use syntect::parsing::SyntaxSet;
+    ///
+    /// let syntax_set = SyntaxSet::load_defaults_newlines();
+    /// let mut buffer = Buffer::new();
+    /// buffer.syntax_definition = syntax_set.find_syntax_by_name("JSON").cloned();
+    /// buffer.insert("{\"scribe\": 1,, \"library\": 2}");
+    ///
+    /// buffer.add_syntax_error_overlays(Duration::from_secs(60));
+    /// assert!(buffer.overlays(&LineRange::new(0, 1)).is_empty());
+    ///
+    /// buffer.add_syntax_error_overlays(Duration::from_secs(0));
+    /// assert_eq!(buffer.overlays(&LineRange::new(0, 1))[0].styles, vec!["syntax_error"]);
+    /// # }
+    /// ```
+    pub fn add_syntax_error_overlays(&mut self, delay: Duration) -> usize {
+        self.add_overlay_provider(OverlayLayer::Diagnostics.priority(), move |buffer, _| {
+            if buffer.last_edited().is_some_and(|edited| edited.elapsed() < delay) {
+                return Vec::new();
+            }
+
+            buffer.syntax_errors()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|error| Overlay::new(error.range, "syntax_error"))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, Range, SyntaxErrorKind};
+    use syntect::parsing::SyntaxSet;
+
+    fn buffer_with_syntax(syntax: &str, data: &str) -> Buffer {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let mut buffer = Buffer::new();
+        buffer.insert(data);
+        buffer.syntax_definition = syntax_set.find_syntax_by_name(syntax).cloned();
+
+        buffer
+    }
+
+    #[test]
+    fn syntax_errors_reports_invalid_tokens() {
+        let buffer = buffer_with_syntax("JSON", "{\"a\": 1,,\n \"b\": 2}");
+        let errors = buffer.syntax_errors().unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, SyntaxErrorKind::InvalidToken);
+        assert_eq!(errors[0].range, Range::new(
+            Position{ line: 0, offset: 8 },
+            Position{ line: 0, offset: 9 }
+        ));
+        assert_eq!(errors[0].scope.build_string(), "invalid.illegal.expected-dictionary-separator.json");
+    }
+
+    #[test]
+    fn syntax_errors_reports_strings_open_at_the_end_of_the_buffer() {
+        let buffer = buffer_with_syntax("Rust", "let a = \"scribe\";\nlet b = \"lib\nrary");
+        let errors = buffer.syntax_errors().unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, SyntaxErrorKind::UnterminatedString);
+        assert_eq!(errors[0].range, Range::new(
+            Position{ line: 1, offset: 8 },
+            Position{ line: 2, offset: 4 }
+        ));
+    }
+
+    #[test]
+    fn syntax_errors_ignores_strings_closed_at_the_end_of_the_buffer() {
+        let samples = [
+            ("Python", "x = 'abc'\n"),
+            ("JSON", "\"abc\""),
+            ("Rust", "let b = \"y\"\n"),
+            ("Rust", "let b = \"y\""),
+        ];
+        for &(syntax, data) in &samples {
+            let buffer = buffer_with_syntax(syntax, data);
+            assert!(buffer.syntax_errors().unwrap().is_empty(), "{}: {:?}", syntax, data);
+        }
+    }
+
+    #[test]
+    fn syntax_errors_requires_a_syntax_definition() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+
+        assert!(buffer.syntax_errors().is_err());
+    }
+}
@@ -0,0 +1,352 @@
+//! Column-aware navigation for CSV and TSV buffers.
+
+use buffer::{Buffer, Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A single cell in a CSV or TSV buffer. The cell's range excludes its
+/// delimiters, but includes any quotes surrounding its content.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableCell {
+    pub line: usize,
+    pub column: usize,
+    pub range: Range,
+}
+
+impl Buffer {
+    /// The delimiter separating cells in the buffer, based on its
+    /// path's extension: commas for CSV files, and tabs for TSV files.
+    /// Other buffers don't have one, and aren't treated as tables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// assert_eq!(buffer.table_delimiter(), None);
+    ///
+    /// buffer.path = Some(PathBuf::from("data.tsv"));
+    /// assert_eq!(buffer.table_delimiter(), Some('\t'));
+    /// ```
+    pub fn table_delimiter(&self) -> Option<char> {
+        let extension = self.path.as_ref()?.extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            "csv" => Some(','),
+            "tsv" | "tab" => Some('\t'),
+            _ => None,
+        }
+    }
+
+    /// Returns the ranges of the cells on the specified line, in column
+    /// order. Delimiters within double-quoted CSV cells are ignored, though
+    /// quoted cells can't span multiple lines. Returns None if the buffer
+    /// isn't a table, or if the line doesn't exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.path = Some(PathBuf::from("data.csv"));
+    /// buffer.insert("name,\"city, country\"");
+    ///
+    /// assert_eq!(buffer.column_ranges(0), Some(vec![
+    ///     Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 4 }),
+    ///     Range::new(Position{ line: 0, offset: 5 }, Position{ line: 0, offset: 20 }),
+    /// ]));
+    /// ```
+    pub fn column_ranges(&self, line: usize) -> Option<Vec<Range>> {
+        let delimiter = self.table_delimiter()?;
+        let data = self.data();
+        let content = data.split('\n').nth(line)?;
+
+        Some(cell_ranges(content.trim_end_matches('\r'), line, delimiter))
+    }
+
+    /// Finds the cell containing the specified position. Positions
+    /// on a delimiter belong to the cell preceding it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.path = Some(PathBuf::from("data.csv"));
+    /// buffer.insert("name,city\nscribe,Toronto");
+    ///
+    /// let cell = buffer.cell_at(&Position{ line: 1, offset: 9 }).unwrap();
+    /// assert_eq!(cell.line, 1);
+    /// assert_eq!(cell.column, 1);
+    /// assert_eq!(buffer.read(&cell.range), Some("Toronto".to_string()));
+    /// ```
+    pub fn cell_at(&self, position: &Position) -> Option<TableCell> {
+        let ranges = self.column_ranges(position.line)?;
+        let column = ranges
+            .iter()
+            .position(|range| position.offset <= range.end().offset)
+            .unwrap_or(ranges.len() - 1);
+
+        Some(TableCell{ line: position.line, column, range: ranges[column].clone() })
+    }
+
+    /// Moves the cursor to the start of the next cell, wrapping onto the
+    /// following line after the last cell. Returns whether the cursor moved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.path = Some(PathBuf::from("data.csv"));
+    /// buffer.insert("name,city\nscribe,Toronto");
+    ///
+    /// assert!(buffer.move_to_next_cell());
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 5 });
+    ///
+    /// assert!(buffer.move_to_next_cell());
+    /// assert_eq!(*buffer.cursor, Position{ line: 1, offset: 0 });
+    /// ```
+    pub fn move_to_next_cell(&mut self) -> bool {
+        let cell = match self.cell_at(&self.cursor) {
+            Some(cell) => cell,
+            None => return false,
+        };
+        let next_range = self
+            .column_ranges(cell.line)
+            .and_then(|ranges| ranges.get(cell.column + 1).cloned());
+        let target = match next_range {
+            Some(range) => range.start(),
+            None => Position{ line: cell.line + 1, offset: 0 },
+        };
+
+        self.cursor.move_to(target)
+    }
+
+    /// Moves the cursor to the start of the current cell or, if it's already
+    /// there, the previous one, wrapping onto the preceding line before the
+    /// first cell. Returns whether the cursor moved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.path = Some(PathBuf::from("data.csv"));
+    /// buffer.insert("name,city\nscribe,Toronto");
+    /// buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+    ///
+    /// assert!(buffer.move_to_previous_cell());
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 5 });
+    /// ```
+    pub fn move_to_previous_cell(&mut self) -> bool {
+        let cell = match self.cell_at(&self.cursor) {
+            Some(cell) => cell,
+            None => return false,
+        };
+        let target = if self.cursor.offset > cell.range.start().offset {
+            cell.range.start()
+        } else if cell.column > 0 {
+            match self.column_ranges(cell.line) {
+                Some(ranges) => ranges[cell.column - 1].start(),
+                None => return false,
+            }
+        } else if cell.line > 0 {
+            match self.column_ranges(cell.line - 1).and_then(|ranges| ranges.last().cloned()) {
+                Some(range) => range.start(),
+                None => return false,
+            }
+        } else {
+            return false;
+        };
+
+        self.cursor.move_to(target)
+    }
+
+    /// Renders the buffer's content with its cells padded to the width of
+    /// their columns, and separated by `separator` rather than their
+    /// delimiter. Intended for display; the buffer itself isn't modified.
+    /// Returns None if the buffer isn't a table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.path = Some(PathBuf::from("data.csv"));
+    /// buffer.insert("name,city\nscribe,Toronto\n");
+    ///
+    /// assert_eq!(
+    ///     buffer.aligned_columns(" | "),
+    ///     Some("name   | city\nscribe | Toronto\n".to_string())
+    /// );
+    /// ```
+    pub fn aligned_columns(&self, separator: &str) -> Option<String> {
+        let delimiter = self.table_delimiter()?;
+        let data = self.data();
+        let rows: Vec<Vec<&str>> = data
+            .split('\n')
+            .enumerate()
+            .map(|(line, content)| {
+                let content = content.trim_end_matches('\r');
+                let graphemes: Vec<&str> = content.graphemes(true).collect();
+                let start_index = |offset| graphemes[..offset].concat().len();
+
+                cell_ranges(content, line, delimiter)
+                    .iter()
+                    .map(|range| &content[start_index(range.start().offset)..start_index(range.end().offset)])
+                    .collect()
+            })
+            .collect();
+
+        let mut widths = Vec::new();
+        for row in &rows {
+            for (column, cell) in row.iter().enumerate() {
+                let width = cell.graphemes(true).count();
+                if column == widths.len() {
+                    widths.push(width);
+                } else if width > widths[column] {
+                    widths[column] = width;
+                }
+            }
+        }
+
+        let lines: Vec<String> = rows.iter().map(|row| {
+            let mut line = String::new();
+            for (column, cell) in row.iter().enumerate() {
+                if column > 0 { line.push_str(separator); }
+                line.push_str(cell);
+
+                // Don't pad the last cell with trailing whitespace.
+                if column + 1 < row.len() {
+                    let padding = widths[column] - cell.graphemes(true).count();
+                    line.extend((0..padding).map(|_| ' '));
+                }
+            }
+
+            line
+        }).collect();
+
+        Some(lines.join("\n"))
+    }
+}
+
+// Splits the line's content into cell ranges, ignoring
+// delimiters within double-quoted (CSV) cells.
+fn cell_ranges(content: &str, line: usize, delimiter: char) -> Vec<Range> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut offset = 0;
+
+    for grapheme in content.graphemes(true) {
+        if grapheme == "\"" && delimiter == ',' {
+            quoted = !quoted;
+        } else if !quoted && grapheme.starts_with(delimiter) {
+            ranges.push(Range::new(
+                Position{ line, offset: start },
+                Position{ line, offset }
+            ));
+            start = offset + 1;
+        }
+        offset += 1;
+    }
+    ranges.push(Range::new(Position{ line, offset: start }, Position{ line, offset }));
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, Range};
+    use std::path::PathBuf;
+
+    fn table_buffer(path: &str, content: &str) -> Buffer {
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from(path));
+        buffer.insert(content);
+
+        buffer
+    }
+
+    #[test]
+    fn column_ranges_returns_none_for_other_buffers() {
+        let buffer = table_buffer("data.txt", "a,b");
+        assert_eq!(buffer.column_ranges(0), None);
+    }
+
+    #[test]
+    fn column_ranges_includes_empty_cells() {
+        let buffer = table_buffer("data.TSV", "a\t\tb,c\t");
+
+        assert_eq!(buffer.column_ranges(0), Some(vec![
+            Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 1 }),
+            Range::new(Position{ line: 0, offset: 2 }, Position{ line: 0, offset: 2 }),
+            Range::new(Position{ line: 0, offset: 3 }, Position{ line: 0, offset: 6 }),
+            Range::new(Position{ line: 0, offset: 7 }, Position{ line: 0, offset: 7 }),
+        ]));
+        assert_eq!(buffer.column_ranges(1), None);
+    }
+
+    #[test]
+    fn column_ranges_handles_escaped_quotes() {
+        let buffer = table_buffer("data.csv", "\"a \"\"b,\"\" c\",d");
+
+        assert_eq!(buffer.column_ranges(0).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn cell_at_assigns_trailing_positions_to_the_last_cell() {
+        let buffer = table_buffer("data.csv", "a,b\n");
+
+        assert_eq!(buffer.cell_at(&Position{ line: 0, offset: 1 }).unwrap().column, 0);
+        assert_eq!(buffer.cell_at(&Position{ line: 0, offset: 3 }).unwrap().column, 1);
+        assert_eq!(buffer.cell_at(&Position{ line: 1, offset: 0 }).unwrap().column, 0);
+    }
+
+    #[test]
+    fn move_to_next_cell_stops_at_the_end_of_the_buffer() {
+        let mut buffer = table_buffer("data.csv", "a,b");
+        buffer.cursor.move_to(Position{ line: 0, offset: 2 });
+
+        assert!(!buffer.move_to_next_cell());
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 2 });
+    }
+
+    #[test]
+    fn move_to_previous_cell_moves_to_the_start_of_the_current_cell_first() {
+        let mut buffer = table_buffer("data.csv", "name,city");
+        buffer.cursor.move_to(Position{ line: 0, offset: 7 });
+
+        assert!(buffer.move_to_previous_cell());
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 5 });
+        assert!(buffer.move_to_previous_cell());
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 0 });
+        assert!(!buffer.move_to_previous_cell());
+    }
+
+    #[test]
+    fn aligned_columns_handles_rows_of_varying_length() {
+        let buffer = table_buffer("data.tsv", "a\tbb\tc\nddd\ne\tf");
+
+        assert_eq!(
+            buffer.aligned_columns(" "),
+            Some("a   bb c\nddd\ne   f".to_string())
+        );
+    }
+}
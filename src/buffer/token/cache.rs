@@ -0,0 +1,135 @@
+use buffer::token::{LexerState, SyntaxState};
+use syntect::parsing::ScopeStackOp;
+
+// The number of lines between stored parser states. Edits discard
+// cached lines back to the nearest preceding stored state.
+const CHECKPOINT_INTERVAL: usize = 64;
+
+// Lexer output for a contiguous run of lines from the start of the buffer,
+// allowing lexing to replay cached lines, rather than lexing them again.
+// Lines are discarded from the point of an edit onwards, and lexing resumes
+// from the nearest stored state preceding it. Stored states also let lexing
+// start partway through the buffer (see `checkpoint_before`).
+pub(crate) struct TokenCache<S: LexerState = SyntaxState> {
+    syntax: Option<String>,
+    max_line_length: Option<usize>,
+    lines: Vec<Vec<(usize, ScopeStackOp)>>,
+    checkpoints: Vec<S>,
+    next_state: Option<S>,
+}
+
+impl<S: LexerState> Default for TokenCache<S> {
+    fn default() -> TokenCache<S> {
+        TokenCache{ syntax: None, max_line_length: None, lines: Vec::new(), checkpoints: Vec::new(), next_state: None }
+    }
+}
+
+impl<S: LexerState> TokenCache<S> {
+    // Discards the cache if it was populated using a different syntax or
+    // maximum line length, starting over with the syntax's initial state.
+    pub(crate) fn prepare<F: FnOnce() -> S>(&mut self, syntax: &str, max_line_length: Option<usize>, initial_state: F) {
+        if self.syntax.as_deref() != Some(syntax) || self.max_line_length != max_line_length {
+            let state = initial_state();
+            *self = TokenCache{
+                syntax: Some(syntax.to_string()),
+                max_line_length,
+                lines: Vec::new(),
+                checkpoints: vec![state.clone()],
+                next_state: Some(state),
+            };
+        }
+    }
+
+    // The number of cached lines.
+    pub(crate) fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub(crate) fn line(&self, line: usize) -> Option<&Vec<(usize, ScopeStackOp)>> {
+        self.lines.get(line)
+    }
+
+    // The lexer state at the start of the first uncached line.
+    pub(crate) fn next_state(&self) -> Option<&S> {
+        self.next_state.as_ref()
+    }
+
+    // The closest stored state at or before the start of the specified
+    // line, along with the line it was captured at.
+    pub(crate) fn checkpoint_before(&self, line: usize) -> Option<(usize, &S)> {
+        if line >= self.lines.len() {
+            return self.next_state.as_ref().map(|state| (self.lines.len(), state));
+        }
+
+        let checkpoint = line / CHECKPOINT_INTERVAL;
+        self.checkpoints.get(checkpoint).map(|state| (checkpoint * CHECKPOINT_INTERVAL, state))
+    }
+
+    // Appends the events for the next uncached line, along
+    // with the state the lexer was left in after producing them.
+    pub(crate) fn push(&mut self, events: Vec<(usize, ScopeStackOp)>, state: &S) {
+        self.lines.push(events);
+        if self.lines.len().is_multiple_of(CHECKPOINT_INTERVAL) {
+            self.checkpoints.push(state.clone());
+        }
+        self.next_state = Some(state.clone());
+    }
+
+    // Discards lines that may have been affected by a change to the specified line.
+    pub(crate) fn invalidate(&mut self, line: usize) {
+        if line >= self.lines.len() { return; }
+
+        let checkpoint = line / CHECKPOINT_INTERVAL;
+        self.checkpoints.truncate(checkpoint + 1);
+        self.lines.truncate(checkpoint * CHECKPOINT_INTERVAL);
+        self.next_state = self.checkpoints.get(checkpoint).cloned();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TokenCache, CHECKPOINT_INTERVAL};
+    use buffer::token::{LexerState, SyntaxState};
+    use syntect::parsing::SyntaxSet;
+
+    #[test]
+    fn invalidate_discards_lines_back_to_the_preceding_checkpoint() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let def = syntax_set.find_syntax_plain_text();
+        let mut cache = TokenCache::default();
+        cache.prepare(&def.name, None, || SyntaxState::new(def));
+
+        let mut state = SyntaxState::new(def);
+        for _ in 0..CHECKPOINT_INTERVAL * 2 + 10 {
+            let events = state.lex_line("scribe\n");
+            cache.push(events, &state);
+        }
+
+        cache.invalidate(CHECKPOINT_INTERVAL * 2 + 5);
+        assert_eq!(cache.len(), CHECKPOINT_INTERVAL * 2);
+
+        cache.invalidate(CHECKPOINT_INTERVAL - 1);
+        assert_eq!(cache.len(), 0);
+        assert_eq!(cache.next_state(), Some(&SyntaxState::new(def)));
+    }
+
+    #[test]
+    fn checkpoint_before_returns_the_closest_preceding_state() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let def = syntax_set.find_syntax_by_extension("rs").unwrap();
+        let mut cache = TokenCache::default();
+        cache.prepare(&def.name, None, || SyntaxState::new(def));
+
+        let mut state = SyntaxState::new(def);
+        for line in 0..CHECKPOINT_INTERVAL + 10 {
+            let events = state.lex_line(if line == 0 { "/*\n" } else { "scribe\n" });
+            cache.push(events, &state);
+        }
+
+        let (line, checkpoint) = cache.checkpoint_before(CHECKPOINT_INTERVAL + 5).unwrap();
+        assert_eq!(line, CHECKPOINT_INTERVAL);
+        assert_eq!(checkpoint.scopes().as_slice().len(), 2);
+        assert_eq!(cache.checkpoint_before(5).unwrap().0, 0);
+        assert_eq!(cache.checkpoint_before(500).unwrap(), (CHECKPOINT_INTERVAL + 10, &state));
+    }
+}
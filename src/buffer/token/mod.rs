@@ -1,6 +1,12 @@
+mod cache;
+mod state;
 mod token_iterator;
 mod token_set;
 
+pub(crate) use self::cache::TokenCache;
+pub(crate) use self::state::lex_line_within;
+
+pub use self::state::{LexerState, SyntaxState};
 pub use self::token_iterator::TokenIterator;
 pub use self::token_set::TokenSet;
 
@@ -0,0 +1,97 @@
+use syntect::parsing::{ParseState, ScopeStack, ScopeStackOp, SyntaxDefinition};
+
+/// State a lexer carries from one line to the next (e.g. being inside a
+/// block comment), captured at the start of a line. Restoring it lets
+/// lexing resume at that line, without revisiting the lines before it.
+pub trait LexerState: Clone {
+    /// Lexes the line (including its trailing newline, if it has one),
+    /// returning the scope changes within it, and advancing the
+    /// state to the start of the following line.
+    fn lex_line(&mut self, line: &str) -> Vec<(usize, ScopeStackOp)>;
+
+    /// The scopes open at the start of the current line.
+    fn scopes(&self) -> &ScopeStack;
+}
+
+/// The `LexerState` of a syntect parser, produced by `Buffer::lexer_state`
+/// and restored using `TokenSet::resuming`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxState {
+    parser: ParseState,
+    scopes: ScopeStack,
+}
+
+impl SyntaxState {
+    /// The state at the start of content using the syntax definition.
+    pub fn new(def: &SyntaxDefinition) -> SyntaxState {
+        SyntaxState{ parser: ParseState::new(def), scopes: ScopeStack::new() }
+    }
+}
+
+impl LexerState for SyntaxState {
+    fn lex_line(&mut self, line: &str) -> Vec<(usize, ScopeStackOp)> {
+        let events = self.parser.parse_line(line);
+        for (_, scope_change) in &events {
+            self.scopes.apply(scope_change);
+        }
+
+        events
+    }
+
+    fn scopes(&self) -> &ScopeStack {
+        &self.scopes
+    }
+}
+
+// Lexes the line, unless it's longer than the maximum length (in bytes, not
+// counting its newline), in which case it isn't lexed, leaving the state
+// unchanged, and the line within the scopes open at its start. At the start
+// of content, an empty line is lexed in its place, to open the root scope.
+pub(crate) fn lex_line_within<S: LexerState>(state: &mut S, line: &str, max_length: Option<usize>) -> Vec<(usize, ScopeStackOp)> {
+    match max_length {
+        Some(max_length) if line.trim_end_matches('\n').len() > max_length => {
+            if state.scopes().is_empty() {
+                state.lex_line("")
+            } else {
+                Vec::new()
+            }
+        },
+        _ => state.lex_line(line),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lex_line_within, LexerState, SyntaxState};
+    use syntect::parsing::SyntaxSet;
+
+    #[test]
+    fn lex_line_tracks_scopes_open_at_the_start_of_the_next_line() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let def = syntax_set.find_syntax_by_extension("rs").unwrap();
+        let mut state = SyntaxState::new(def);
+
+        state.lex_line("fn scribe() { /* library\n");
+        let scopes: Vec<String> = state.scopes().as_slice().iter().map(|scope| scope.build_string()).collect();
+        assert_eq!(scopes.last().map(|scope| scope.as_str()), Some("comment.block.rust"));
+
+        state.lex_line("*/ }\n");
+        assert_eq!(state.scopes().as_slice().len(), 1);
+    }
+
+    #[test]
+    fn lex_line_within_skips_lines_beyond_the_maximum_length() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let def = syntax_set.find_syntax_by_extension("rs").unwrap();
+        let mut state = SyntaxState::new(def);
+
+        lex_line_within(&mut state, "/* scribe\n", Some(8));
+        assert_eq!(state.scopes().as_slice().len(), 1);
+        let skipped = state.clone();
+        assert!(lex_line_within(&mut state, "/* scribe\n", Some(8)).is_empty());
+        assert_eq!(state, skipped);
+
+        lex_line_within(&mut state, "/* scribe\n", Some(9));
+        assert_eq!(state.scopes().as_slice().len(), 2);
+    }
+}
@@ -1,29 +1,69 @@
+use std::cell::RefCell;
 use std::cmp;
-use buffer::{Lexeme, Position, Token};
-use syntect::parsing::{ParseState, ScopeStack, ScopeStackOp, SyntaxDefinition};
+use buffer::{Lexeme, Position, Token, TokenCache};
+use buffer::token::{lex_line_within, LexerState, SyntaxState};
+use syntect::parsing::{ScopeStack, ScopeStackOp, SyntaxDefinition};
 use util::LineIterator;
 use unicode_segmentation::UnicodeSegmentation;
 
 pub struct TokenIterator<'a> {
     scopes: ScopeStack,
-    parser: ParseState,
+    state: SyntaxState,
     lines: LineIterator<'a>,
     current_line: Option<&'a str>,
     current_byte_offset: usize,
     current_position: Position,
     line_events: Vec<(usize, ScopeStackOp)>,
+    line_offset: usize,
+    end_line: Option<usize>,
+    cache: Option<&'a RefCell<TokenCache>>,
+    parser_current: bool,
+    max_line_length: Option<usize>,
 }
 
 impl<'a> TokenIterator<'a> {
     pub fn new(data: &'a str, def: &SyntaxDefinition) -> TokenIterator<'a> {
+        TokenIterator::with_line_offset(data, def, 0, None)
+    }
+
+    // Like new, but adds the specified offset to token line numbers, and
+    // leaves lines longer than the maximum length (in bytes) unlexed.
+    pub(crate) fn with_line_offset(data: &'a str, def: &SyntaxDefinition, line_offset: usize,
+                                   max_line_length: Option<usize>) -> TokenIterator<'a> {
+        TokenIterator::build(data, SyntaxState::new(def), line_offset, None, max_line_length)
+    }
+
+    // Like with_line_offset, but lexes the data starting from the specified
+    // state, as though it followed the content that left the lexer in it.
+    pub(crate) fn resuming(data: &'a str, state: SyntaxState, line_offset: usize,
+                           max_line_length: Option<usize>) -> TokenIterator<'a> {
+        TokenIterator::build(data, state, line_offset, None, max_line_length)
+    }
+
+    // Like with_line_offset, but replays parser output for lines held in the
+    // cache, adding any lines that are parsed to it. The cache must have been
+    // prepared for the syntax definition and maximum line length, and
+    // populated using the same data.
+    pub(crate) fn with_cache(data: &'a str, def: &SyntaxDefinition, cache: &'a RefCell<TokenCache>,
+                             max_line_length: Option<usize>) -> TokenIterator<'a> {
+        TokenIterator::build(data, SyntaxState::new(def), 0, Some(cache), max_line_length)
+    }
+
+    fn build(data: &'a str, state: SyntaxState, line_offset: usize,
+             cache: Option<&'a RefCell<TokenCache>>, max_line_length: Option<usize>) -> TokenIterator<'a> {
         let mut token_iterator = TokenIterator{
-            scopes: ScopeStack::new(),
-            parser: ParseState::new(def),
+            scopes: state.scopes().clone(),
+            state,
             lines: LineIterator::new(data),
             current_line: None,
             current_byte_offset: 0,
             current_position: Position{ line: 0, offset: 0 },
             line_events: Vec::new(),
+            line_offset,
+            end_line: None,
+            cache,
+            parser_current: true,
+            max_line_length,
         };
 
         // Preload the first line
@@ -32,6 +72,41 @@ impl<'a> TokenIterator<'a> {
         token_iterator
     }
 
+    // Skips to the start line (parsing, but not tokenizing, the lines before
+    // it), and stops producing tokens once the end line is reached. When
+    // using a cache, lines before its closest stored state are skipped.
+    pub(crate) fn within_lines(mut self, start: usize, end: usize) -> TokenIterator<'a> {
+        self.end_line = Some(end);
+
+        let checkpoint = self.cache.and_then(|cache| {
+            cache.borrow().checkpoint_before(start).map(|(line, state)| (line, state.clone()))
+        });
+        if let Some((line, state)) = checkpoint {
+            if self.current_line.is_some() && line > self.current_position.line {
+                for _ in self.current_position.line + 1..line {
+                    self.lines.next();
+                }
+                self.scopes = state.scopes().clone();
+                self.state = state;
+                self.parser_current = true;
+                self.parse_next_line();
+            }
+        }
+
+        while self.current_line.is_some() && self.current_position.line < start {
+            while let Some((_, scope_change)) = self.line_events.pop() {
+                self.scopes.apply(&scope_change);
+            }
+            self.parse_next_line();
+        }
+
+        if self.current_position.line >= end {
+            self.current_line = None;
+        }
+
+        self
+    }
+
     fn next_token(&mut self) -> Option<Token<'a>> {
         // Try to fetch a token from the current line.
         if let Some(token) = self.build_next_token() {
@@ -110,10 +185,14 @@ impl<'a> TokenIterator<'a> {
     }
 
     fn parse_next_line(&mut self) {
-        if let Some((line_number, line)) = self.lines.next() {
+        let next_line = self.lines.next().filter(|&(line_number, _)| {
+            self.end_line.is_none_or(|end| line_number + self.line_offset < end)
+        });
+
+        if let Some((line_number, line)) = next_line {
             // We reverse the line elements so that we can pop them off one at a
             // time, handling each event while allowing us to stop at any point.
-            let mut line_events = self.parser.parse_line(line);
+            let mut line_events = self.line_events_for(line_number, line);
             line_events.reverse();
             self.line_events = line_events;
 
@@ -121,7 +200,7 @@ impl<'a> TokenIterator<'a> {
             self.current_line = Some(line);
 
             // Track our position, which we'll pass to generated tokens.
-            self.current_position = Position{ line: line_number, offset: 0 };
+            self.current_position = Position{ line: line_number + self.line_offset, offset: 0 };
 
             // Reset byte-based line offset.
             self.current_byte_offset = 0;
@@ -131,6 +210,36 @@ impl<'a> TokenIterator<'a> {
     }
 }
 
+impl<'a> TokenIterator<'a> {
+    // Parses the line, or replays its cached parser output.
+    fn line_events_for(&mut self, line_number: usize, line: &str) -> Vec<(usize, ScopeStackOp)> {
+        let mut cache = match self.cache {
+            Some(cache) => cache.borrow_mut(),
+            None => return lex_line_within(&mut self.state, line, self.max_line_length),
+        };
+
+        if let Some(events) = cache.line(line_number) {
+            // The lexer is left behind while replaying.
+            self.parser_current = false;
+            return events.clone();
+        }
+
+        if !self.parser_current {
+            if let Some(state) = cache.next_state() {
+                self.state = state.clone();
+            }
+            self.parser_current = true;
+        }
+
+        let events = lex_line_within(&mut self.state, line, self.max_line_length);
+        if cache.len() == line_number {
+            cache.push(events.clone(), &self.state);
+        }
+
+        events
+    }
+}
+
 impl<'a> Iterator for TokenIterator<'a> {
     type Item = Token<'a>;
 
@@ -277,6 +386,31 @@ mod tests {
         //assert_eq!(expected_tokens, actual_tokens);
     }
 
+    #[test]
+    fn token_iterator_within_lines_omits_tokens_outside_of_the_range() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let def = syntax_set.find_syntax_plain_text();
+        let tokens: Vec<Token> = TokenIterator::new("scribe\nlibrary\neditor\ntext", def)
+            .within_lines(1, 3)
+            .collect();
+
+        let scope = ScopeStack::from_vec(vec![Scope::new("text.plain").unwrap()]);
+        assert_eq!(tokens, vec![
+            Token::Lexeme(Lexeme{ value: "library", scope: scope.clone(), position: Position{ line: 1, offset: 0 } }),
+            Token::Newline,
+            Token::Lexeme(Lexeme{ value: "editor", scope, position: Position{ line: 2, offset: 0 } }),
+        ]);
+    }
+
+    #[test]
+    fn token_iterator_within_lines_handles_ranges_beyond_the_data() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let def = syntax_set.find_syntax_plain_text();
+
+        assert_eq!(TokenIterator::new("scribe\n", def).within_lines(5, 10).count(), 0);
+        assert_eq!(TokenIterator::new("scribe\n", def).within_lines(0, 0).count(), 0);
+    }
+
     #[test]
     fn token_iterator_handles_unicode_characters() {
         let syntax_set = SyntaxSet::load_defaults_newlines();
@@ -1,20 +1,137 @@
+use std::cell::RefCell;
+use std::rc::Rc;
 use syntect::parsing::SyntaxDefinition;
-use buffer::token::TokenIterator;
+use buffer::token::{SyntaxState, TokenCache, TokenIterator};
 
 pub struct TokenSet<'a> {
-    data: String,
+    data: Rc<String>,
     syntax_definition: &'a SyntaxDefinition,
+    line_offset: usize,
+    lines: Option<(usize, usize)>,
+    cache: Option<&'a RefCell<TokenCache>>,
+    state: Option<SyntaxState>,
+    max_line_length: Option<usize>,
 }
 
 impl<'a> TokenSet<'a> {
     pub fn new(data: String, def: &SyntaxDefinition) -> TokenSet {
+        TokenSet::shared(Rc::new(data), def)
+    }
+
+    // Like `new`, but lexes data that's shared with its owner (e.g. a
+    // buffer's copy of its content), rather than taking ownership of it.
+    pub(crate) fn shared(data: Rc<String>, def: &SyntaxDefinition) -> TokenSet<'_> {
         TokenSet{
             data,
-            syntax_definition: def
+            syntax_definition: def,
+            line_offset: 0,
+            lines: None,
+            cache: None,
+            state: None,
+            max_line_length: None,
         }
     }
 
+    // Offsets the line numbers of produced tokens, for
+    // data that doesn't start at the beginning of a buffer.
+    pub(crate) fn with_line_offset(mut self, line_offset: usize) -> TokenSet<'a> {
+        self.line_offset = line_offset;
+        self
+    }
+
+    /// Lexes the data starting from a captured state (see
+    /// `Buffer::lexer_state`), as though it followed the content preceding
+    /// the specified line, so that a range of lines can be lexed correctly
+    /// on its own. Produced tokens are positioned from that line onwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate syntect;
+    /// extern crate scribe;
+    ///
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Token, TokenSet};
+    /// use syntect::parsing::SyntaxSet;
+    ///
+    /// let syntax_set = SyntaxSet::load_defaults_newlines();
+    /// let def = syntax_set.find_syntax_by_extension("rs").unwrap();
+    /// let mut buffer = Buffer::new();
+    /// buffer.syntax_definition = Some(def.clone());
+    /// buffer.insert("/*\nscribe */\nstruct Buffer;\n");
+    ///
+    /// let state = buffer.lexer_state(1).unwrap();
+    /// let tokens = TokenSet::new("scribe */\n".to_string(), def).resuming(state, 1);
+    /// match tokens.iter().next() {
+    ///     Some(Token::Lexeme(lexeme)) => {
+    ///         assert_eq!(lexeme.value, "scribe ");
+    ///         assert_eq!(lexeme.position, Position{ line: 1, offset: 0 });
+    ///         assert!(lexeme.scope.as_slice().iter().any(|scope| scope.build_string().starts_with("comment")));
+    ///     },
+    ///     _ => panic!("expected a lexeme"),
+    /// }
+    /// ```
+    pub fn resuming(mut self, state: SyntaxState, line: usize) -> TokenSet<'a> {
+        self.state = Some(state);
+        self.line_offset = line;
+        self
+    }
+
+    /// Leaves lines longer than the specified length (in bytes, not counting
+    /// their newline) unlexed (see `Buffer::max_line_length`). Each such line
+    /// is produced as a single lexeme, within the scopes open at its start.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate syntect;
+    /// extern crate scribe;
+    ///
+    /// use scribe::buffer::{Token, TokenSet};
+    /// use syntect::parsing::SyntaxSet;
+    ///
+    /// let syntax_set = SyntaxSet::load_defaults_newlines();
+    /// let def = syntax_set.find_syntax_by_extension("rs").unwrap();
+    /// let tokens = TokenSet::new("let scribe = 1;\n".to_string(), def).with_max_line_length(Some(10));
+    /// match tokens.iter().next() {
+    ///     Some(Token::Lexeme(lexeme)) => {
+    ///         assert_eq!(lexeme.value, "let scribe = 1;");
+    ///         assert_eq!(lexeme.scope.as_slice().len(), 1);
+    ///     },
+    ///     _ => panic!("expected a lexeme"),
+    /// }
+    /// ```
+    pub fn with_max_line_length(mut self, max_line_length: Option<usize>) -> TokenSet<'a> {
+        self.max_line_length = max_line_length;
+        self
+    }
+
+    // Replays and populates the cache, which must have been prepared for the
+    // syntax definition and maximum line length, and invalidated since the
+    // data was last changed.
+    pub(crate) fn with_cache(mut self, cache: &'a RefCell<TokenCache>) -> TokenSet<'a> {
+        self.cache = Some(cache);
+        self
+    }
+
+    // Restricts produced tokens to lines `start` (inclusive) through `end`
+    // (exclusive). Preceding lines are still parsed, to establish their
+    // scopes, but aren't tokenized; following lines aren't parsed at all.
+    pub(crate) fn with_lines(mut self, start: usize, end: usize) -> TokenSet<'a> {
+        self.lines = Some((start, end));
+        self
+    }
+
     pub fn iter(&self) -> TokenIterator {
-        TokenIterator::new(&self.data, self.syntax_definition)
+        let iterator = match (self.cache, &self.state) {
+            (Some(cache), _) => TokenIterator::with_cache(&self.data, self.syntax_definition, cache, self.max_line_length),
+            (None, Some(state)) => TokenIterator::resuming(&self.data, state.clone(), self.line_offset, self.max_line_length),
+            (None, None) => TokenIterator::with_line_offset(&self.data, self.syntax_definition, self.line_offset, self.max_line_length),
+        };
+
+        match self.lines {
+            Some((start, end)) => iterator.within_lines(start, end),
+            None => iterator,
+        }
     }
 }
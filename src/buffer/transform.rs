@@ -0,0 +1,122 @@
+//! Transformations applied to inserted text.
+
+use buffer::{Buffer, Position};
+
+// A function producing the text to insert in place of the text provided.
+pub(crate) type InsertTransform = Box<dyn Fn(&str) -> String>;
+
+impl Buffer {
+    /// Registers a transform, applied to typed, pasted and replacement text
+    /// (i.e. text inserted using `insert`, `replace`, `replace_all`,
+    /// `replace_selections` or `commit_pending`) before it's added to the
+    /// buffer. Transforms are applied in the order they're registered, each
+    /// receiving the output of the last. Returns an ID that can be used to
+    /// remove the transform.
+    ///
+    /// Content the buffer produces itself (e.g. indentation, or the output
+    /// of `replace_contents` and `filter_lines`) isn't transformed. See the
+    /// `util` module for common transforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::util::{expand_tabs, strip_control_characters};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.add_insert_transform(strip_control_characters);
+    /// let id = buffer.add_insert_transform(expand_tabs(2));
+    ///
+    /// buffer.insert("\tscribe\u{7}");
+    /// assert_eq!(buffer.data(), "  scribe");
+    ///
+    /// assert!(buffer.remove_insert_transform(id));
+    /// buffer.insert("\t");
+    /// assert_eq!(buffer.data(), "\t  scribe");
+    /// ```
+    pub fn add_insert_transform<F: Fn(&str) -> String + 'static>(&mut self, transform: F) -> usize {
+        let id = self.next_transform_id;
+        self.next_transform_id += 1;
+        self.insert_transforms.push((id, Box::new(transform)));
+
+        id
+    }
+
+    /// Removes a previously registered transform, returning
+    /// whether or not a transform with the specified ID existed.
+    pub fn remove_insert_transform(&mut self, id: usize) -> bool {
+        let count = self.insert_transforms.len();
+        self.insert_transforms.retain(|&(transform_id, _)| transform_id != id);
+
+        self.insert_transforms.len() != count
+    }
+
+    // Runs the data through each registered transform.
+    pub(crate) fn transform_insertion(&self, data: String) -> String {
+        self.insert_transforms.iter().fold(data, |data, (_, transform)| transform(&data))
+    }
+
+    // Inserts user-supplied text at the position, after running it through
+    // the registered transforms. Returns the text that was inserted.
+    pub(crate) fn insert_transformed(&mut self, data: String, position: Position) -> String {
+        let data = self.transform_insertion(data);
+        if !data.is_empty() {
+            self.insert_at(data.as_str(), position);
+        }
+
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, Range};
+
+    #[test]
+    fn transformed_insertions_are_undone_as_inserted() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        buffer.add_insert_transform(|data| data.to_uppercase());
+
+        buffer.insert("library ");
+        assert_eq!(buffer.data(), "LIBRARY scribe");
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "scribe");
+    }
+
+    #[test]
+    fn insertions_transformed_to_nothing_are_ignored() {
+        let mut buffer = Buffer::new();
+        buffer.add_insert_transform(|_| String::new());
+        buffer.insert("scribe");
+
+        assert_eq!(buffer.data(), "");
+        assert!(buffer.last_edited().is_none());
+    }
+
+    #[test]
+    fn replacements_are_transformed() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe library");
+        buffer.add_insert_transform(|data| data.to_uppercase());
+
+        buffer.replace(&Range::new(Position{ line: 0, offset: 7 }, Position{ line: 0, offset: 14 }), "editor");
+        assert_eq!(buffer.data(), "scribe EDITOR");
+
+        buffer.replace_all("scribe", "amp");
+        assert_eq!(buffer.data(), "AMP EDITOR");
+    }
+
+    #[test]
+    fn selection_replacements_are_transformed_and_selected() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        buffer.add_insert_transform(|data| data.replace('\t', "    "));
+        buffer.select_all_occurrences("scribe");
+
+        buffer.replace_selections("\tamp");
+        assert_eq!(buffer.data(), "    amp");
+        assert_eq!(buffer.selections(), &[Range::new(Position{ line: 0, offset: 0 }, Position{ line: 0, offset: 7 })]);
+    }
+}
@@ -0,0 +1,106 @@
+//! Typographic punctuation substitution, for prose.
+
+use buffer::{Buffer, Position, Range};
+
+impl Buffer {
+    // Replaces the straight quote or hyphen just inserted at the position with
+    // its typographic equivalent, as a separate history entry, so that undoing
+    // it restores the straight character. Hyphens are combined with a
+    // preceding hyphen or en dash, forming en and em dashes, respectively.
+    pub(crate) fn substitute_punctuation(&mut self, position: Position) {
+        let previous = if position.offset > 0 {
+            let start = Position{ line: position.line, offset: position.offset - 1 };
+            self.read(&Range::new(start, position))
+        } else {
+            None
+        };
+        let inserted = self.read(&Range::new(position, Position{
+            line: position.line,
+            offset: position.offset + 1
+        }));
+
+        let opening = previous.as_ref().is_none_or(|previous| opens_quotation(previous));
+        let (start, replacement) = match (inserted.as_deref(), previous.as_deref()) {
+            (Some("\""), _) => (position, if opening { "\u{201C}" } else { "\u{201D}" }),
+            (Some("'"), _) => (position, if opening { "\u{2018}" } else { "\u{2019}" }),
+            (Some("-"), Some("-")) => (Position{ offset: position.offset - 1, ..position }, "\u{2013}"),
+            (Some("-"), Some("\u{2013}")) => (Position{ offset: position.offset - 1, ..position }, "\u{2014}"),
+            _ => return,
+        };
+
+        let end = Position{ line: position.line, offset: position.offset + 1 };
+        self.replace_verbatim(&Range::new(start, end), replacement);
+
+        // Dashes replace the preceding character, too. Move the cursor back
+        // to account for it, so that moving past the inserted character
+        // leaves the cursor after the dash.
+        if start != position && *self.cursor == position {
+            self.cursor.move_to(start);
+        }
+    }
+}
+
+// Whether a quote following the grapheme would open a quotation.
+fn opens_quotation(grapheme: &str) -> bool {
+    grapheme.chars().all(|c| {
+        c.is_whitespace() || "([{<\u{201C}\u{2018}\u{2013}\u{2014}".contains(c)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::Buffer;
+
+    // Types the text one character at a time, as a user would.
+    fn type_text(buffer: &mut Buffer, text: &str) {
+        for c in text.chars() {
+            buffer.insert(c.to_string());
+            if c == '\n' {
+                buffer.cursor.move_down();
+                buffer.cursor.move_to_start_of_line();
+            } else {
+                buffer.cursor.move_right();
+            }
+        }
+    }
+
+    #[test]
+    fn quotes_are_curled_based_on_the_preceding_character() {
+        let mut buffer = Buffer::new();
+        buffer.smart_punctuation = true;
+        type_text(&mut buffer, "\"Don't,\" she said ('twas late).\n'Fine'");
+
+        assert_eq!(buffer.data(), "“Don’t,” she said (‘twas late).\n‘Fine’");
+    }
+
+    #[test]
+    fn hyphens_are_combined_into_dashes() {
+        let mut buffer = Buffer::new();
+        buffer.smart_punctuation = true;
+        type_text(&mut buffer, "1--2 and so---on");
+
+        assert_eq!(buffer.data(), "1–2 and so—on");
+        assert_eq!(buffer.cursor.offset, 13);
+    }
+
+    #[test]
+    fn undoing_a_substitution_restores_the_straight_character() {
+        let mut buffer = Buffer::new();
+        buffer.smart_punctuation = true;
+        type_text(&mut buffer, "a--");
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "a--");
+        buffer.undo();
+        assert_eq!(buffer.data(), "a-");
+    }
+
+    #[test]
+    fn pasted_text_is_left_alone() {
+        let mut buffer = Buffer::new();
+        buffer.smart_punctuation = true;
+        buffer.insert("\"scribe\" -- library");
+
+        assert_eq!(buffer.data(), "\"scribe\" -- library");
+    }
+}
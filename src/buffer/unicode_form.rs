@@ -0,0 +1,90 @@
+//! Unicode normalization of buffer content.
+
+use buffer::Buffer;
+use unicode_normalization::UnicodeNormalization;
+
+/// A Unicode normalization form. Text using a mix of forms can contain
+/// visually identical characters that compare (and search) differently;
+/// normalizing it to a single form avoids this.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnicodeForm {
+    /// Canonical composition (e.g. "é" as a single character).
+    Nfc,
+
+    /// Canonical decomposition (e.g. "é" as "e" and a combining accent).
+    Nfd,
+}
+
+impl UnicodeForm {
+    /// Converts the text to this form.
+    pub fn apply(&self, data: &str) -> String {
+        match *self {
+            UnicodeForm::Nfc => data.nfc().collect(),
+            UnicodeForm::Nfd => data.nfd().collect(),
+        }
+    }
+}
+
+impl Buffer {
+    /// Converts the buffer's content to the specified normalization form, as
+    /// a single operation that can be undone, returning whether it changed
+    /// anything. Typically used after loading a file (see the
+    /// `unicode_form` field of `BufferPreferences`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::UnicodeForm;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe\u{301} and scrib\u{e9}");
+    ///
+    /// assert!(buffer.normalize_unicode(UnicodeForm::Nfc));
+    /// assert_eq!(buffer.data(), "scrib\u{e9} and scrib\u{e9}");
+    /// assert!(!buffer.normalize_unicode(UnicodeForm::Nfc));
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "scribe\u{301} and scrib\u{e9}");
+    /// ```
+    pub fn normalize_unicode(&mut self, form: UnicodeForm) -> bool {
+        if self.read_only { return false; }
+
+        let data = self.data();
+        let normalized = form.apply(&data);
+        if normalized == data {
+            return false;
+        }
+
+        self.try_replace_contents(&normalized).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, UnicodeForm};
+
+    #[test]
+    fn normalize_unicode_keeps_the_cursor_on_the_same_text() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scrib\u{e9}\nlibrary");
+        buffer.cursor.move_to(Position{ line: 1, offset: 3 });
+
+        assert!(buffer.normalize_unicode(UnicodeForm::Nfd));
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 3 });
+
+        buffer.insert("\u{e9}");
+        assert!(buffer.normalize_unicode(UnicodeForm::Nfd));
+        assert_eq!(buffer.data(), "scribe\u{301}\nlibe\u{301}rary");
+    }
+
+    #[test]
+    fn normalize_unicode_leaves_read_only_buffers_alone() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scrib\u{e9}");
+        buffer.read_only = true;
+
+        assert!(!buffer.normalize_unicode(UnicodeForm::Nfd));
+        assert_eq!(buffer.data(), "scrib\u{e9}");
+    }
+}
@@ -0,0 +1,97 @@
+//! Internal consistency checks.
+
+use buffer::{Buffer, GapBuffer};
+use errors::*;
+use std::rc::Rc;
+
+impl GapBuffer {
+    /// Checks the buffer's internal consistency: the gap must lie within its
+    /// allocation, and the data on either side of it must be valid UTF-8.
+    /// In debug builds, this is verified after every modification.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::buffer::{GapBuffer, Position};
+    ///
+    /// let mut buffer = GapBuffer::new("scribe".to_string());
+    /// buffer.insert("नी", &Position{ line: 0, offset: 3 });
+    /// assert!(buffer.validate().is_ok());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        match self.violation() {
+            Some(violation) => Err(ErrorKind::InvariantViolation(violation).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Buffer {
+    /// Checks the buffer's internal consistency, returning an error describing
+    /// the first violation found. In addition to the underlying `GapBuffer`'s
+    /// checks (which run automatically after every modification in debug
+    /// builds), this verifies that the cursor is bound to the buffer's data,
+    /// and that it's positioned within its bounds. Operations that shrink the
+    /// buffer (e.g. `delete_range` or `undo`) don't move the cursor, so the
+    /// latter should be checked after the cursor has been updated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    /// assert!(buffer.validate().is_ok());
+    ///
+    /// // The cursor's position can be set without bounds-checking.
+    /// buffer.cursor.position = Position{ line: 1, offset: 0 };
+    /// assert!(buffer.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        let data = self.data.borrow();
+        data.validate()?;
+
+        if !Rc::ptr_eq(&self.data, &self.cursor.data) {
+            return Err(ErrorKind::InvariantViolation(
+                "cursor isn't bound to the buffer's data".to_string()
+            ).into());
+        }
+
+        if !data.in_bounds(&self.cursor.position) {
+            return Err(ErrorKind::InvariantViolation(format!(
+                "cursor ({}, {}) is out of bounds",
+                self.cursor.line,
+                self.cursor.offset
+            )).into());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Cursor, GapBuffer, Position};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn validate_detects_cursors_bound_to_other_data() {
+        let mut buffer = Buffer::new();
+        let other_data = Rc::new(RefCell::new(GapBuffer::new(String::new())));
+        buffer.cursor = Cursor::new(other_data, Position::new());
+
+        assert!(buffer.validate().is_err());
+    }
+
+    #[test]
+    fn validate_accepts_cursors_at_the_end_of_the_buffer() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\n");
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+
+        assert!(buffer.validate().is_ok());
+    }
+}
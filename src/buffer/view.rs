@@ -0,0 +1,249 @@
+//! Read-only snapshots of earlier (or later) points in a buffer's history.
+
+use buffer::operation::Operation;
+use buffer::Buffer;
+#[cfg(feature = "syntax")]
+use buffer::TokenSet;
+#[cfg(feature = "syntax")]
+use errors::*;
+#[cfg(feature = "syntax")]
+use syntect::parsing::SyntaxDefinition;
+use util::matching_elements;
+
+/// A read-only view of a buffer's contents at a point in its undo history.
+pub struct BufferView {
+    data: String,
+    history_index: usize,
+    #[cfg(feature = "syntax")]
+    syntax_definition: Option<SyntaxDefinition>,
+}
+
+impl BufferView {
+    /// The view's contents.
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    /// The point in the buffer's history that the view represents.
+    pub fn history_index(&self) -> usize {
+        self.history_index
+    }
+
+    /// Produces a set of tokens for the view's contents, using the syntax
+    /// definition the buffer had when the view was created.
+    #[cfg(feature = "syntax")]
+    pub fn tokens(&self) -> Result<TokenSet<'_>> {
+        if let Some(ref def) = self.syntax_definition {
+            Ok(TokenSet::new(self.data.clone(), def))
+        } else {
+            Err(ErrorKind::MissingSyntaxDefinition)?
+        }
+    }
+
+    /// Aligns the view's lines with the buffer's current lines, returning
+    /// the index of each view line's counterpart in the buffer, or None for
+    /// lines that don't have one (i.e. those that have since been modified
+    /// or removed). Lines are matched using a minimal diff, so aligned lines
+    /// appear in the same order in both, suitable for side-by-side display.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    /// use std::path::Path;
+    ///
+    /// # #[cfg(feature = "fs")] {
+    /// // The buffer's contents are "it works!\n".
+    /// let mut buffer = Buffer::from_file(Path::new("tests/sample/file")).unwrap();
+    /// buffer.insert("scribe\n");
+    /// buffer.cursor.move_to(Position{ line: 2, offset: 0 });
+    /// buffer.insert("library");
+    ///
+    /// let saved = buffer.saved_view().unwrap();
+    /// assert_eq!(saved.data(), "it works!\n");
+    /// assert_eq!(saved.line_alignment(&buffer), vec![Some(1), None]);
+    /// # }
+    /// ```
+    pub fn line_alignment(&self, buffer: &Buffer) -> Vec<Option<usize>> {
+        let data = buffer.data();
+        let lines: Vec<&str> = data.split('\n').collect();
+        let view_lines: Vec<&str> = self.data.split('\n').collect();
+
+        matching_elements(&view_lines, &lines)
+    }
+}
+
+impl Buffer {
+    /// The buffer's current point in its undo history, counted in undoable
+    /// operations; undoing moves it back by one, and redoing moves it forward.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe");
+    /// buffer.insert(" ");
+    /// assert_eq!(buffer.history_index(), 2);
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.history_index(), 1);
+    /// ```
+    pub fn history_index(&self) -> usize {
+        self.history.position()
+    }
+
+    /// Reconstructs the buffer's contents at the specified point in its undo
+    /// history (see `history_index`), without modifying the buffer. Indices
+    /// beyond the current point are reached by replaying operations that can
+    /// be redone. Returns None if the index is beyond the end of the history.
+    ///
+    /// Operations in an open operation group haven't been added to the
+    /// history yet, and are excluded from all views.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("library");
+    /// buffer.insert("scribe ");
+    ///
+    /// // Preview the result of an undo.
+    /// let index = buffer.history_index();
+    /// assert_eq!(buffer.view_at(index - 1).unwrap().data(), "library");
+    /// assert_eq!(buffer.view_at(0).unwrap().data(), "");
+    /// assert!(buffer.view_at(index + 1).is_none());
+    /// ```
+    pub fn view_at(&self, history_index: usize) -> Option<BufferView> {
+        if history_index > self.history.len() {
+            return None;
+        }
+
+        // Work on a copy of the buffer, rewinding any ungrouped
+        // changes to arrive at the current point in the history.
+        let mut scratch = self.derive(self.data());
+        if let Some(ref group) = self.operation_group {
+            group.clone_operation().reverse(&mut scratch);
+        }
+
+        let position = self.history.position();
+        for index in (history_index..position).rev() {
+            self.history.operation(index)?.clone_operation().reverse(&mut scratch);
+        }
+        for index in position..history_index {
+            self.history.operation(index)?.clone_operation().run(&mut scratch);
+        }
+
+        Some(BufferView{
+            data: scratch.data(),
+            history_index,
+            #[cfg(feature = "syntax")]
+            syntax_definition: self.syntax_definition.clone(),
+        })
+    }
+
+    /// A view of the buffer's contents when it was last loaded or saved
+    /// (i.e. the point at which it's not `modified`), for comparison with its
+    /// current contents (see `BufferView::line_alignment`). Returns None for
+    /// buffers that haven't been loaded or saved, or whose history no longer
+    /// reaches that point (e.g. having undone changes past it and made others).
+    pub fn saved_view(&self) -> Option<BufferView> {
+        self.view_at(self.history.marked_position()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::Buffer;
+    use std::cell::Cell;
+    #[cfg(feature = "fs")]
+    use std::env;
+    #[cfg(feature = "fs")]
+    use std::fs;
+    use std::rc::Rc;
+    #[cfg(feature = "syntax")]
+    use syntect::parsing::SyntaxSet;
+
+    #[test]
+    fn view_at_replays_operations_that_can_be_redone() {
+        let mut buffer = Buffer::new();
+        buffer.insert("library");
+        buffer.insert("scribe ");
+        buffer.undo();
+        buffer.undo();
+
+        assert_eq!(buffer.view_at(2).unwrap().data(), "scribe library");
+        assert_eq!(buffer.data(), "");
+    }
+
+    #[test]
+    fn view_at_excludes_open_operation_groups() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        buffer.start_operation_group();
+        buffer.delete();
+
+        assert_eq!(buffer.view_at(1).unwrap().data(), "scribe");
+        assert_eq!(buffer.data(), "cribe");
+    }
+
+    #[test]
+    fn view_at_does_not_notify_the_change_callback() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+
+        let notified = Rc::new(Cell::new(false));
+        let callback_notified = notified.clone();
+        buffer.change_callback = Some(Box::new(move |_| callback_notified.set(true)));
+
+        buffer.view_at(0);
+        assert!(!notified.get());
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn saved_view_is_unavailable_once_the_history_diverges() {
+        let mut buffer = Buffer::new();
+        assert!(buffer.saved_view().is_none());
+
+        buffer.path = Some(env::temp_dir().join("scribe_saved_view_test"));
+        buffer.insert("scribe");
+        buffer.save().unwrap();
+        buffer.insert(" library");
+        assert_eq!(buffer.saved_view().unwrap().data(), "scribe");
+
+        buffer.undo();
+        buffer.undo();
+        assert_eq!(buffer.saved_view().unwrap().data(), "scribe");
+
+        buffer.insert("text");
+        assert!(buffer.saved_view().is_none());
+        fs::remove_file(buffer.path.as_ref().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn line_alignment_matches_lines_in_order() {
+        let mut buffer = Buffer::new();
+        buffer.insert("a\nb\nc\nd");
+        let view = buffer.view_at(1).unwrap();
+        buffer.replace_contents("d\nb\nx\nc");
+
+        assert_eq!(view.line_alignment(&buffer), vec![None, Some(1), Some(3), None]);
+    }
+
+    #[test]
+    #[cfg(feature = "syntax")]
+    fn view_tokens_use_the_buffer_syntax_definition() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe");
+        assert!(buffer.view_at(0).unwrap().tokens().is_err());
+
+        buffer.syntax_definition = Some(syntax_set.find_syntax_plain_text().clone());
+        assert!(buffer.view_at(1).unwrap().tokens().is_ok());
+    }
+}
@@ -0,0 +1,261 @@
+//! Word boundaries used by word-wise motions.
+
+use buffer::{Buffer, Position, Range};
+use buffer::cursor::GraphemeClass;
+#[cfg(feature = "syntax")]
+use buffer::{Distance, LineRange, Scope, Token};
+
+/// How the buffer divides its content into words, for word-wise motions
+/// (e.g. `Buffer::move_to_next_word`) and `Buffer::current_word`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum WordBoundaries {
+    /// Words are runs of alphanumeric characters and underscores, or of
+    /// other non-whitespace characters (e.g. operators).
+    #[default]
+    Characters,
+
+    /// Words are runs of non-whitespace characters within tokens of the same
+    /// category, as produced by the buffer's lexer, so that they follow the
+    /// rules of its language (e.g. keeping `$total` or `background-color`
+    /// together). A token's category is its scope, ignoring any delimiter
+    /// scope (e.g. "punctuation.definition.variable") it ends with. Tokens
+    /// without a category beyond the syntax's own scope, and buffers that
+    /// can't be lexed, fall back to `Characters`.
+    Tokens,
+}
+
+impl Buffer {
+    /// Moves the cursor to the start of the next word, as delimited by the
+    /// buffer's `word_boundaries` (see `Cursor::move_to_next_word`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::Position;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("p { background-color: red; }");
+    /// buffer.cursor.move_to(Position{ line: 0, offset: 4 });
+    ///
+    /// buffer.move_to_next_word();
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 14 });
+    /// ```
+    pub fn move_to_next_word(&mut self) {
+        match self.cursor.next_word(|lines| self.word_graphemes(lines)) {
+            Some(position) => { self.cursor.move_to(position); },
+            None => self.cursor.move_to_end_of_buffer(),
+        }
+    }
+
+    /// Moves the cursor to the start of the current word, or the previous
+    /// word if it's already at the start of one, as delimited by the
+    /// buffer's `word_boundaries` (see `Cursor::move_to_previous_word`).
+    pub fn move_to_previous_word(&mut self) {
+        match self.cursor.previous_word(|lines| self.word_graphemes(lines)) {
+            Some(position) => { self.cursor.move_to(position); },
+            None => self.cursor.move_to_start_of_buffer(),
+        }
+    }
+
+    /// The range of the word at the cursor, or if there isn't one, the word
+    /// immediately preceding it, as delimited by the buffer's
+    /// `word_boundaries`. Returns `None` if the cursor isn't next to a word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::buffer::{Position, Range};
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("foo_bar.baz()");
+    /// buffer.cursor.move_to(Position{ line: 0, offset: 3 });
+    ///
+    /// assert_eq!(buffer.current_word(), Some(Range::new(
+    ///     Position{ line: 0, offset: 0 },
+    ///     Position{ line: 0, offset: 7 }
+    /// )));
+    /// ```
+    pub fn current_word(&self) -> Option<Range> {
+        let line = self.cursor.line;
+        let graphemes = self.word_graphemes(line..line + 1);
+        let mut index = graphemes.iter().take_while(|&&(position, _)| position < *self.cursor).count();
+
+        let is_word = |index: usize| graphemes.get(index).is_some_and(|&(_, class)| class != GraphemeClass::Whitespace);
+        if !is_word(index) {
+            if index == 0 || !is_word(index - 1) {
+                return None;
+            }
+            index -= 1;
+        }
+
+        let class = graphemes[index].1;
+        let mut start = index;
+        let mut end = index;
+        while start > 0 && graphemes[start - 1].1 == class { start -= 1; }
+        while end < graphemes.len() && graphemes[end].1 == class { end += 1; }
+
+        let last = graphemes[end - 1].0;
+        Some(Range::new(graphemes[start].0, Position{ line: last.line, offset: last.offset + 1 }))
+    }
+
+    // The position and word class of each grapheme on the lines in the range.
+    fn word_graphemes(&self, lines: ::std::ops::Range<usize>) -> Vec<(Position, GraphemeClass)> {
+        #[cfg_attr(not(feature = "syntax"), allow(unused_mut))]
+        let mut graphemes = self.cursor.classified_graphemes(lines.clone());
+
+        #[cfg(feature = "syntax")]
+        {
+            if self.word_boundaries == WordBoundaries::Tokens {
+                self.classify_by_token(lines, &mut graphemes);
+            }
+        }
+
+        graphemes
+    }
+
+    // Reclassifies non-whitespace graphemes on the lines in the range by the
+    // category of the lexeme they're part of, so that adjacent lexemes sharing
+    // a category are grouped into the same word. Only those lines are lexed,
+    // along with any preceding them that aren't in the token cache.
+    #[cfg(feature = "syntax")]
+    fn classify_by_token(&self, lines: ::std::ops::Range<usize>, graphemes: &mut [(Position, GraphemeClass)]) {
+        let tokens = match self.tokens_for_lines(&LineRange::new(lines.start, lines.end)) {
+            Ok(tokens) => tokens,
+            Err(_) => return,
+        };
+        let delimiter = Scope::new("punctuation.definition").unwrap();
+
+        let mut index = 0;
+        let mut group = 0;
+        let mut category: Option<Vec<Scope>> = None;
+        for token in tokens.iter() {
+            let lexeme = match token {
+                Token::Lexeme(lexeme) => lexeme,
+                Token::Newline => {
+                    category = None;
+                    continue;
+                },
+            };
+            let mut scopes = lexeme.scope.as_slice().to_vec();
+            while scopes.last().is_some_and(|&scope| delimiter.is_prefix_of(scope)) {
+                scopes.pop();
+            }
+            if category.as_ref() != Some(&scopes) {
+                group += 1;
+                category = Some(scopes);
+            }
+
+            let end = lexeme.position + Distance::of_str(lexeme.value);
+            let categorized = category.as_ref().is_some_and(|scopes| scopes.len() > 1);
+
+            while index < graphemes.len() && graphemes[index].0 < lexeme.position {
+                index += 1;
+            }
+            while index < graphemes.len() && graphemes[index].0 < end {
+                if categorized && graphemes[index].1 != GraphemeClass::Whitespace {
+                    graphemes[index].1 = GraphemeClass::Token(group);
+                }
+                index += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::{Buffer, Position, Range, WordBoundaries};
+    #[cfg(feature = "syntax")]
+    use syntect::parsing::SyntaxSet;
+
+    #[test]
+    fn current_word_prefers_the_word_at_the_cursor() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe(library) ");
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 6 });
+        assert_eq!(buffer.current_word(), Some(Range::new(
+            Position{ line: 0, offset: 6 },
+            Position{ line: 0, offset: 7 }
+        )));
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 15 });
+        assert_eq!(buffer.current_word(), Some(Range::new(
+            Position{ line: 0, offset: 14 },
+            Position{ line: 0, offset: 15 }
+        )));
+    }
+
+    #[test]
+    fn current_word_returns_none_between_whitespace() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe  library");
+        buffer.cursor.move_to(Position{ line: 0, offset: 7 });
+
+        assert_eq!(buffer.current_word(), None);
+    }
+
+    #[test]
+    fn token_boundaries_fall_back_to_characters_without_a_syntax() {
+        let mut buffer = Buffer::new();
+        buffer.insert("foo_bar.baz()");
+        buffer.word_boundaries = WordBoundaries::Tokens;
+
+        buffer.move_to_next_word();
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 7 });
+    }
+
+    #[test]
+    #[cfg(feature = "syntax")]
+    fn token_boundaries_follow_the_buffer_syntax() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let mut buffer = Buffer::new();
+        buffer.syntax_definition = syntax_set.find_syntax_by_name("PHP").cloned();
+        buffer.insert("<?php\n$total = $count + 1;");
+        buffer.word_boundaries = WordBoundaries::Tokens;
+        buffer.cursor.move_to(Position{ line: 1, offset: 3 });
+
+        assert_eq!(buffer.current_word(), Some(Range::new(
+            Position{ line: 1, offset: 0 },
+            Position{ line: 1, offset: 6 }
+        )));
+
+        buffer.move_to_previous_word();
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 0 });
+        buffer.move_to_previous_word();
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 0 });
+    }
+
+    #[test]
+    #[cfg(feature = "syntax")]
+    fn token_boundaries_separate_member_accesses() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let mut buffer = Buffer::new();
+        buffer.syntax_definition = syntax_set.find_syntax_by_extension("rs").cloned();
+        buffer.insert("foo_bar.baz();");
+        buffer.word_boundaries = WordBoundaries::Tokens;
+
+        let mut offsets = Vec::new();
+        for _ in 0..4 {
+            buffer.move_to_next_word();
+            offsets.push(buffer.cursor.offset);
+        }
+        assert_eq!(offsets, vec![7, 8, 11, 12]);
+    }
+
+    #[test]
+    #[cfg(feature = "syntax")]
+    fn token_boundaries_only_lex_the_lines_around_the_cursor() {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let mut buffer = Buffer::new();
+        buffer.syntax_definition = syntax_set.find_syntax_by_extension("rs").cloned();
+        buffer.insert("foo_bar.baz();\n".repeat(200));
+        buffer.word_boundaries = WordBoundaries::Tokens;
+        buffer.cursor.move_to(Position::new());
+
+        buffer.move_to_next_word();
+        assert_eq!(*buffer.cursor, Position{ line: 0, offset: 7 });
+        assert!(buffer.token_cache.borrow().len() < 10);
+    }
+}
@@ -0,0 +1,109 @@
+//! External commands whose output is streamed into workspace buffers.
+
+use std::io::{self, Read};
+use std::path::Path;
+use std::process::{Child, ExitStatus, Stdio};
+use std::str;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use util::shell_command;
+
+/// The state of a command started by `Workspace::run_command`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CommandStatus {
+    Running,
+    Finished(ExitStatus),
+}
+
+// A command, along with the output it's produced that
+// hasn't yet been added to its buffer.
+pub(crate) struct RunningCommand {
+    pub buffer_id: usize,
+    child: Child,
+    output: Receiver<Vec<u8>>,
+    pending_output: Vec<u8>,
+    status: CommandStatus,
+}
+
+impl RunningCommand {
+    // Starts the command in the specified directory, reading its standard
+    // output and error streams on background threads.
+    pub fn spawn(command: &str, directory: &Path, buffer_id: usize) -> io::Result<RunningCommand> {
+        let mut child = shell_command(command)
+            .current_dir(directory)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let (sender, output) = mpsc::channel();
+
+        if let Some(stdout) = child.stdout.take() {
+            let sender = sender.clone();
+            thread::spawn(move || forward(stdout, &sender));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            thread::spawn(move || forward(stderr, &sender));
+        }
+
+        Ok(RunningCommand{
+            buffer_id,
+            child,
+            output,
+            pending_output: Vec::new(),
+            status: CommandStatus::Running,
+        })
+    }
+
+    pub fn status(&self) -> CommandStatus {
+        self.status
+    }
+
+    // Returns output received since the last call, updating the command's
+    // status once its output streams have been closed and it has exited.
+    pub fn read_output(&mut self) -> String {
+        loop {
+            match self.output.try_recv() {
+                Ok(data) => self.pending_output.extend(data),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    if let Ok(Some(status)) = self.child.try_wait() {
+                        self.status = CommandStatus::Finished(status);
+                    }
+                    break;
+                },
+            }
+        }
+
+        // Hold on to incomplete UTF-8 sequences until the rest of them arrive.
+        let length = match str::from_utf8(&self.pending_output) {
+            Ok(_) => self.pending_output.len(),
+            Err(ref error) if error.error_len().is_none() && self.status == CommandStatus::Running => {
+                error.valid_up_to()
+            },
+            Err(_) => self.pending_output.len(),
+        };
+        let data: Vec<u8> = self.pending_output.drain(..length).collect();
+
+        String::from_utf8_lossy(&data).into_owned()
+    }
+}
+
+// Stop commands whose buffers have been closed, or whose workspace has been dropped.
+impl Drop for RunningCommand {
+    fn drop(&mut self) {
+        if self.status == CommandStatus::Running {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+fn forward<R: Read>(mut reader: R, sender: &mpsc::Sender<Vec<u8>>) {
+    let mut buffer = [0; 4096];
+
+    while let Ok(length) = reader.read(&mut buffer) {
+        if length == 0 || sender.send(buffer[..length].to_vec()).is_err() {
+            break;
+        }
+    }
+}
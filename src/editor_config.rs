@@ -0,0 +1,161 @@
+//! EditorConfig (https://editorconfig.org) property lookup.
+
+use globset::GlobBuilder;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const FILE_NAME: &str = ".editorconfig";
+
+// A parsed .editorconfig file.
+struct EditorConfig {
+    root: bool,
+    sections: Vec<Section>,
+}
+
+// A glob pattern and the properties that apply to files matching it.
+struct Section {
+    pattern: String,
+    properties: Vec<(String, String)>,
+}
+
+/// Finds the EditorConfig properties that apply to the specified (absolute)
+/// file path, reading `.editorconfig` files from its directory upwards, and
+/// stopping at one declaring `root = true`. Properties from closer files take
+/// precedence, as do those from later sections within a file. Property names
+/// and values are lower-cased. Unreadable files are ignored.
+pub(crate) fn properties(path: &Path) -> HashMap<String, String> {
+    let mut configs = Vec::new();
+
+    for directory in path.ancestors().skip(1) {
+        let config = match fs::read_to_string(directory.join(FILE_NAME)) {
+            Ok(content) => parse(&content),
+            Err(_) => continue,
+        };
+        let root = config.root;
+        configs.push((directory, config));
+
+        if root { break; }
+    }
+
+    // Apply the furthest configuration first, so
+    // that closer ones can override its properties.
+    let mut properties = HashMap::new();
+    for (directory, config) in configs.iter().rev() {
+        let relative_path = match path.strip_prefix(directory) {
+            Ok(relative_path) => relative_path,
+            Err(_) => continue,
+        };
+
+        for section in &config.sections {
+            if matches(&section.pattern, relative_path) {
+                for (name, value) in &section.properties {
+                    properties.insert(name.clone(), value.clone());
+                }
+            }
+        }
+    }
+
+    properties
+}
+
+fn parse(content: &str) -> EditorConfig {
+    let mut config = EditorConfig{ root: false, sections: Vec::new() };
+
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            config.sections.push(Section{
+                pattern: line[1..line.len() - 1].to_string(),
+                properties: Vec::new(),
+            });
+            continue;
+        }
+
+        let mut pair = line.splitn(2, '=');
+        let (name, value) = match (pair.next(), pair.next()) {
+            (Some(name), Some(value)) => (name.trim().to_lowercase(), value.trim().to_lowercase()),
+            _ => continue,
+        };
+
+        match config.sections.last_mut() {
+            Some(section) => section.properties.push((name, value)),
+            None => if name == "root" { config.root = value == "true" },
+        }
+    }
+
+    config
+}
+
+// Patterns without a path separator match file names at any depth;
+// others are relative to the directory containing the configuration.
+fn matches(pattern: &str, relative_path: &Path) -> bool {
+    let pattern = if pattern.contains('/') {
+        pattern.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{}", pattern)
+    };
+
+    GlobBuilder::new(&pattern)
+        .literal_separator(true)
+        .build()
+        .map(|glob| glob.compile_matcher().is_match(relative_path))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::properties;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        let root = env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+
+        root
+    }
+
+    #[test]
+    fn properties_prefer_closer_files_and_later_sections() {
+        let root = fixture("scribe_editor_config_precedence");
+        fs::write(root.join(".editorconfig"), "root = true\n\n[*]\nindent_size = 2\ninsert_final_newline = true\n").unwrap();
+        fs::write(root.join("src/.editorconfig"), "[*.rs]\nindent_size = 4\n\n[lib.rs]\nindent_size = 8\n").unwrap();
+
+        let lib_properties = properties(&root.join("src/lib.rs"));
+        assert_eq!(lib_properties.get("indent_size").unwrap(), "8");
+        assert_eq!(lib_properties.get("insert_final_newline").unwrap(), "true");
+        assert_eq!(properties(&root.join("src/mod.rs")).get("indent_size").unwrap(), "4");
+        assert_eq!(properties(&root.join("README")).get("indent_size").unwrap(), "2");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn properties_stop_at_root_files() {
+        let root = fixture("scribe_editor_config_root");
+        fs::write(root.join(".editorconfig"), "[*]\nindent_size = 2\n").unwrap();
+        fs::write(root.join("src/.editorconfig"), "root = true\n[*.md]\nindent_size = 4\n").unwrap();
+
+        assert!(properties(&root.join("src/lib.rs")).is_empty());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn properties_match_patterns_with_separators_relative_to_the_file() {
+        let root = fixture("scribe_editor_config_separators");
+        fs::write(root.join(".editorconfig"), "root = true\n[/src/*.{rs,toml}]\nindent_size = 4\n").unwrap();
+
+        assert_eq!(properties(&root.join("src/lib.rs")).get("indent_size").unwrap(), "4");
+        assert!(properties(&root.join("lib.rs")).is_empty());
+        assert!(properties(&root.join("src/nested/lib.rs")).is_empty());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,5 +1,9 @@
 error_chain! {
     errors {
+        AppendOnly {
+            description("buffer is append-only")
+            display("buffer is append-only; its content can't be removed")
+        }
         EmptyWorkspace {
             description("the workspace is empty")
             display("the workspace is empty")
@@ -12,5 +16,46 @@ error_chain! {
             description("couldn't find any scopes at the cursor position")
             display("couldn't find any scopes at the cursor position")
         }
+        ConflictingChanges(paths: Vec<::std::path::PathBuf>) {
+            description("files have been modified on disk")
+            display("files have been modified on disk: {:?}", paths)
+        }
+        InvalidScope(scope: String) {
+            description("invalid scope selector")
+            display("invalid scope selector: {}", scope)
+        }
+        InvariantViolation(reason: String) {
+            description("buffer invariant violated")
+            display("buffer invariant violated: {}", reason)
+        }
+        UnsupportedFormat(syntax: String) {
+            description("unsupported document format")
+            display("unsupported document format: {}", syntax)
+        }
+        InvalidDocument(reason: String) {
+            description("invalid document")
+            display("invalid document: {}", reason)
+        }
+        InvalidPattern(reason: String) {
+            description("invalid search pattern")
+            display("invalid search pattern: {}", reason)
+        }
+        OutOfBounds(position: ::buffer::Position, bounds: ::buffer::Range) {
+            description("position out of bounds")
+            display(
+                "position {}:{} is out of bounds (valid positions are {}:{} to {}:{})",
+                position.line, position.offset,
+                bounds.start().line, bounds.start().offset,
+                bounds.end().line, bounds.end().offset
+            )
+        }
+        UnknownFunction(name: String) {
+            description("unknown function")
+            display("unknown function: {}", name)
+        }
+        InvalidArguments(function: String) {
+            description("invalid function arguments")
+            display("invalid arguments to {}", function)
+        }
     }
 }
@@ -0,0 +1,59 @@
+//! Workspace-level notifications about its buffers.
+
+use std::cell::{Cell, RefCell};
+
+/// Something that happened to one of a workspace's buffers, identified by
+/// its ID. Subscribers registered using `Workspace::subscribe` receive these.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WorkspaceEvent {
+    /// The buffer was added to the workspace.
+    Opened(usize),
+
+    /// The buffer was closed.
+    Closed(usize),
+
+    /// The buffer's content changed. This is sent for each edit.
+    Modified(usize),
+
+    /// The buffer was saved by the workspace.
+    Saved(usize),
+
+    /// The buffer's file was changed by another program (see
+    /// `Workspace::detect_external_changes`).
+    ExternallyModified(usize),
+}
+
+// A function called with each workspace event.
+type Subscriber = Box<dyn Fn(&WorkspaceEvent)>;
+
+// The subscribers registered with a workspace. They're shared with the
+// observers it registers with its buffers, which report their edits.
+#[derive(Default)]
+pub(crate) struct Subscribers {
+    entries: RefCell<Vec<(usize, Subscriber)>>,
+    next_id: Cell<usize>,
+}
+
+impl Subscribers {
+    pub(crate) fn add(&self, subscriber: Subscriber) -> usize {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        self.entries.borrow_mut().push((id, subscriber));
+
+        id
+    }
+
+    pub(crate) fn remove(&self, id: usize) -> bool {
+        let mut entries = self.entries.borrow_mut();
+        let count = entries.len();
+        entries.retain(|&(subscriber_id, _)| subscriber_id != id);
+
+        entries.len() != count
+    }
+
+    pub(crate) fn notify(&self, event: WorkspaceEvent) {
+        for (_, subscriber) in self.entries.borrow().iter() {
+            subscriber(&event);
+        }
+    }
+}
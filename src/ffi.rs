@@ -0,0 +1,259 @@
+//! A C ABI for embedding scribe's editing engine in other languages.
+//!
+//! Buffers are handed out as opaque pointers, created using
+//! `scribe_buffer_new` or `scribe_buffer_open`, and released using
+//! `scribe_buffer_free`. Positions are passed as separate line and offset
+//! arguments, with offsets counted in grapheme clusters (as with
+//! `Position`). Strings are null-terminated UTF-8; those returned by
+//! `scribe_buffer_data` are owned by the caller, and must be released using
+//! `scribe_string_free`.
+//!
+//! Functions that can fail return a boolean (or a null pointer) rather than
+//! an error, and treat null handles as a failure. To build a shared library
+//! for use from C, enable the `ffi` feature and override the crate type:
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! ```
+
+use buffer::{Buffer, Position, Range};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::path::Path;
+use std::ptr;
+
+/// Creates an empty buffer, without a path.
+#[no_mangle]
+pub extern "C" fn scribe_buffer_new() -> *mut Buffer {
+    Box::into_raw(Box::new(Buffer::new()))
+}
+
+/// Creates a buffer using the contents of the file at the specified path.
+/// Returns a null pointer if the path isn't valid UTF-8, or the file can't
+/// be read.
+///
+/// # Safety
+///
+/// `path` must be null, or point to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn scribe_buffer_open(path: *const c_char) -> *mut Buffer {
+    let path = match string_from(path) {
+        Some(path) => path,
+        None => return ptr::null_mut(),
+    };
+
+    match Buffer::from_file(Path::new(path)) {
+        Ok(buffer) => Box::into_raw(Box::new(buffer)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Releases a buffer. Null handles are ignored.
+///
+/// # Safety
+///
+/// `buffer` must be null, or a handle produced by this module that hasn't
+/// already been released.
+#[no_mangle]
+pub unsafe extern "C" fn scribe_buffer_free(buffer: *mut Buffer) {
+    if !buffer.is_null() {
+        drop(Box::from_raw(buffer));
+    }
+}
+
+/// Inserts `data` at the specified position, as an operation that can be
+/// undone. The cursor isn't moved. Returns false if the position is out of
+/// bounds, the buffer is read-only, or `data` isn't valid UTF-8.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle, and `data` must be null or point
+/// to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn scribe_buffer_insert(buffer: *mut Buffer, line: usize, offset: usize, data: *const c_char) -> bool {
+    let (buffer, data) = match (buffer.as_mut(), string_from(data)) {
+        (Some(buffer), Some(data)) => (buffer, data),
+        _ => return false,
+    };
+    let position = Position{ line, offset };
+    if buffer.read_only || buffer.check_position(&position).is_err() {
+        return false;
+    }
+
+    buffer.insert_transformed(data.to_string(), position);
+    true
+}
+
+/// Deletes the content between the specified positions, as an operation
+/// that can be undone. Returns false if either position is out of bounds,
+/// or the buffer is read-only.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn scribe_buffer_delete(buffer: *mut Buffer, start_line: usize, start_offset: usize, end_line: usize, end_offset: usize) -> bool {
+    let buffer = match buffer.as_mut() {
+        Some(buffer) => buffer,
+        None => return false,
+    };
+    if buffer.read_only { return false; }
+
+    let range = Range::new(
+        Position{ line: start_line, offset: start_offset },
+        Position{ line: end_line, offset: end_offset }
+    );
+    buffer.try_delete_range(range).is_ok()
+}
+
+/// The buffer's content, as a string that must be released using
+/// `scribe_string_free`. Returns a null pointer if the content contains a
+/// null character, which can't be represented.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn scribe_buffer_data(buffer: *const Buffer) -> *mut c_char {
+    let buffer = match buffer.as_ref() {
+        Some(buffer) => buffer,
+        None => return ptr::null_mut(),
+    };
+
+    match CString::new(buffer.data()) {
+        Ok(data) => data.into_raw(),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Releases a string returned by `scribe_buffer_data`. Null pointers are
+/// ignored.
+///
+/// # Safety
+///
+/// `data` must be null, or a string produced by this module that hasn't
+/// already been released.
+#[no_mangle]
+pub unsafe extern "C" fn scribe_string_free(data: *mut c_char) {
+    if !data.is_null() {
+        drop(CString::from_raw(data));
+    }
+}
+
+/// Writes the buffer's content to its path (see `Buffer::save`). Returns
+/// false if the buffer doesn't have a path, or it couldn't be written.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn scribe_buffer_save(buffer: *mut Buffer) -> bool {
+    match buffer.as_mut() {
+        Some(buffer) => buffer.save().is_ok(),
+        None => false,
+    }
+}
+
+/// Reverses the buffer's last change, if there is one.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn scribe_buffer_undo(buffer: *mut Buffer) {
+    if let Some(buffer) = buffer.as_mut() {
+        buffer.undo();
+    }
+}
+
+/// Re-applies the last change reversed using `scribe_buffer_undo`, if any.
+///
+/// # Safety
+///
+/// `buffer` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn scribe_buffer_redo(buffer: *mut Buffer) {
+    if let Some(buffer) = buffer.as_mut() {
+        buffer.redo();
+    }
+}
+
+// Borrows a null-terminated UTF-8 string, if the pointer isn't null.
+unsafe fn string_from<'a>(data: *const c_char) -> Option<&'a str> {
+    if data.is_null() {
+        return None;
+    }
+
+    CStr::from_ptr(data).to_str().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::ffi::{CStr, CString};
+    use std::fs;
+    use std::ptr;
+
+    #[test]
+    fn buffers_can_be_edited_through_handles() {
+        let data = CString::new("scribe\nlibrary").unwrap();
+
+        unsafe {
+            let buffer = scribe_buffer_new();
+            assert!(scribe_buffer_insert(buffer, 0, 0, data.as_ptr()));
+            assert!(!scribe_buffer_insert(buffer, 2, 0, data.as_ptr()));
+            assert!(scribe_buffer_delete(buffer, 0, 3, 1, 3));
+            assert!(!scribe_buffer_delete(buffer, 0, 0, 0, 9));
+
+            let content = scribe_buffer_data(buffer);
+            assert_eq!(CStr::from_ptr(content).to_str().unwrap(), "scrrary");
+            scribe_string_free(content);
+
+            scribe_buffer_undo(buffer);
+            assert_eq!((*buffer).data(), "scribe\nlibrary");
+            scribe_buffer_redo(buffer);
+            assert_eq!((*buffer).data(), "scrrary");
+
+            scribe_buffer_free(buffer);
+        }
+    }
+
+    #[test]
+    fn null_handles_and_strings_are_rejected() {
+        let data = CString::new("scribe").unwrap();
+
+        unsafe {
+            assert!(!scribe_buffer_insert(ptr::null_mut(), 0, 0, data.as_ptr()));
+            assert!(!scribe_buffer_save(ptr::null_mut()));
+            assert!(scribe_buffer_data(ptr::null()).is_null());
+            assert!(scribe_buffer_open(ptr::null()).is_null());
+            scribe_buffer_undo(ptr::null_mut());
+            scribe_buffer_free(ptr::null_mut());
+
+            let buffer = scribe_buffer_new();
+            assert!(!scribe_buffer_insert(buffer, 0, 0, ptr::null()));
+            assert!(!scribe_buffer_save(buffer));
+            scribe_buffer_free(buffer);
+        }
+    }
+
+    #[test]
+    fn opened_buffers_can_be_saved() {
+        let path = env::temp_dir().join("scribe_ffi_save_test");
+        fs::write(&path, "scribe").unwrap();
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+        let data = CString::new(" library").unwrap();
+
+        unsafe {
+            let buffer = scribe_buffer_open(c_path.as_ptr());
+            assert!(!buffer.is_null());
+            assert!(scribe_buffer_insert(buffer, 0, 6, data.as_ptr()));
+            assert!(scribe_buffer_save(buffer));
+            scribe_buffer_free(buffer);
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "scribe library");
+        fs::remove_file(&path).unwrap();
+    }
+}
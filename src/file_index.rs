@@ -0,0 +1,141 @@
+//! Project file indexing and fuzzy path matching.
+
+use ignore::WalkBuilder;
+use std::io;
+use std::path::{Path, PathBuf};
+use util::fuzzy_rank;
+
+/// A list of the files found beneath a project root.
+///
+/// Hidden files and anything excluded by `.gitignore` (and similar) files are
+/// omitted, so that the index reflects the files a user would want to open.
+/// Paths are stored relative to the root, which is what `find` matches
+/// against.
+pub struct FileIndex {
+    root: PathBuf,
+    paths: Vec<PathBuf>,
+}
+
+impl FileIndex {
+    /// Builds an index of the files beneath the specified root. The path is
+    /// converted to its canonical, absolute equivalent. Entries that can't be
+    /// read (e.g. due to permissions) are skipped, rather than failing the
+    /// entire index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::FileIndex;
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// let index = FileIndex::new(Path::new("tests")).unwrap();
+    /// assert_eq!(index.paths(), &[PathBuf::from("sample/file")]);
+    /// ```
+    pub fn new(root: &Path) -> io::Result<FileIndex> {
+        let mut index = FileIndex{
+            root: root.canonicalize()?,
+            paths: Vec::new(),
+        };
+        index.refresh();
+
+        Ok(index)
+    }
+
+    /// Re-walks the root directory, picking up added and removed files.
+    pub fn refresh(&mut self) {
+        let mut paths = Vec::new();
+
+        // Don't require a git repository to honour .gitignore files;
+        // projects are often indexed before being put under version control.
+        let walker = WalkBuilder::new(&self.root).require_git(false).build();
+        for entry in walker.filter_map(|e| e.ok()) {
+            if entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+                if let Ok(path) = entry.path().strip_prefix(&self.root) {
+                    paths.push(path.to_path_buf());
+                }
+            }
+        }
+        paths.sort();
+
+        self.paths = paths;
+    }
+
+    /// The canonical root path of the index.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// All indexed paths, relative to the root and sorted alphabetically.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Returns the indexed paths that fuzzily match the query (see
+    /// `util::fuzzy_match`), best match first. An empty query matches
+    /// every path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::FileIndex;
+    /// use std::path::Path;
+    ///
+    /// let index = FileIndex::new(Path::new("tests")).unwrap();
+    /// assert_eq!(index.find("smpfl"), vec![Path::new("sample/file")]);
+    /// assert!(index.find("missing").is_empty());
+    /// ```
+    pub fn find(&self, query: &str) -> Vec<&Path> {
+        let mut matches = fuzzy_rank(
+            query,
+            self.paths.iter().map(|path| (path.as_path(), path.to_string_lossy()))
+        );
+
+        // Order by score, preferring shorter paths when tied.
+        matches.sort_by(|a, b| {
+            b.1.score.cmp(&a.1.score).then_with(|| {
+                a.0.as_os_str().len().cmp(&b.0.as_os_str().len())
+            })
+        });
+
+        matches.into_iter().map(|(path, _)| path).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileIndex;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn new_respects_gitignore_files() {
+        let root = env::temp_dir().join("scribe_file_index_gitignore");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::write(root.join("target/output"), "").unwrap();
+        fs::write(root.join("lib.rs"), "").unwrap();
+
+        let index = FileIndex::new(&root).unwrap();
+        assert_eq!(index.paths(), &[PathBuf::from("lib.rs")]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn refresh_picks_up_new_files() {
+        let root = env::temp_dir().join("scribe_file_index_refresh");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        let mut index = FileIndex::new(&root).unwrap();
+        assert!(index.paths().is_empty());
+
+        fs::write(root.join("lib.rs"), "").unwrap();
+        index.refresh();
+        assert_eq!(index.paths(), &[PathBuf::from("lib.rs")]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
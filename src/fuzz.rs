@@ -0,0 +1,181 @@
+//! Deterministic generation of random buffer operations, for fuzzing.
+//!
+//! A `Fuzzer` produces commands that are valid for a buffer's current state
+//! (e.g. positions within its bounds), using a seeded generator, so that any
+//! failure can be reproduced by running the same seed against the same
+//! starting buffer.
+
+use buffer::{Buffer, Command, Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+// The fragments inserted text is assembled from, chosen to exercise line
+// handling and multi-byte (and multi-character) grapheme clusters.
+const FRAGMENTS: &[&str] = &["a", "b", " ", "\t", "\n", "\r\n", "é", "e\u{301}", "नी", "🦀"];
+
+/// A seeded generator of random buffer commands.
+///
+/// # Examples
+///
+/// ```
+/// use scribe::Buffer;
+/// use scribe::fuzz::Fuzzer;
+///
+/// let mut buffer = Buffer::new();
+/// let commands = Fuzzer::new(42).run(&mut buffer, 100);
+///
+/// // Replaying the commands (or the seed) reproduces the result.
+/// let mut replayed = Buffer::new();
+/// for command in commands {
+///     replayed.execute(command);
+/// }
+/// assert_eq!(replayed.data(), buffer.data());
+/// assert_eq!(*replayed.cursor, *buffer.cursor);
+///
+/// let mut reseeded = Buffer::new();
+/// Fuzzer::new(42).run(&mut reseeded, 100);
+/// assert_eq!(reseeded.data(), buffer.data());
+/// ```
+pub struct Fuzzer {
+    generator: Generator,
+
+    /// The maximum number of fragments in generated insertions (8, by default).
+    pub max_insert_length: usize,
+}
+
+impl Fuzzer {
+    /// Creates a generator with the specified seed.
+    pub fn new(seed: u64) -> Fuzzer {
+        Fuzzer{ generator: Generator::new(seed), max_insert_length: 8 }
+    }
+
+    /// Generates a random command that's valid for the buffer's current state.
+    pub fn next_command(&mut self, buffer: &Buffer) -> Command {
+        match self.below(16) {
+            0..=3 => Command::Insert(self.text()),
+            4 | 5 => Command::Delete,
+            6 => Command::DeleteRange(self.range(buffer)),
+            7 => {
+                let range = self.range(buffer);
+                Command::Replace{ range, data: self.text() }
+            },
+            8 | 9 => Command::MoveTo(self.position(buffer)),
+            10 => match self.below(6) {
+                0 => Command::MoveUp,
+                1 => Command::MoveDown,
+                2 => Command::MoveLeft,
+                3 => Command::MoveRight,
+                4 => Command::MoveToStartOfLine,
+                _ => Command::MoveToEndOfLine,
+            },
+            11 => Command::StartOperationGroup,
+            12 => Command::EndOperationGroup,
+            13 | 14 => Command::Undo,
+            _ => Command::Redo,
+        }
+    }
+
+    /// Generates and executes the specified number of commands
+    /// against the buffer, returning them for later replay.
+    pub fn run(&mut self, buffer: &mut Buffer, steps: usize) -> Vec<Command> {
+        (0..steps).map(|_| {
+            let command = self.next_command(buffer);
+            buffer.execute(command.clone());
+
+            command
+        }).collect()
+    }
+
+    fn below(&mut self, limit: usize) -> usize {
+        self.generator.below(limit)
+    }
+
+    fn text(&mut self) -> String {
+        let length = 1 + self.below(self.max_insert_length.max(1));
+
+        (0..length).map(|_| FRAGMENTS[self.below(FRAGMENTS.len())]).collect()
+    }
+
+    fn position(&mut self, buffer: &Buffer) -> Position {
+        // Buffer positions only treat "\n" as a line break.
+        let data = buffer.data();
+        let lines: Vec<&str> = data.split('\n').collect();
+        let line = self.below(lines.len());
+        let offset = self.below(lines[line].graphemes(true).count() + 1);
+
+        Position{ line, offset }
+    }
+
+    fn range(&mut self, buffer: &Buffer) -> Range {
+        let (first, second) = (self.position(buffer), self.position(buffer));
+
+        if first <= second {
+            Range::new(first, second)
+        } else {
+            Range::new(second, first)
+        }
+    }
+}
+
+// A seeded generator of random numbers, using the SplitMix64
+// algorithm, which accepts any seed (including zero).
+pub(crate) struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    pub(crate) fn new(seed: u64) -> Generator {
+        Generator{ state: seed }
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        value ^ (value >> 31)
+    }
+
+    // A random number in the range 0..limit.
+    pub(crate) fn below(&mut self, limit: usize) -> usize {
+        (self.next() % limit as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fuzzer;
+    use buffer::Buffer;
+
+    #[test]
+    fn generated_commands_run_without_panicking() {
+        // The gap buffer's invariants are checked
+        // after every modification in debug builds.
+        for seed in 0..50 {
+            Fuzzer::new(seed).run(&mut Buffer::new(), 200);
+        }
+    }
+
+    #[test]
+    fn undoing_generated_commands_restores_the_original_content() {
+        for seed in 0..50 {
+            let mut buffer = Buffer::new();
+            buffer.insert("scribe\nlibrary");
+            Fuzzer::new(seed).run(&mut buffer, 100);
+            buffer.end_operation_group();
+
+            for _ in 0..200 {
+                buffer.undo();
+            }
+            assert_eq!(buffer.data(), "", "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn generators_with_different_seeds_diverge() {
+        let first = Fuzzer::new(1).run(&mut Buffer::new(), 10);
+        let second = Fuzzer::new(2).run(&mut Buffer::new(), 10);
+
+        assert_ne!(first, second);
+    }
+}
@@ -1,18 +1,82 @@
+// Allocation-only storage primitives (see buffer::GapBuffer)
+extern crate alloc;
+extern crate core;
+
 // Syntax highlighting
+#[cfg(feature = "syntax")]
 extern crate syntect;
 
 // Grapheme cluster iteration
 extern crate unicode_segmentation;
 
+// Unicode normalization forms
+extern crate unicode_normalization;
+
+// Bidirectional text layout
+extern crate unicode_bidi;
+
 // Error definition/handling
 #[macro_use]
 extern crate error_chain;
 
+// Gitignore-aware directory traversal
+#[cfg(feature = "workspace")]
+extern crate ignore;
+
+// EditorConfig section matching
+#[cfg(feature = "workspace")]
+extern crate globset;
+
+// Snapshot compression
+#[cfg(feature = "workspace")]
+extern crate flate2;
+
+// Regular expression matching
+#[cfg(feature = "regex")]
+extern crate onig;
+
 pub mod buffer;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fuzz;
+pub mod scripting;
+pub mod stress;
 pub mod util;
+#[cfg(feature = "workspace")]
+mod command;
+#[cfg(feature = "workspace")]
+mod editor_config;
 mod errors;
+#[cfg(feature = "workspace")]
+mod event;
+#[cfg(feature = "workspace")]
+mod file_index;
+#[cfg(feature = "workspace")]
+mod snapshots;
+#[cfg(feature = "syntax")]
+mod syntax_detector;
+#[cfg(feature = "workspace")]
+mod syntax_warm_up;
+mod tags;
+#[cfg(feature = "workspace")]
+mod task;
+#[cfg(feature = "workspace")]
 mod workspace;
 
 pub use errors::*;
 pub use buffer::Buffer;
-pub use workspace::Workspace;
+#[cfg(feature = "workspace")]
+pub use command::CommandStatus;
+#[cfg(feature = "workspace")]
+pub use event::WorkspaceEvent;
+#[cfg(feature = "workspace")]
+pub use file_index::FileIndex;
+#[cfg(feature = "workspace")]
+pub use snapshots::{Snapshot, SnapshotStore};
+#[cfg(feature = "syntax")]
+pub use syntax_detector::{ExtensionDetector, SyntaxDetector};
+pub use tags::{Tag, TagAddress, TagIndex};
+#[cfg(feature = "workspace")]
+pub use task::WorkspaceTask;
+#[cfg(feature = "workspace")]
+pub use workspace::{SaveFailure, Workspace};
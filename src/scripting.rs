@@ -0,0 +1,290 @@
+//! A string-named, plain-data interface to buffers, for embedding scribe in
+//! plugin hosts (e.g. Lua, Python, or WASM runtimes).
+//!
+//! Rather than binding each buffer method individually, hosts can register
+//! every name in `FUNCTIONS`, forwarding calls (and their arguments) to
+//! `call`. Arguments and results are `Value`s, which map onto most languages'
+//! native types. Positions are passed as a line and offset (two integers),
+//! and ranges as a start and end position (four integers); results describe
+//! positions as two-element lists.
+
+use buffer::{Buffer, Command, Position, Range};
+use errors::*;
+
+/// The functions available through `call`. Apart from the queries (`data`,
+/// `line_count`, `cursor`, `modified`, `path`, `read`, and `search`), each
+/// corresponds to a `Command` variant, taking its fields as arguments.
+pub const FUNCTIONS: &[&str] = &[
+    "data", "line_count", "cursor", "modified", "path", "read", "search",
+    "insert", "delete", "delete_range", "replace", "replace_all", "replace_all_preserving_case",
+    "replace_contents", "increment_number", "expand_abbreviation_before_cursor",
+    "ensure_trailing_newline", "set_pending", "commit_pending", "cancel_pending",
+    "accept_ours", "accept_theirs", "accept_both", "start_operation_group",
+    "end_operation_group", "undo", "redo", "move_to", "move_up", "move_down",
+    "move_left", "move_right", "move_to_start_of_line", "move_to_end_of_line",
+    "move_to_start_of_logical_line", "move_to_end_of_logical_line",
+    "move_to_first_line", "move_to_last_line", "move_to_start_of_buffer",
+    "move_to_end_of_buffer", "move_to_next_word", "move_to_previous_word",
+    "move_to_next_cell", "move_to_previous_cell",
+];
+
+/// A plain-data argument or result.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Integer(i64),
+    String(String),
+    List(Vec<Value>),
+}
+
+// Typed access to a function's arguments, by index.
+struct Arguments<'a> {
+    function: &'a str,
+    values: &'a [Value],
+}
+
+impl<'a> Arguments<'a> {
+    fn string(&self, index: usize) -> Result<String> {
+        match self.values.get(index) {
+            Some(Value::String(value)) => Ok(value.clone()),
+            _ => Err(self.invalid()),
+        }
+    }
+
+    fn integer(&self, index: usize) -> Result<i64> {
+        match self.values.get(index) {
+            Some(&Value::Integer(value)) => Ok(value),
+            _ => Err(self.invalid()),
+        }
+    }
+
+    fn index(&self, index: usize) -> Result<usize> {
+        let value = self.integer(index)?;
+        if value < 0 { return Err(self.invalid()); }
+
+        Ok(value as usize)
+    }
+
+    fn position(&self, index: usize) -> Result<Position> {
+        Ok(Position{ line: self.index(index)?, offset: self.index(index + 1)? })
+    }
+
+    fn range(&self, index: usize) -> Result<Range> {
+        Ok(Range::new(self.position(index)?, self.position(index + 2)?))
+    }
+
+    fn invalid(&self) -> Error {
+        ErrorKind::InvalidArguments(self.function.to_string()).into()
+    }
+}
+
+/// Calls one of the `FUNCTIONS` on the buffer, returning its result (or
+/// `Value::Nil`, for commands). Returns an error if the function doesn't
+/// exist, or its arguments are missing or of the wrong type.
+///
+/// # Examples
+///
+/// ```
+/// use scribe::Buffer;
+/// use scribe::scripting::{call, Value};
+///
+/// let mut buffer = Buffer::new();
+/// call(&mut buffer, "insert", &[Value::String("scribe\nlibrary".to_string())]).unwrap();
+/// call(&mut buffer, "move_to", &[Value::Integer(1), Value::Integer(3)]).unwrap();
+///
+/// assert_eq!(
+///     call(&mut buffer, "cursor", &[]).unwrap(),
+///     Value::List(vec![Value::Integer(1), Value::Integer(3)])
+/// );
+/// assert_eq!(
+///     call(&mut buffer, "read", &[0, 1, 1, 3].iter().map(|&n| Value::Integer(n)).collect::<Vec<_>>()).unwrap(),
+///     Value::String("cribe\nlib".to_string())
+/// );
+/// assert!(call(&mut buffer, "move_to", &[Value::Integer(1)]).is_err());
+/// assert!(call(&mut buffer, "explode", &[]).is_err());
+/// ```
+pub fn call(buffer: &mut Buffer, function: &str, arguments: &[Value]) -> Result<Value> {
+    let args = Arguments{ function, values: arguments };
+
+    let command = match function {
+        "data" => return Ok(Value::String(buffer.data())),
+        "line_count" => return Ok(Value::Integer(buffer.line_count() as i64)),
+        "cursor" => return Ok(position_value(&buffer.cursor)),
+        "modified" => return Ok(Value::Bool(buffer.modified())),
+        "path" => {
+            return Ok(buffer.path.as_ref().map_or(Value::Nil, |path| {
+                Value::String(path.to_string_lossy().into_owned())
+            }));
+        },
+        "read" => return Ok(buffer.read(&args.range(0)?).map_or(Value::Nil, Value::String)),
+        "search" => {
+            let results = buffer.search(&args.string(0)?);
+            return Ok(Value::List(results.iter().map(position_value).collect()));
+        },
+        _ => command(function, arguments)?,
+    };
+    buffer.execute(command);
+
+    Ok(Value::Nil)
+}
+
+// Builds the command corresponding to one of the non-query `FUNCTIONS`.
+pub(crate) fn command(function: &str, arguments: &[Value]) -> Result<Command> {
+    let args = Arguments{ function, values: arguments };
+
+    Ok(match function {
+        "insert" => Command::Insert(args.string(0)?),
+        "delete" => Command::Delete,
+        "delete_range" => Command::DeleteRange(args.range(0)?),
+        "replace" => Command::Replace{ range: args.range(0)?, data: args.string(4)? },
+        "replace_all" => Command::ReplaceAll{ needle: args.string(0)?, replacement: args.string(1)? },
+        "replace_all_preserving_case" => {
+            Command::ReplaceAllPreservingCase{ needle: args.string(0)?, replacement: args.string(1)? }
+        },
+        "replace_contents" => Command::ReplaceContents(args.string(0)?),
+        "increment_number" => Command::IncrementNumber(args.integer(0)?),
+        "expand_abbreviation_before_cursor" => Command::ExpandAbbreviationBeforeCursor,
+        "ensure_trailing_newline" => Command::EnsureTrailingNewline,
+        "set_pending" => Command::SetPending(args.string(0)?),
+        "commit_pending" => Command::CommitPending,
+        "cancel_pending" => Command::CancelPending,
+        "accept_ours" => Command::AcceptOurs(args.range(0)?),
+        "accept_theirs" => Command::AcceptTheirs(args.range(0)?),
+        "accept_both" => Command::AcceptBoth(args.range(0)?),
+        "start_operation_group" => Command::StartOperationGroup,
+        "end_operation_group" => Command::EndOperationGroup,
+        "undo" => Command::Undo,
+        "redo" => Command::Redo,
+        "move_to" => Command::MoveTo(args.position(0)?),
+        "move_up" => Command::MoveUp,
+        "move_down" => Command::MoveDown,
+        "move_left" => Command::MoveLeft,
+        "move_right" => Command::MoveRight,
+        "move_to_start_of_line" => Command::MoveToStartOfLine,
+        "move_to_end_of_line" => Command::MoveToEndOfLine,
+        "move_to_start_of_logical_line" => Command::MoveToStartOfLogicalLine,
+        "move_to_end_of_logical_line" => Command::MoveToEndOfLogicalLine,
+        "move_to_first_line" => Command::MoveToFirstLine,
+        "move_to_last_line" => Command::MoveToLastLine,
+        "move_to_start_of_buffer" => Command::MoveToStartOfBuffer,
+        "move_to_end_of_buffer" => Command::MoveToEndOfBuffer,
+        "move_to_next_word" => Command::MoveToNextWord,
+        "move_to_previous_word" => Command::MoveToPreviousWord,
+        "move_to_next_cell" => Command::MoveToNextCell,
+        "move_to_previous_cell" => Command::MoveToPreviousCell,
+        _ => return Err(ErrorKind::UnknownFunction(function.to_string()).into()),
+    })
+}
+
+// The inverse of `command`: the function corresponding to
+// the command, along with the arguments that produce it.
+#[cfg(feature = "fs")]
+pub(crate) fn function(command: &Command) -> (&'static str, Vec<Value>) {
+    let string = |value: &str| Value::String(value.to_string());
+
+    match *command {
+        Command::Insert(ref data) => ("insert", vec![string(data)]),
+        Command::Delete => ("delete", vec![]),
+        Command::DeleteRange(ref range) => ("delete_range", range_values(range)),
+        Command::Replace{ ref range, ref data } => {
+            let mut values = range_values(range);
+            values.push(string(data));
+            ("replace", values)
+        },
+        Command::ReplaceAll{ ref needle, ref replacement } => {
+            ("replace_all", vec![string(needle), string(replacement)])
+        },
+        Command::ReplaceAllPreservingCase{ ref needle, ref replacement } => {
+            ("replace_all_preserving_case", vec![string(needle), string(replacement)])
+        },
+        Command::ReplaceContents(ref content) => ("replace_contents", vec![string(content)]),
+        Command::IncrementNumber(delta) => ("increment_number", vec![Value::Integer(delta)]),
+        Command::ExpandAbbreviationBeforeCursor => ("expand_abbreviation_before_cursor", vec![]),
+        Command::EnsureTrailingNewline => ("ensure_trailing_newline", vec![]),
+        Command::SetPending(ref content) => ("set_pending", vec![string(content)]),
+        Command::CommitPending => ("commit_pending", vec![]),
+        Command::CancelPending => ("cancel_pending", vec![]),
+        Command::AcceptOurs(ref range) => ("accept_ours", range_values(range)),
+        Command::AcceptTheirs(ref range) => ("accept_theirs", range_values(range)),
+        Command::AcceptBoth(ref range) => ("accept_both", range_values(range)),
+        Command::StartOperationGroup => ("start_operation_group", vec![]),
+        Command::EndOperationGroup => ("end_operation_group", vec![]),
+        Command::Undo => ("undo", vec![]),
+        Command::Redo => ("redo", vec![]),
+        Command::MoveTo(ref position) => ("move_to", position_values(position)),
+        Command::MoveUp => ("move_up", vec![]),
+        Command::MoveDown => ("move_down", vec![]),
+        Command::MoveLeft => ("move_left", vec![]),
+        Command::MoveRight => ("move_right", vec![]),
+        Command::MoveToStartOfLine => ("move_to_start_of_line", vec![]),
+        Command::MoveToEndOfLine => ("move_to_end_of_line", vec![]),
+        Command::MoveToStartOfLogicalLine => ("move_to_start_of_logical_line", vec![]),
+        Command::MoveToEndOfLogicalLine => ("move_to_end_of_logical_line", vec![]),
+        Command::MoveToFirstLine => ("move_to_first_line", vec![]),
+        Command::MoveToLastLine => ("move_to_last_line", vec![]),
+        Command::MoveToStartOfBuffer => ("move_to_start_of_buffer", vec![]),
+        Command::MoveToEndOfBuffer => ("move_to_end_of_buffer", vec![]),
+        Command::MoveToNextWord => ("move_to_next_word", vec![]),
+        Command::MoveToPreviousWord => ("move_to_previous_word", vec![]),
+        Command::MoveToNextCell => ("move_to_next_cell", vec![]),
+        Command::MoveToPreviousCell => ("move_to_previous_cell", vec![]),
+    }
+}
+
+fn position_values(position: &Position) -> Vec<Value> {
+    vec![Value::Integer(position.line as i64), Value::Integer(position.offset as i64)]
+}
+
+#[cfg(feature = "fs")]
+fn range_values(range: &Range) -> Vec<Value> {
+    let mut values = position_values(&range.start());
+    values.extend(position_values(&range.end()));
+    values
+}
+
+fn position_value(position: &Position) -> Value {
+    Value::List(position_values(position))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{call, Value, FUNCTIONS};
+    use buffer::Buffer;
+    use errors::{Error, ErrorKind};
+
+    #[test]
+    fn call_recognizes_every_listed_function() {
+        for function in FUNCTIONS {
+            let mut buffer = Buffer::new();
+
+            if let Err(Error(ErrorKind::UnknownFunction(_), _)) = call(&mut buffer, function, &[]) {
+                panic!("{} wasn't recognized", function);
+            }
+        }
+    }
+
+    #[test]
+    fn call_rejects_negative_positions() {
+        let mut buffer = Buffer::new();
+
+        match call(&mut buffer, "move_to", &[Value::Integer(-1), Value::Integer(0)]) {
+            Err(Error(ErrorKind::InvalidArguments(function), _)) => assert_eq!(function, "move_to"),
+            _ => panic!("expected invalid arguments"),
+        }
+    }
+
+    #[test]
+    fn call_returns_search_results_as_position_lists() {
+        let mut buffer = Buffer::new();
+        buffer.insert("scribe\nscribe");
+
+        assert_eq!(
+            call(&mut buffer, "search", &[Value::String("ribe".to_string())]).unwrap(),
+            Value::List(vec![
+                Value::List(vec![Value::Integer(0), Value::Integer(2)]),
+                Value::List(vec![Value::Integer(1), Value::Integer(2)]),
+            ])
+        );
+    }
+}
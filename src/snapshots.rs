@@ -0,0 +1,254 @@
+//! Compressed copies of file contents, kept on disk as a local history.
+
+use buffer::Buffer;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::cmp::Reverse;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_LIMIT: usize = 50;
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// A directory of gzip-compressed snapshots of file contents, which outlive
+/// buffers' in-memory undo histories. Snapshots are grouped by the path of
+/// the file they were taken of, and once a file has more than `limit`
+/// snapshots, or any older than `max_age`, the oldest are discarded.
+///
+/// The `interval` is the minimum amount of time between periodic snapshots
+/// (see `Workspace::store_snapshots`); 5 minutes, by default.
+pub struct SnapshotStore {
+    directory: PathBuf,
+    pub limit: usize,
+    pub max_age: Option<Duration>,
+    pub interval: Duration,
+}
+
+/// A snapshot of a file's contents, as listed by `SnapshotStore::snapshots`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub created: SystemTime,
+    location: PathBuf,
+}
+
+impl SnapshotStore {
+    /// Creates a store that keeps snapshots beneath the specified state
+    /// directory, which is created as needed. Up to 50 snapshots are
+    /// kept for each file, regardless of their age.
+    pub fn new(directory: &Path) -> SnapshotStore {
+        SnapshotStore{
+            directory: directory.to_path_buf(),
+            limit: DEFAULT_LIMIT,
+            max_age: None,
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Stores a snapshot of a file's contents, pruning its older snapshots.
+    /// Returns None if the content matches the file's latest snapshot, in
+    /// which case nothing is stored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::SnapshotStore;
+    /// use std::env;
+    /// use std::fs;
+    /// use std::path::Path;
+    ///
+    /// let directory = env::temp_dir().join("scribe_snapshot_store_example");
+    /// let store = SnapshotStore::new(&directory);
+    /// let path = Path::new("/project/lib.rs");
+    ///
+    /// assert!(store.store(path, "scribe").unwrap().is_some());
+    /// assert!(store.store(path, "scribe").unwrap().is_none());
+    ///
+    /// let snapshots = store.snapshots(path).unwrap();
+    /// assert_eq!(snapshots.len(), 1);
+    /// assert_eq!(snapshots[0].content().unwrap(), "scribe");
+    /// # fs::remove_dir_all(&directory).unwrap();
+    /// ```
+    pub fn store(&self, path: &Path, content: &str) -> io::Result<Option<Snapshot>> {
+        if let Some(latest) = self.snapshots(path)?.first() {
+            if latest.content()? == content {
+                return Ok(None);
+            }
+        }
+
+        let directory = self.file_directory(path);
+        fs::create_dir_all(&directory)?;
+
+        // Name snapshots after their creation time, so that they sort
+        // chronologically and don't need any additional metadata.
+        let created = SystemTime::now();
+        let timestamp = created.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let location = directory.join(
+            format!("{}.{:09}.gz", timestamp.as_secs(), timestamp.subsec_nanos())
+        );
+
+        let mut encoder = GzEncoder::new(File::create(&location)?, Compression::default());
+        encoder.write_all(content.as_bytes())?;
+        encoder.finish()?.sync_all()?;
+
+        self.prune(path)?;
+
+        Ok(Some(Snapshot{ path: path.to_path_buf(), created, location }))
+    }
+
+    /// Lists the snapshots of the file at the specified path, newest first.
+    pub fn snapshots(&self, path: &Path) -> io::Result<Vec<Snapshot>> {
+        let entries = match fs::read_dir(self.file_directory(path)) {
+            Ok(entries) => entries,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(error) => return Err(error),
+        };
+
+        let mut snapshots = Vec::new();
+        for entry in entries {
+            let location = entry?.path();
+
+            if let Some(created) = location.file_name().and_then(|name| parse_timestamp(name.to_str()?)) {
+                snapshots.push(Snapshot{ path: path.to_path_buf(), created, location });
+            }
+        }
+        snapshots.sort_by_key(|snapshot| Reverse(snapshot.created));
+
+        Ok(snapshots)
+    }
+
+    // The most recent time at which a snapshot of the file was taken.
+    pub(crate) fn latest(&self, path: &Path) -> io::Result<Option<SystemTime>> {
+        Ok(self.snapshots(path)?.first().map(|snapshot| snapshot.created))
+    }
+
+    // Discards snapshots beyond the limit, and those older than the maximum age.
+    fn prune(&self, path: &Path) -> io::Result<()> {
+        let now = SystemTime::now();
+
+        for (index, snapshot) in self.snapshots(path)?.iter().enumerate() {
+            let expired = self.max_age.is_some_and(|max_age| {
+                now.duration_since(snapshot.created).is_ok_and(|age| age > max_age)
+            });
+
+            if index >= self.limit || expired {
+                fs::remove_file(&snapshot.location)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Snapshots for each file are kept in a directory named after a hash of
+    // its path, which (unlike the standard library's hasher) is stable.
+    pub(crate) fn file_directory(&self, path: &Path) -> PathBuf {
+        let hash = path.to_string_lossy().bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+
+        self.directory.join(format!("{:016x}", hash))
+    }
+}
+
+impl Snapshot {
+    /// Reads and decompresses the snapshot's contents.
+    pub fn content(&self) -> io::Result<String> {
+        let mut content = String::new();
+        GzDecoder::new(File::open(&self.location)?).read_to_string(&mut content)?;
+
+        Ok(content)
+    }
+
+    /// Replaces the buffer's contents with the snapshot's, as a single
+    /// operation that can be undone (see `Buffer::replace_contents`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::{Buffer, SnapshotStore};
+    /// use std::env;
+    /// use std::fs;
+    /// use std::path::Path;
+    ///
+    /// let directory = env::temp_dir().join("scribe_snapshot_restore_example");
+    /// let store = SnapshotStore::new(&directory);
+    /// let snapshot = store.store(Path::new("/project/lib.rs"), "scribe").unwrap().unwrap();
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("library");
+    /// snapshot.restore(&mut buffer).unwrap();
+    /// assert_eq!(buffer.data(), "scribe");
+    ///
+    /// buffer.undo();
+    /// assert_eq!(buffer.data(), "library");
+    /// # fs::remove_dir_all(&directory).unwrap();
+    /// ```
+    pub fn restore(&self, buffer: &mut Buffer) -> io::Result<()> {
+        let content = self.content()?;
+        buffer.replace_contents(&content);
+
+        Ok(())
+    }
+}
+
+// Parses "<seconds>.<nanoseconds>.gz" snapshot file names.
+fn parse_timestamp(name: &str) -> Option<SystemTime> {
+    let mut parts = name.strip_suffix(".gz")?.splitn(2, '.');
+    let seconds = parts.next()?.parse().ok()?;
+    let nanoseconds = parts.next()?.parse().ok()?;
+
+    UNIX_EPOCH.checked_add(Duration::new(seconds, nanoseconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_timestamp, SnapshotStore};
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn store_prunes_snapshots_beyond_the_limit() {
+        let directory = env::temp_dir().join("scribe_snapshots_limit");
+        let mut store = SnapshotStore::new(&directory);
+        store.limit = 2;
+        let path = Path::new("/project/lib.rs");
+
+        for content in ["scribe", "library", "editor"].iter() {
+            store.store(path, content).unwrap();
+        }
+
+        let contents: Vec<String> = store.snapshots(path).unwrap()
+            .iter()
+            .map(|snapshot| snapshot.content().unwrap())
+            .collect();
+        assert_eq!(contents, vec!["editor", "library"]);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn store_keeps_snapshots_for_each_file_separately() {
+        let directory = env::temp_dir().join("scribe_snapshots_separate_files");
+        let store = SnapshotStore::new(&directory);
+
+        store.store(Path::new("/project/a.rs"), "scribe").unwrap();
+        assert!(store.store(Path::new("/project/b.rs"), "scribe").unwrap().is_some());
+        assert_eq!(store.snapshots(Path::new("/project/a.rs")).unwrap().len(), 1);
+        assert!(store.snapshots(Path::new("/project/c.rs")).unwrap().is_empty());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn parse_timestamp_ignores_unrelated_files() {
+        assert_eq!(
+            parse_timestamp("12.000000034.gz"),
+            Some(UNIX_EPOCH + Duration::new(12, 34))
+        );
+        assert!(parse_timestamp("12.gz").is_none());
+        assert!(parse_timestamp("notes.txt").is_none());
+    }
+}
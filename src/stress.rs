@@ -0,0 +1,286 @@
+//! Deterministic generation of pseudo-localized content, for stress testing.
+//!
+//! A `StressGenerator` produces text full of content that's easy to mishandle
+//! when rendering or moving a cursor (combining marks, right-to-left scripts,
+//! wide characters, zero-width joiners, and very long lines), along with the
+//! position of each sample as scribe computes it. Integrators can load the
+//! content into a buffer and check that their own rendering and cursor logic
+//! agrees with scribe's position math.
+
+use buffer::{Buffer, Position, Range};
+use fuzz::Generator;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Pseudo-localized words, used to fill the space between samples.
+const FILLER: &[&str] = &["Ŝçŕîƀé", "ļîƀŕåŕý", "ëðîţöŕ", "ƀüƒƒéŕ", "çüŕšöŕ", "scribe"];
+
+const COMBINING_MARKS: &[&str] = &["e\u{301}", "a\u{308}\u{332}", "Z\u{335}\u{321}\u{36a}", "n\u{303}o\u{302}"];
+const RIGHT_TO_LEFT: &[&str] = &["שלום", "مرحبا", "עִבְרִית", "كتاب"];
+const WIDE_CHARACTERS: &[&str] = &["漢字", "日本語", "한국어", "🦀", "ｆｕｌｌ"];
+const ZERO_WIDTH_JOINERS: &[&str] = &[
+    "👩\u{200d}💻",
+    "👨\u{200d}👩\u{200d}👧",
+    "🏳\u{fe0f}\u{200d}🌈",
+    "क\u{94d}\u{200d}ष",
+];
+
+/// The kinds of content produced by a `StressGenerator`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StressKind {
+    /// Characters followed by one or more combining marks.
+    CombiningMarks,
+
+    /// Words in right-to-left scripts (Hebrew and Arabic).
+    RightToLeft,
+
+    /// Characters typically displayed across two columns (e.g. CJK and emoji).
+    WideCharacters,
+
+    /// Sequences of characters joined by zero-width joiners.
+    ZeroWidthJoiners,
+
+    /// An entire line of mixed content, at least
+    /// `StressGenerator::long_line_length` graphemes long.
+    LongLine,
+}
+
+const KINDS: &[StressKind] = &[
+    StressKind::CombiningMarks,
+    StressKind::RightToLeft,
+    StressKind::WideCharacters,
+    StressKind::ZeroWidthJoiners,
+    StressKind::LongLine,
+];
+
+/// A sample of tricky content within generated `StressContent`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StressSample {
+    pub kind: StressKind,
+
+    /// The sample's text.
+    pub text: String,
+
+    /// The sample's range, in buffer positions (i.e. grapheme offsets).
+    pub range: Range,
+
+    /// The sample's byte offset within `StressContent::data`.
+    pub byte_offset: usize,
+}
+
+/// Content produced by a `StressGenerator`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StressContent {
+    pub data: String,
+
+    /// The samples within the content, in the order they appear.
+    pub samples: Vec<StressSample>,
+}
+
+impl StressContent {
+    /// Creates a buffer containing the content, with its cursor at the start.
+    pub fn buffer(&self) -> Buffer {
+        let mut buffer = Buffer::new();
+        buffer.insert(self.data.as_str());
+
+        buffer
+    }
+}
+
+/// A seeded generator of pseudo-localized content.
+///
+/// # Examples
+///
+/// ```
+/// use scribe::stress::StressGenerator;
+///
+/// let content = StressGenerator::new(42).generate();
+/// let mut buffer = content.buffer();
+///
+/// for sample in &content.samples {
+///     assert_eq!(buffer.read(&sample.range).unwrap(), sample.text);
+///     assert!(content.data[sample.byte_offset..].starts_with(&sample.text));
+///
+///     // The end of each sample is a valid cursor position.
+///     assert!(buffer.cursor.move_to(sample.range.end()));
+/// }
+///
+/// // The same seed reproduces the same content.
+/// assert_eq!(StressGenerator::new(42).generate(), content);
+/// ```
+pub struct StressGenerator {
+    generator: Generator,
+
+    /// The number of lines to generate (40, by default). Every run of five
+    /// lines contains at least one sample of each `StressKind`.
+    pub lines: usize,
+
+    /// The minimum length, in graphemes, of `StressKind::LongLine`
+    /// samples (10,000, by default).
+    pub long_line_length: usize,
+}
+
+impl StressGenerator {
+    /// Creates a generator with the specified seed.
+    pub fn new(seed: u64) -> StressGenerator {
+        StressGenerator{ generator: Generator::new(seed), lines: 40, long_line_length: 10_000 }
+    }
+
+    /// Generates content, advancing the generator.
+    pub fn generate(&mut self) -> StressContent {
+        let mut content = StressContent{ data: String::new(), samples: Vec::new() };
+
+        for line in 0..self.lines {
+            if line > 0 {
+                content.data.push('\n');
+            }
+
+            let kind = KINDS[line % KINDS.len()];
+            if kind == StressKind::LongLine {
+                self.long_line(&mut content, line);
+            } else {
+                self.mixed_line(&mut content, line, kind);
+            }
+        }
+
+        content
+    }
+
+    // Appends a line of filler words and samples,
+    // including at least one sample of the specified kind.
+    fn mixed_line(&mut self, content: &mut StressContent, line: usize, kind: StressKind) {
+        let words = 2 + self.generator.below(5);
+        let featured = self.generator.below(words);
+        let mut offset = 0;
+
+        for word in 0..words {
+            if word > 0 {
+                content.data.push(' ');
+                offset += 1;
+            }
+
+            let kind = if word == featured {
+                Some(kind)
+            } else if self.generator.below(3) == 0 {
+                Some(self.mixed_kind())
+            } else {
+                None
+            };
+
+            let text = match kind {
+                Some(kind) => self.sample_text(kind),
+                None => self.choose(FILLER),
+            };
+            let length = text.graphemes(true).count();
+
+            if let Some(kind) = kind {
+                content.samples.push(StressSample{
+                    kind,
+                    text: text.to_string(),
+                    range: Range::new(
+                        Position{ line, offset },
+                        Position{ line, offset: offset + length }
+                    ),
+                    byte_offset: content.data.len(),
+                });
+            }
+            content.data.push_str(text);
+            offset += length;
+        }
+    }
+
+    // Appends a line of filler words and samples of every other kind,
+    // recorded as a single sample spanning the entire line.
+    fn long_line(&mut self, content: &mut StressContent, line: usize) {
+        let mut text = String::new();
+        let mut length = 0;
+
+        while length < self.long_line_length {
+            if length > 0 {
+                text.push(' ');
+                length += 1;
+            }
+
+            let word = if self.generator.below(2) == 0 {
+                let kind = self.mixed_kind();
+                self.sample_text(kind)
+            } else {
+                self.choose(FILLER)
+            };
+            text.push_str(word);
+            length += word.graphemes(true).count();
+        }
+
+        content.samples.push(StressSample{
+            kind: StressKind::LongLine,
+            text: text.clone(),
+            range: Range::new(Position{ line, offset: 0 }, Position{ line, offset: length }),
+            byte_offset: content.data.len(),
+        });
+        content.data.push_str(&text);
+    }
+
+    // A random kind, other than LongLine.
+    fn mixed_kind(&mut self) -> StressKind {
+        KINDS[self.generator.below(KINDS.len() - 1)]
+    }
+
+    fn sample_text(&mut self, kind: StressKind) -> &'static str {
+        match kind {
+            StressKind::CombiningMarks => self.choose(COMBINING_MARKS),
+            StressKind::RightToLeft => self.choose(RIGHT_TO_LEFT),
+            StressKind::WideCharacters => self.choose(WIDE_CHARACTERS),
+            StressKind::ZeroWidthJoiners => self.choose(ZERO_WIDTH_JOINERS),
+            StressKind::LongLine => unreachable!(),
+        }
+    }
+
+    fn choose(&mut self, values: &[&'static str]) -> &'static str {
+        values[self.generator.below(values.len())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StressGenerator, StressKind, KINDS};
+    use buffer::Position;
+
+    #[test]
+    fn samples_match_buffer_positions() {
+        for seed in 0..20 {
+            let mut generator = StressGenerator::new(seed);
+            generator.long_line_length = 500;
+            let content = generator.generate();
+            let mut buffer = content.buffer();
+
+            for sample in &content.samples {
+                assert_eq!(buffer.read(&sample.range).as_ref(), Some(&sample.text), "seed {}", seed);
+                assert!(buffer.cursor.move_to(sample.range.end()), "seed {}", seed);
+                assert_eq!(*buffer.cursor, sample.range.end());
+            }
+        }
+    }
+
+    #[test]
+    fn every_kind_is_generated_within_five_lines() {
+        let mut generator = StressGenerator::new(7);
+        generator.lines = 5;
+        let content = generator.generate();
+
+        for kind in KINDS {
+            assert!(content.samples.iter().any(|sample| sample.kind == *kind), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn long_lines_span_entire_lines() {
+        let content = StressGenerator::new(3).generate();
+        let buffer = content.buffer();
+
+        for sample in content.samples.iter().filter(|sample| sample.kind == StressKind::LongLine) {
+            let line = sample.range.start().line;
+            assert_eq!(sample.range.start(), Position{ line, offset: 0 });
+            assert!(sample.range.end().offset >= 10_000);
+            assert_eq!(buffer.line(line), Some(sample.text.clone()));
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Syntax definition detection for buffers.
+
+use buffer::Buffer;
+use std::path::Path;
+use syntect::parsing::{SyntaxDefinition, SyntaxSet};
+
+// Definitions for common file types missing from syntect's default set.
+#[cfg(feature = "workspace")]
+const BUNDLED_SYNTAXES: &[&str] = &[
+    include_str!("syntaxes/TOML.sublime-syntax"),
+];
+
+/// Chooses a syntax definition for a buffer. Detectors registered with a
+/// workspace (see `Workspace::register_syntax_detector`) are consulted, most
+/// recently registered first, before its built-in detection. This lets
+/// applications supply their own definitions, or choose between definitions
+/// using their own criteria, without changing the crate.
+///
+/// Closures taking a buffer and syntax set are detectors.
+pub trait SyntaxDetector {
+    /// Returns a definition for the buffer, or `None`
+    /// to defer to the detectors that follow it.
+    fn detect(&self, buffer: &Buffer, syntax_set: &SyntaxSet) -> Option<SyntaxDefinition>;
+}
+
+impl<F> SyntaxDetector for F where F: Fn(&Buffer, &SyntaxSet) -> Option<SyntaxDefinition> {
+    fn detect(&self, buffer: &Buffer, syntax_set: &SyntaxSet) -> Option<SyntaxDefinition> {
+        self(buffer, syntax_set)
+    }
+}
+
+/// Detects a fixed definition for buffers whose paths have any of the
+/// specified extensions (or file names, e.g. "Makefile"), compared
+/// case-insensitively.
+///
+/// # Examples
+///
+/// ```
+/// extern crate syntect;
+/// extern crate scribe;
+///
+/// use scribe::{Buffer, ExtensionDetector, SyntaxDetector};
+/// use syntect::parsing::SyntaxSet;
+/// use std::path::PathBuf;
+///
+/// let syntax_set = SyntaxSet::load_defaults_newlines();
+/// let definition = syntax_set.find_syntax_by_name("YAML").unwrap().clone();
+/// let detector = ExtensionDetector::new(&["conf"], definition);
+///
+/// let mut buffer = Buffer::new();
+/// buffer.path = Some(PathBuf::from("app.CONF"));
+/// assert_eq!(detector.detect(&buffer, &syntax_set).unwrap().name, "YAML");
+/// ```
+pub struct ExtensionDetector {
+    extensions: Vec<String>,
+    definition: SyntaxDefinition,
+}
+
+impl ExtensionDetector {
+    pub fn new(extensions: &[&str], definition: SyntaxDefinition) -> ExtensionDetector {
+        ExtensionDetector{
+            extensions: extensions.iter().map(|extension| extension.to_lowercase()).collect(),
+            definition,
+        }
+    }
+}
+
+impl SyntaxDetector for ExtensionDetector {
+    fn detect(&self, buffer: &Buffer, _: &SyntaxSet) -> Option<SyntaxDefinition> {
+        let path = buffer.path.as_ref()?;
+        let matches = path_keys(path).iter().any(|key| {
+            self.extensions.contains(&key.to_lowercase())
+        });
+
+        if matches { Some(self.definition.clone()) } else { None }
+    }
+}
+
+// Syntect's default definitions, along with those bundled with the crate.
+#[cfg(feature = "workspace")]
+pub(crate) fn default_syntax_set() -> SyntaxSet {
+    let mut syntax_set = SyntaxSet::load_defaults_newlines();
+    for syntax in BUNDLED_SYNTAXES {
+        let definition = SyntaxDefinition::load_from_str(syntax, true, None)
+            .expect("bundled syntax definitions are valid");
+        syntax_set.add_syntax(definition);
+    }
+    syntax_set.link_syntaxes();
+
+    syntax_set
+}
+
+// Detects a definition using the buffer's file extension or name,
+// followed by its first line (e.g. a shebang or XML declaration),
+// falling back to a plain text definition if neither are recognized.
+#[cfg(feature = "workspace")]
+pub(crate) fn detect_syntax(buffer: &Buffer, syntax_set: &SyntaxSet) -> SyntaxDefinition {
+    let by_path = buffer.path.as_ref().and_then(|path| {
+        path_keys(path).iter().filter_map(|key| syntax_set.find_syntax_by_extension(key)).next()
+    });
+
+    by_path
+        .or_else(|| {
+            // Only the first line is read, rather than copying the whole buffer.
+            let first_line = buffer.line(0)?;
+
+            syntax_set.find_syntax_by_first_line(&first_line).or_else(|| {
+                syntax_set.find_syntax_by_token(shebang_interpreter(&first_line)?)
+            })
+        })
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text())
+        .clone()
+}
+
+// The path's extension and file name, in that order, used to look up
+// definitions; syntect lists some file names (e.g. "Gemfile") as extensions.
+fn path_keys(path: &Path) -> Vec<&str> {
+    path.extension().into_iter()
+        .chain(path.file_name())
+        .filter_map(|key| key.to_str())
+        .collect()
+}
+
+// The name of the interpreter in a shebang line, skipping "env" and its
+// options, and without any version suffix (e.g. "python" for "python3.8").
+#[cfg(feature = "workspace")]
+fn shebang_interpreter(line: &str) -> Option<&str> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    if name.is_empty() { None } else { Some(name) }
+}
+
+#[cfg(all(test, feature = "workspace"))]
+mod tests {
+    use super::{default_syntax_set, detect_syntax, shebang_interpreter};
+    use buffer::{Buffer, Token};
+    use std::path::PathBuf;
+
+    fn detected_name(path: Option<&str>, data: &str) -> String {
+        let syntax_set = default_syntax_set();
+        let mut buffer = Buffer::new();
+        buffer.path = path.map(PathBuf::from);
+        buffer.insert(data);
+
+        detect_syntax(&buffer, &syntax_set).name
+    }
+
+    #[test]
+    fn detect_syntax_uses_extensions_and_file_names() {
+        assert_eq!(detected_name(Some("src/lib.rs"), ""), "Rust");
+        assert_eq!(detected_name(Some("lib/scribe.rb"), ""), "Ruby");
+        assert_eq!(detected_name(Some("README.md"), ""), "Markdown");
+        assert_eq!(detected_name(Some("Cargo.toml"), ""), "TOML");
+        assert_eq!(detected_name(Some("project/Gemfile"), ""), "Ruby");
+    }
+
+    #[test]
+    fn bundled_toml_definition_lexes_tables_keys_and_values() {
+        let syntax_set = default_syntax_set();
+        let mut buffer = Buffer::new();
+        buffer.insert("[package]\nname = \"scribe\" # the name\nversion = 1\n");
+        buffer.syntax_definition = syntax_set.find_syntax_by_extension("toml").cloned();
+
+        let scopes: Vec<(String, String)> = buffer.tokens().unwrap().iter().filter_map(|token| match token {
+            Token::Lexeme(lexeme) => Some((
+                lexeme.value.to_string(),
+                lexeme.scope.as_slice().last().unwrap().build_string()
+            )),
+            Token::Newline => None,
+        }).collect();
+        let scope_of = |value: &str| scopes.iter().find(|(lexeme, _)| lexeme == value).map(|(_, scope)| scope.as_str());
+
+        assert_eq!(scope_of("package"), Some("entity.name.section.table.toml"));
+        assert_eq!(scope_of("name"), Some("meta.mapping.key.toml"));
+        assert_eq!(scope_of("scribe"), Some("string.quoted.double.basic.toml"));
+        assert_eq!(scope_of("1"), Some("constant.numeric.toml"));
+        assert_eq!(scope_of(" the name"), Some("comment.line.number-sign.toml"));
+    }
+
+    #[test]
+    fn detect_syntax_falls_back_to_the_first_line() {
+        assert_eq!(detected_name(Some("bin/setup"), "#!/usr/bin/env ruby\nputs 1"), "Ruby");
+        assert_eq!(detected_name(None, "#!/usr/local/bin/python3.8\n"), "Python");
+        assert_eq!(detected_name(None, "<?xml version=\"1.0\"?>\n<a/>"), "XML");
+        assert_eq!(detected_name(Some("notes"), "scribe"), "Plain Text");
+    }
+
+    #[test]
+    fn shebang_interpreter_skips_env_and_its_options() {
+        assert_eq!(shebang_interpreter("#!/usr/bin/env -S ruby -w"), Some("ruby"));
+        assert_eq!(shebang_interpreter("#!/bin/sh"), Some("sh"));
+        assert_eq!(shebang_interpreter("#!/usr/bin/python3.8"), Some("python"));
+        assert_eq!(shebang_interpreter("# comment"), None);
+    }
+}
@@ -0,0 +1,74 @@
+//! Ahead-of-time compilation of syntax definitions' patterns.
+
+use std::collections::HashSet;
+use syntect::parsing::SyntaxDefinition;
+use syntect::parsing::syntax_definition::{ContextPtr, ContextReference, MatchOperation, Pattern};
+
+// Compiles the patterns of every context reachable from the syntax definition
+// (including those of syntaxes it embeds), which syntect otherwise compiles
+// as they're first encountered while lexing. Contexts are shared by clones of
+// the definition, so this benefits every buffer using it. Patterns that refer
+// to captures from other patterns can only be compiled while lexing, and are
+// skipped. Returns the number of contexts visited.
+pub(crate) fn compile_patterns(def: &SyntaxDefinition) -> usize {
+    let mut visited = HashSet::new();
+    let mut pending: Vec<ContextPtr> = def.contexts.values().cloned().collect();
+    pending.extend(def.prototype.iter().cloned());
+
+    while let Some(context) = pending.pop() {
+        if !visited.insert(&*context as *const _ as usize) { continue; }
+
+        let mut context = context.borrow_mut();
+        pending.extend(context.prototype.iter().cloned());
+        for pattern in &mut context.patterns {
+            match *pattern {
+                Pattern::Match(ref mut pattern) => {
+                    pattern.ensure_compiled_if_possible();
+                    pending.extend(pattern.with_prototype.iter().cloned());
+                    match pattern.operation {
+                        MatchOperation::Push(ref references) |
+                        MatchOperation::Set(ref references) => {
+                            pending.extend(references.iter().filter_map(referenced_context));
+                        },
+                        MatchOperation::Pop | MatchOperation::None => (),
+                    }
+                },
+                Pattern::Include(ref reference) => pending.extend(referenced_context(reference)),
+            }
+        }
+    }
+
+    visited.len()
+}
+
+// The context a linked reference points to.
+fn referenced_context(reference: &ContextReference) -> Option<ContextPtr> {
+    match *reference {
+        ContextReference::Inline(ref context) => Some(context.clone()),
+        ContextReference::Direct(ref link) => link.link.upgrade(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compile_patterns;
+    use syntect::parsing::syntax_definition::Pattern;
+    use syntect::parsing::SyntaxSet;
+
+    #[test]
+    fn compile_patterns_compiles_reachable_contexts() {
+        let mut syntax_set = SyntaxSet::load_defaults_newlines();
+        syntax_set.link_syntaxes();
+        let html = syntax_set.find_syntax_by_name("HTML").unwrap();
+
+        assert!(compile_patterns(html) > html.contexts.len());
+        for context in html.contexts.values() {
+            for pattern in &context.borrow().patterns {
+                if let Pattern::Match(ref pattern) = *pattern {
+                    assert!(pattern.regex.is_some() || pattern.has_captures);
+                }
+            }
+        }
+    }
+}
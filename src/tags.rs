@@ -0,0 +1,257 @@
+//! Symbol lookup using ctags-generated `tags` files.
+
+use buffer::{Buffer, Position};
+#[cfg(feature = "fs")]
+use std::fs;
+#[cfg(feature = "fs")]
+use std::io;
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::path::PathBuf;
+#[cfg(feature = "workspace")]
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A symbol definition listed in a tags file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tag {
+    pub name: String,
+    pub path: PathBuf,
+    pub address: TagAddress,
+}
+
+/// The location of a tag within its file: either a (zero-based) line number,
+/// or a search pattern matching the line's content. Patterns are stored
+/// without their delimiters and escapes, but retain their anchors (`^`, `$`).
+#[derive(Clone, Debug, PartialEq)]
+pub enum TagAddress {
+    Line(usize),
+    Pattern(String),
+}
+
+/// The tags listed in a tags file, as generated by ctags (e.g. `ctags -R`).
+pub struct TagIndex {
+    tags: Vec<Tag>,
+}
+
+impl TagIndex {
+    /// Reads a tags file. Tag paths are resolved relative to the
+    /// directory containing it, and lines that can't be parsed (such as
+    /// the `!_TAG_` metadata ctags adds) are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::{TagAddress, TagIndex};
+    /// use std::env;
+    /// use std::fs;
+    ///
+    /// let path = env::temp_dir().join("scribe_tag_index_example");
+    /// fs::write(&path, "main\tsrc/main.rs\t/^fn main() {$/;\"\tf\n").unwrap();
+    ///
+    /// let index = TagIndex::from_file(&path).unwrap();
+    /// let tags = index.find("main");
+    /// assert_eq!(tags[0].path, env::temp_dir().join("src/main.rs"));
+    /// assert_eq!(tags[0].address, TagAddress::Pattern("^fn main() {$".to_string()));
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn from_file(path: &Path) -> io::Result<TagIndex> {
+        let content = fs::read_to_string(path)?;
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        let tags = content.lines().filter_map(|line| parse_tag(line, directory)).collect();
+
+        Ok(TagIndex{ tags })
+    }
+
+    /// Returns the tags with the specified name, in the order they're listed.
+    pub fn find(&self, name: &str) -> Vec<&Tag> {
+        self.tags.iter().filter(|tag| tag.name == name).collect()
+    }
+}
+
+impl Tag {
+    /// Locates the tag within a buffer containing its file, returning the
+    /// start of the line it's defined on. Returns None if the line doesn't
+    /// exist, or no lines match the tag's pattern.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::{Buffer, Tag, TagAddress};
+    /// use scribe::buffer::Position;
+    /// use std::path::PathBuf;
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("mod buffer;\nfn main() {\n}\n");
+    ///
+    /// let tag = Tag{
+    ///     name: "main".to_string(),
+    ///     path: PathBuf::from("main.rs"),
+    ///     address: TagAddress::Pattern("^fn main() {$".to_string()),
+    /// };
+    /// assert_eq!(tag.position_in(&buffer), Some(Position{ line: 1, offset: 0 }));
+    /// ```
+    pub fn position_in(&self, buffer: &Buffer) -> Option<Position> {
+        let data = buffer.data();
+
+        let line = match self.address {
+            TagAddress::Line(line) if line < buffer.line_count() => line,
+            TagAddress::Line(_) => return None,
+            TagAddress::Pattern(ref pattern) => {
+                let (start_anchored, pattern) = match pattern.strip_prefix('^') {
+                    Some(pattern) => (true, pattern),
+                    None => (false, pattern.as_str()),
+                };
+                let (end_anchored, pattern) = match pattern.strip_suffix('$') {
+                    Some(pattern) => (true, pattern),
+                    None => (false, pattern),
+                };
+
+                data.lines().position(|line| match (start_anchored, end_anchored) {
+                    (true, true) => line == pattern,
+                    (true, false) => line.starts_with(pattern),
+                    (false, true) => line.ends_with(pattern),
+                    (false, false) => line.contains(pattern),
+                })?
+            },
+        };
+
+        Some(Position{ line, offset: 0 })
+    }
+}
+
+// Finds the identifier (a run of alphanumeric characters and underscores)
+// at or immediately preceding the cursor.
+#[cfg(feature = "workspace")]
+pub(crate) fn symbol_at_cursor(buffer: &Buffer) -> Option<String> {
+    let data = buffer.data();
+    let line = data.lines().nth(buffer.cursor.line)?;
+    let graphemes: Vec<&str> = line.graphemes(true).collect();
+    let is_identifier = |grapheme: &&str| grapheme.chars().all(|c| c.is_alphanumeric() || c == '_');
+
+    let mut start = buffer.cursor.offset.min(graphemes.len());
+    if !graphemes.get(start).is_some_and(is_identifier) && start > 0 {
+        start -= 1;
+    }
+    if !graphemes.get(start).is_some_and(is_identifier) {
+        return None;
+    }
+
+    let mut end = start;
+    while start > 0 && is_identifier(&graphemes[start - 1]) { start -= 1; }
+    while end < graphemes.len() && is_identifier(&graphemes[end]) { end += 1; }
+
+    Some(graphemes[start..end].concat())
+}
+
+// Parses a "name<TAB>path<TAB>address[;"<TAB>fields]" line.
+#[cfg(feature = "fs")]
+fn parse_tag(line: &str, directory: &Path) -> Option<Tag> {
+    if line.starts_with("!_TAG_") { return None; }
+
+    let mut fields = line.splitn(3, '\t');
+    let name = fields.next()?;
+    let path = fields.next()?;
+    let remainder = fields.next()?;
+    let address = match remainder.find(";\"") {
+        Some(index) => &remainder[..index],
+        None => remainder,
+    };
+
+    let address = if let Ok(line) = address.parse::<usize>() {
+        TagAddress::Line(line.checked_sub(1)?)
+    } else {
+        let delimiter = address.chars().next().filter(|&c| c == '/' || c == '?')?;
+        let pattern = address[1..].strip_suffix(delimiter)?;
+        TagAddress::Pattern(unescape_pattern(pattern, delimiter))
+    };
+
+    Some(Tag{ name: name.to_string(), path: directory.join(path), address })
+}
+
+// Removes backslash escapes from delimiters and backslashes.
+#[cfg(feature = "fs")]
+fn unescape_pattern(pattern: &str, delimiter: char) -> String {
+    let mut unescaped = String::new();
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(&next) = chars.peek() {
+                if next == delimiter || next == '\\' {
+                    unescaped.push(next);
+                    chars.next();
+                    continue;
+                }
+            }
+        }
+        unescaped.push(c);
+    }
+
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "fs")]
+    use super::{parse_tag, TagAddress};
+    #[cfg(feature = "workspace")]
+    use super::symbol_at_cursor;
+    #[cfg(feature = "fs")]
+    use buffer::Buffer;
+    #[cfg(feature = "workspace")]
+    use buffer::Position;
+    #[cfg(feature = "fs")]
+    use std::path::Path;
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn parse_tag_handles_line_addresses() {
+        let tag = parse_tag("VERSION\tlib.rs\t12;\"\tc", Path::new("/project")).unwrap();
+
+        assert_eq!(tag.address, TagAddress::Line(11));
+        assert_eq!(tag.path, Path::new("/project/lib.rs"));
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn parse_tag_unescapes_patterns() {
+        let tag = parse_tag("f\tx.c\t?a\\?b\\\\/?", Path::new("")).unwrap();
+
+        assert_eq!(tag.address, TagAddress::Pattern("a?b\\/".to_string()));
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn parse_tag_skips_metadata_and_malformed_lines() {
+        assert!(parse_tag("!_TAG_FILE_SORTED\t1\t/0=unsorted/", Path::new("")).is_none());
+        assert!(parse_tag("name\tpath", Path::new("")).is_none());
+        assert!(parse_tag("name\tpath\tsearch", Path::new("")).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn position_in_returns_none_for_missing_lines() {
+        let tag = parse_tag("f\tx.c\t/^int f()$/", Path::new("")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("int g()\n");
+
+        assert_eq!(tag.position_in(&buffer), None);
+    }
+
+    #[test]
+    #[cfg(feature = "workspace")]
+    fn symbol_at_cursor_includes_identifiers_ending_at_the_cursor() {
+        let mut buffer = Buffer::new();
+        buffer.insert("let value = other_value;");
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 14 });
+        assert_eq!(symbol_at_cursor(&buffer), Some("other_value".to_string()));
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 9 });
+        assert_eq!(symbol_at_cursor(&buffer), Some("value".to_string()));
+
+        buffer.cursor.move_to(Position{ line: 0, offset: 11 });
+        assert_eq!(symbol_at_cursor(&buffer), None);
+    }
+}
@@ -0,0 +1,92 @@
+//! Deferred work, queued by a workspace for its host application to perform.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+/// Work deferred by a workspace until its host application runs it (see
+/// `Workspace::run_tasks`), rather than being performed on other threads.
+/// Buffers are identified by their IDs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WorkspaceTask {
+    /// Store snapshots of the workspace's buffers, if it has a snapshot
+    /// store (see `Workspace::store_snapshots`). Queued when buffers change.
+    StoreSnapshots,
+
+    /// Store a snapshot of the buffer, if the workspace has a snapshot store,
+    /// regardless of its `interval`. Queued when buffers are saved.
+    StoreSnapshot(usize),
+
+    /// Check for buffers whose files were changed by other programs (see
+    /// `Workspace::detect_external_changes`). Queued by the application,
+    /// e.g. periodically, or when it regains focus.
+    DetectExternalChanges,
+
+    /// Compile queued syntax definitions (see `Workspace::warm_up_syntaxes`).
+    /// Queued along with syntax definitions.
+    WarmUpSyntaxes,
+
+    /// Lex the buffer, populating its token cache, so that it can be
+    /// displayed without delay. Queued when buffers are added or changed.
+    LexBuffer(usize),
+
+    /// Perform maintenance on the buffer, such as trimming its undo history
+    /// (see `Buffer::perform_maintenance`). Queued when buffers change.
+    PerformMaintenance(usize),
+}
+
+// A workspace's queued tasks, in the order they'll be run. They're shared
+// with the observers it registers with its buffers, which queue work
+// following their edits.
+#[derive(Default)]
+pub(crate) struct TaskQueue {
+    tasks: RefCell<VecDeque<WorkspaceTask>>,
+}
+
+impl TaskQueue {
+    // Adds the task to the back of the queue, unless it's already queued.
+    pub(crate) fn push(&self, task: WorkspaceTask) {
+        let mut tasks = self.tasks.borrow_mut();
+        if !tasks.contains(&task) {
+            tasks.push_back(task);
+        }
+    }
+
+    pub(crate) fn pop(&self) -> Option<WorkspaceTask> {
+        self.tasks.borrow_mut().pop_front()
+    }
+
+    // Removes queued tasks for which the predicate returns false.
+    pub(crate) fn retain<F: FnMut(&WorkspaceTask) -> bool>(&self, predicate: F) {
+        self.tasks.borrow_mut().retain(predicate);
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.tasks.borrow().len()
+    }
+
+    pub(crate) fn to_vec(&self) -> Vec<WorkspaceTask> {
+        self.tasks.borrow().iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TaskQueue, WorkspaceTask};
+
+    #[test]
+    fn push_ignores_tasks_that_are_already_queued() {
+        let queue = TaskQueue::default();
+        queue.push(WorkspaceTask::LexBuffer(0));
+        queue.push(WorkspaceTask::StoreSnapshots);
+        queue.push(WorkspaceTask::LexBuffer(0));
+        queue.push(WorkspaceTask::LexBuffer(1));
+
+        assert_eq!(queue.to_vec(), vec![
+            WorkspaceTask::LexBuffer(0),
+            WorkspaceTask::StoreSnapshots,
+            WorkspaceTask::LexBuffer(1),
+        ]);
+        assert_eq!(queue.pop(), Some(WorkspaceTask::LexBuffer(0)));
+        assert_eq!(queue.len(), 2);
+    }
+}
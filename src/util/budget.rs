@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+
+/// A limit on the amount of work an interruptible operation can perform in a
+/// single call, expressed either as a number of work units (the definition of
+/// which is operation-specific, e.g. lines searched) or as elapsed time.
+///
+/// Operations accepting a budget always make progress, performing at least
+/// one unit of work regardless of the budget's size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Budget {
+    Units(usize),
+    Time(Duration),
+}
+
+impl Budget {
+    // Starts tracking work performed against the budget.
+    pub(crate) fn meter(self) -> BudgetMeter {
+        BudgetMeter{ budget: self, started: Instant::now(), spent: 0 }
+    }
+}
+
+// Tracks the work performed against a budget.
+pub(crate) struct BudgetMeter {
+    budget: Budget,
+    started: Instant,
+    spent: usize,
+}
+
+impl BudgetMeter {
+    // Requests another unit of work, returning whether or not it fits
+    // within the budget. The first unit of work is always permitted.
+    pub(crate) fn permit(&mut self) -> bool {
+        self.spent += 1;
+
+        match self.budget {
+            Budget::Units(units) => self.spent <= units.max(1),
+            Budget::Time(duration) => self.spent == 1 || self.started.elapsed() < duration,
+        }
+    }
+
+    // Whether the budget has been spent, such that no further
+    // work would be permitted. Nothing is spent by checking.
+    #[cfg(feature = "workspace")]
+    pub(crate) fn exhausted(&self) -> bool {
+        match self.budget {
+            Budget::Units(units) => self.spent >= units.max(1),
+            Budget::Time(duration) => self.spent >= 1 && self.started.elapsed() >= duration,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Budget;
+    use std::time::Duration;
+
+    #[test]
+    fn unit_budgets_allow_the_specified_number_of_units() {
+        let mut meter = Budget::Units(2).meter();
+        assert!(meter.permit());
+        assert!(meter.permit());
+        assert!(!meter.permit());
+    }
+
+    #[test]
+    fn budgets_always_allow_one_unit_of_work() {
+        assert!(Budget::Units(0).meter().permit());
+        assert!(Budget::Time(Duration::from_secs(0)).meter().permit());
+    }
+
+    #[test]
+    #[cfg(feature = "workspace")]
+    fn budgets_are_exhausted_once_their_units_are_spent() {
+        let mut meter = Budget::Units(2).meter();
+        assert!(!meter.exhausted());
+        meter.permit();
+        assert!(!meter.exhausted());
+        meter.permit();
+        assert!(meter.exhausted());
+    }
+
+    #[test]
+    fn exhausted_time_budgets_stop_after_the_first_unit() {
+        let mut meter = Budget::Time(Duration::from_secs(0)).meter();
+        meter.permit();
+        assert!(!meter.permit());
+    }
+}
@@ -0,0 +1,61 @@
+/// Applies the case pattern of `pattern` to `text`. Upper-case and
+/// capitalized patterns produce upper-case and capitalized text, respectively;
+/// anything else (lower-case, mixed case, or no letters at all) leaves the
+/// text as-is.
+///
+/// # Examples
+///
+/// ```
+/// use scribe::util::mirror_case;
+///
+/// assert_eq!(mirror_case("FOO", "bar"), "BAR");
+/// assert_eq!(mirror_case("Foo", "bar"), "Bar");
+/// assert_eq!(mirror_case("foo", "bar"), "bar");
+/// ```
+pub fn mirror_case(pattern: &str, text: &str) -> String {
+    let has_letters = pattern.chars().any(|c| c.is_alphabetic());
+    let has_lowercase = pattern.chars().any(|c| c.is_lowercase());
+
+    if has_letters && !has_lowercase {
+        return text.to_uppercase();
+    }
+
+    let mut pattern_chars = pattern.chars();
+    let capitalized = pattern_chars.next().map(|c| c.is_uppercase()).unwrap_or(false) &&
+        !pattern_chars.any(|c| c.is_uppercase());
+
+    if capitalized {
+        let mut text_chars = text.chars();
+        match text_chars.next() {
+            Some(first) => first.to_uppercase().chain(text_chars).collect(),
+            None => String::new(),
+        }
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mirror_case;
+
+    #[test]
+    fn mirror_case_leaves_mixed_case_patterns_alone() {
+        assert_eq!(mirror_case("fooBar", "bazQux"), "bazQux");
+    }
+
+    #[test]
+    fn mirror_case_treats_single_upper_case_letters_as_upper_case() {
+        assert_eq!(mirror_case("F", "bar"), "BAR");
+    }
+
+    #[test]
+    fn mirror_case_ignores_patterns_without_letters() {
+        assert_eq!(mirror_case("123", "Bar"), "Bar");
+    }
+
+    #[test]
+    fn mirror_case_only_capitalizes_the_first_letter_of_text() {
+        assert_eq!(mirror_case("Foo", "bar baz"), "Bar baz");
+    }
+}
@@ -0,0 +1,234 @@
+// Finds the longest common subsequence of two slices using Myers' algorithm,
+// returning the index of the matching element in `b` for each element in `a`
+// that's part of it. Runs in O((N + M) * D) time, where D is the number of
+// elements that differ, which keeps similar (i.e. typical) inputs cheap.
+// Rather than storing the paths explored for each edit distance, the slices
+// are divided at the middle of their shortest edit script and each half is
+// diffed in turn, using O(N + M) space.
+pub(crate) fn matching_elements<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; a.len()];
+    match_range(a, b, 0, 0, None, &mut matches);
+
+    matches
+}
+
+// Like `matching_elements`, but gives up on finding the edit script for any
+// section of the slices that differs by more than roughly twice `max_cost`
+// elements, leaving it unmatched. This bounds the running time to about
+// O((N + M) * max_cost) per level of division, at the cost of a minimal
+// result for heavily changed input.
+pub(crate) fn approximate_matching_elements<T: PartialEq>(a: &[T], b: &[T], max_cost: usize) -> Vec<Option<usize>> {
+    let mut matches = vec![None; a.len()];
+    match_range(a, b, 0, 0, Some(max_cost as isize), &mut matches);
+
+    matches
+}
+
+// Records the matches between the slices, which start
+// at the specified offsets within the original slices.
+fn match_range<T: PartialEq>(a: &[T], b: &[T], a_offset: usize, b_offset: usize, max_cost: Option<isize>, matches: &mut [Option<usize>]) {
+    // Common prefixes and suffixes match trivially.
+    let prefix = a.iter().zip(b).take_while(|&(x, y)| x == y).count();
+    for index in 0..prefix {
+        matches[a_offset + index] = Some(b_offset + index);
+    }
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let (a_offset, b_offset) = (a_offset + prefix, b_offset + prefix);
+
+    let suffix = a.iter().rev().zip(b.iter().rev()).take_while(|&(x, y)| x == y).count();
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+    for index in 0..suffix {
+        matches[a_offset + a.len() + index] = Some(b_offset + b.len() + index);
+    }
+
+    if a.is_empty() || b.is_empty() {
+        return;
+    }
+
+    // Split the slices where the shortest edit script's forward and reverse
+    // paths meet, unless they've nothing in common (or the split wouldn't
+    // reduce the problem, which doesn't happen once affixes are removed).
+    if let Some((x, y)) = middle(a, b, max_cost) {
+        if (x, y) != (0, 0) && (x, y) != (a.len(), b.len()) {
+            match_range(&a[..x], &b[..y], a_offset, b_offset, max_cost, matches);
+            match_range(&a[x..], &b[y..], a_offset + x, b_offset + y, max_cost, matches);
+        }
+    }
+}
+
+// Searches for the shortest edit script from both ends of the slices at
+// once, returning the point at which the paths meet, if they have any
+// elements in common (and they meet within `max_cost` steps). The paths are
+// tracked by their furthest reaching x coordinate on each diagonal; reverse
+// paths measure it from the end.
+fn middle<T: PartialEq>(a: &[T], b: &[T], max_cost: Option<isize>) -> Option<(usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max_d = (n + m + 1) / 2;
+    let limit = max_cost.map_or(max_d, |max_cost| max_cost.min(max_d));
+    let offset = max_d;
+    let length = (2 * max_d + 2) as usize;
+    let mut forward = vec![-1isize; length];
+    let mut reverse = vec![-1isize; length];
+    forward[(offset + 1) as usize] = 0;
+    reverse[(offset + 1) as usize] = 0;
+
+    // When the difference in length is odd, the paths meet
+    // while extending the forward path, and otherwise, the reverse.
+    let delta = n - m;
+    let front = delta % 2 != 0;
+
+    // Diagonals that have run off the edges are skipped.
+    let (mut forward_start, mut forward_end) = (0, 0);
+    let (mut reverse_start, mut reverse_end) = (0, 0);
+
+    for d in 0..limit {
+        let mut k = -d + forward_start;
+        while k <= d - forward_end {
+            let index = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && forward[index - 1] < forward[index + 1]) {
+                forward[index + 1]
+            } else {
+                forward[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[index] = x;
+
+            if x > n {
+                forward_end += 2;
+            } else if y > m {
+                forward_start += 2;
+            } else if front {
+                let reverse_index = offset + delta - k;
+                if reverse_index >= 0 && reverse_index < length as isize &&
+                   reverse[reverse_index as usize] != -1 &&
+                   x >= n - reverse[reverse_index as usize] {
+                    return Some((x as usize, y as usize));
+                }
+            }
+            k += 2;
+        }
+
+        let mut k = -d + reverse_start;
+        while k <= d - reverse_end {
+            let index = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && reverse[index - 1] < reverse[index + 1]) {
+                reverse[index + 1]
+            } else {
+                reverse[index - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            reverse[index] = x;
+
+            if x > n {
+                reverse_end += 2;
+            } else if y > m {
+                reverse_start += 2;
+            } else if !front {
+                let forward_index = offset + delta - k;
+                if forward_index >= 0 && forward_index < length as isize &&
+                   forward[forward_index as usize] != -1 {
+                    let forward_x = forward[forward_index as usize];
+                    let forward_y = offset + forward_x - forward_index;
+                    if forward_x >= n - x {
+                        return Some((forward_x as usize, forward_y as usize));
+                    }
+                }
+            }
+            k += 2;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{approximate_matching_elements, matching_elements};
+
+    // Checks that the matches are equal elements, in order.
+    fn assert_valid<T: PartialEq>(a: &[T], b: &[T], matches: &[Option<usize>]) {
+        for (index, matched) in matches.iter().enumerate() {
+            if let Some(matched) = *matched {
+                assert!(a[index] == b[matched]);
+            }
+        }
+        let matched: Vec<usize> = matches.iter().filter_map(|m| *m).collect();
+        assert!(matched.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    // The length of the longest common subsequence, computed by dynamic programming.
+    fn lcs_length<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+        let mut lengths = vec![vec![0; b.len() + 1]; a.len() + 1];
+        for i in 0..a.len() {
+            for j in 0..b.len() {
+                lengths[i + 1][j + 1] = if a[i] == b[j] {
+                    lengths[i][j] + 1
+                } else {
+                    lengths[i][j + 1].max(lengths[i + 1][j])
+                };
+            }
+        }
+
+        lengths[a.len()][b.len()]
+    }
+
+    #[test]
+    fn matching_elements_finds_the_longest_common_subsequence() {
+        let a = ["a", "b", "c", "a", "b", "b", "a"];
+        let b = ["c", "b", "a", "b", "a", "c"];
+        let matches = matching_elements(&a, &b);
+
+        assert_eq!(matches.iter().filter(|m| m.is_some()).count(), 4);
+        assert_valid(&a, &b, &matches);
+    }
+
+    #[test]
+    fn matching_elements_finds_the_longest_common_subsequence_of_generated_input() {
+        let mut seed = 7u64;
+        let mut next = move |limit: u64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) % limit
+        };
+
+        for _ in 0..200 {
+            let a: Vec<u64> = (0..next(20)).map(|_| next(4)).collect();
+            let b: Vec<u64> = (0..next(20)).map(|_| next(4)).collect();
+            let matches = matching_elements(&a, &b);
+
+            assert_eq!(matches.iter().filter(|m| m.is_some()).count(), lcs_length(&a, &b));
+            assert_valid(&a, &b, &matches);
+        }
+    }
+
+    #[test]
+    fn approximate_matching_elements_gives_up_on_heavily_changed_sections() {
+        let a: Vec<usize> = (0..100).collect();
+        let mut b: Vec<usize> = (100..200).collect();
+        b.insert(50, 50);
+
+        assert_eq!(matching_elements(&a, &b)[50], Some(50));
+        let matches = approximate_matching_elements(&a, &b, 10);
+        assert!(matches.iter().all(Option::is_none));
+
+        // Sections differing by fewer elements are still matched.
+        let mut c = a.clone();
+        c[50] = 1000;
+        let matches = approximate_matching_elements(&a, &c, 10);
+        assert_eq!(matches.iter().filter(|m| m.is_some()).count(), 99);
+    }
+
+    #[test]
+    fn matching_elements_handles_empty_slices() {
+        assert_eq!(matching_elements::<&str>(&[], &["a"]), vec![]);
+        assert_eq!(matching_elements(&["a"], &[]), vec![None]);
+        assert_eq!(matching_elements(&["a", "b"], &["a", "b"]), vec![Some(0), Some(1)]);
+    }
+}
@@ -0,0 +1,139 @@
+use std::cmp::Reverse;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The result of a successful fuzzy match.
+///
+/// `offsets` holds the grapheme cluster-based offsets of the matched
+/// characters within the candidate, suitable for highlighting them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: usize,
+    pub offsets: Vec<usize>,
+}
+
+/// Matches the candidate against the query as a case-insensitive subsequence,
+/// returning None if the candidate doesn't contain all of the query's
+/// characters, in order. Runs of consecutive matches, and matches at the
+/// start of words and path components, score higher. An empty query matches
+/// every candidate, with a score of zero.
+///
+/// # Examples
+///
+/// ```
+/// use scribe::util::fuzzy_match;
+///
+/// let result = fuzzy_match("bfr", "buffer").unwrap();
+/// assert_eq!(result.offsets, vec![0, 2, 5]);
+///
+/// assert!(fuzzy_match("rfb", "buffer").is_none());
+/// ```
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let query: Vec<String> = query.graphemes(true).map(|g| g.to_lowercase()).collect();
+    let mut remaining = query.iter().peekable();
+    let mut score = 0;
+    let mut offsets = Vec::new();
+    let mut previous: Option<&str> = None;
+    let mut previous_matched = false;
+
+    for (offset, grapheme) in candidate.graphemes(true).enumerate() {
+        let next = match remaining.peek() {
+            Some(next) => *next,
+            None => break,
+        };
+
+        if grapheme.to_lowercase() == *next {
+            remaining.next();
+            offsets.push(offset);
+            score += 1;
+
+            // Reward runs of matching characters.
+            if previous_matched {
+                score += 5;
+            }
+
+            // Reward matches at the start of a word or path component.
+            match previous {
+                None | Some("/") | Some("_") | Some("-") | Some(".") | Some(" ") => score += 10,
+                _ => (),
+            }
+
+            previous_matched = true;
+        } else {
+            previous_matched = false;
+        }
+
+        previous = Some(grapheme);
+    }
+
+    if remaining.peek().is_none() {
+        Some(FuzzyMatch{ score, offsets })
+    } else {
+        None
+    }
+}
+
+/// Fuzzy matches a set of keyed candidates against the query, returning the
+/// keys of those that match, paired with their results, best match first.
+/// Candidates with equal scores retain their original relative order.
+///
+/// # Examples
+///
+/// ```
+/// use scribe::util::fuzzy_rank;
+///
+/// let candidates = vec![(0, "comodo.rs"), (1, "lib.rs"), (2, "mod.rs")];
+/// let keys: Vec<usize> = fuzzy_rank("mod", candidates)
+///     .into_iter()
+///     .map(|(key, _)| key)
+///     .collect();
+///
+/// assert_eq!(keys, vec![2, 0]);
+/// ```
+pub fn fuzzy_rank<I, K, S>(query: &str, candidates: I) -> Vec<(K, FuzzyMatch)>
+    where I: IntoIterator<Item=(K, S)>, S: AsRef<str>
+{
+    let mut matches: Vec<(K, FuzzyMatch)> = candidates.into_iter().filter_map(|(key, candidate)| {
+        fuzzy_match(query, candidate.as_ref()).map(|result| (key, result))
+    }).collect();
+    matches.sort_by_key(|m| Reverse(m.1.score));
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fuzzy_match, fuzzy_rank};
+
+    #[test]
+    fn fuzzy_match_prefers_matches_at_the_start_of_components() {
+        let component_match = fuzzy_match("mod", "src/mod.rs").unwrap();
+        let inner_match = fuzzy_match("mod", "src/comodo.rs").unwrap();
+        assert!(component_match.score > inner_match.score);
+    }
+
+    #[test]
+    fn fuzzy_match_is_case_insensitive() {
+        assert!(fuzzy_match("READ", "readme.md").is_some());
+        assert!(fuzzy_match("read", "README.md").is_some());
+    }
+
+    #[test]
+    fn fuzzy_match_uses_grapheme_cluster_offsets() {
+        let result = fuzzy_match("x", "नीx").unwrap();
+        assert_eq!(result.offsets, vec![1]);
+    }
+
+    #[test]
+    fn fuzzy_match_matches_everything_with_an_empty_query() {
+        let result = fuzzy_match("", "scribe").unwrap();
+        assert_eq!(result.score, 0);
+        assert!(result.offsets.is_empty());
+    }
+
+    #[test]
+    fn fuzzy_rank_preserves_order_of_equal_scores() {
+        let ranked = fuzzy_rank("", vec![("first", "a"), ("second", "b")]);
+        assert_eq!(ranked[0].0, "first");
+        assert_eq!(ranked[1].0, "second");
+    }
+}
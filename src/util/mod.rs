@@ -1,3 +1,18 @@
+mod budget;
+mod case;
+mod diff;
+mod fuzzy;
 mod line_iterator;
+#[cfg(feature = "fs")]
+mod shell;
+mod transform;
 
+pub use self::budget::Budget;
+pub use self::case::mirror_case;
+pub use self::fuzzy::{fuzzy_match, fuzzy_rank, FuzzyMatch};
 pub use self::line_iterator::LineIterator;
+pub use self::transform::{expand_tabs, strip_control_characters};
+pub(crate) use self::budget::BudgetMeter;
+pub(crate) use self::diff::{approximate_matching_elements, matching_elements};
+#[cfg(feature = "fs")]
+pub(crate) use self::shell::shell_command;
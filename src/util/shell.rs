@@ -0,0 +1,17 @@
+use std::process::Command;
+
+// Builds a command that runs the provided command line using the platform's shell.
+pub(crate) fn shell_command(command_line: &str) -> Command {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+    command.arg(command_line);
+
+    command
+}
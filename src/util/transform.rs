@@ -0,0 +1,41 @@
+/// Returns an insert transform (see `Buffer::add_insert_transform`)
+/// that replaces each tab with `width` spaces.
+///
+/// # Examples
+///
+/// ```
+/// use scribe::util::expand_tabs;
+///
+/// let transform = expand_tabs(2);
+/// assert_eq!(transform("\tscribe\t"), "  scribe  ");
+/// ```
+pub fn expand_tabs(width: usize) -> impl Fn(&str) -> String {
+    let spaces = " ".repeat(width);
+
+    move |data| data.replace('\t', &spaces)
+}
+
+/// Removes control characters (e.g. those pasted from terminal output) other
+/// than newlines and tabs. Suitable for use as an insert transform (see
+/// `Buffer::add_insert_transform`).
+///
+/// # Examples
+///
+/// ```
+/// use scribe::util::strip_control_characters;
+///
+/// assert_eq!(strip_control_characters("\u{1b}[1mscribe\r\n\tlibrary\u{7}"), "[1mscribe\n\tlibrary");
+/// ```
+pub fn strip_control_characters(data: &str) -> String {
+    data.chars().filter(|&c| c == '\n' || c == '\t' || !c.is_control()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::strip_control_characters;
+
+    #[test]
+    fn strip_control_characters_leaves_other_text_alone() {
+        assert_eq!(strip_control_characters("scribé नी\u{200B}"), "scribé नी\u{200B}");
+    }
+}
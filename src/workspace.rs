@@ -1,36 +1,127 @@
 //! Buffer and working directory management.
 
-use buffer::Buffer;
+use buffer::{self, Buffer, BufferPreferences, Distance, IndentRules, Position, Range};
+use command::{CommandStatus, RunningCommand};
+use event::{Subscribers, WorkspaceEvent};
 use errors::*;
+use snapshots::SnapshotStore;
+use syntax_detector::{self, SyntaxDetector};
+use syntax_warm_up;
+use task::{TaskQueue, WorkspaceTask};
+use tags::{self, TagAddress, TagIndex};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::io;
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
 use syntect::parsing::{SyntaxDefinition, SyntaxSet};
+use util::{fuzzy_rank, Budget, BudgetMeter, FuzzyMatch};
+
+/// A buffer that couldn't be saved by `Workspace::save_all`, or whose
+/// snapshot couldn't be stored by `Workspace::store_snapshots`.
+#[derive(Debug)]
+pub struct SaveFailure {
+    pub buffer_id: Option<usize>,
+    pub path: Option<PathBuf>,
+    pub error: io::Error,
+}
 
 /// An owned collection of buffers and associated path,
 /// representing a running editor environment.
+///
+/// Buffers opened by the workspace are configured using its
+/// `buffer_preferences`, overridden by any EditorConfig properties
+/// that apply to them (see `BufferPreferences::for_path`).
+///
+/// If the workspace is configured with a `snapshot_store`, a snapshot of
+/// each buffer is stored whenever it's saved, as well as periodically (see
+/// `store_snapshots`). Buffers saved by the workspace (e.g. by `save_all`)
+/// are snapshotted immediately; those saved directly (using `Buffer::save`)
+/// queue a `WorkspaceTask::StoreSnapshot` task.
+///
+/// If the workspace is configured with a limit on `loaded_buffers`, the least
+/// recently used buffers beyond it are unloaded, keeping only their path and
+/// cursor position, whenever the current buffer is accessed. Only unmodified,
+/// writable buffers with paths are unloaded. Unloaded buffers are read from
+/// their files again when next accessed as the current buffer; if that fails
+/// (e.g. because the file has been removed), the buffer is left empty.
+/// Unloading a buffer discards its undo history.
+///
+/// Buffers are given a syntax definition from the workspace's `syntax_set`
+/// when they're added, based on their file extension or name, or their first
+/// line (e.g. a shebang). Applications can override this by registering their
+/// own detectors (see `register_syntax_detector`). Buffers without
+/// `indent_rules` are then given the workspace's rules for their syntax.
+///
+/// Text copied or killed from its buffers (see `copy_range` and
+/// `kill_range`) is kept in a history shared between them, holding up to
+/// `clipboard_history_limit` entries, which can be pasted from (see
+/// `paste_from_history`).
+///
+/// Subscribers registered using `subscribe` are notified as buffers are
+/// opened, modified, saved, and closed, providing a single integration point
+/// for status bars, file trees, and the like.
 pub struct Workspace {
     pub path: PathBuf,
+    given_path: (PathBuf, PathBuf),
     buffers: Vec<Buffer>,
     next_buffer_id: usize,
     current_buffer_index: Option<usize>,
     pub syntax_set: SyntaxSet,
+    pub buffer_preferences: BufferPreferences,
+    pub indent_rules: HashMap<String, IndentRules>,
+    pub snapshot_store: Option<SnapshotStore>,
+    pub loaded_buffers: Option<usize>,
+    pub clipboard_history_limit: usize,
+    running_commands: Vec<RunningCommand>,
+    unloaded_buffers: HashMap<usize, Position>,
+    recently_used: Vec<usize>,
+    syntax_detectors: Vec<Box<dyn SyntaxDetector>>,
+    subscribers: Rc<Subscribers>,
+    tasks: Rc<TaskQueue>,
+    externally_modified: HashSet<usize>,
+    clipboard_history: Vec<String>,
+    pending_syntaxes: VecDeque<String>,
+    warmed_syntaxes: HashSet<String>,
 }
 
 impl Workspace {
-    /// Creates a new empty workspace for the specified path.
+    /// Creates a new empty workspace for the specified path, queueing the
+    /// syntax definitions for the files at its top level to be warmed up
+    /// (see `warm_up_syntaxes`).
     pub fn new(path: &Path) -> io::Result<Workspace> {
         // Set up syntax parsers.
-        let mut syntax_set = SyntaxSet::load_defaults_newlines();
-        syntax_set.link_syntaxes();
+        let syntax_set = syntax_detector::default_syntax_set();
+
+        let given_path = (buffer::absolute_path(path)?, path.canonicalize()?);
 
-        Ok(Workspace{
-            path: try!(path.canonicalize()),
+        let mut workspace = Workspace{
+            path: given_path.1.clone(),
+            given_path,
             buffers: Vec::new(),
             next_buffer_id: 0,
             current_buffer_index: None,
             syntax_set,
-        })
+            buffer_preferences: BufferPreferences::default(),
+            indent_rules: IndentRules::defaults(),
+            snapshot_store: None,
+            loaded_buffers: None,
+            clipboard_history_limit: 50,
+            running_commands: Vec::new(),
+            unloaded_buffers: HashMap::new(),
+            recently_used: Vec::new(),
+            syntax_detectors: Vec::new(),
+            subscribers: Rc::new(Subscribers::default()),
+            tasks: Rc::new(TaskQueue::default()),
+            externally_modified: HashSet::new(),
+            clipboard_history: Vec::new(),
+            pending_syntaxes: VecDeque::new(),
+            warmed_syntaxes: HashSet::new(),
+        };
+        workspace.queue_directory_syntaxes();
+
+        Ok(workspace)
     }
 
     /// Adds a buffer to the workspace, *inserting it after the
@@ -57,7 +148,22 @@ impl Workspace {
     /// ```
     pub fn add_buffer(&mut self, mut buf: Buffer) {
         // Set a unique buffer ID.
-        buf.id = Some(self.next_buffer_id);
+        let id = self.next_buffer_id;
+        buf.id = Some(id);
+
+        // Report the buffer's edits to the workspace's subscribers,
+        // and queue the work that follows them.
+        let subscribers = self.subscribers.clone();
+        let tasks = self.tasks.clone();
+        buf.on_change(move |_| {
+            subscribers.notify(WorkspaceEvent::Modified(id));
+            tasks.push(WorkspaceTask::LexBuffer(id));
+            tasks.push(WorkspaceTask::PerformMaintenance(id));
+            tasks.push(WorkspaceTask::StoreSnapshots);
+        });
+        let tasks = self.tasks.clone();
+        buf.save_callback = Some(Box::new(move || tasks.push(WorkspaceTask::StoreSnapshot(id))));
+        self.tasks.push(WorkspaceTask::LexBuffer(id));
 
         // Increment the ID for the next time.
         self.next_buffer_id += 1;
@@ -70,16 +176,32 @@ impl Workspace {
             buf.syntax_definition = self.find_syntax_definition(&buf);
         }
 
+        // Use the indent rules for the buffer's syntax, if it doesn't have any.
+        if buf.indent_rules.is_none() {
+            buf.indent_rules = buf.syntax_definition.as_ref()
+                .and_then(|definition| self.indent_rules.get(&definition.name).cloned());
+        }
+
+        // Warm up the buffer's syntax ahead of any others.
+        if let Some(ref definition) = buf.syntax_definition {
+            if !self.warmed_syntaxes.contains(&definition.name) {
+                self.pending_syntaxes.retain(|name| *name != definition.name);
+                self.pending_syntaxes.push_front(definition.name.clone());
+                self.tasks.push(WorkspaceTask::WarmUpSyntaxes);
+            }
+        }
+
         // Insert the buffer and select it.
         self.buffers.insert(target_index, buf);
         self.current_buffer_index = Some(target_index);
+        self.subscribers.notify(WorkspaceEvent::Opened(id));
     }
 
     /// Opens a buffer at the specified path, *inserting
     /// it after the current buffer*, and selects it.
-    /// The path is converted to its canonical, absolute equivalent;
-    /// if a buffer with the specified path already exists,
-    /// it is selected, rather than opening a duplicate buffer.
+    /// If a buffer for the same file already exists (comparing
+    /// canonical paths; see `Buffer::canonical_path`), it is
+    /// selected, rather than opening a duplicate buffer.
     /// Any errors encountered while opening the buffer are returned.
     ///
     /// # Examples
@@ -106,32 +228,110 @@ impl Workspace {
     pub fn open_buffer_with_opts(
         &mut self, path: &Path, opts: &mut OpenOptions,
     ) -> io::Result<()> {
-        if self.contains_buffer_with_path(path) {
-            // We already have this buffer in the workspace.
-            // Loop through the buffers until it's selected.
-            let canonical_path = path.canonicalize().unwrap();
-            loop {
-                if let Some(buffer) = self.current_buffer() {
-                    if let Some(ref current_path) = buffer.path {
-                        if *current_path == canonical_path {
-                            break;
-                        }
-                    }
-                }
-
-                self.next_buffer()
-            }
+        if let Some(index) = self.buffer_index_for_path(path) {
+            // We already have this buffer in the workspace; select it.
+            self.current_buffer_index = Some(index);
 
             // Not going to run into IO errors if we're not opening a buffer.
             Ok(())
         } else {
-            let buffer = try!(Buffer::from_file_with_opts(path, opts));
+            let mut buffer = try!(Buffer::from_file_with_opts(path, opts));
+            if let Some(ref path) = buffer.path.clone() {
+                self.buffer_preferences.for_path(path).apply(&mut buffer);
+            }
             self.add_buffer(buffer);
 
             Ok(())
         }
     }
 
+    /// Opens (or selects) a buffer for the specified path, as with
+    /// `open_buffer`, and moves its cursor to the specified position. Relative
+    /// paths are resolved against the workspace path, making this suitable for
+    /// jumping to locations reported by tools run in the workspace (see
+    /// `Buffer::jump_targets`). If the position is out of bounds, the cursor
+    /// is moved to the start of its line, if that exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Workspace;
+    /// use scribe::buffer::Position;
+    /// use std::path::Path;
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// workspace.open_at(Path::new("file"), &Position{ line: 0, offset: 3 }).unwrap();
+    ///
+    /// let buffer = workspace.current_buffer().unwrap();
+    /// assert_eq!(buffer.data(), "it works!\n");
+    /// assert_eq!(*buffer.cursor, Position{ line: 0, offset: 3 });
+    /// ```
+    pub fn open_at(&mut self, path: &Path, position: &Position) -> io::Result<()> {
+        let path = self.path.join(path);
+        self.open_buffer(&path)?;
+
+        if let Some(buffer) = self.current_buffer() {
+            if !buffer.cursor.move_to(*position) {
+                buffer.cursor.move_to(Position{ line: position.line, offset: 0 });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Looks up the identifier at the current buffer's cursor in the tags
+    /// index, opening the file containing its first definition with the
+    /// cursor at the start of the defining line (see `open_at`). Returns
+    /// whether or not a definition was found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::{Buffer, TagIndex, Workspace};
+    /// use scribe::buffer::Position;
+    /// use std::env;
+    /// use std::fs;
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// let tags_path = env::temp_dir().join("scribe_open_tag_example");
+    /// fs::write(&tags_path, format!("works\t{}\t/^it works!$/\n", fs::canonicalize("tests/sample/file").unwrap().display())).unwrap();
+    /// let tags = TagIndex::from_file(&tags_path).unwrap();
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("works");
+    /// workspace.add_buffer(buffer);
+    ///
+    /// assert!(workspace.open_tag_at_cursor(&tags).unwrap());
+    /// assert_eq!(workspace.current_buffer_path(), Some(PathBuf::from("file")));
+    /// # fs::remove_file(&tags_path).unwrap();
+    /// ```
+    pub fn open_tag_at_cursor(&mut self, tags: &TagIndex) -> io::Result<bool> {
+        let symbol = match self.current_buffer().and_then(|buffer| tags::symbol_at_cursor(buffer)) {
+            Some(symbol) => symbol,
+            None => return Ok(false),
+        };
+        let tag = match tags.find(&symbol).first() {
+            Some(&tag) => tag.clone(),
+            None => return Ok(false),
+        };
+
+        let line = match tag.address {
+            TagAddress::Line(line) => line,
+            TagAddress::Pattern(_) => 0,
+        };
+        self.open_at(&tag.path, &Position{ line, offset: 0 })?;
+
+        // Patterns can only be resolved once the file has been opened.
+        if let Some(buffer) = self.current_buffer() {
+            if let Some(position) = tag.position_in(buffer) {
+                buffer.cursor.move_to(position);
+            }
+        }
+
+        Ok(true)
+    }
+
     /// Returns a mutable reference to the currently
     /// selected buffer, unless the workspace is empty.
     ///
@@ -157,24 +357,53 @@ impl Workspace {
     /// let buffer_reference = workspace.current_buffer().unwrap();
     /// ```
     pub fn current_buffer(&mut self) -> Option<&mut Buffer> {
-        match self.current_buffer_index {
-            Some(index) => Some(&mut self.buffers[index]),
-            None => None,
+        let index = self.current_buffer_index?;
+        if let Some(id) = self.buffers[index].id {
+            self.load_buffer(index);
+            self.recently_used.retain(|&used_id| used_id != id);
+            self.recently_used.push(id);
+            self.unload_buffers();
         }
+
+        Some(&mut self.buffers[index])
     }
 
-    /// Returns a reference to the current buffer's path.
+    /// Whether or not the buffer with the specified ID has been
+    /// unloaded to respect the workspace's `loaded_buffers` limit.
     ///
-    /// If the path can be represented relative to the workspace path,
-    /// a relative path will be returned. Otherwise, the buffer path
-    /// is returned as-is.
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Workspace;
+    /// use std::path::Path;
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// workspace.loaded_buffers = Some(1);
+    /// workspace.open_buffer(Path::new("tests/sample/file")).unwrap();
+    /// workspace.current_buffer();
+    /// workspace.open_buffer(Path::new("Cargo.toml")).unwrap();
+    /// workspace.current_buffer();
+    /// assert!(workspace.is_unloaded(0));
+    ///
+    /// // Accessing the buffer loads it again.
+    /// workspace.select_buffer(0);
+    /// assert_eq!(workspace.current_buffer().unwrap().data(), "it works!\n");
+    /// assert!(!workspace.is_unloaded(0));
+    /// assert!(workspace.is_unloaded(1));
+    /// ```
+    pub fn is_unloaded(&self, id: usize) -> bool {
+        self.unloaded_buffers.contains_key(&id)
+    }
+
+    /// Returns the current buffer's path, relative to the workspace
+    /// path where possible (see `relative_path`).
     ///
     /// # Examples
     ///
     /// ```
     /// use scribe::Buffer;
     /// use scribe::Workspace;
-    /// use std::path::Path;
+    /// use std::path::{Path, PathBuf};
     ///
     /// // Set up the paths we'll use.
     /// let directory_path = Path::new("tests/sample");
@@ -187,15 +416,136 @@ impl Workspace {
     /// let buf = Buffer::from_file(file_path).unwrap();
     /// workspace.add_buffer(buf);
     ///
-    /// assert_eq!(workspace.current_buffer_path(), Some(Path::new("file")));
+    /// assert_eq!(workspace.current_buffer_path(), Some(PathBuf::from("file")));
     /// ```
-    pub fn current_buffer_path(&self) -> Option<&Path> {
+    pub fn current_buffer_path(&self) -> Option<PathBuf> {
         self.current_buffer_index
-          .and_then(|i| self.buffers[i].path.as_ref()
-              .and_then(|path| path.strip_prefix(&self.path).ok()
-                  .or_else(|| Some(path))
-              )
-          )
+          .and_then(|i| self.relative_path(&self.buffers[i]))
+    }
+
+    /// Returns the specified buffer's path.
+    ///
+    /// If the path can be represented relative to the workspace path,
+    /// a relative path will be returned. Otherwise, the buffer path
+    /// is returned as-is. The buffer doesn't need to belong to the workspace.
+    ///
+    /// Buffer paths are compared against the workspace path both as it was
+    /// given and in its canonical form, so that paths going through the same
+    /// symbolic links as the workspace path are still made relative to it.
+    /// Failing that, the buffer's canonical path is tried (see
+    /// `Buffer::canonical_path`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::Workspace;
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// let workspace = Workspace::new(Path::new("tests")).unwrap();
+    /// let buffer = Buffer::from_file(Path::new("tests/sample/file")).unwrap();
+    ///
+    /// assert_eq!(workspace.relative_path(&buffer), Some(PathBuf::from("sample/file")));
+    /// ```
+    pub fn relative_path(&self, buffer: &Buffer) -> Option<PathBuf> {
+        let path = buffer.path.as_ref()?;
+
+        // The given path is only used while it still resolves to the
+        // workspace path, which may have been changed since.
+        let (ref given_path, ref resolved_path) = self.given_path;
+        let mut roots = vec![self.path.as_path()];
+        if *resolved_path == self.path {
+            roots.insert(0, given_path.as_path());
+        }
+        let strip = |path: &Path| {
+            roots.iter()
+                .filter_map(|root| path.strip_prefix(root).ok())
+                .map(Path::to_path_buf)
+                .next()
+        };
+
+        strip(path)
+            .or_else(|| buffer.canonical_path().and_then(|path| strip(&path)))
+            .or_else(|| Some(path.clone()))
+    }
+
+    /// Returns the shortest trailing portion of the buffer's relative path
+    /// that distinguishes it from every other buffer in the workspace.
+    ///
+    /// Buffers with a unique file name are represented by that name alone;
+    /// buffers sharing a file name with another are prefixed with as many
+    /// parent directories as are needed to tell them apart.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::Workspace;
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// // Add a buffer to the workspace.
+    /// let mut workspace = Workspace::new(Path::new("tests")).unwrap();
+    /// let mut buffer = Buffer::new();
+    /// buffer.path = Some(workspace.path.join("src/buffer/mod.rs"));
+    /// workspace.add_buffer(buffer);
+    ///
+    /// // Build another buffer with the same file name.
+    /// let mut other_buffer = Buffer::new();
+    /// other_buffer.path = Some(workspace.path.join("src/workspace/mod.rs"));
+    ///
+    /// assert_eq!(
+    ///     workspace.short_path(&other_buffer),
+    ///     Some(PathBuf::from("workspace/mod.rs"))
+    /// );
+    /// ```
+    pub fn short_path(&self, buffer: &Buffer) -> Option<PathBuf> {
+        let path = self.relative_path(buffer)?;
+        let components: Vec<_> = path.components().collect();
+
+        // Relative paths of every other buffer, used for comparison.
+        let other_paths: Vec<PathBuf> = self.buffers.iter()
+            .filter(|other| other.path != buffer.path)
+            .filter_map(|other| self.relative_path(other))
+            .collect();
+
+        // Grow the suffix one component at a time until it's unique.
+        for length in 1..components.len() {
+            let suffix: PathBuf = components[components.len() - length..]
+                .iter()
+                .collect();
+
+            if !other_paths.iter().any(|other| other.ends_with(&suffix)) {
+                return Some(suffix);
+            }
+        }
+
+        Some(path)
+    }
+
+    /// Updates the workspace path, against which buffer paths are made
+    /// relative. The path is converted to its canonical, absolute equivalent,
+    /// though buffer paths going through its symbolic links are still made
+    /// relative to it (see `relative_path`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::Workspace;
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// workspace.open_buffer(Path::new("tests/sample/file")).unwrap();
+    /// assert_eq!(workspace.current_buffer_path(), Some(PathBuf::from("file")));
+    ///
+    /// workspace.set_path(Path::new("tests")).unwrap();
+    /// assert_eq!(workspace.current_buffer_path(), Some(PathBuf::from("sample/file")));
+    /// ```
+    pub fn set_path(&mut self, path: &Path) -> io::Result<()> {
+        self.given_path = (buffer::absolute_path(path)?, path.canonicalize()?);
+        self.path = self.given_path.1.clone();
+
+        Ok(())
     }
 
     /// Removes the currently selected buffer from the collection.
@@ -224,7 +574,15 @@ impl Workspace {
     /// ```
     pub fn close_current_buffer(&mut self) {
         if let Some(index) = self.current_buffer_index {
-            self.buffers.remove(index);
+            if let Some(id) = self.buffers.remove(index).id {
+                self.unloaded_buffers.remove(&id);
+                self.externally_modified.remove(&id);
+                self.recently_used.retain(|&used_id| used_id != id);
+                self.tasks.retain(|&task| {
+                    task != WorkspaceTask::LexBuffer(id) && task != WorkspaceTask::PerformMaintenance(id)
+                });
+                self.subscribers.notify(WorkspaceEvent::Closed(id));
+            }
 
             if self.buffers.is_empty() {
                 self.current_buffer_index = None;
@@ -310,8 +668,285 @@ impl Workspace {
         }
     }
 
+    /// Selects the buffer with the specified ID, returning
+    /// whether or not it was found in the workspace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Buffer;
+    /// use scribe::Workspace;
+    /// use std::path::Path;
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// workspace.add_buffer(Buffer::new());
+    /// workspace.add_buffer(Buffer::new());
+    ///
+    /// assert!(workspace.select_buffer(0));
+    /// assert_eq!(workspace.current_buffer().unwrap().id, Some(0));
+    /// assert!(!workspace.select_buffer(2));
+    /// ```
+    pub fn select_buffer(&mut self, id: usize) -> bool {
+        match self.buffers.iter().position(|buffer| buffer.id == Some(id)) {
+            Some(index) => {
+                self.current_buffer_index = Some(index);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Fuzzy matches the short paths (see `short_path`) of the workspace's
+    /// buffers against the query (see `util::fuzzy_match`), returning the IDs
+    /// of those that match alongside their results, best match first. Buffers
+    /// without paths are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Workspace;
+    /// use std::path::Path;
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// workspace.open_buffer(Path::new("tests/sample/file")).unwrap();
+    ///
+    /// let results = workspace.fuzzy_search_buffers("fl");
+    /// assert_eq!(results[0].0, 0);
+    /// assert_eq!(results[0].1.offsets, vec![0, 2]);
+    /// ```
+    pub fn fuzzy_search_buffers(&self, query: &str) -> Vec<(usize, FuzzyMatch)> {
+        let candidates = self.buffers.iter().filter_map(|buffer| {
+            match (buffer.id, self.short_path(buffer)) {
+                (Some(id), Some(path)) => Some((id, path.to_string_lossy().into_owned())),
+                _ => None,
+            }
+        });
+
+        fuzzy_rank(query, candidates)
+    }
+
+    /// Saves every modified buffer atomically (see `Buffer::save`),
+    /// continuing past any buffers that can't be saved, and returning their
+    /// errors. Buffers without a path, and `read_only` buffers, are skipped.
+    /// Unless `force` is set, nothing is saved if any of the modified
+    /// buffers' files have been changed on disk since they were loaded or
+    /// last saved; a `ConflictingChanges` error listing them is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::{Buffer, Workspace};
+    /// use std::env;
+    /// use std::fs;
+    /// use std::path::Path;
+    ///
+    /// let path = env::temp_dir().join("scribe_save_all_example");
+    /// fs::write(&path, "scribe").unwrap();
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// workspace.open_buffer(&path).unwrap();
+    /// workspace.current_buffer().unwrap().insert("library ");
+    ///
+    /// let failures = workspace.save_all(false).unwrap();
+    /// assert!(failures.is_empty());
+    /// assert_eq!(fs::read_to_string(&path).unwrap(), "library scribe");
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save_all(&mut self, force: bool) -> Result<Vec<SaveFailure>> {
+        if !force {
+            let conflicts: Vec<PathBuf> = self.buffers
+                .iter()
+                .filter(|buffer| buffer.modified() && !buffer.read_only && buffer.outdated())
+                .filter_map(|buffer| buffer.path.clone())
+                .collect();
+
+            if !conflicts.is_empty() {
+                return Err(ErrorKind::ConflictingChanges(conflicts).into());
+            }
+        }
+
+        let mut failures = Vec::new();
+        for index in 0..self.buffers.len() {
+            let buffer = &mut self.buffers[index];
+            if !buffer.modified() || buffer.path.is_none() || buffer.read_only { continue; }
+
+            if let Err(error) = buffer.save() {
+                failures.push(SaveFailure{
+                    buffer_id: buffer.id,
+                    path: buffer.path.clone(),
+                    error,
+                });
+            } else {
+                if let Some(id) = buffer.id {
+                    self.subscribers.notify(WorkspaceEvent::Saved(id));
+                }
+                self.snapshot_saved_buffer(index);
+            }
+        }
+
+        Ok(failures)
+    }
+
+    /// Stores a snapshot of each buffer with a path, if its last snapshot
+    /// is older than the snapshot store's `interval`, and its content has
+    /// changed since. Intended to be called periodically, so that unsaved
+    /// changes are also recoverable. Returns the number of snapshots stored
+    /// (zero if the workspace doesn't have a snapshot store), along with the
+    /// buffers whose snapshots couldn't be stored; a failure doesn't prevent
+    /// the remaining buffers from being snapshotted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::{Buffer, SnapshotStore, Workspace};
+    /// use std::env;
+    /// use std::fs;
+    /// use std::path::Path;
+    ///
+    /// let directory = env::temp_dir().join("scribe_store_snapshots_example");
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// workspace.snapshot_store = Some(SnapshotStore::new(&directory));
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.path = Some(env::temp_dir().join("scribe_store_snapshots_file"));
+    /// buffer.insert("scribe");
+    /// workspace.add_buffer(buffer);
+    ///
+    /// let (stored, failures) = workspace.store_snapshots();
+    /// assert_eq!(stored, 1);
+    /// assert!(failures.is_empty());
+    ///
+    /// // The interval hasn't elapsed since the last snapshot.
+    /// workspace.current_buffer().unwrap().insert("library ");
+    /// assert_eq!(workspace.store_snapshots().0, 0);
+    /// # fs::remove_dir_all(&directory).unwrap();
+    /// ```
+    pub fn store_snapshots(&mut self) -> (usize, Vec<SaveFailure>) {
+        let store = match self.snapshot_store {
+            Some(ref store) => store,
+            None => return (0, Vec::new()),
+        };
+
+        let mut stored = 0;
+        let mut failures = Vec::new();
+        for buffer in &self.buffers {
+            if buffer.id.is_some_and(|id| self.unloaded_buffers.contains_key(&id)) {
+                continue;
+            }
+
+            let path = match buffer.canonical_path() {
+                Some(path) => path,
+                None => continue,
+            };
+            let result = store.latest(&path).and_then(|latest| {
+                let due = latest.is_none_or(|latest| {
+                    SystemTime::now().duration_since(latest).is_ok_and(|age| age >= store.interval)
+                });
+
+                if due { store_snapshot(store, buffer) } else { Ok(false) }
+            });
+
+            match result {
+                Ok(true) => stored += 1,
+                Ok(false) => (),
+                Err(error) => failures.push(SaveFailure{
+                    buffer_id: buffer.id,
+                    path: buffer.path.clone(),
+                    error,
+                }),
+            }
+        }
+
+        (stored, failures)
+    }
+
+    // Stores a snapshot of the buffer, which has just been saved, if the
+    // workspace has a snapshot store. The snapshot queued by the save is
+    // left to be retried by run_tasks if it can't be stored.
+    fn snapshot_saved_buffer(&mut self, index: usize) {
+        let (store, buffer) = match (self.snapshot_store.as_ref(), self.buffers.get(index)) {
+            (Some(store), Some(buffer)) => (store, buffer),
+            _ => return,
+        };
+
+        if store_snapshot(store, buffer).is_ok() {
+            let id = buffer.id;
+            self.tasks.retain(|&task| Some(task) != id.map(WorkspaceTask::StoreSnapshot));
+        }
+    }
+
+    /// Runs `command` using the platform's shell, in the workspace's
+    /// directory, adding a read-only buffer for its output and selecting it.
+    /// Returns the new buffer's ID. Output from the command's standard output
+    /// and error streams is added to the buffer as it's produced, whenever
+    /// `update_command_output` is called. The buffer is never considered
+    /// modified, and so isn't saved by `save_all`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::{CommandStatus, Workspace};
+    /// use std::path::Path;
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// let buffer_id = workspace.run_command("echo scribe").unwrap();
+    ///
+    /// while workspace.command_status(buffer_id) == Some(CommandStatus::Running) {
+    ///     workspace.update_command_output();
+    /// }
+    ///
+    /// assert_eq!(workspace.current_buffer().unwrap().data().trim(), "scribe");
+    /// match workspace.command_status(buffer_id) {
+    ///     Some(CommandStatus::Finished(status)) => assert!(status.success()),
+    ///     _ => panic!("expected the command to have finished"),
+    /// }
+    /// ```
+    pub fn run_command(&mut self, command: &str) -> io::Result<usize> {
+        let buffer_id = self.next_buffer_id;
+        let running_command = RunningCommand::spawn(command, &self.path, buffer_id)?;
+
+        self.add_buffer(Buffer::for_output());
+        self.running_commands.push(running_command);
+
+        Ok(buffer_id)
+    }
+
+    /// Adds any output produced by commands started using `run_command` to
+    /// their buffers, returning whether or not any buffers were changed.
+    /// Commands whose buffers have been closed are stopped.
+    pub fn update_command_output(&mut self) -> bool {
+        let mut updated = false;
+        let buffers = &mut self.buffers;
+
+        self.running_commands.retain(|command| {
+            buffers.iter().any(|buffer| buffer.id == Some(command.buffer_id))
+        });
+
+        for command in &mut self.running_commands {
+            let output = command.read_output();
+            if output.is_empty() { continue; }
+
+            if let Some(buffer) = buffers.iter_mut().find(|buffer| buffer.id == Some(command.buffer_id)) {
+                buffer.append_unrecorded(&output);
+                updated = true;
+            }
+        }
+
+        updated
+    }
+
+    /// The status of the command whose output is shown in the specified
+    /// buffer, as of the last call to `update_command_output`. Returns None
+    /// if the buffer wasn't created by `run_command`, or has been closed.
+    pub fn command_status(&self, buffer_id: usize) -> Option<CommandStatus> {
+        self.running_commands
+            .iter()
+            .find(|command| command.buffer_id == buffer_id)
+            .map(RunningCommand::status)
+    }
+
     /// Whether or not the workspace contains a buffer with the specified path.
-    /// The path is converted to its canonical, absolute equivalent before comparison.
+    /// Paths are compared in their canonical form (see `Buffer::canonical_path`).
     ///
     /// # Examples
     ///
@@ -334,16 +969,16 @@ impl Workspace {
     /// assert!(workspace.contains_buffer_with_path(&file_path));
     /// ```
     pub fn contains_buffer_with_path(&self, path: &Path) -> bool {
-        if let Ok(ref canonical_path) = path.canonicalize() {
-            self.buffers.iter().any(|buffer| {
-                match buffer.path {
-                    Some(ref buffer_path) => buffer_path == canonical_path,
-                    None => false,
-                }
-            })
-        } else {
-            false
-        }
+        self.buffer_index_for_path(path).is_some()
+    }
+
+    // The index of the buffer whose file is found at the specified path.
+    fn buffer_index_for_path(&self, path: &Path) -> Option<usize> {
+        let canonical_path = path.canonicalize().ok()?;
+
+        self.buffers.iter().position(|buffer| {
+            buffer.canonical_path().as_ref() == Some(&canonical_path)
+        })
     }
 
     /// Updates the current buffer's syntax definition.
@@ -390,6 +1025,7 @@ impl Workspace {
     /// ```
     pub fn update_current_syntax(&mut self) -> Result<()> {
         let index = self.current_buffer_index.ok_or(ErrorKind::EmptyWorkspace)?;
+        self.load_buffer(index);
         let syntax_definition = self.find_syntax_definition(&self.buffers[index]);
         let buffer = &mut self.buffers[index];
         buffer.syntax_definition = syntax_definition;
@@ -397,29 +1033,553 @@ impl Workspace {
         Ok(())
     }
 
-    // Returns a syntax definition based on the buffer's file extension,
-    // falling back to a plain text definition if one cannot be found.
-    fn find_syntax_definition(&self, buffer: &Buffer) -> Option<SyntaxDefinition> {
-        // Find the syntax definition using the buffer's file extension.
-        buffer.path.as_ref().and_then(|path|
-            path.to_str().and_then(|p| p.split('.').last()).and_then(|ex|
-                self.syntax_set.find_syntax_by_extension(ex).and_then(|s|
-                    Some(s.clone())
-                )
-            )
-        ).or_else(||
-            // Fall back to a plain text definition.
-            Some(self.syntax_set.find_syntax_plain_text().clone())
-        )
+    /// Registers a detector used to choose syntax definitions for buffers
+    /// added to the workspace, and when updating them (e.g. using
+    /// `update_current_syntax`). Detectors are consulted before the built-in
+    /// detection, with those registered most recently consulted first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate syntect;
+    /// extern crate scribe;
+    ///
+    /// use scribe::{Buffer, Workspace};
+    /// use syntect::parsing::SyntaxSet;
+    /// use std::path::Path;
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// workspace.register_syntax_detector(|buffer: &Buffer, syntax_set: &SyntaxSet| {
+    ///     if buffer.data().starts_with("---\n") {
+    ///         syntax_set.find_syntax_by_name("YAML").cloned()
+    ///     } else {
+    ///         None
+    ///     }
+    /// });
+    ///
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("---\nname: scribe\n");
+    /// workspace.add_buffer(buffer);
+    ///
+    /// assert_eq!(
+    ///     workspace.current_buffer().unwrap().syntax_definition.as_ref().unwrap().name,
+    ///     "YAML"
+    /// );
+    /// ```
+    pub fn register_syntax_detector<D: SyntaxDetector + 'static>(&mut self, detector: D) {
+        self.syntax_detectors.push(Box::new(detector));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::Workspace;
-    use buffer::Buffer;
-    use std::path::Path;
-    use std::env;
+    /// Saves the current buffer to a new path (see `Buffer::save_as`),
+    /// updating its syntax definition to reflect the path's file extension.
+    /// Returns an `InvalidInput` error if the workspace is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::{Buffer, Workspace};
+    /// use std::env;
+    /// use std::fs;
+    /// use std::path::Path;
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// workspace.add_buffer(Buffer::new());
+    /// workspace.current_buffer().unwrap().insert("fn main() {}");
+    ///
+    /// let path = env::temp_dir().join("scribe_save_current_buffer_as_example.rs");
+    /// workspace.save_current_buffer_as(&path).unwrap();
+    ///
+    /// assert_eq!(
+    ///     workspace.current_buffer().unwrap().syntax_definition.as_ref().unwrap().name,
+    ///     "Rust"
+    /// );
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save_current_buffer_as(&mut self, path: &Path) -> io::Result<()> {
+        let index = self.current_buffer_index.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "the workspace is empty")
+        })?;
+        self.load_buffer(index);
+        self.buffers[index].save_as(path)?;
+        if let Some(id) = self.buffers[index].id {
+            self.subscribers.notify(WorkspaceEvent::Saved(id));
+        }
+        self.snapshot_saved_buffer(index);
+
+        let syntax_definition = self.find_syntax_definition(&self.buffers[index]);
+        self.buffers[index].syntax_definition = syntax_definition;
+
+        Ok(())
+    }
+
+    /// Reloads the current buffer from disk (see `Buffer::reload`), updating
+    /// its syntax definition to reflect its new content (e.g. an added
+    /// shebang line). Returns an error if the workspace is empty, or the
+    /// buffer's file can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Workspace;
+    /// use std::env;
+    /// use std::fs;
+    /// use std::path::Path;
+    ///
+    /// let path = env::temp_dir().join("scribe_reload_current_buffer_example");
+    /// fs::write(&path, "scribe").unwrap();
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// workspace.open_buffer(&path).unwrap();
+    /// fs::write(&path, "#!/usr/bin/env ruby\nputs 'scribe'").unwrap();
+    /// workspace.reload_current_buffer().unwrap();
+    ///
+    /// let buffer = workspace.current_buffer().unwrap();
+    /// assert_eq!(buffer.data(), "#!/usr/bin/env ruby\nputs 'scribe'");
+    /// assert_eq!(buffer.syntax_definition.as_ref().unwrap().name, "Ruby");
+    /// # fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn reload_current_buffer(&mut self) -> io::Result<()> {
+        let index = self.current_buffer_index.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "the workspace is empty")
+        })?;
+        self.load_buffer(index);
+        self.buffers[index].reload()?;
+
+        let syntax_definition = self.find_syntax_definition(&self.buffers[index]);
+        self.buffers[index].syntax_definition = syntax_definition;
+
+        Ok(())
+    }
+
+    /// Registers a subscriber, called with each event concerning the
+    /// workspace's buffers (see `WorkspaceEvent`). Returns an ID that
+    /// can be used to remove the subscriber.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::{Buffer, Workspace, WorkspaceEvent};
+    /// use std::cell::RefCell;
+    /// use std::path::Path;
+    /// use std::rc::Rc;
+    ///
+    /// let events = Rc::new(RefCell::new(Vec::new()));
+    /// let received_events = events.clone();
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// let id = workspace.subscribe(move |event: &WorkspaceEvent| received_events.borrow_mut().push(*event));
+    ///
+    /// workspace.add_buffer(Buffer::new());
+    /// workspace.current_buffer().unwrap().insert("scribe");
+    /// workspace.close_current_buffer();
+    ///
+    /// assert_eq!(*events.borrow(), vec![
+    ///     WorkspaceEvent::Opened(0),
+    ///     WorkspaceEvent::Modified(0),
+    ///     WorkspaceEvent::Closed(0),
+    /// ]);
+    /// assert!(workspace.unsubscribe(id));
+    /// ```
+    pub fn subscribe<F: Fn(&WorkspaceEvent) + 'static>(&mut self, subscriber: F) -> usize {
+        self.subscribers.add(Box::new(subscriber))
+    }
+
+    /// Removes a previously registered subscriber, returning
+    /// whether or not a subscriber with the specified ID existed.
+    pub fn unsubscribe(&mut self, id: usize) -> bool {
+        self.subscribers.remove(id)
+    }
+
+    /// Copies the current buffer's content within the range to the clipboard
+    /// history, returning it. Returns an `EmptyWorkspace` error if there's no
+    /// current buffer, or an `OutOfBounds` error if the range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::{Buffer, Workspace};
+    /// use scribe::buffer::{Position, Range};
+    /// use std::path::Path;
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// let mut buffer = Buffer::new();
+    /// buffer.insert("scribe library");
+    /// workspace.add_buffer(buffer);
+    ///
+    /// let range = |start, end| Range::new(Position{ line: 0, offset: start }, Position{ line: 0, offset: end });
+    /// workspace.copy_range(&range(0, 6)).unwrap();
+    /// workspace.kill_range(&range(6, 14)).unwrap();
+    /// assert_eq!(workspace.clipboard_history(), [" library", "scribe"]);
+    ///
+    /// // Paste the older entry.
+    /// workspace.paste_from_history(1).unwrap();
+    /// assert_eq!(workspace.current_buffer().unwrap().data(), "scribescribe");
+    /// ```
+    pub fn copy_range(&mut self, range: &Range) -> Result<String> {
+        let buffer = self.current_buffer().ok_or(ErrorKind::EmptyWorkspace)?;
+        buffer.check_position(&range.start())?;
+        buffer.check_position(&range.end())?;
+        let content = buffer.read(range).unwrap_or_default();
+        self.add_to_clipboard_history(content.clone());
+
+        Ok(content)
+    }
+
+    /// Like `copy_range`, but also deletes the content from the buffer.
+    pub fn kill_range(&mut self, range: &Range) -> Result<String> {
+        let content = self.copy_range(range)?;
+        if let Some(buffer) = self.current_buffer() {
+            buffer.delete_range(range.clone());
+        }
+
+        Ok(content)
+    }
+
+    /// Adds text to the clipboard history (e.g. when copied from another
+    /// application), as its most recent entry. Empty text is ignored, and
+    /// text already in the history is moved to its front, rather than being
+    /// duplicated. The oldest entries beyond `clipboard_history_limit` are
+    /// discarded.
+    pub fn add_to_clipboard_history<T: Into<String>>(&mut self, content: T) {
+        let content = content.into();
+        if content.is_empty() { return; }
+
+        self.clipboard_history.retain(|entry| *entry != content);
+        self.clipboard_history.insert(0, content);
+        self.clipboard_history.truncate(self.clipboard_history_limit);
+    }
+
+    /// The clipboard history, from the most to least recent entry.
+    pub fn clipboard_history(&self) -> &[String] {
+        &self.clipboard_history
+    }
+
+    /// Inserts the clipboard history entry at the specified index (with zero
+    /// being the most recent) at the current buffer's cursor, as a single
+    /// operation, moving the cursor to the end of it. Returns whether the
+    /// entry exists, or an `EmptyWorkspace` error if there's no current
+    /// buffer.
+    pub fn paste_from_history(&mut self, index: usize) -> Result<bool> {
+        let content = match self.clipboard_history.get(index) {
+            Some(content) => content.clone(),
+            None => return Ok(false),
+        };
+        let buffer = self.current_buffer().ok_or(ErrorKind::EmptyWorkspace)?;
+        if buffer.read_only { return Ok(true); }
+
+        let position = *buffer.cursor;
+        let inserted = buffer.insert_transformed(content, position);
+        buffer.cursor.move_to(position + Distance::of_str(&inserted));
+
+        Ok(true)
+    }
+
+    /// Checks whether the workspace's buffers' files have been changed by
+    /// other programs (see `Buffer::outdated`), notifying subscribers of
+    /// changes that haven't previously been reported, and returning the IDs
+    /// of those buffers. Intended to be called periodically, or when the
+    /// application regains focus.
+    pub fn detect_external_changes(&mut self) -> Vec<usize> {
+        let mut changed = Vec::new();
+        for buffer in &self.buffers {
+            let id = match buffer.id {
+                Some(id) => id,
+                None => continue,
+            };
+
+            if !buffer.outdated() {
+                self.externally_modified.remove(&id);
+            } else if self.externally_modified.insert(id) {
+                self.subscribers.notify(WorkspaceEvent::ExternallyModified(id));
+                changed.push(id);
+            }
+        }
+
+        changed
+    }
+
+    /// Queues the syntax definition with the specified name to be warmed up
+    /// by `warm_up_syntaxes`, unless it's already been warmed up or queued,
+    /// along with a `WarmUpSyntaxes` task.
+    pub fn queue_syntax_warm_up(&mut self, name: &str) {
+        if self.warmed_syntaxes.contains(name) || self.pending_syntaxes.iter().any(|pending| pending == name) {
+            return;
+        }
+
+        self.pending_syntaxes.push_back(name.to_string());
+        self.tasks.push(WorkspaceTask::WarmUpSyntaxes);
+    }
+
+    /// Compiles the patterns of queued syntax definitions, which syntect
+    /// otherwise does as they're first needed while lexing, stalling the
+    /// first edits made to a buffer. Definitions are compiled one at a time,
+    /// in the order they were queued (though those of added buffers go
+    /// first), until the budget has been spent. Since buffers' definitions
+    /// are cloned from the workspace's `syntax_set`, and clones share their
+    /// compiled patterns, this benefits every buffer of the same type.
+    ///
+    /// syntect's definitions can't be shared across threads, so this is
+    /// intended to be called on the main thread whenever the application
+    /// is idle. Returns the number of definitions still queued.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::Workspace;
+    /// use std::path::Path;
+    /// use std::time::Duration;
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// workspace.queue_syntax_warm_up("Rust");
+    ///
+    /// while workspace.warm_up_syntaxes(Duration::from_millis(10)) > 0 {}
+    /// ```
+    pub fn warm_up_syntaxes(&mut self, budget: Duration) -> usize {
+        let started_at = Instant::now();
+        self.warm_up_syntaxes_while(|| started_at.elapsed() < budget)
+    }
+
+    // Compiles queued syntax definitions for as long as the predicate
+    // permits another, returning the number of definitions still queued.
+    fn warm_up_syntaxes_while<F: FnMut() -> bool>(&mut self, mut permit: F) -> usize {
+        while permit() {
+            let name = match self.pending_syntaxes.pop_front() {
+                Some(name) => name,
+                None => break,
+            };
+            if let Some(definition) = self.syntax_set.find_syntax_by_name(&name) {
+                syntax_warm_up::compile_patterns(definition);
+            }
+            self.warmed_syntaxes.insert(name);
+        }
+
+        self.pending_syntaxes.len()
+    }
+
+    /// Queues a task (see `WorkspaceTask`) to be performed by `run_tasks`,
+    /// unless it's already queued. The workspace queues its own tasks (e.g.
+    /// when buffers change); applications can queue others, such as
+    /// `DetectExternalChanges`, on their own schedule.
+    pub fn queue_task(&self, task: WorkspaceTask) {
+        self.tasks.push(task);
+    }
+
+    /// The queued tasks, in the order they'll be performed.
+    pub fn queued_tasks(&self) -> Vec<WorkspaceTask> {
+        self.tasks.to_vec()
+    }
+
+    /// Performs queued tasks, in the order they were queued, until the
+    /// budget has been spent. This gives applications a single place to
+    /// perform the workspace's deferred work (lexing, buffer maintenance,
+    /// snapshots, and so on) on the main thread, whenever it suits them (e.g.
+    /// when idle).
+    ///
+    /// The budget is shared by every task performed. Units of work are lines
+    /// lexed, syntax definitions compiled, and buffer maintenance tasks (see
+    /// `Buffer::perform_maintenance`); other tasks count as a single unit.
+    /// Tasks that don't finish within the budget are queued again, and
+    /// resume where they left off. Tasks for buffers that have been unloaded
+    /// are skipped.
+    ///
+    /// Returns the number of tasks still queued, or the first error
+    /// encountered (e.g. storing snapshots), leaving the failed task and
+    /// those following it queued.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scribe::{Buffer, Workspace, WorkspaceTask};
+    /// use scribe::util::Budget;
+    /// use std::path::Path;
+    ///
+    /// let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+    /// workspace.add_buffer(Buffer::new());
+    /// workspace.current_buffer().unwrap().insert("scribe");
+    /// workspace.queue_task(WorkspaceTask::DetectExternalChanges);
+    ///
+    /// while workspace.run_tasks(Budget::Units(2)).unwrap() > 0 {}
+    /// assert!(workspace.queued_tasks().is_empty());
+    /// ```
+    pub fn run_tasks(&mut self, budget: Budget) -> io::Result<usize> {
+        let mut meter = budget.meter();
+        while !meter.exhausted() {
+            let task = match self.tasks.pop() {
+                Some(task) => task,
+                None => break,
+            };
+            if !self.run_task(task, &mut meter)? {
+                self.tasks.push(task);
+            }
+        }
+
+        Ok(self.tasks.len())
+    }
+
+    // Performs the task, returning whether or not it was finished.
+    fn run_task(&mut self, task: WorkspaceTask, meter: &mut BudgetMeter) -> io::Result<bool> {
+        match task {
+            WorkspaceTask::StoreSnapshots => {
+                meter.permit();
+                if let Some(failure) = self.store_snapshots().1.into_iter().next() {
+                    // Leave the snapshots to be stored on the next run.
+                    self.tasks.push(task);
+                    return Err(failure.error);
+                }
+            },
+            WorkspaceTask::StoreSnapshot(id) => {
+                meter.permit();
+                if self.unloaded_buffers.contains_key(&id) { return Ok(true); }
+                let buffer = self.buffers.iter().find(|buffer| buffer.id == Some(id));
+                if let (Some(store), Some(buffer)) = (self.snapshot_store.as_ref(), buffer) {
+                    if let Err(error) = store_snapshot(store, buffer) {
+                        self.tasks.push(task);
+                        return Err(error);
+                    }
+                }
+            },
+            WorkspaceTask::DetectExternalChanges => {
+                meter.permit();
+                self.detect_external_changes();
+            },
+            WorkspaceTask::WarmUpSyntaxes => {
+                return Ok(self.warm_up_syntaxes_while(|| meter.permit()) == 0);
+            },
+            WorkspaceTask::LexBuffer(id) | WorkspaceTask::PerformMaintenance(id) => {
+                if self.unloaded_buffers.contains_key(&id) { return Ok(true); }
+                let buffer = match self.buffers.iter_mut().find(|buffer| buffer.id == Some(id)) {
+                    Some(buffer) => buffer,
+                    None => return Ok(true),
+                };
+
+                // Lexing populates the buffer's token cache, and both
+                // resume where they left off the next time they're run.
+                if task == WorkspaceTask::LexBuffer(id) {
+                    return Ok(buffer.lex_within(meter));
+                } else {
+                    return Ok(!buffer.perform_maintenance_within(meter).incomplete);
+                }
+            },
+        }
+
+        Ok(true)
+    }
+
+    // Queues the syntax definitions for the files at the top
+    // level of the workspace directory to be warmed up.
+    fn queue_directory_syntaxes(&mut self) {
+        let entries = match fs::read_dir(&self.path) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        let plain_text = self.syntax_set.find_syntax_plain_text().name.clone();
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            if !entry.file_type().map(|file_type| file_type.is_file()).unwrap_or(false) {
+                continue;
+            }
+
+            // Detect the syntax by path alone; reading files would be slower
+            // than the warm-up it's intended to save.
+            let mut buffer = Buffer::new();
+            buffer.path = Some(entry.path());
+            let name = syntax_detector::detect_syntax(&buffer, &self.syntax_set).name;
+            if name != plain_text {
+                self.queue_syntax_warm_up(&name);
+            }
+        }
+    }
+
+    // Reads the buffer at the specified index back into memory, if it's been
+    // unloaded, restoring its cursor position. Buffers that can't be read
+    // are left empty, as with `Buffer::open` for missing files.
+    fn load_buffer(&mut self, index: usize) {
+        let position = match self.buffers[index].id.and_then(|id| self.unloaded_buffers.remove(&id)) {
+            Some(position) => position,
+            None => return,
+        };
+
+        let buffer = &mut self.buffers[index];
+        if buffer.load().is_ok() {
+            buffer.cursor.move_to(position);
+        }
+        if let Some(id) = buffer.id {
+            self.tasks.push(WorkspaceTask::LexBuffer(id));
+        }
+    }
+
+    // Unloads the least recently used buffers beyond the configured limit.
+    fn unload_buffers(&mut self) {
+        let limit = match self.loaded_buffers {
+            Some(limit) => limit,
+            None => return,
+        };
+
+        // Buffers that have never been accessed are the least recently used.
+        let mut candidates: Vec<usize> = self.buffers.iter()
+            .filter_map(|buffer| buffer.id)
+            .filter(|id| !self.recently_used.contains(id))
+            .collect();
+        candidates.extend(self.recently_used.iter().cloned());
+
+        let loaded = candidates.iter().filter(|id| !self.unloaded_buffers.contains_key(id)).count();
+        let mut excess = loaded.saturating_sub(limit);
+
+        for id in candidates {
+            if excess == 0 { break; }
+
+            let current_id = self.current_buffer_index.and_then(|index| self.buffers[index].id);
+            let buffer = match self.buffers.iter_mut().find(|buffer| buffer.id == Some(id)) {
+                Some(buffer) => buffer,
+                None => continue,
+            };
+            if Some(id) == current_id || self.unloaded_buffers.contains_key(&id) ||
+               buffer.path.is_none() || buffer.read_only || buffer.modified() {
+                continue;
+            }
+
+            self.unloaded_buffers.insert(id, *buffer.cursor);
+            buffer.unload();
+            excess -= 1;
+        }
+    }
+
+    // Returns a syntax definition from the first registered detector to
+    // produce one, falling back to the built-in detection (which in turn
+    // falls back to a plain text definition).
+    fn find_syntax_definition(&self, buffer: &Buffer) -> Option<SyntaxDefinition> {
+        self.syntax_detectors.iter().rev()
+            .filter_map(|detector| detector.detect(buffer, &self.syntax_set))
+            .next()
+            .or_else(|| Some(syntax_detector::detect_syntax(buffer, &self.syntax_set)))
+    }
+}
+
+// Stores a snapshot of the buffer's content, keyed by its canonical path.
+// Returns whether a snapshot was stored (i.e. the content had changed).
+fn store_snapshot(store: &SnapshotStore, buffer: &Buffer) -> io::Result<bool> {
+    match buffer.canonical_path() {
+        Some(path) => Ok(store.store(&path, &buffer.data())?.is_some()),
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Workspace;
+    use command::CommandStatus;
+    use snapshots::SnapshotStore;
+    use buffer::{Buffer, IndentRules, Position, Range};
+    use errors::ErrorKind;
+    use event::WorkspaceEvent;
+    use std::cell::{Cell, RefCell};
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+    use std::env;
+    use std::fs;
+    use std::time::{Duration, SystemTime};
+    use task::WorkspaceTask;
+    use util::Budget;
 
     #[test]
     fn add_buffer_adds_and_selects_the_passed_buffer() {
@@ -499,6 +1659,61 @@ mod tests {
         assert_eq!(name, Some("Plain Text".to_string()));
     }
 
+    #[test]
+    fn add_buffer_populates_indent_rules_using_the_buffer_syntax() {
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        workspace.indent_rules.insert("Plain Text".to_string(), IndentRules::tags());
+        workspace.add_buffer(Buffer::new());
+        assert_eq!(workspace.current_buffer().unwrap().indent_rules, Some(IndentRules::tags()));
+
+        let mut buf = Buffer::new();
+        buf.indent_rules = Some(IndentRules::brackets());
+        workspace.add_buffer(buf);
+        assert_eq!(workspace.current_buffer().unwrap().indent_rules, Some(IndentRules::brackets()));
+    }
+
+    #[test]
+    fn clipboard_history_is_bounded_and_deduplicated() {
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        workspace.clipboard_history_limit = 2;
+
+        workspace.add_to_clipboard_history("scribe");
+        workspace.add_to_clipboard_history("library");
+        workspace.add_to_clipboard_history("");
+        workspace.add_to_clipboard_history("scribe");
+        assert_eq!(workspace.clipboard_history(), ["scribe", "library"]);
+
+        workspace.add_to_clipboard_history("editor");
+        assert_eq!(workspace.clipboard_history(), ["editor", "scribe"]);
+    }
+
+    #[test]
+    fn paste_from_history_requires_an_entry_and_a_buffer() {
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        assert!(!workspace.paste_from_history(0).unwrap());
+
+        workspace.add_to_clipboard_history("scribe\nlibrary");
+        assert!(workspace.paste_from_history(0).is_err());
+
+        workspace.add_buffer(Buffer::new());
+        assert!(workspace.paste_from_history(0).unwrap());
+        assert_eq!(*workspace.current_buffer().unwrap().cursor, Position{ line: 1, offset: 7 });
+
+        workspace.current_buffer().unwrap().undo();
+        assert_eq!(workspace.current_buffer().unwrap().data(), "");
+    }
+
+    #[test]
+    fn copy_range_rejects_invalid_ranges() {
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        let range = Range::new(Position::new(), Position{ line: 1, offset: 0 });
+        assert!(workspace.copy_range(&range).is_err());
+
+        workspace.add_buffer(Buffer::new());
+        assert!(workspace.copy_range(&range).is_err());
+        assert!(workspace.clipboard_history().is_empty());
+    }
+
     #[test]
     fn open_buffer_adds_and_selects_the_buffer_at_the_specified_path() {
         let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
@@ -558,7 +1773,30 @@ mod tests {
         let absolute_path = env::current_dir().unwrap();
         buf.path = Some(absolute_path.clone());
         workspace.add_buffer(buf);
-        assert_eq!(workspace.current_buffer_path(), Some(absolute_path.as_path()));
+        assert_eq!(workspace.current_buffer_path(), Some(absolute_path));
+    }
+
+    #[test]
+    fn short_path_uses_file_name_when_it_is_unique() {
+        let mut workspace = Workspace::new(Path::new("tests")).unwrap();
+        let mut buf = Buffer::new();
+        buf.path = Some(workspace.path.join("src/mod.rs"));
+        workspace.add_buffer(buf);
+
+        let buffer = Buffer::from_file(Path::new("tests/sample/file")).unwrap();
+        assert_eq!(workspace.short_path(&buffer), Some(PathBuf::from("file")));
+    }
+
+    #[test]
+    fn short_path_uses_full_relative_path_when_suffixes_are_ambiguous() {
+        let mut workspace = Workspace::new(Path::new("tests")).unwrap();
+        let mut buf = Buffer::new();
+        buf.path = Some(workspace.path.join("other/src/mod.rs"));
+        workspace.add_buffer(buf);
+
+        let mut buffer = Buffer::new();
+        buffer.path = Some(workspace.path.join("src/mod.rs"));
+        assert_eq!(workspace.short_path(&buffer), Some(PathBuf::from("src/mod.rs")));
     }
 
     #[test]
@@ -698,4 +1936,478 @@ mod tests {
         workspace.next_buffer();
         assert_eq!(workspace.current_buffer().unwrap().data(), "third buffer");
     }
+
+    #[test]
+    fn save_all_collects_failures_and_saves_remaining_buffers() {
+        let path = env::temp_dir().join("scribe_save_all_collects_failures");
+        let unwritable_path = env::temp_dir().join("scribe_save_all_missing_directory").join("file");
+        fs::write(&path, "scribe").unwrap();
+
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        let mut unwritable_buffer = Buffer::new();
+        unwritable_buffer.path = Some(unwritable_path.clone());
+        unwritable_buffer.insert("unsaved");
+        workspace.add_buffer(unwritable_buffer);
+        workspace.open_buffer(&path).unwrap();
+        workspace.current_buffer().unwrap().insert("library ");
+
+        let failures = workspace.save_all(false).unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].path, Some(unwritable_path));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "library scribe");
+        assert!(!workspace.current_buffer().unwrap().modified());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn save_all_skips_buffers_without_paths_and_read_only_buffers() {
+        let path = env::temp_dir().join("scribe_save_all_skips_read_only");
+        fs::write(&path, "scribe").unwrap();
+
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        let mut unbound_buffer = Buffer::new();
+        unbound_buffer.insert("unsaved");
+        workspace.add_buffer(unbound_buffer);
+        workspace.open_buffer(&path).unwrap();
+        workspace.current_buffer().unwrap().insert("library ");
+        workspace.current_buffer().unwrap().read_only = true;
+
+        assert!(workspace.save_all(false).unwrap().is_empty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "scribe");
+        assert!(workspace.current_buffer().unwrap().modified());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn save_all_refuses_to_overwrite_changes_on_disk_unless_forced() {
+        let path = env::temp_dir().join("scribe_save_all_refuses_conflicts");
+        fs::write(&path, "scribe").unwrap();
+
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        workspace.open_buffer(&path).unwrap();
+        workspace.current_buffer().unwrap().insert("library ");
+
+        let file = fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60)).unwrap();
+
+        match workspace.save_all(false) {
+            Err(error) => match *error.kind() {
+                ErrorKind::ConflictingChanges(ref paths) => assert_eq!(paths.len(), 1),
+                _ => panic!("unexpected error: {}", error),
+            },
+            Ok(_) => panic!("conflicting changes were overwritten"),
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "scribe");
+
+        assert!(workspace.save_all(true).unwrap().is_empty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "library scribe");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn open_buffer_applies_buffer_preferences_and_editor_config() {
+        let directory = env::temp_dir().join("scribe_open_buffer_applies_preferences");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join(".editorconfig"), "root = true\n[*.md]\ninsert_final_newline = false\n").unwrap();
+        fs::write(directory.join("lib.rs"), "scribe").unwrap();
+        fs::write(directory.join("README.md"), "scribe").unwrap();
+
+        let mut workspace = Workspace::new(&directory).unwrap();
+        workspace.buffer_preferences.insert_final_newline = true;
+        workspace.buffer_preferences.history_limit = Some(10);
+        workspace.buffer_preferences.max_line_length = Some(100);
+
+        workspace.open_buffer(&directory.join("lib.rs")).unwrap();
+        assert!(workspace.current_buffer().unwrap().insert_final_newline);
+        assert_eq!(workspace.current_buffer().unwrap().history_limit, Some(10));
+        assert_eq!(workspace.current_buffer().unwrap().max_line_length, Some(100));
+
+        workspace.open_buffer(&directory.join("README.md")).unwrap();
+        assert!(!workspace.current_buffer().unwrap().insert_final_newline);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn open_buffer_does_not_duplicate_buffers_opened_through_symbolic_links() {
+        let directory = env::temp_dir().join("scribe_open_buffer_symbolic_links");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("target"), "scribe").unwrap();
+        ::std::os::unix::fs::symlink(directory.join("target"), directory.join("link")).unwrap();
+
+        let mut workspace = Workspace::new(&directory).unwrap();
+        workspace.open_buffer(&directory.join("link")).unwrap();
+        workspace.open_buffer(&directory.join("./target")).unwrap();
+        assert_eq!(workspace.buffers.len(), 1);
+        assert_eq!(workspace.current_buffer_path(), Some(PathBuf::from("link")));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn relative_paths_are_found_for_workspaces_opened_through_symbolic_links() {
+        let directory = env::temp_dir().join("scribe_workspace_symbolic_link_root");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(directory.join("real/sub")).unwrap();
+        fs::write(directory.join("real/sub/file"), "scribe").unwrap();
+        fs::create_dir_all(directory.join("other")).unwrap();
+        ::std::os::unix::fs::symlink(directory.join("real"), directory.join("link")).unwrap();
+        ::std::os::unix::fs::symlink(directory.join("real"), directory.join("other/link")).unwrap();
+
+        let mut workspace = Workspace::new(&directory.join("link")).unwrap();
+        workspace.open_buffer(&directory.join("link/sub/file")).unwrap();
+        assert_eq!(workspace.current_buffer_path(), Some(PathBuf::from("sub/file")));
+
+        // Paths going through other links are made relative using their canonical form.
+        let buffer = Buffer::from_file(&directory.join("other/link/sub/file")).unwrap();
+        assert_eq!(workspace.relative_path(&buffer), Some(PathBuf::from("sub/file")));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn unloaded_buffers_are_reloaded_with_their_cursor_positions_and_settings() {
+        let directory = env::temp_dir().join("scribe_unloaded_buffers_reload");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("first"), "scribe\nlibrary").unwrap();
+        fs::write(directory.join("second"), "editor").unwrap();
+
+        let mut workspace = Workspace::new(&directory).unwrap();
+        workspace.loaded_buffers = Some(1);
+        workspace.open_buffer(&directory.join("first")).unwrap();
+        {
+            let buffer = workspace.current_buffer().unwrap();
+            buffer.cursor.move_to(Position{ line: 1, offset: 3 });
+            buffer.tab_width = 2;
+        }
+        workspace.open_buffer(&directory.join("second")).unwrap();
+        workspace.current_buffer();
+        assert!(workspace.is_unloaded(0));
+        assert_eq!(workspace.buffers[0].data(), "");
+
+        workspace.select_buffer(0);
+        let buffer = workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "scribe\nlibrary");
+        assert_eq!(*buffer.cursor, Position{ line: 1, offset: 3 });
+        assert_eq!(buffer.tab_width, 2);
+        assert!(!buffer.modified());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn modified_buffers_are_not_unloaded() {
+        let directory = env::temp_dir().join("scribe_modified_buffers_not_unloaded");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("first"), "scribe").unwrap();
+        fs::write(directory.join("second"), "editor").unwrap();
+
+        let mut workspace = Workspace::new(&directory).unwrap();
+        workspace.loaded_buffers = Some(1);
+        workspace.open_buffer(&directory.join("first")).unwrap();
+        workspace.current_buffer().unwrap().insert("library ");
+        workspace.open_buffer(&directory.join("second")).unwrap();
+        workspace.current_buffer();
+
+        assert!(!workspace.is_unloaded(0));
+        assert_eq!(workspace.buffers[0].data(), "library scribe");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn unloaded_buffers_are_loaded_before_being_saved_elsewhere() {
+        let directory = env::temp_dir().join("scribe_unloaded_buffers_save_as");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("first"), "scribe").unwrap();
+        fs::write(directory.join("second"), "editor").unwrap();
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let received_events = events.clone();
+        let mut workspace = Workspace::new(&directory).unwrap();
+        workspace.subscribe(move |event| received_events.borrow_mut().push(*event));
+        workspace.loaded_buffers = Some(1);
+        workspace.open_buffer(&directory.join("first")).unwrap();
+        workspace.current_buffer();
+        workspace.open_buffer(&directory.join("second")).unwrap();
+        workspace.current_buffer();
+        assert!(workspace.is_unloaded(0));
+
+        workspace.select_buffer(0);
+        workspace.save_current_buffer_as(&directory.join("third")).unwrap();
+        assert_eq!(fs::read_to_string(directory.join("third")).unwrap(), "scribe");
+
+        // Loading the buffer doesn't modify it.
+        assert!(!events.borrow().contains(&WorkspaceEvent::Modified(0)));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn store_snapshots_skips_unloaded_buffers() {
+        let directory = env::temp_dir().join("scribe_store_snapshots_unloaded");
+        let snapshot_directory = env::temp_dir().join("scribe_store_snapshots_unloaded_store");
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("first"), "scribe").unwrap();
+        fs::write(directory.join("second"), "editor").unwrap();
+
+        let mut workspace = Workspace::new(&directory).unwrap();
+        workspace.loaded_buffers = Some(1);
+        workspace.open_buffer(&directory.join("first")).unwrap();
+        workspace.open_buffer(&directory.join("second")).unwrap();
+        workspace.current_buffer();
+        workspace.snapshot_store = Some(SnapshotStore::new(&snapshot_directory));
+
+        assert_eq!(workspace.store_snapshots().0, 1);
+
+        fs::remove_dir_all(&directory).unwrap();
+        fs::remove_dir_all(&snapshot_directory).unwrap();
+    }
+
+    #[test]
+    fn store_snapshots_continues_past_failures() {
+        let directory = env::temp_dir().join("scribe_store_snapshots_failures");
+        let snapshot_directory = env::temp_dir().join("scribe_store_snapshots_failures_store");
+        let _ = fs::remove_dir_all(&directory);
+        let _ = fs::remove_dir_all(&snapshot_directory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("first"), "scribe").unwrap();
+        fs::write(directory.join("second"), "editor").unwrap();
+
+        // Block the first file's snapshots with a file in place of their directory.
+        let store = SnapshotStore::new(&snapshot_directory);
+        let blocked = directory.join("first").canonicalize().unwrap();
+        fs::create_dir_all(&snapshot_directory).unwrap();
+        fs::write(store.file_directory(&blocked), "").unwrap();
+
+        let mut workspace = Workspace::new(&directory).unwrap();
+        workspace.open_buffer(&directory.join("first")).unwrap();
+        workspace.open_buffer(&directory.join("second")).unwrap();
+        workspace.snapshot_store = Some(store);
+
+        let (stored, failures) = workspace.store_snapshots();
+        assert_eq!(stored, 1);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].path, Some(directory.join("first")));
+
+        fs::remove_dir_all(&directory).unwrap();
+        fs::remove_dir_all(&snapshot_directory).unwrap();
+    }
+
+    #[test]
+    fn saves_store_snapshots() {
+        let directory = env::temp_dir().join("scribe_saves_store_snapshots");
+        let snapshot_directory = env::temp_dir().join("scribe_saves_store_snapshots_store");
+        let _ = fs::remove_dir_all(&directory);
+        let _ = fs::remove_dir_all(&snapshot_directory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("first"), "scribe").unwrap();
+
+        let mut workspace = Workspace::new(&directory).unwrap();
+        workspace.snapshot_store = Some(SnapshotStore::new(&snapshot_directory));
+        workspace.open_buffer(&directory.join("first")).unwrap();
+        let id = workspace.current_buffer().unwrap().id.unwrap();
+
+        // Saving the buffer directly queues a snapshot.
+        workspace.current_buffer().unwrap().insert("library ");
+        workspace.current_buffer().unwrap().save().unwrap();
+        assert!(workspace.queued_tasks().contains(&WorkspaceTask::StoreSnapshot(id)));
+        while workspace.run_tasks(Budget::Units(10)).unwrap() > 0 {}
+
+        // Saving it through the workspace stores one immediately.
+        workspace.current_buffer().unwrap().insert("editor ");
+        workspace.save_current_buffer_as(&directory.join("second")).unwrap();
+        assert!(!workspace.queued_tasks().contains(&WorkspaceTask::StoreSnapshot(id)));
+
+        let store = workspace.snapshot_store.as_ref().unwrap();
+        let first = store.snapshots(&directory.join("first").canonicalize().unwrap()).unwrap();
+        let second = store.snapshots(&directory.join("second").canonicalize().unwrap()).unwrap();
+        assert_eq!(first[0].content().unwrap(), "library scribe");
+        assert_eq!(second[0].content().unwrap(), "editor library scribe");
+
+        fs::remove_dir_all(&directory).unwrap();
+        fs::remove_dir_all(&snapshot_directory).unwrap();
+    }
+
+    // Updates command output until the command finishes.
+    fn wait_for_command(workspace: &mut Workspace, buffer_id: usize) -> CommandStatus {
+        let start = SystemTime::now();
+        while workspace.command_status(buffer_id) == Some(CommandStatus::Running) {
+            assert!(start.elapsed().unwrap() < Duration::from_secs(10));
+            workspace.update_command_output();
+        }
+
+        workspace.command_status(buffer_id).unwrap()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn run_command_captures_output_streams_and_exit_status() {
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        let buffer_id = workspace.run_command("cat file; echo error >&2; exit 3").unwrap();
+
+        match wait_for_command(&mut workspace, buffer_id) {
+            CommandStatus::Finished(status) => assert_eq!(status.code(), Some(3)),
+            CommandStatus::Running => unreachable!(),
+        }
+
+        let buffer = workspace.current_buffer().unwrap();
+        assert_eq!(buffer.id, Some(buffer_id));
+        assert!(buffer.read_only);
+        assert!(!buffer.modified());
+
+        // The streams are read concurrently, so their output can be interleaved in either order.
+        let data = buffer.data();
+        let mut lines: Vec<&str> = data.lines().collect();
+        lines.sort();
+        assert_eq!(lines, vec!["error", "it works!"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn update_command_output_stops_commands_whose_buffers_are_closed() {
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        let buffer_id = workspace.run_command("sleep 10").unwrap();
+        workspace.close_current_buffer();
+
+        assert!(!workspace.update_command_output());
+        assert_eq!(workspace.command_status(buffer_id), None);
+    }
+
+    #[test]
+    fn save_all_stores_snapshots_of_saved_buffers() {
+        let directory = env::temp_dir().join("scribe_save_all_snapshots");
+        let path = env::temp_dir().join("scribe_save_all_snapshots_file");
+        fs::write(&path, "scribe").unwrap();
+
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        workspace.snapshot_store = Some(SnapshotStore::new(&directory));
+        workspace.open_buffer(&path).unwrap();
+        workspace.current_buffer().unwrap().insert("library ");
+        workspace.save_all(false).unwrap();
+
+        let store = workspace.snapshot_store.as_ref().unwrap();
+        let snapshots = store.snapshots(&path.canonicalize().unwrap()).unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].content().unwrap(), "library scribe");
+
+        fs::remove_dir_all(&directory).unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn subscribers_are_notified_of_saves_and_external_changes_once() {
+        let path = env::temp_dir().join("scribe_subscribers_are_notified");
+        fs::write(&path, "scribe").unwrap();
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let received_events = events.clone();
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        workspace.subscribe(move |event| received_events.borrow_mut().push(*event));
+        workspace.open_buffer(&path).unwrap();
+        workspace.current_buffer().unwrap().insert("library ");
+        workspace.save_all(false).unwrap();
+
+        let file = fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60)).unwrap();
+        assert_eq!(workspace.detect_external_changes(), vec![0]);
+        assert!(workspace.detect_external_changes().is_empty());
+
+        assert_eq!(*events.borrow(), vec![
+            WorkspaceEvent::Opened(0),
+            WorkspaceEvent::Modified(0),
+            WorkspaceEvent::Saved(0),
+            WorkspaceEvent::ExternallyModified(0),
+        ]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn unsubscribed_subscribers_are_not_notified() {
+        let count = Rc::new(Cell::new(0));
+        let received_count = count.clone();
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        let id = workspace.subscribe(move |_| received_count.set(received_count.get() + 1));
+        workspace.add_buffer(Buffer::new());
+
+        assert!(workspace.unsubscribe(id));
+        assert!(!workspace.unsubscribe(id));
+        workspace.close_current_buffer();
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn syntaxes_are_warmed_up_once_with_added_buffers_first() {
+        let mut workspace = Workspace::new(Path::new("src")).unwrap();
+        assert_eq!(workspace.pending_syntaxes, ["Rust"]);
+
+        workspace.queue_syntax_warm_up("Markdown");
+        workspace.queue_syntax_warm_up("Rust");
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("scribe.rb"));
+        workspace.add_buffer(buffer);
+        assert_eq!(workspace.pending_syntaxes, ["Ruby", "Rust", "Markdown"]);
+
+        assert_eq!(workspace.warm_up_syntaxes(Duration::from_secs(0)), 3);
+        assert_eq!(workspace.warm_up_syntaxes(Duration::from_secs(60)), 0);
+
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("scribe.rb"));
+        workspace.add_buffer(buffer);
+        workspace.queue_syntax_warm_up("Rust");
+        assert!(workspace.pending_syntaxes.is_empty());
+    }
+
+    #[test]
+    fn run_tasks_shares_the_budget_and_requeues_unfinished_tasks() {
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        workspace.run_tasks(Budget::Units(1000)).unwrap();
+
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("scribe.rs"));
+        buffer.insert("let x = 1;\n".repeat(100));
+        workspace.add_buffer(buffer);
+        let id = workspace.current_buffer().unwrap().id.unwrap();
+        workspace.warm_up_syntaxes(Duration::from_secs(60));
+
+        // Unfinished lexing is queued again, behind the tasks
+        // the budget didn't reach, and resumes where it left off.
+        assert_eq!(workspace.run_tasks(Budget::Units(60)).unwrap(), 2);
+        assert_eq!(workspace.queued_tasks(), vec![
+            WorkspaceTask::WarmUpSyntaxes,
+            WorkspaceTask::LexBuffer(id),
+        ]);
+        assert_eq!(workspace.run_tasks(Budget::Units(60)).unwrap(), 0);
+    }
+
+    #[test]
+    fn buffer_edits_queue_tasks_until_the_buffer_is_closed() {
+        let mut workspace = Workspace::new(Path::new("tests/sample")).unwrap();
+        workspace.add_buffer(Buffer::new());
+        workspace.run_tasks(Budget::Units(10)).unwrap();
+
+        workspace.current_buffer().unwrap().insert("scribe");
+        workspace.current_buffer().unwrap().insert("library ");
+        assert_eq!(workspace.queued_tasks(), vec![
+            WorkspaceTask::LexBuffer(0),
+            WorkspaceTask::PerformMaintenance(0),
+            WorkspaceTask::StoreSnapshots,
+        ]);
+
+        workspace.close_current_buffer();
+        assert_eq!(workspace.queued_tasks(), vec![WorkspaceTask::StoreSnapshots]);
+        assert_eq!(workspace.run_tasks(Budget::Units(1)).unwrap(), 0);
+    }
 }